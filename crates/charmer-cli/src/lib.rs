@@ -1,7 +1,7 @@
 //! CLI argument parsing for charmer.
 
 use camino::Utf8PathBuf;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(name = "charmer")]
@@ -31,7 +31,53 @@ pub struct Args {
     #[arg(long, default_value = "false")]
     pub all_jobs: bool,
 
+    /// Query SLURM through slurmrestd instead of squeue/sacct
+    /// (http://host:port or unix:/path; falls back to $SLURMRESTD_URL).
+    /// Authenticates with the JWT in $SLURM_JWT.
+    #[arg(long, value_name = "URL")]
+    pub slurmrestd: Option<String>,
+
+    /// Read structured Snakemake logger events (JSON lines) from a file,
+    /// or listen for them on a Unix socket (unix:/path). Used instead of
+    /// the text of the main log once events arrive.
+    #[arg(long, value_name = "SOURCE")]
+    pub events: Option<String>,
+
+    /// Listen for Snakemake's `--wms-monitor` updates on this address
+    /// (e.g. 127.0.0.1:5000), then run snakemake with
+    /// `--wms-monitor http://127.0.0.1:5000`.
+    #[arg(long, value_name = "ADDR")]
+    pub wms_listen: Option<String>,
+
+    /// Don't load or save state snapshots (.snakemake/charmer/state.json)
+    #[arg(long)]
+    pub no_snapshot: bool,
+
+    /// Show the final state of a past run from the files in DIR alone,
+    /// without querying a scheduler or watching for changes
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Main log of the run to show with --offline (file name in
+    /// .snakemake/log); defaults to the latest
+    #[arg(long, value_name = "FILE", requires = "offline")]
+    pub log: Option<String>,
+
     /// List recent runs and exit
     #[arg(long)]
     pub list_runs: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Start snakemake in DIR and monitor it. Quitting charmer interrupts
+    /// snakemake; a failed run can be restarted from the TUI.
+    Run {
+        /// Arguments for snakemake, after `--` (e.g. `charmer run -- -j 10`)
+        #[arg(last = true, value_name = "SNAKEMAKE_ARGS")]
+        snakemake_args: Vec<String>,
+    },
 }
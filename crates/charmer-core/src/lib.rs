@@ -1,14 +1,24 @@
 //! Snakemake metadata parsing for charmer.
 //!
 //! This crate handles parsing of `.snakemake/metadata/` files
-//! and the main snakemake log file.
+//! and the main snakemake log file, plus structured logger plugin events
+//! and executor job logs.
 
+pub mod logger;
 pub mod main_log;
 pub mod metadata;
+pub mod scheduler_logs;
+pub mod tailer;
 
-pub use main_log::{SnakemakeLogInfo, find_latest_log, parse_log_file, parse_main_log};
+pub use logger::{LoggerEvent, LoggerJobInfo, parse_logger_line};
+pub use main_log::{
+    LogEvent, LogJob, SnakemakeLogInfo, find_latest_log, list_logs, parse_log_file,
+    parse_log_file_events, parse_main_log,
+};
 pub use metadata::{
     IncrementalScanResult, MetadataError, SnakemakeJob, SnakemakeMetadata,
     decode_metadata_filename, parse_metadata_file, scan_metadata_dir,
     scan_metadata_dir_incremental,
 };
+pub use scheduler_logs::{SchedulerLog, find_scheduler_logs};
+pub use tailer::MainLogTailer;
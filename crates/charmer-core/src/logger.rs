@@ -0,0 +1,175 @@
+//! Structured events from a Snakemake logger plugin (JSON lines).
+//!
+//! Snakemake 8+ can hand log records to logger plugins. A companion plugin
+//! writes each record as one JSON object per line, tagged by its `event`
+//! field, to a file or a Unix socket:
+//!
+//! ```text
+//! {"event": "run_info", "per_rule_job_counts": {"align": 2, "all": 1}, "total_job_count": 3}
+//! {"event": "job_info", "jobid": 1, "rule_name": "align", "output": ["aligned/S1.bam"], "wildcards": {"sample": "S1"}}
+//! {"event": "job_finished", "job_id": 1}
+//! {"event": "job_error", "jobid": 2, "msg": "Command exited with 1"}
+//! {"event": "progress", "done": 1, "total": 3}
+//! ```
+//!
+//! Other events and unknown fields are ignored.
+
+use crate::main_log::LogJob;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// One logger plugin record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LoggerEvent {
+    /// Job counts for the whole run, sent once the DAG is built
+    RunInfo {
+        #[serde(default)]
+        per_rule_job_counts: HashMap<String, usize>,
+        #[serde(default)]
+        total_job_count: Option<usize>,
+    },
+    /// A job was scheduled
+    JobInfo(LoggerJobInfo),
+    /// A job finished successfully
+    JobFinished {
+        #[serde(alias = "jobid")]
+        job_id: u64,
+    },
+    /// A job failed
+    JobError {
+        #[serde(alias = "job_id")]
+        jobid: u64,
+        #[serde(default)]
+        msg: Option<String>,
+    },
+    /// Overall progress
+    Progress { done: usize, total: usize },
+    /// Any event charmer doesn't use
+    #[serde(other)]
+    Other,
+}
+
+/// Fields of a `job_info` record.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggerJobInfo {
+    #[serde(alias = "job_id")]
+    pub jobid: u64,
+    #[serde(alias = "rule")]
+    pub rule_name: String,
+    pub input: Vec<String>,
+    pub output: Vec<String>,
+    pub log: Vec<String>,
+    pub wildcards: BTreeMap<String, Value>,
+    pub resources: BTreeMap<String, Value>,
+    pub threads: Option<u32>,
+    pub reason: Option<String>,
+}
+
+impl LoggerJobInfo {
+    /// The same job as a main log job block.
+    pub fn to_log_job(&self) -> LogJob {
+        let wildcards = self
+            .wildcards
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, value_string(v)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        LogJob {
+            jobid: self.jobid,
+            rule: self.rule_name.clone(),
+            inputs: self.input.clone(),
+            outputs: self.output.clone(),
+            log: self.log.clone(),
+            wildcards: (!wildcards.is_empty()).then_some(wildcards),
+            resources: self
+                .resources
+                .iter()
+                .map(|(k, v)| (k.clone(), value_string(v)))
+                .collect(),
+            reason: self.reason.clone(),
+            threads: self.threads,
+            external_id: None,
+        }
+    }
+}
+
+/// Render a JSON value the way the text log prints it (strings unquoted).
+fn value_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Parse one line of the event stream. Blank lines yield `None`.
+pub fn parse_logger_line(line: &str) -> Result<Option<LoggerEvent>, serde_json::Error> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(line).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_logger_events() {
+        let event = parse_logger_line(
+            r#"{"event": "run_info", "per_rule_job_counts": {"align": 2}, "total_job_count": 3}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            event,
+            Some(LoggerEvent::RunInfo { ref per_rule_job_counts, total_job_count: Some(3) })
+                if per_rule_job_counts["align"] == 2
+        ));
+
+        assert_eq!(
+            parse_logger_line(r#"{"event": "job_finished", "job_id": 4, "level": "info"}"#)
+                .unwrap(),
+            Some(LoggerEvent::JobFinished { job_id: 4 })
+        );
+        assert_eq!(
+            parse_logger_line(r#"{"event": "job_error", "jobid": 5}"#).unwrap(),
+            Some(LoggerEvent::JobError {
+                jobid: 5,
+                msg: None
+            })
+        );
+        assert_eq!(
+            parse_logger_line(r#"{"event": "progress", "done": 1, "total": 3}"#).unwrap(),
+            Some(LoggerEvent::Progress { done: 1, total: 3 })
+        );
+        assert_eq!(
+            parse_logger_line(r#"{"event": "shellcmd", "shellcmd": "echo"}"#).unwrap(),
+            Some(LoggerEvent::Other)
+        );
+        assert_eq!(parse_logger_line("  ").unwrap(), None);
+        assert!(parse_logger_line("Building DAG of jobs...").is_err());
+    }
+
+    #[test]
+    fn test_job_info_to_log_job() {
+        let line = r#"{"event": "job_info", "jobid": 3, "rule_name": "align",
+            "input": ["raw/S1.fq"], "output": ["aligned/S1.bam"], "threads": 4,
+            "wildcards": {"sample": "S1", "lane": 2}, "resources": {"mem_mb": 1000}}"#;
+        let Some(LoggerEvent::JobInfo(info)) = parse_logger_line(&line.replace('\n', " ")).unwrap()
+        else {
+            panic!("expected job_info");
+        };
+
+        let job = info.to_log_job();
+        assert_eq!(job.jobid, 3);
+        assert_eq!(job.rule, "align");
+        assert_eq!(job.outputs, vec!["aligned/S1.bam"]);
+        assert_eq!(job.wildcards.as_deref(), Some("lane=2, sample=S1"));
+        assert_eq!(job.resources["mem_mb"], "1000");
+        assert_eq!(job.threads, Some(4));
+    }
+}
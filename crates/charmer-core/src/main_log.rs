@@ -1,9 +1,11 @@
 //! Parser for main snakemake log file (.snakemake/log/*.snakemake.log).
 //!
-//! Extracts pipeline-level information like total job count and progress.
+//! Extracts pipeline-level information like total job count and progress,
+//! plus the fields of each job block (jobid, rule, files, wildcards, resources).
 
-use camino::Utf8Path;
-use std::collections::{HashMap, HashSet};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io;
 
@@ -29,6 +31,35 @@ pub struct SnakemakeLogInfo {
     pub has_errors: bool,
     /// Error messages found
     pub errors: Vec<String>,
+    /// Job blocks ("rule X:" with its fields), one per snakemake job ID
+    pub jobs: Vec<LogJob>,
+    /// Snakemake job ID for each external (scheduler) job ID
+    pub external_ids: HashMap<String, u64>,
+}
+
+/// A job block from the main log: the "rule X:" line and its indented fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogJob {
+    /// Snakemake job ID
+    pub jobid: u64,
+    /// Rule name
+    pub rule: String,
+    /// Input files
+    pub inputs: Vec<String>,
+    /// Output files
+    pub outputs: Vec<String>,
+    /// Log files
+    pub log: Vec<String>,
+    /// Wildcards as printed (e.g., "sample=S1, lane=L002")
+    pub wildcards: Option<String>,
+    /// Resources as key/value pairs (e.g., mem_mb=1000)
+    pub resources: BTreeMap<String, String>,
+    /// Why snakemake scheduled the job
+    pub reason: Option<String>,
+    /// Threads
+    pub threads: Option<u32>,
+    /// Scheduler job ID from the job's submission line
+    pub external_id: Option<String>,
 }
 
 impl SnakemakeLogInfo {
@@ -73,6 +104,25 @@ pub fn find_latest_log(working_dir: &Utf8Path) -> Option<camino::Utf8PathBuf> {
     latest.map(|(_, path)| path)
 }
 
+/// List every snakemake log file in the working directory, oldest first.
+///
+/// Ordered by file name, which snakemake derives from the start time, so the
+/// order survives copies that reset modification times.
+pub fn list_logs(working_dir: &Utf8Path) -> Vec<Utf8PathBuf> {
+    let log_dir = working_dir.join(".snakemake").join("log");
+    let Ok(entries) = log_dir.read_dir_utf8() else {
+        return Vec::new();
+    };
+
+    let mut logs: Vec<Utf8PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().ends_with(".snakemake.log"))
+        .map(|entry| entry.into_path())
+        .collect();
+    logs.sort();
+    logs
+}
+
 /// Parse the main snakemake log file.
 pub fn parse_main_log(working_dir: &Utf8Path) -> io::Result<SnakemakeLogInfo> {
     let log_path = find_latest_log(working_dir)
@@ -87,19 +137,98 @@ pub fn parse_log_file(path: &Utf8Path) -> io::Result<SnakemakeLogInfo> {
     Ok(parse_log_content(&content))
 }
 
+/// Parse a specific snakemake log file, also returning what happened in it.
+pub fn parse_log_file_events(path: &Utf8Path) -> io::Result<(SnakemakeLogInfo, Vec<LogEvent>)> {
+    let content = fs::read_to_string(path)?;
+    let mut parser = LogParser::default();
+    let mut events = Vec::new();
+    for line in content.lines() {
+        parser.feed_line(line, &mut events);
+    }
+    Ok((parser.finish(), events))
+}
+
 /// Parse snakemake log content.
 pub fn parse_log_content(content: &str) -> SnakemakeLogInfo {
-    let mut info = SnakemakeLogInfo::default();
-    let mut in_job_stats = false;
+    let mut parser = LogParser::default();
+    let mut events = Vec::new();
+    for line in content.lines() {
+        parser.feed_line(line, &mut events);
+        events.clear();
+    }
+    parser.finish()
+}
+
+/// Something that happened in the main log, as seen by [`crate::MainLogTailer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogEvent {
+    /// Reading (re)started from the beginning of a log file, after a new
+    /// run, rotation or truncation. Earlier events no longer apply.
+    LogOpened { path: Utf8PathBuf },
+    /// A job block was printed: snakemake scheduled the job
+    JobScheduled(LogJob),
+    /// A job was handed to the scheduler
+    JobSubmitted { jobid: u64, external_id: String },
+    /// A job finished successfully
+    JobFinished { jobid: u64 },
+    /// An "Error in rule X:" block named a failed job
+    JobFailed { jobid: u64, rule: String },
+    /// "X of Y steps done"
+    Progress { completed: usize, total: usize },
+    /// An error line
+    Error(String),
+}
+
+/// Line-by-line parser state, kept between reads by the tailer.
+#[derive(Debug, Default)]
+pub(crate) struct LogParser {
+    info: SnakemakeLogInfo,
+    in_job_stats: bool,
 
     // State for tracking rule blocks to identify target rules (rules without outputs)
-    let mut current_rule: Option<String> = None;
-    let mut current_rule_has_output = false;
+    current_rule: Option<String>,
+    current_rule_has_output: bool,
     // Track all rules we've seen with their output status
-    let mut rules_with_outputs: HashSet<String> = HashSet::new();
-    let mut all_seen_rules: HashSet<String> = HashSet::new();
+    rules_with_outputs: HashSet<String>,
+    all_seen_rules: HashSet<String>,
+    // Job block being parsed, and its job ID once the "jobid:" line is seen
+    current_job: LogJob,
+    current_jobid: Option<u64>,
+    // Position of each job ID in info.jobs (blocks can be printed again on retry)
+    job_index: HashMap<u64, usize>,
+    // External IDs whose job block hasn't been stored yet
+    pending_external_ids: HashMap<u64, String>,
+    // Rule of the "Error in rule X:" block being read
+    error_rule: Option<String>,
+}
 
-    for line in content.lines() {
+impl LogParser {
+    /// Information parsed so far.
+    pub(crate) fn info(&self) -> &SnakemakeLogInfo {
+        &self.info
+    }
+
+    /// Finish the trailing job block and return the parsed information.
+    pub(crate) fn finish(mut self) -> SnakemakeLogInfo {
+        // Handle any remaining rule block at end of file
+        self.end_rule_block(&mut Vec::new());
+        self.update_target_rules();
+        self.info
+    }
+
+    /// Recompute target rules from the rule blocks seen so far.
+    pub(crate) fn update_target_rules(&mut self) {
+        // Target rules are those we've seen in rule blocks that have no outputs
+        // These are rules like "all" that just aggregate other targets
+        self.info.target_rules = self
+            .all_seen_rules
+            .difference(&self.rules_with_outputs)
+            .cloned()
+            .collect();
+    }
+
+    /// Consume one line of the log, pushing any resulting events.
+    pub(crate) fn feed_line(&mut self, line: &str, events: &mut Vec<LogEvent>) {
         let line = line.trim();
 
         // Detect rule block start: "localrule X:" or "rule X:"
@@ -108,47 +237,74 @@ pub fn parse_log_content(content: &str) -> SnakemakeLogInfo {
             && line.ends_with(':')
             && !line.contains("(Rule:")
         {
-            // Save previous rule's output status
-            if let Some(ref rule) = current_rule {
-                all_seen_rules.insert(rule.clone());
-                if current_rule_has_output {
-                    rules_with_outputs.insert(rule.clone());
-                }
-            }
+            self.end_rule_block(events);
 
             // Extract rule name: "localrule X:" or "rule X:" -> "X"
             let rule_part = line
                 .trim_start_matches("localrule ")
                 .trim_start_matches("rule ");
             let rule_name = rule_part.trim_end_matches(':').to_string();
-            current_rule = Some(rule_name);
-            current_rule_has_output = false;
-            continue;
+            self.current_job.rule = rule_name.clone();
+            self.current_rule = Some(rule_name);
+            return;
+        }
+
+        // Fields of the current job block ("output: ...", "jobid: 5", ...)
+        if self.current_rule.is_some() {
+            if line.starts_with("output:") {
+                self.current_rule_has_output = true;
+            }
+            if let Some(jobid) = parse_block_field(&mut self.current_job, line) {
+                self.current_jobid = Some(jobid);
+            }
         }
 
-        // Track if current rule has an output line
-        if current_rule.is_some() && line.starts_with("output:") {
-            current_rule_has_output = true;
+        // Map external (scheduler) job IDs to snakemake job IDs
+        if let Some((jobid, external_id)) = parse_submission(line) {
+            self.attach_external_id(jobid, &external_id);
+            self.info.external_ids.insert(external_id.clone(), jobid);
+            events.push(LogEvent::JobSubmitted { jobid, external_id });
+            return;
+        }
+
+        if let Some(jobid) = parse_finished(line) {
+            events.push(LogEvent::JobFinished { jobid });
+        }
+
+        // Failed job: "Error in rule X:" followed by its fields
+        if let Some(rule) = self.error_rule.as_ref() {
+            if line.is_empty() {
+                self.error_rule = None;
+            } else if let Some(jobid) = line.strip_prefix("jobid:") {
+                if let Ok(jobid) = jobid.trim().parse() {
+                    events.push(LogEvent::JobFailed {
+                        jobid,
+                        rule: rule.clone(),
+                    });
+                }
+                self.error_rule = None;
+            }
+        }
+        if let Some(rule) = line
+            .strip_prefix("Error in rule ")
+            .and_then(|r| r.strip_suffix(':'))
+        {
+            self.error_rule = Some(rule.to_string());
         }
 
         // End of rule block detection: timestamp line or certain keywords
-        if current_rule.is_some()
+        if self.current_rule.is_some()
             && (line.starts_with('[') || line.starts_with("Select jobs") || line.is_empty())
         {
-            if let Some(ref rule) = current_rule {
-                all_seen_rules.insert(rule.clone());
-                if current_rule_has_output {
-                    rules_with_outputs.insert(rule.clone());
-                }
-            }
-            current_rule = None;
-            current_rule_has_output = false;
+            self.end_rule_block(events);
         }
 
+        let info = &mut self.info;
+
         // Parse host
         if line.starts_with("host:") {
             info.host = Some(line.trim_start_matches("host:").trim().to_string());
-            continue;
+            return;
         }
 
         // Parse cores
@@ -156,33 +312,34 @@ pub fn parse_log_content(content: &str) -> SnakemakeLogInfo {
             if let Some(cores_str) = line.strip_prefix("Provided cores:") {
                 info.cores = cores_str.trim().parse().ok();
             }
-            continue;
+            return;
         }
 
         // Detect job stats section
         if line == "Job stats:" {
-            in_job_stats = true;
-            continue;
+            self.in_job_stats = true;
+            return;
         }
 
         // Parse job stats table
-        if in_job_stats {
+        if self.in_job_stats {
             // End of table (empty line or next section)
             if line.is_empty() || line.starts_with("Select jobs") {
-                in_job_stats = false;
-                continue;
+                self.in_job_stats = false;
+                return;
             }
 
             // Skip header line
             if line.starts_with("job") || line.starts_with("---") {
-                continue;
+                return;
             }
 
-            // Parse "rule_name    count" lines
+            // Parse "rule_name    count" lines (Snakemake 7 adds min/max
+            // threads columns after the count)
             let parts: Vec<&str> = line.split_whitespace().collect();
             if parts.len() >= 2 {
                 let rule = parts[0];
-                if let Ok(count) = parts[parts.len() - 1].parse::<usize>() {
+                if let Ok(count) = parts[1].parse::<usize>() {
                     if rule == "total" {
                         info.total_jobs = Some(count);
                     } else {
@@ -190,7 +347,7 @@ pub fn parse_log_content(content: &str) -> SnakemakeLogInfo {
                     }
                 }
             }
-            continue;
+            return;
         }
 
         // Parse progress: "X of Y steps (Z%) done"
@@ -203,18 +360,31 @@ pub fn parse_log_content(content: &str) -> SnakemakeLogInfo {
                     .split_whitespace()
                     .last()
                     .and_then(|s| s.parse().ok());
+                let total: Option<usize> = line[of_idx + 4..]
+                    .split_whitespace()
+                    .next()
+                    .and_then(|s| s.parse().ok());
 
                 if let Some(c) = completed {
                     info.completed_jobs = c;
+                    if let Some(total) = total {
+                        events.push(LogEvent::Progress {
+                            completed: c,
+                            total,
+                        });
+                    }
                 }
             }
-            continue;
+            if line.contains("steps (100%) done") {
+                info.finished = true;
+            }
+            return;
         }
 
         // Detect completion
         if line.contains("steps (100%) done") || line.contains("Nothing to be done") {
             info.finished = true;
-            continue;
+            return;
         }
 
         // Detect errors
@@ -222,6 +392,7 @@ pub fn parse_log_content(content: &str) -> SnakemakeLogInfo {
             info.has_errors = true;
             if line.len() < 200 {
                 info.errors.push(line.to_string());
+                events.push(LogEvent::Error(line.to_string()));
             }
         }
 
@@ -229,25 +400,129 @@ pub fn parse_log_content(content: &str) -> SnakemakeLogInfo {
         if line.contains("Exiting because a job execution failed") {
             info.has_errors = true;
             info.errors.push(line.to_string());
+            events.push(LogEvent::Error(line.to_string()));
         }
     }
 
-    // Handle any remaining rule block at end of file
-    if let Some(ref rule) = current_rule {
-        all_seen_rules.insert(rule.clone());
-        if current_rule_has_output {
-            rules_with_outputs.insert(rule.clone());
+    /// Close the current rule block, recording its output status and job.
+    fn end_rule_block(&mut self, events: &mut Vec<LogEvent>) {
+        // Save previous rule's output status
+        if let Some(rule) = self.current_rule.take() {
+            if self.current_rule_has_output {
+                self.rules_with_outputs.insert(rule.clone());
+            }
+            self.all_seen_rules.insert(rule);
+        }
+        self.current_rule_has_output = false;
+        if let Some(job) = self.finish_job_block() {
+            events.push(LogEvent::JobScheduled(job));
         }
     }
 
-    // Target rules are those we've seen in rule blocks that have no outputs
-    // These are rules like "all" that just aggregate other targets
-    info.target_rules = all_seen_rules
-        .difference(&rules_with_outputs)
-        .cloned()
-        .collect();
+    /// Store the finished job block, replacing an earlier block for the same
+    /// job. Returns the stored job.
+    fn finish_job_block(&mut self) -> Option<LogJob> {
+        let mut job = std::mem::take(&mut self.current_job);
+        let id = self.current_jobid.take()?;
+        job.jobid = id;
+        // Submission lines normally follow the block, but keep what we have
+        if let Some(external_id) = self.pending_external_ids.remove(&id) {
+            job.external_id = Some(external_id);
+        } else if let Some(&i) = self.job_index.get(&id) {
+            job.external_id = self.info.jobs[i].external_id.take();
+        }
+        match self.job_index.get(&id) {
+            Some(&i) => self.info.jobs[i] = job.clone(),
+            None => {
+                self.job_index.insert(id, self.info.jobs.len());
+                self.info.jobs.push(job.clone());
+            }
+        }
+        Some(job)
+    }
 
-    info
+    /// Record the external ID on the job's block, or hold it until the block is stored.
+    fn attach_external_id(&mut self, jobid: u64, external_id: &str) {
+        match self.job_index.get(&jobid) {
+            Some(&i) => self.info.jobs[i].external_id = Some(external_id.to_string()),
+            None => {
+                self.pending_external_ids
+                    .insert(jobid, external_id.to_string());
+            }
+        }
+    }
+}
+
+/// Split a comma-separated field value into its items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Apply one field line of a job block. Returns the job ID for "jobid:" lines.
+fn parse_block_field(job: &mut LogJob, line: &str) -> Option<u64> {
+    let (key, value) = line.split_once(':')?;
+    let value = value.trim();
+    match key {
+        "input" => job.inputs = split_list(value),
+        "output" => job.outputs = split_list(value),
+        "log" => job.log = split_list(value),
+        "jobid" => return value.parse().ok(),
+        "wildcards" if !value.is_empty() => job.wildcards = Some(value.to_string()),
+        "reason" if !value.is_empty() => job.reason = Some(value.to_string()),
+        "threads" => job.threads = value.parse().ok(),
+        "resources" => {
+            job.resources = split_list(value)
+                .iter()
+                .filter_map(|kv| kv.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        }
+        _ => {}
+    }
+    None
+}
+
+/// Parse a job completion line ("Finished jobid: 22 (Rule: x)" or
+/// "Finished job 22.") into the snakemake job ID.
+fn parse_finished(line: &str) -> Option<u64> {
+    let rest = line
+        .strip_prefix("Finished jobid: ")
+        .or_else(|| line.strip_prefix("Finished job "))?;
+    rest.split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Parse a job submission line into (snakemake job ID, external job ID).
+///
+/// Handles the generic cluster executor ("Submitted job 5 with external
+/// jobid 'Submitted batch job 12345'.") and executor plugins ("Job 5 has
+/// been submitted with SLURM jobid 12345 (log: ...).").
+fn parse_submission(line: &str) -> Option<(u64, String)> {
+    if let Some(rest) = line.strip_prefix("Submitted job ") {
+        let (jobid, rest) = rest.split_once(" with external jobid ")?;
+        // The quoted value may be the scheduler's whole submit message
+        let external = rest
+            .trim_end_matches('.')
+            .trim_matches(|c| c == '\'' || c == '"');
+        // SGE says 'Your job 4103 ("snakejob.align.3.sh") has been submitted'
+        let external = match external.strip_prefix("Your job ") {
+            Some(rest) => rest.split_whitespace().next()?,
+            None => external.split_whitespace().last()?,
+        };
+        return Some((jobid.trim().parse().ok()?, external.to_string()));
+    }
+
+    let rest = line.strip_prefix("Job ")?;
+    let (jobid, rest) = rest.split_once(" has been submitted with ")?;
+    let (_, rest) = rest.split_once("jobid ")?;
+    let external = rest.split_whitespace().next()?.trim_end_matches('.');
+    Some((jobid.trim().parse().ok()?, external.to_string()))
 }
 
 #[cfg(test)]
@@ -273,6 +548,22 @@ Select jobs to execute...
         assert_eq!(info.jobs_by_rule.get("process_sample"), Some(&4));
     }
 
+    #[test]
+    fn test_parse_job_stats_with_threads() {
+        let content = r#"
+Job stats:
+job              count    min threads    max threads
+-------------  -------  -------------  -------------
+align_sample         4              8              8
+all                  1              1              1
+total                5              1              8
+"#;
+        let info = parse_log_content(content);
+        assert_eq!(info.total_jobs, Some(5));
+        assert_eq!(info.jobs_by_rule.get("align_sample"), Some(&4));
+        assert_eq!(info.jobs_by_rule.get("all"), Some(&1));
+    }
+
     #[test]
     fn test_parse_progress() {
         let content = r#"
@@ -282,6 +573,11 @@ Finished jobid: 22 (Rule: call_variants)
 "#;
         let info = parse_log_content(content);
         assert_eq!(info.completed_jobs, 5);
+        assert!(!info.finished);
+
+        let info = parse_log_content("27 of 27 steps (100%) done\n");
+        assert_eq!(info.completed_jobs, 27);
+        assert!(info.finished);
     }
 
     #[test]
@@ -345,4 +641,80 @@ localrule all:
         assert!(info.target_rules.contains("all"));
         assert!(!info.target_rules.contains("call_variants"));
     }
+
+    #[test]
+    fn test_parse_external_job_ids() {
+        let content = r#"
+[Thu Dec 18 16:17:43 2025]
+rule call_variants:
+    input: results/aligned/sample6.bam
+    output: results/variants/sample6_chr2.vcf, results/variants/sample6_chr2.vcf.idx
+    jobid: 35
+    wildcards: sample=sample6, chrom=chr2
+
+Submitted job 35 with external jobid 'Submitted batch job 4101'.
+Job 36 has been submitted with SLURM jobid 4102 (log: .snakemake/slurm_logs/rule_x/4102.log).
+Submitted job 37 with external jobid 'Your job 4103 ("snakejob.call_variants.37.sh") has been submitted'.
+"#;
+        let info = parse_log_content(content);
+        assert_eq!(
+            info.jobs[0].outputs,
+            vec![
+                "results/variants/sample6_chr2.vcf",
+                "results/variants/sample6_chr2.vcf.idx"
+            ]
+        );
+        assert_eq!(info.external_ids["4101"], 35);
+        assert_eq!(info.jobs[0].external_id.as_deref(), Some("4101"));
+        assert_eq!(info.external_ids["4102"], 36);
+        assert_eq!(info.external_ids["4103"], 37);
+    }
+
+    #[test]
+    fn test_parse_job_blocks() {
+        let content = r#"
+[Thu Dec 18 16:17:43 2025]
+rule align:
+    input: data/S1_L002.fastq, ref/genome.fa
+    output: results/aligned/S1_L002.bam
+    log: logs/align/S1_L002.log
+    jobid: 12
+    reason: Missing output files: results/aligned/S1_L002.bam
+    wildcards: sample=S1, lane=L002
+    threads: 4
+    resources: mem_mb=8000, disk_mb=1000, tmpdir=/tmp
+
+[Thu Dec 18 16:17:44 2025]
+localrule all:
+    input: results/aligned/S1_L002.bam
+    jobid: 0
+    reason: Input files updated by another job: results/aligned/S1_L002.bam
+"#;
+        let info = parse_log_content(content);
+        assert_eq!(info.jobs.len(), 2);
+
+        let align = &info.jobs[0];
+        assert_eq!(align.jobid, 12);
+        assert_eq!(align.rule, "align");
+        assert_eq!(align.inputs, vec!["data/S1_L002.fastq", "ref/genome.fa"]);
+        assert_eq!(align.outputs, vec!["results/aligned/S1_L002.bam"]);
+        assert_eq!(align.log, vec!["logs/align/S1_L002.log"]);
+        assert_eq!(align.wildcards.as_deref(), Some("sample=S1, lane=L002"));
+        assert_eq!(align.threads, Some(4));
+        assert_eq!(align.resources["mem_mb"], "8000");
+        assert_eq!(align.resources["tmpdir"], "/tmp");
+        assert!(
+            align
+                .reason
+                .as_deref()
+                .unwrap()
+                .starts_with("Missing output")
+        );
+
+        let all = &info.jobs[1];
+        assert_eq!(all.jobid, 0);
+        assert_eq!(all.rule, "all");
+        assert!(all.outputs.is_empty());
+        assert_eq!(all.wildcards, None);
+    }
 }
@@ -0,0 +1,106 @@
+//! Job logs written by snakemake executor plugins.
+//!
+//! The SLURM and LSF executors write each job's output to
+//! `.snakemake/{slurm,lsf}_logs/rule_{rule}/[{wildcards}/]{jobid}.log`.
+//! Without a scheduler to query (e.g., an archived run) these files are the
+//! only record of what the scheduler did with a job.
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+/// Log directories under `.snakemake`, by scheduler name.
+const LOG_DIRS: [(&str, &str); 2] = [("slurm", "slurm_logs"), ("lsf", "lsf_logs")];
+
+/// One job log found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulerLog {
+    /// Scheduler that ran the job ("slurm" or "lsf")
+    pub scheduler: &'static str,
+    /// Rule, from the `rule_{rule}` directory
+    pub rule: Option<String>,
+    /// Scheduler job ID, from the file name
+    pub job_id: String,
+    pub path: Utf8PathBuf,
+}
+
+/// Find every executor job log in the working directory.
+pub fn find_scheduler_logs(working_dir: &Utf8Path) -> Vec<SchedulerLog> {
+    let mut logs = Vec::new();
+    for (scheduler, dir) in LOG_DIRS {
+        let root = working_dir.join(".snakemake").join(dir);
+        collect_logs(scheduler, &root, &root, &mut logs);
+    }
+    logs.sort_by(|a, b| a.path.cmp(&b.path));
+    logs
+}
+
+fn collect_logs(
+    scheduler: &'static str,
+    root: &Utf8Path,
+    dir: &Utf8Path,
+    logs: &mut Vec<SchedulerLog>,
+) {
+    let Ok(entries) = dir.read_dir_utf8() else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_logs(scheduler, root, path, logs);
+            continue;
+        }
+        let Some(job_id) = path.file_name().and_then(|n| n.strip_suffix(".log")) else {
+            continue;
+        };
+        // Array tasks are named "{jobid}_{task}"
+        if !job_id.starts_with(|c: char| c.is_ascii_digit())
+            || !job_id.chars().all(|c| c.is_ascii_digit() || c == '_')
+        {
+            continue;
+        }
+        let rule = path
+            .strip_prefix(root)
+            .ok()
+            .and_then(|rel| rel.components().next())
+            .and_then(|c| c.as_str().strip_prefix("rule_"))
+            .map(str::to_string);
+        logs.push(SchedulerLog {
+            scheduler,
+            rule,
+            job_id: job_id.to_string(),
+            path: path.to_path_buf(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_scheduler_logs() {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(temp.path()).unwrap();
+        let slurm = dir.join(".snakemake/slurm_logs/rule_align/sample=S1");
+        let lsf = dir.join(".snakemake/lsf_logs/rule_sort");
+        fs::create_dir_all(&slurm).unwrap();
+        fs::create_dir_all(&lsf).unwrap();
+        fs::write(slurm.join("4102.log"), "").unwrap();
+        fs::write(slurm.join("notes.txt"), "").unwrap();
+        fs::write(lsf.join("881_3.log"), "").unwrap();
+        fs::write(lsf.join("summary.log"), "").unwrap();
+
+        let logs = find_scheduler_logs(dir);
+        assert_eq!(
+            logs.iter()
+                .map(|l| (l.scheduler, l.rule.as_deref(), l.job_id.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("lsf", Some("sort"), "881_3"),
+                ("slurm", Some("align"), "4102")
+            ]
+        );
+        assert!(find_scheduler_logs(&dir.join("missing")).is_empty());
+    }
+}
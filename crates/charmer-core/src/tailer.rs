@@ -0,0 +1,240 @@
+//! Incremental reader for the main snakemake log.
+//!
+//! Large runs produce logs of hundreds of MB, so instead of re-parsing the
+//! whole file on every refresh, [`MainLogTailer`] remembers how far it has
+//! read and feeds only the appended lines to a persistent parser.
+
+use crate::main_log::{LogEvent, LogParser, SnakemakeLogInfo, find_latest_log};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Follows the latest `.snakemake.log` of a working directory.
+///
+/// Starts over from the beginning when a newer log file appears (a new run
+/// or rotation), or when the current file is truncated or replaced.
+#[derive(Debug)]
+pub struct MainLogTailer {
+    working_dir: Utf8PathBuf,
+    /// Log file being read
+    path: Option<Utf8PathBuf>,
+    /// Identity of the file at `path`, to notice replacement
+    file_id: Option<u64>,
+    /// Bytes consumed so far
+    offset: u64,
+    /// Trailing bytes of an incomplete line
+    partial: Vec<u8>,
+    parser: LogParser,
+}
+
+impl MainLogTailer {
+    /// Create a tailer for the logs under `working_dir/.snakemake/log`.
+    pub fn new(working_dir: impl Into<Utf8PathBuf>) -> Self {
+        Self {
+            working_dir: working_dir.into(),
+            path: None,
+            file_id: None,
+            offset: 0,
+            partial: Vec::new(),
+            parser: LogParser::default(),
+        }
+    }
+
+    /// Log file currently being followed.
+    pub fn path(&self) -> Option<&Utf8Path> {
+        self.path.as_deref()
+    }
+
+    /// Information accumulated from everything read so far.
+    pub fn info(&self) -> &SnakemakeLogInfo {
+        self.parser.info()
+    }
+
+    /// Read lines appended since the last poll and return what happened.
+    ///
+    /// Returns no events when there is no log yet or nothing new was written.
+    pub fn poll(&mut self) -> io::Result<Vec<LogEvent>> {
+        let mut events = Vec::new();
+        let Some(latest) = find_latest_log(&self.working_dir) else {
+            return Ok(events);
+        };
+
+        let mut file = File::open(&latest)?;
+        let metadata = file.metadata()?;
+        let file_id = file_identity(&metadata);
+        let len = metadata.len();
+
+        let rotated = self.path.as_ref() != Some(&latest) || self.file_id != file_id;
+        if rotated || len < self.offset {
+            self.restart(latest, file_id, &mut events);
+        }
+        if len == self.offset {
+            return Ok(events);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let read = file
+            .take(len - self.offset)
+            .read_to_end(&mut self.partial)?;
+        self.offset += read as u64;
+
+        // Only complete lines are parsed; the rest waits for the next poll
+        if let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') {
+            let lines: Vec<u8> = self.partial.drain(..=end).collect();
+            for line in String::from_utf8_lossy(&lines).lines() {
+                self.parser.feed_line(line, &mut events);
+            }
+            self.parser.update_target_rules();
+        }
+
+        Ok(events)
+    }
+
+    /// Forget everything read so far and start reading `path` from the top.
+    fn restart(&mut self, path: Utf8PathBuf, file_id: Option<u64>, events: &mut Vec<LogEvent>) {
+        tracing::debug!("Reading main log from the start: {}", path);
+        self.parser = LogParser::default();
+        self.partial.clear();
+        self.offset = 0;
+        self.file_id = file_id;
+        self.path = Some(path.clone());
+        events.push(LogEvent::LogOpened { path });
+    }
+}
+
+#[cfg(unix)]
+fn file_identity(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn log_dir(temp: &TempDir) -> Utf8PathBuf {
+        let dir = Utf8Path::from_path(temp.path())
+            .unwrap()
+            .join(".snakemake/log");
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn append(path: &Utf8Path, content: &str) {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    const JOB_BLOCK: &str = "[Mon Jan 15 10:00:00 2024]
+rule align:
+    input: raw/S1.fq
+    output: aligned/S1.bam
+    jobid: 3
+    wildcards: sample=S1
+
+";
+
+    #[test]
+    fn test_tail_appended_lines() {
+        let temp = TempDir::new().unwrap();
+        let log = log_dir(&temp).join("2024-01-15T100000.snakemake.log");
+        let mut tailer = MainLogTailer::new(Utf8Path::from_path(temp.path()).unwrap());
+
+        assert!(tailer.poll().unwrap().is_empty());
+
+        append(&log, JOB_BLOCK);
+        let events = tailer.poll().unwrap();
+        assert!(matches!(&events[0], LogEvent::LogOpened { path } if path == &log));
+        assert!(matches!(&events[1], LogEvent::JobScheduled(job) if job.jobid == 3));
+        assert!(tailer.poll().unwrap().is_empty());
+
+        // A line written in two pieces is parsed once it is complete
+        append(&log, "Submitted job 3 with external jobid 'Submitted batch");
+        assert!(tailer.poll().unwrap().is_empty());
+        append(&log, " job 12345'.\n");
+        assert_eq!(
+            tailer.poll().unwrap(),
+            vec![LogEvent::JobSubmitted {
+                jobid: 3,
+                external_id: "12345".to_string()
+            }]
+        );
+        assert_eq!(tailer.info().jobs[0].external_id.as_deref(), Some("12345"));
+
+        append(
+            &log,
+            "[Mon Jan 15 10:05:00 2024]\nFinished jobid: 3 (Rule: align)\n1 of 2 steps (50%) done\n",
+        );
+        assert_eq!(
+            tailer.poll().unwrap(),
+            vec![
+                LogEvent::JobFinished { jobid: 3 },
+                LogEvent::Progress {
+                    completed: 1,
+                    total: 2
+                }
+            ]
+        );
+        assert_eq!(tailer.info().completed_jobs, 1);
+    }
+
+    #[test]
+    fn test_tail_truncated_log() {
+        let temp = TempDir::new().unwrap();
+        let log = log_dir(&temp).join("2024-01-15T100000.snakemake.log");
+        let mut tailer = MainLogTailer::new(Utf8Path::from_path(temp.path()).unwrap());
+
+        append(&log, JOB_BLOCK);
+        tailer.poll().unwrap();
+        assert_eq!(tailer.info().jobs.len(), 1);
+
+        fs::write(&log, "Provided cores: 8\n").unwrap();
+        let events = tailer.poll().unwrap();
+        assert!(matches!(&events[0], LogEvent::LogOpened { .. }));
+        assert!(tailer.info().jobs.is_empty());
+        assert_eq!(tailer.info().cores, Some(8));
+    }
+
+    #[test]
+    fn test_tail_rotated_log() {
+        let temp = TempDir::new().unwrap();
+        let dir = log_dir(&temp);
+        let first = dir.join("2024-01-15T100000.snakemake.log");
+        let mut tailer = MainLogTailer::new(Utf8Path::from_path(temp.path()).unwrap());
+
+        append(&first, JOB_BLOCK);
+        tailer.poll().unwrap();
+
+        // A newer log file takes over
+        let second = dir.join("2024-01-15T110000.snakemake.log");
+        append(&second, "Error in rule align:\n    jobid: 7\n\n");
+        File::options()
+            .write(true)
+            .open(&second)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+
+        let events = tailer.poll().unwrap();
+        assert!(matches!(&events[0], LogEvent::LogOpened { path } if path == &second));
+        assert!(events.contains(&LogEvent::JobFailed {
+            jobid: 7,
+            rule: "align".to_string()
+        }));
+        assert_eq!(tailer.path(), Some(second.as_path()));
+        assert!(tailer.info().jobs.is_empty());
+        assert!(tailer.info().has_errors);
+    }
+}
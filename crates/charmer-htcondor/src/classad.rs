@@ -0,0 +1,190 @@
+//! Parse JSON ClassAds from `condor_q -json` / `condor_history -json`.
+//!
+//! Snakemake job metadata is read from custom ClassAd attributes set at
+//! submit time (`+SnakemakeRule = "align"`, `+SnakemakeWildcards = "..."`),
+//! or a single `SnakemakeComment` in the SLURM comment format.
+
+use crate::types::{HoldReason, HtcondorJob, HtcondorJobState};
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClassAdError {
+    #[error("Failed to parse ClassAd JSON: {0}")]
+    ParseError(String),
+}
+
+fn attr_i64(ad: &Value, name: &str) -> Option<i64> {
+    let value = ad.get(name)?;
+    value
+        .as_i64()
+        .or_else(|| value.as_f64().map(|v| v as i64))
+        .or_else(|| value.as_bool().map(i64::from))
+}
+
+fn attr_str(ad: &Value, name: &str) -> Option<String> {
+    ad.get(name)
+        .and_then(|v| v.as_str())
+        .and_then(charmer_parsers::non_empty_string)
+}
+
+/// Unix timestamp attribute; HTCondor uses 0 for "never".
+fn attr_time(ad: &Value, name: &str) -> Option<DateTime<Utc>> {
+    attr_i64(ad, name)
+        .filter(|&ts| ts > 0)
+        .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+}
+
+/// Build the snakemake comment ("rule_{rule}_wildcards_{wildcards}") from
+/// ClassAd attributes, so it can be parsed like a SLURM comment.
+pub fn snakemake_comment(ad: &Value) -> Option<String> {
+    if let Some(comment) = attr_str(ad, "SnakemakeComment") {
+        return Some(comment);
+    }
+
+    let rule = attr_str(ad, "SnakemakeRule")?;
+    Some(match attr_str(ad, "SnakemakeWildcards") {
+        Some(wildcards) => format!("rule_{}_wildcards_{}", rule, wildcards),
+        None => format!("rule_{}", rule),
+    })
+}
+
+/// Parse the `JobStatus` attribute (with hold and exit details).
+fn parse_state(ad: &Value) -> HtcondorJobState {
+    match attr_i64(ad, "JobStatus").unwrap_or(0) {
+        1 => HtcondorJobState::Idle,
+        2 => HtcondorJobState::Running,
+        3 => HtcondorJobState::Removed {
+            reason: attr_str(ad, "RemoveReason"),
+        },
+        4 => HtcondorJobState::Completed {
+            exit_code: attr_i64(ad, "ExitCode").unwrap_or(0) as i32,
+        },
+        5 => HtcondorJobState::Held(HoldReason {
+            code: attr_i64(ad, "HoldReasonCode").map(|v| v as i32),
+            subcode: attr_i64(ad, "HoldReasonSubCode").map(|v| v as i32),
+            message: attr_str(ad, "HoldReason").unwrap_or_default(),
+        }),
+        6 => HtcondorJobState::TransferringOutput,
+        7 => HtcondorJobState::Suspended,
+        other => HtcondorJobState::Unknown(other),
+    }
+}
+
+/// Parse a single job ClassAd.
+fn parse_job(ad: &Value) -> HtcondorJob {
+    let job_id = format!(
+        "{}.{}",
+        attr_i64(ad, "ClusterId").unwrap_or(0),
+        attr_i64(ad, "ProcId").unwrap_or(0)
+    );
+
+    let by_signal = attr_i64(ad, "ExitBySignal").unwrap_or(0) != 0;
+    let cpu_seconds = match (attr_i64(ad, "RemoteUserCpu"), attr_i64(ad, "RemoteSysCpu")) {
+        (None, None) => None,
+        (user, sys) => Some((user.unwrap_or(0) + sys.unwrap_or(0)) as u64),
+    };
+
+    HtcondorJob {
+        job_id,
+        name: attr_str(ad, "JobBatchName").unwrap_or_default(),
+        state: parse_state(ad),
+        submit_time: attr_time(ad, "QDate"),
+        start_time: attr_time(ad, "JobCurrentStartDate").or_else(|| attr_time(ad, "JobStartDate")),
+        end_time: attr_time(ad, "CompletionDate"),
+        remote_host: attr_str(ad, "RemoteHost").or_else(|| attr_str(ad, "LastRemoteHost")),
+        request_cpus: attr_i64(ad, "RequestCpus").map(|v| v as u32),
+        request_memory_mb: attr_i64(ad, "RequestMemory").map(|v| v as u64),
+        memory_usage_mb: attr_i64(ad, "MemoryUsage").map(|v| v as u64),
+        allowed_duration: attr_i64(ad, "AllowedJobDuration").map(|v| Duration::from_secs(v as u64)),
+        wall_clock_seconds: attr_i64(ad, "RemoteWallClockTime").map(|v| v as u64),
+        cpu_seconds,
+        exit_signal: if by_signal {
+            attr_i64(ad, "ExitSignal").map(|v| v as i32)
+        } else {
+            None
+        },
+        comment: snakemake_comment(ad),
+    }
+}
+
+/// Parse `-json` output (an array of ClassAds) into jobs.
+pub fn parse_classads(output: &str) -> Result<Vec<HtcondorJob>, ClassAdError> {
+    // condor_q prints nothing at all when there are no jobs
+    if output.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ads: Vec<Value> =
+        serde_json::from_str(output).map_err(|e| ClassAdError::ParseError(e.to_string()))?;
+
+    Ok(ads.iter().map(parse_job).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_snakemake_comment() {
+        let ad = json!({"SnakemakeRule": "align", "SnakemakeWildcards": "sample=S1"});
+        assert_eq!(
+            snakemake_comment(&ad).as_deref(),
+            Some("rule_align_wildcards_sample=S1")
+        );
+
+        let ad = json!({"SnakemakeRule": "index"});
+        assert_eq!(snakemake_comment(&ad).as_deref(), Some("rule_index"));
+
+        let ad = json!({"SnakemakeComment": "rule_sort_wildcards_sample=S2"});
+        assert_eq!(
+            snakemake_comment(&ad).as_deref(),
+            Some("rule_sort_wildcards_sample=S2")
+        );
+
+        assert!(snakemake_comment(&json!({"Cmd": "/bin/sh"})).is_none());
+    }
+
+    #[test]
+    fn test_parse_condor_q_fixture() {
+        let jobs = parse_classads(include_str!("../tests/fixtures/condor_q.json")).unwrap();
+        assert_eq!(jobs.len(), 3);
+
+        let running = &jobs[0];
+        assert_eq!(running.job_id, "812.0");
+        assert_eq!(running.name, "run-3f2a");
+        assert_eq!(running.state, HtcondorJobState::Running);
+        assert_eq!(
+            running.remote_host.as_deref(),
+            Some("slot1@exec07.example.org")
+        );
+        assert_eq!(running.request_cpus, Some(4));
+        assert_eq!(running.request_memory_mb, Some(8192));
+        assert_eq!(
+            running.comment.as_deref(),
+            Some("rule_align_wildcards_sample=S1")
+        );
+        assert!(running.start_time.is_some());
+        assert!(running.end_time.is_none());
+
+        assert_eq!(jobs[1].state, HtcondorJobState::Idle);
+
+        match &jobs[2].state {
+            HtcondorJobState::Held(reason) => {
+                assert_eq!(reason.code, Some(34));
+                assert!(reason.message.contains("memory"));
+            }
+            other => panic!("expected held, got {:?}", other),
+        }
+        assert_eq!(jobs[2].memory_usage_mb, Some(2200));
+    }
+
+    #[test]
+    fn test_parse_empty_output() {
+        assert!(parse_classads("").unwrap().is_empty());
+        assert!(parse_classads("[]").unwrap().is_empty());
+    }
+}
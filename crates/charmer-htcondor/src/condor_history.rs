@@ -0,0 +1,87 @@
+//! Query finished HTCondor jobs via condor_history.
+
+use crate::classad::parse_classads;
+use crate::condor_q::batch_name_constraint;
+use crate::types::HtcondorJob;
+use charmer_parsers::run_command;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Error, Debug)]
+pub enum CondorHistoryError {
+    #[error("Failed to execute condor_history: {0}")]
+    ExecutionError(String),
+    #[error("Failed to parse condor_history output: {0}")]
+    ParseError(String),
+}
+
+/// Query job history with `condor_history -json`.
+pub async fn query_condor_history(
+    batch_name_filter: Option<&str>,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<HtcondorJob>, CondorHistoryError> {
+    let user = std::env::var("USER").unwrap_or_default();
+
+    let mut cmd = Command::new("condor_history");
+    cmd.args([&user, "-json"]);
+
+    if let Some(name) = batch_name_filter {
+        cmd.args(["-constraint", &batch_name_constraint(name)]);
+    }
+
+    // Stop scanning the history file once older jobs are reached
+    if let Some(since) = since {
+        cmd.args(["-completedsince", &since.timestamp().to_string()]);
+    }
+
+    let stdout = run_command(&mut cmd, "condor_history")
+        .await
+        .map_err(|e| CondorHistoryError::ExecutionError(e.to_string()))?;
+
+    parse_classads(&stdout).map_err(|e| CondorHistoryError::ParseError(e.to_string()))
+}
+
+/// Query the history record for a single job ID ("ClusterId.ProcId").
+pub(crate) async fn query_condor_history_job(
+    job_id: &str,
+) -> Result<Option<HtcondorJob>, CondorHistoryError> {
+    let mut cmd = Command::new("condor_history");
+    cmd.args(["-json", "-limit", "1", job_id]);
+
+    let stdout = run_command(&mut cmd, "condor_history")
+        .await
+        .map_err(|e| CondorHistoryError::ExecutionError(e.to_string()))?;
+
+    Ok(parse_classads(&stdout)
+        .map_err(|e| CondorHistoryError::ParseError(e.to_string()))?
+        .into_iter()
+        .next())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::classad::parse_classads;
+    use crate::types::HtcondorJobState;
+
+    #[test]
+    fn test_parse_history_fixture() {
+        let jobs = parse_classads(include_str!("../tests/fixtures/condor_history.json")).unwrap();
+        assert_eq!(jobs.len(), 3);
+
+        assert_eq!(jobs[0].state, HtcondorJobState::Completed { exit_code: 0 });
+        assert_eq!(jobs[0].cpu_seconds, Some(2280));
+        assert_eq!(jobs[0].wall_clock_seconds, Some(1200));
+        assert!(jobs[0].end_time.is_some());
+        assert_eq!(
+            jobs[0].remote_host.as_deref(),
+            Some("slot1@exec03.example.org")
+        );
+
+        assert_eq!(jobs[1].state, HtcondorJobState::Completed { exit_code: 2 });
+        assert!(matches!(
+            jobs[2].state,
+            HtcondorJobState::Removed { reason: Some(_) }
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+//! Query active HTCondor jobs via condor_q.
+
+use crate::classad::parse_classads;
+use crate::types::HtcondorJob;
+use charmer_parsers::run_command;
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Error, Debug)]
+pub enum CondorQError {
+    #[error("Failed to execute condor_q: {0}")]
+    ExecutionError(String),
+    #[error("Failed to parse condor_q output: {0}")]
+    ParseError(String),
+}
+
+/// Build a ClassAd constraint matching a batch name.
+pub(crate) fn batch_name_constraint(name: &str) -> String {
+    format!("JobBatchName == \"{}\"", name.replace('"', "\\\""))
+}
+
+/// Query active jobs with `condor_q -json`.
+///
+/// condor_q only lists the current user's jobs by default.
+pub async fn query_condor_q(
+    batch_name_filter: Option<&str>,
+) -> Result<Vec<HtcondorJob>, CondorQError> {
+    let mut cmd = Command::new("condor_q");
+    cmd.arg("-json");
+
+    if let Some(name) = batch_name_filter {
+        cmd.args(["-constraint", &batch_name_constraint(name)]);
+    }
+
+    let stdout = run_command(&mut cmd, "condor_q")
+        .await
+        .map_err(|e| CondorQError::ExecutionError(e.to_string()))?;
+
+    parse_classads(&stdout).map_err(|e| CondorQError::ParseError(e.to_string()))
+}
+
+/// Query a single active job by ID ("ClusterId.ProcId").
+pub(crate) async fn query_condor_q_job(job_id: &str) -> Result<Option<HtcondorJob>, CondorQError> {
+    let mut cmd = Command::new("condor_q");
+    cmd.args(["-json", job_id]);
+
+    let stdout = run_command(&mut cmd, "condor_q")
+        .await
+        .map_err(|e| CondorQError::ExecutionError(e.to_string()))?;
+
+    Ok(parse_classads(&stdout)
+        .map_err(|e| CondorQError::ParseError(e.to_string()))?
+        .into_iter()
+        .next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_name_constraint() {
+        assert_eq!(
+            batch_name_constraint("run-3f2a"),
+            "JobBatchName == \"run-3f2a\""
+        );
+        assert_eq!(batch_name_constraint("a\"b"), "JobBatchName == \"a\\\"b\"");
+    }
+}
@@ -0,0 +1,335 @@
+//! HTCondor job failure analysis.
+//!
+//! Map hold reasons and exit status to failure modes and provide
+//! actionable suggestions.
+
+use crate::condor_history::query_condor_history_job;
+use crate::condor_q::query_condor_q_job;
+use crate::types::{HoldReason, HtcondorJob, HtcondorJobState};
+use charmer_parsers::format_duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FailureError {
+    #[error("Failed to query job: {0}")]
+    ExecutionError(String),
+    #[error("Job not found: {0}")]
+    NotFound(String),
+}
+
+/// `HoldReasonCode` values charmer distinguishes.
+/// See the HTCondor manual, "Job ClassAd Attributes".
+mod hold_code {
+    pub const USER_REQUEST: i32 = 1;
+    pub const TRANSFER_OUTPUT_ERROR: i32 = 12;
+    pub const TRANSFER_INPUT_ERROR: i32 = 13;
+    pub const MAX_TRANSFER_INPUT_SIZE_EXCEEDED: i32 = 32;
+    pub const MAX_TRANSFER_OUTPUT_SIZE_EXCEEDED: i32 = 33;
+    pub const JOB_OUT_OF_RESOURCES: i32 = 34;
+    pub const JOB_DURATION_EXCEEDED: i32 = 46;
+    pub const JOB_EXECUTE_EXCEEDED: i32 = 47;
+}
+
+/// Failure mode classification for HTCondor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureMode {
+    /// Job held for exceeding its memory request
+    OutOfMemory {
+        used_mb: u64,
+        requested_mb: u64,
+        suggested_mb: u64,
+    },
+    /// Job held for exceeding its allowed duration
+    Timeout {
+        elapsed_seconds: u64,
+        limit_seconds: u64,
+        suggested_seconds: u64,
+    },
+    /// Job completed with non-zero exit code or was killed by a signal
+    ExitCode { code: i32, signal: Option<i32> },
+    /// Job was removed (condor_rm or policy)
+    Removed { reason: Option<String> },
+    /// File transfer to or from the execute node failed
+    TransferFailure { reason: String },
+    /// Job is held for another reason
+    Held { code: Option<i32>, reason: String },
+    /// Unknown failure mode
+    Unknown { state: String },
+}
+
+/// Detailed failure analysis result for HTCondor.
+#[derive(Debug, Clone)]
+pub struct FailureAnalysis {
+    /// Job ID ("ClusterId.ProcId")
+    pub job_id: String,
+    /// Classified failure mode
+    pub mode: FailureMode,
+    /// Human-readable explanation
+    pub explanation: String,
+    /// Suggested fix
+    pub suggestion: String,
+    /// Exit code (completed jobs only)
+    pub exit_code: Option<i32>,
+    /// Peak memory (MB)
+    pub memory_usage_mb: Option<u64>,
+    /// Requested memory (MB)
+    pub request_memory_mb: Option<u64>,
+    /// Wall clock time (seconds)
+    pub wall_clock_seconds: Option<u64>,
+    /// Allowed job duration (seconds)
+    pub allowed_duration_seconds: Option<u64>,
+}
+
+impl FailureAnalysis {
+    /// Generate explanation and suggestion based on failure mode.
+    fn generate_messages(mode: &FailureMode) -> (String, String) {
+        match mode {
+            FailureMode::OutOfMemory {
+                used_mb,
+                requested_mb,
+                suggested_mb,
+            } => {
+                let explanation = format!(
+                    "Job exceeded memory limit. Used {:.1} GB but only {:.1} GB was allocated.",
+                    *used_mb as f64 / 1024.0,
+                    *requested_mb as f64 / 1024.0
+                );
+                let suggestion = format!(
+                    "Increase memory to at least {:.1} GB. In your Snakefile, add:\n  resources: mem_mb={}",
+                    *suggested_mb as f64 / 1024.0,
+                    suggested_mb
+                );
+                (explanation, suggestion)
+            }
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                suggested_seconds,
+            } => {
+                let explanation = format!(
+                    "Job exceeded time limit. Ran for {} but limit was {}.",
+                    format_duration(*elapsed_seconds),
+                    format_duration(*limit_seconds)
+                );
+                let suggestion = format!(
+                    "Increase time limit to at least {}. In your Snakefile, add:\n  resources: runtime={}",
+                    format_duration(*suggested_seconds),
+                    suggested_seconds.div_ceil(60)
+                );
+                (explanation, suggestion)
+            }
+            FailureMode::ExitCode { code, signal } => {
+                let explanation = match signal {
+                    Some(sig) => format!("Job was killed by signal {}", sig),
+                    None if *code == 1 => "Job failed with exit code 1 (general error)".to_string(),
+                    None => format!("Job failed with exit code {}", code),
+                };
+                (
+                    explanation,
+                    "Check the job's stderr log for error details.".to_string(),
+                )
+            }
+            FailureMode::Removed { reason } => (
+                match reason {
+                    Some(r) => format!("Job was removed {}", r),
+                    None => "Job was removed".to_string(),
+                },
+                "Consider if this was intentional or due to dependency failure.".to_string(),
+            ),
+            FailureMode::TransferFailure { reason } => (
+                format!("File transfer failed: {}", reason),
+                "Check that input files exist and the output directory is writable.".to_string(),
+            ),
+            FailureMode::Held { code, reason } => (
+                match code {
+                    Some(c) => format!("Job is held (code {}): {}", c, reason),
+                    None => format!("Job is held: {}", reason),
+                },
+                "Fix the cause, then run condor_release on the job.".to_string(),
+            ),
+            FailureMode::Unknown { state } => (
+                format!("Job failed: {}", state),
+                "Check the job's HTCondor user log for details.".to_string(),
+            ),
+        }
+    }
+}
+
+/// Query detailed failure information for an HTCondor job.
+///
+/// Held jobs are still in the queue, so condor_q is tried before
+/// condor_history.
+pub async fn analyze_failure(job_id: &str) -> Result<FailureAnalysis, FailureError> {
+    let active = query_condor_q_job(job_id)
+        .await
+        .map_err(|e| FailureError::ExecutionError(e.to_string()))?;
+
+    let job = match active {
+        Some(job) => job,
+        None => query_condor_history_job(job_id)
+            .await
+            .map_err(|e| FailureError::ExecutionError(e.to_string()))?
+            .ok_or_else(|| FailureError::NotFound(job_id.to_string()))?,
+    };
+
+    Ok(analyze_job(&job))
+}
+
+/// Classify a hold reason.
+fn classify_hold(job: &HtcondorJob, hold: &HoldReason) -> FailureMode {
+    let message = hold.message.to_lowercase();
+
+    match hold.code {
+        Some(hold_code::JOB_OUT_OF_RESOURCES) => out_of_memory(job),
+        Some(hold_code::JOB_DURATION_EXCEEDED | hold_code::JOB_EXECUTE_EXCEEDED) => timeout(job),
+        Some(
+            hold_code::TRANSFER_OUTPUT_ERROR
+            | hold_code::TRANSFER_INPUT_ERROR
+            | hold_code::MAX_TRANSFER_INPUT_SIZE_EXCEEDED
+            | hold_code::MAX_TRANSFER_OUTPUT_SIZE_EXCEEDED,
+        ) => FailureMode::TransferFailure {
+            reason: hold.message.clone(),
+        },
+        // Periodic hold policies only leave a message to go on
+        Some(code) if code != hold_code::USER_REQUEST && message.contains("memory") => {
+            out_of_memory(job)
+        }
+        Some(code) if code != hold_code::USER_REQUEST && message.contains("duration") => {
+            timeout(job)
+        }
+        code => FailureMode::Held {
+            code,
+            reason: hold.message.clone(),
+        },
+    }
+}
+
+fn out_of_memory(job: &HtcondorJob) -> FailureMode {
+    let used = job.memory_usage_mb.unwrap_or(0);
+    let requested = job.request_memory_mb.unwrap_or(0);
+    let suggested = ((used as f64 * 1.5) / 1024.0).ceil() as u64 * 1024;
+    FailureMode::OutOfMemory {
+        used_mb: used,
+        requested_mb: requested,
+        suggested_mb: suggested.max(requested + 1024),
+    }
+}
+
+fn timeout(job: &HtcondorJob) -> FailureMode {
+    let elapsed = job.wall_clock_seconds.unwrap_or(0);
+    let limit = job.allowed_duration.map(|d| d.as_secs()).unwrap_or(0);
+    let suggested = (elapsed as f64 * 1.5) as u64;
+    FailureMode::Timeout {
+        elapsed_seconds: elapsed,
+        limit_seconds: limit,
+        suggested_seconds: suggested.max(limit + 3600),
+    }
+}
+
+/// Classify a failed, held or removed HTCondor job.
+pub fn analyze_job(job: &HtcondorJob) -> FailureAnalysis {
+    let (mode, exit_code) = match &job.state {
+        HtcondorJobState::Held(hold) => (classify_hold(job, hold), None),
+        HtcondorJobState::Removed { reason } => (
+            FailureMode::Removed {
+                reason: reason.clone(),
+            },
+            None,
+        ),
+        HtcondorJobState::Completed { exit_code } => (
+            FailureMode::ExitCode {
+                code: *exit_code,
+                signal: job.exit_signal,
+            },
+            Some(*exit_code),
+        ),
+        other => (
+            FailureMode::Unknown {
+                state: format!("{:?}", other),
+            },
+            None,
+        ),
+    };
+
+    let (explanation, suggestion) = FailureAnalysis::generate_messages(&mode);
+
+    FailureAnalysis {
+        job_id: job.job_id.clone(),
+        mode,
+        explanation,
+        suggestion,
+        exit_code,
+        memory_usage_mb: job.memory_usage_mb,
+        request_memory_mb: job.request_memory_mb,
+        wall_clock_seconds: job.wall_clock_seconds,
+        allowed_duration_seconds: job.allowed_duration.map(|d| d.as_secs()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classad::parse_classads;
+    use std::time::Duration;
+
+    fn queue_jobs() -> Vec<HtcondorJob> {
+        parse_classads(include_str!("../tests/fixtures/condor_q.json")).unwrap()
+    }
+
+    #[test]
+    fn test_memory_hold() {
+        let analysis = analyze_job(&queue_jobs()[2]);
+        assert_eq!(
+            analysis.mode,
+            FailureMode::OutOfMemory {
+                used_mb: 2200,
+                requested_mb: 2048,
+                suggested_mb: 4096,
+            }
+        );
+    }
+
+    #[test]
+    fn test_duration_hold() {
+        let mut job = queue_jobs()[2].clone();
+        job.allowed_duration = Some(Duration::from_secs(360));
+        job.wall_clock_seconds = Some(380);
+        job.state = HtcondorJobState::Held(HoldReason {
+            code: Some(46),
+            subcode: None,
+            message: "The job exceeded allowed job duration of 6m 0s".to_string(),
+        });
+        assert!(matches!(
+            analyze_job(&job).mode,
+            FailureMode::Timeout {
+                elapsed_seconds: 380,
+                limit_seconds: 360,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_other_holds() {
+        let mut job = queue_jobs()[2].clone();
+        job.state = HtcondorJobState::Held(HoldReason {
+            code: Some(13),
+            subcode: Some(2),
+            message: "Transfer input files failure".to_string(),
+        });
+        assert!(matches!(
+            analyze_job(&job).mode,
+            FailureMode::TransferFailure { .. }
+        ));
+
+        job.state = HtcondorJobState::Held(HoldReason {
+            code: Some(1),
+            subcode: None,
+            message: "via condor_hold (by user alice): memory check".to_string(),
+        });
+        assert!(matches!(
+            analyze_job(&job).mode,
+            FailureMode::Held { code: Some(1), .. }
+        ));
+    }
+}
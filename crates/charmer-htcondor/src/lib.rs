@@ -0,0 +1,17 @@
+//! HTCondor integration for charmer.
+//!
+//! Query job status via `condor_q -json` and `condor_history -json`.
+
+pub mod classad;
+pub mod condor_history;
+pub mod condor_q;
+pub mod failure;
+pub mod scheduler;
+pub mod types;
+
+pub use classad::{ClassAdError, parse_classads, snakemake_comment};
+pub use condor_history::{CondorHistoryError, query_condor_history};
+pub use condor_q::{CondorQError, query_condor_q};
+pub use failure::{FailureAnalysis, FailureError, FailureMode, analyze_failure, analyze_job};
+pub use scheduler::HtcondorScheduler;
+pub use types::{HoldReason, HtcondorJob, HtcondorJobState};
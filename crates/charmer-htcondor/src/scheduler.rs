@@ -0,0 +1,220 @@
+//! `Scheduler` trait implementation for HTCondor.
+
+use crate::condor_history::{self, query_condor_history_job};
+use crate::condor_q;
+use crate::failure::{self, FailureAnalysis, FailureMode};
+use crate::types::{HtcondorJob, HtcondorJobState};
+use async_trait::async_trait;
+use charmer_scheduler::{
+    ResourceUsage, Scheduler, SchedulerCapabilities, SchedulerError, SchedulerJob,
+    SchedulerJobState,
+};
+use chrono::{DateTime, Utc};
+
+/// HTCondor backend (condor_q / condor_history).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HtcondorScheduler;
+
+#[async_trait]
+impl Scheduler for HtcondorScheduler {
+    fn name(&self) -> &'static str {
+        "htcondor"
+    }
+
+    fn capabilities(&self) -> SchedulerCapabilities {
+        SchedulerCapabilities {
+            history: true,
+            resource_usage: true,
+            failure_analysis: true,
+            job_control: false,
+            run_names: true,
+        }
+    }
+
+    async fn is_available(&self) -> bool {
+        tokio::process::Command::new("condor_version")
+            .output()
+            .await
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    async fn query_active(
+        &self,
+        run_uuid: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = condor_q::query_condor_q(run_uuid)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_history(
+        &self,
+        run_uuid: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = condor_history::query_condor_history(run_uuid, since)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_resource_usage(
+        &self,
+        job_id: &str,
+    ) -> Result<Option<ResourceUsage>, SchedulerError> {
+        let job = query_condor_history_job(job_id)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(job.map(|j| ResourceUsage {
+            max_rss_mb: j.memory_usage_mb,
+            elapsed_seconds: j.wall_clock_seconds,
+            cpu_time_seconds: j.cpu_seconds,
+            ..Default::default()
+        }))
+    }
+
+    async fn analyze_failure(
+        &self,
+        job_id: &str,
+    ) -> Result<charmer_scheduler::FailureAnalysis, SchedulerError> {
+        let analysis = failure::analyze_failure(job_id)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok((&analysis).into())
+    }
+}
+
+impl From<HtcondorJobState> for SchedulerJobState {
+    fn from(state: HtcondorJobState) -> Self {
+        match state {
+            HtcondorJobState::Idle => Self::Pending,
+            HtcondorJobState::Running | HtcondorJobState::TransferringOutput => Self::Running,
+            HtcondorJobState::Suspended => Self::Suspended,
+            HtcondorJobState::Removed { .. } => Self::Cancelled,
+            HtcondorJobState::Completed { exit_code: 0 } => Self::Completed,
+            HtcondorJobState::Completed { exit_code } => Self::Failed {
+                exit_code,
+                message: format!("Job exited with code {}", exit_code),
+            },
+            // A held job will not run again without intervention, so surface it
+            // as failed with the hold reason
+            HtcondorJobState::Held(hold) => Self::Failed {
+                exit_code: -1,
+                message: hold.message,
+            },
+            HtcondorJobState::Unknown(status) => Self::Unknown(status.to_string()),
+        }
+    }
+}
+
+impl From<HtcondorJob> for SchedulerJob {
+    fn from(job: HtcondorJob) -> Self {
+        // "slot1@exec07.example.org" -> "exec07.example.org"
+        let node = job.remote_host.map(|h| {
+            h.rsplit_once('@')
+                .map(|(_, host)| host.to_string())
+                .unwrap_or(h)
+        });
+
+        Self {
+            job_id: job.job_id,
+            name: job.name,
+            state: job.state.into(),
+            queue: None,
+            submit_time: job.submit_time,
+            start_time: job.start_time,
+            end_time: job.end_time,
+            node,
+            cpus: job.request_cpus,
+            mem_mb: job.request_memory_mb,
+            time_limit: job.allowed_duration,
+            comment: job.comment,
+            array: None,
+            pending_reason: None,
+            priority: None,
+            estimated_start: None,
+        }
+    }
+}
+
+/// Convert HTCondor failure analysis to unified format.
+impl From<&FailureAnalysis> for charmer_scheduler::FailureAnalysis {
+    fn from(analysis: &FailureAnalysis) -> Self {
+        use charmer_scheduler::FailureMode as Unified;
+
+        let mode = match &analysis.mode {
+            FailureMode::OutOfMemory { .. } => Unified::OutOfMemory,
+            FailureMode::Timeout { .. } => Unified::Timeout,
+            FailureMode::ExitCode { .. } => Unified::ExitCode,
+            FailureMode::Removed { .. } => Unified::Cancelled,
+            FailureMode::TransferFailure { .. } => Unified::NodeFailure,
+            FailureMode::Held { .. } | FailureMode::Unknown { .. } => Unified::Unknown,
+        };
+
+        let (memory_used_mb, memory_limit_mb) = match &analysis.mode {
+            FailureMode::OutOfMemory {
+                used_mb,
+                requested_mb,
+                ..
+            } => (Some(*used_mb), Some(*requested_mb)),
+            _ => (analysis.memory_usage_mb, analysis.request_memory_mb),
+        };
+
+        let (runtime_seconds, time_limit_seconds) = match &analysis.mode {
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                ..
+            } => (Some(*elapsed_seconds), Some(*limit_seconds)),
+            _ => (
+                analysis.wall_clock_seconds,
+                analysis.allowed_duration_seconds,
+            ),
+        };
+
+        let (suggested_mem_mb, suggested_runtime_seconds) = match &analysis.mode {
+            FailureMode::OutOfMemory { suggested_mb, .. } => (Some(*suggested_mb), None),
+            FailureMode::Timeout {
+                suggested_seconds, ..
+            } => (None, Some(*suggested_seconds)),
+            _ => (None, None),
+        };
+
+        Self {
+            mode,
+            explanation: analysis.explanation.clone(),
+            suggestion: analysis.suggestion.clone(),
+            exit_code: analysis.exit_code,
+            memory_used_mb,
+            memory_limit_mb,
+            runtime_seconds,
+            time_limit_seconds,
+            suggested_mem_mb,
+            suggested_runtime_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::HoldReason;
+
+    #[test]
+    fn test_state_conversion() {
+        assert_eq!(
+            SchedulerJobState::from(HtcondorJobState::Idle),
+            SchedulerJobState::Pending
+        );
+        assert_eq!(
+            SchedulerJobState::from(HtcondorJobState::Completed { exit_code: 0 }),
+            SchedulerJobState::Completed
+        );
+        assert!(matches!(
+            SchedulerJobState::from(HtcondorJobState::Held(HoldReason::default())),
+            SchedulerJobState::Failed { exit_code: -1, .. }
+        ));
+    }
+}
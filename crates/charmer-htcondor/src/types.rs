@@ -0,0 +1,88 @@
+//! HTCondor job types.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// HTCondor job status (the `JobStatus` ClassAd attribute).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HtcondorJobState {
+    /// 1 - Idle
+    Idle,
+    /// 2 - Running
+    Running,
+    /// 3 - Removed (condor_rm or a removal policy)
+    Removed {
+        reason: Option<String>,
+    },
+    /// 4 - Completed
+    Completed {
+        exit_code: i32,
+    },
+    /// 5 - Held
+    Held(HoldReason),
+    /// 6 - Transferring output
+    TransferringOutput,
+    /// 7 - Suspended
+    Suspended,
+    Unknown(i64),
+}
+
+/// Why a job was put on hold.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HoldReason {
+    /// `HoldReasonCode`
+    pub code: Option<i32>,
+    /// `HoldReasonSubCode` (often the errno or signal)
+    pub subcode: Option<i32>,
+    /// `HoldReason` text
+    pub message: String,
+}
+
+/// HTCondor job information from condor_q/condor_history.
+#[derive(Debug, Clone)]
+pub struct HtcondorJob {
+    /// Job ID ("ClusterId.ProcId")
+    pub job_id: String,
+
+    /// Batch name (`JobBatchName`, the run UUID for snakemake)
+    pub name: String,
+
+    /// Job state
+    pub state: HtcondorJobState,
+
+    /// Submit time (`QDate`)
+    pub submit_time: Option<DateTime<Utc>>,
+
+    /// Start time (`JobCurrentStartDate`)
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// End time (`CompletionDate`)
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Execution slot (`RemoteHost` or `LastRemoteHost`)
+    pub remote_host: Option<String>,
+
+    /// Requested CPUs (`RequestCpus`)
+    pub request_cpus: Option<u32>,
+
+    /// Requested memory in MB (`RequestMemory`)
+    pub request_memory_mb: Option<u64>,
+
+    /// Peak memory in MB (`MemoryUsage`)
+    pub memory_usage_mb: Option<u64>,
+
+    /// Runtime limit (`AllowedJobDuration`)
+    pub allowed_duration: Option<Duration>,
+
+    /// Wall clock time in seconds (`RemoteWallClockTime`)
+    pub wall_clock_seconds: Option<u64>,
+
+    /// CPU time in seconds (`RemoteUserCpu` + `RemoteSysCpu`)
+    pub cpu_seconds: Option<u64>,
+
+    /// Signal that killed the job (`ExitSignal` when `ExitBySignal`)
+    pub exit_signal: Option<i32>,
+
+    /// Snakemake rule info in SLURM comment format, from ClassAd attributes
+    pub comment: Option<String>,
+}
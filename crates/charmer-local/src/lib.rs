@@ -0,0 +1,11 @@
+//! Local executor monitoring for charmer.
+//!
+//! When snakemake runs jobs on the local machine (`--cores N`), there is no
+//! scheduler to query. This crate walks `/proc` for descendants of the
+//! snakemake process and samples their memory and CPU usage.
+
+pub mod monitor;
+pub mod proc;
+
+pub use monitor::{LocalMonitor, LocalProcess, find_snakemake_pids, normalize_command};
+pub use proc::{ProcError, ProcessInfo, is_local_host, list_processes, local_hostname};
@@ -0,0 +1,253 @@
+//! Sample resource usage of snakemake's local job processes.
+
+use crate::proc::{CLOCK_TICKS, ProcError, ProcessInfo, boot_time, list_processes};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A job process and everything it spawned, with usage summed over the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalProcess {
+    pub pid: u32,
+    /// Command line of the top process
+    pub cmdline: String,
+    /// Current RSS of the process tree (MB)
+    pub rss_mb: u64,
+    /// Highest RSS seen for this process tree (MB)
+    pub peak_rss_mb: u64,
+    /// CPU usage since the previous sample, 100% = one core
+    pub cpu_percent: Option<f64>,
+    /// Total CPU time of the live process tree (seconds)
+    pub cpu_time_seconds: u64,
+    /// Wall time since the process started (seconds)
+    pub elapsed_seconds: u64,
+}
+
+/// Collapse runs of whitespace so command lines can be compared with the
+/// `shellcmd` recorded in snakemake metadata.
+pub fn normalize_command(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Find snakemake processes running in `working_dir`.
+///
+/// Processes started by a matching process (e.g., `script:` jobs whose
+/// path contains ".snakemake/") are not counted as separate roots.
+pub fn find_snakemake_pids(processes: &[ProcessInfo], working_dir: &Utf8Path) -> Vec<u32> {
+    let matching: HashSet<u32> = processes
+        .iter()
+        .filter(|p| {
+            p.cmdline.contains("snakemake") && p.cwd.as_deref().is_some_and(|c| c == working_dir)
+        })
+        .map(|p| p.pid)
+        .collect();
+
+    let mut roots: Vec<u32> = processes
+        .iter()
+        .filter(|p| matching.contains(&p.pid) && !matching.contains(&p.ppid))
+        .map(|p| p.pid)
+        .collect();
+    roots.sort_unstable();
+    roots
+}
+
+/// Tracks process samples between polls to derive CPU% and peak RSS.
+pub struct LocalMonitor {
+    working_dir: Utf8PathBuf,
+    /// Previous (cpu_ticks, sample time) per process tree
+    previous: HashMap<u32, (u64, Instant)>,
+    /// Peak tree RSS (KB) per process tree
+    peaks: HashMap<u32, u64>,
+}
+
+impl LocalMonitor {
+    pub fn new(working_dir: Utf8PathBuf) -> Self {
+        Self {
+            working_dir,
+            previous: HashMap::new(),
+            peaks: HashMap::new(),
+        }
+    }
+
+    /// Sample all descendants of the snakemake processes in the working directory.
+    ///
+    /// Processes are returned parents before children, so the first one whose
+    /// command line matches a job is the job's top-level process.
+    pub fn sample(&mut self) -> Result<Vec<LocalProcess>, ProcError> {
+        let processes = list_processes()?;
+        let now_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Ok(self.update(&processes, boot_time()?, now_secs, Instant::now()))
+    }
+
+    /// Build samples from a process listing.
+    fn update(
+        &mut self,
+        processes: &[ProcessInfo],
+        boot_time: u64,
+        now_secs: u64,
+        now: Instant,
+    ) -> Vec<LocalProcess> {
+        let by_pid: HashMap<u32, &ProcessInfo> = processes.iter().map(|p| (p.pid, p)).collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for p in processes {
+            children.entry(p.ppid).or_default().push(p.pid);
+        }
+
+        // Sum a value over a process and all of its descendants
+        let subtree = |pid: u32, value: &dyn Fn(&ProcessInfo) -> u64| -> u64 {
+            let mut total = 0;
+            let mut stack = vec![pid];
+            while let Some(pid) = stack.pop() {
+                if let Some(p) = by_pid.get(&pid) {
+                    total += value(p);
+                }
+                if let Some(kids) = children.get(&pid) {
+                    stack.extend(kids);
+                }
+            }
+            total
+        };
+
+        // Breadth-first from each snakemake root, skipping the root itself
+        let mut queue: VecDeque<u32> = VecDeque::new();
+        for root in find_snakemake_pids(processes, &self.working_dir) {
+            if let Some(kids) = children.get(&root) {
+                queue.extend(kids);
+            }
+        }
+
+        let mut samples = Vec::new();
+        let mut seen = HashSet::new();
+        while let Some(pid) = queue.pop_front() {
+            let Some(p) = by_pid.get(&pid) else { continue };
+            if let Some(kids) = children.get(&pid) {
+                queue.extend(kids);
+            }
+
+            let rss_kb = subtree(pid, &|p| p.rss_kb);
+            let cpu_ticks = subtree(pid, &|p| p.cpu_ticks);
+
+            let peak = self.peaks.entry(pid).or_insert(0);
+            *peak = (*peak).max(rss_kb);
+
+            let cpu_percent = self.previous.get(&pid).and_then(|(prev_ticks, prev_at)| {
+                let wall = now.duration_since(*prev_at).as_secs_f64();
+                (wall > 0.0).then(|| {
+                    cpu_ticks.saturating_sub(*prev_ticks) as f64 / CLOCK_TICKS as f64 / wall * 100.0
+                })
+            });
+            self.previous.insert(pid, (cpu_ticks, now));
+            seen.insert(pid);
+
+            let started = boot_time + p.start_ticks / CLOCK_TICKS;
+            samples.push(LocalProcess {
+                pid,
+                cmdline: p.cmdline.clone(),
+                rss_mb: rss_kb / 1024,
+                peak_rss_mb: *peak / 1024,
+                cpu_percent,
+                cpu_time_seconds: cpu_ticks / CLOCK_TICKS,
+                elapsed_seconds: now_secs.saturating_sub(started),
+            });
+        }
+
+        // Forget processes that have exited
+        self.previous.retain(|pid, _| seen.contains(pid));
+        self.peaks.retain(|pid, _| seen.contains(pid));
+
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn process(pid: u32, ppid: u32, cmdline: &str, rss_kb: u64, cpu_ticks: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            cmdline: cmdline.to_string(),
+            cwd: Some(Utf8PathBuf::from("/work")),
+            rss_kb,
+            cpu_ticks,
+            start_ticks: 1000,
+        }
+    }
+
+    fn tree(tool_rss_kb: u64, tool_ticks: u64) -> Vec<ProcessInfo> {
+        vec![
+            process(1, 0, "/sbin/init", 0, 0),
+            process(
+                100,
+                1,
+                "/usr/bin/python3 /usr/bin/snakemake --cores 4",
+                200_000,
+                500,
+            ),
+            process(
+                200,
+                100,
+                "/bin/bash -c set -euo pipefail;  bwa mem ref.fa S1.fq > S1.sam",
+                4096,
+                10,
+            ),
+            process(201, 200, "bwa mem ref.fa S1.fq", tool_rss_kb, tool_ticks),
+        ]
+    }
+
+    #[test]
+    fn test_normalize_command() {
+        assert_eq!(
+            normalize_command("  bwa mem\n   ref.fa\tS1.fq "),
+            "bwa mem ref.fa S1.fq"
+        );
+    }
+
+    #[test]
+    fn test_find_snakemake_pids() {
+        let mut processes = tree(0, 0);
+        // A script job under snakemake should not count as another root
+        processes.push(process(
+            300,
+            100,
+            "python /work/.snakemake/scripts/tmpab12.plot.py",
+            0,
+            0,
+        ));
+        assert_eq!(
+            find_snakemake_pids(&processes, Utf8Path::new("/work")),
+            vec![100]
+        );
+        assert!(find_snakemake_pids(&processes, Utf8Path::new("/elsewhere")).is_empty());
+    }
+
+    #[test]
+    fn test_update_tracks_peak_and_cpu() {
+        let mut monitor = LocalMonitor::new(Utf8PathBuf::from("/work"));
+        let start = Instant::now();
+
+        let first = monitor.update(&tree(2_097_152, 100), 1_700_000_000, 1_700_000_100, start);
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].pid, 200);
+        assert_eq!(first[0].rss_mb, 2052);
+        assert_eq!(first[0].elapsed_seconds, 90);
+        assert!(first[0].cpu_percent.is_none());
+
+        // RSS drops, CPU advances by 2 seconds over 1 second of wall time
+        let second = monitor.update(
+            &tree(1_048_576, 300),
+            1_700_000_000,
+            1_700_000_101,
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(second[0].rss_mb, 1028);
+        assert_eq!(second[0].peak_rss_mb, 2052);
+        assert_eq!(second[0].cpu_percent, Some(200.0));
+        assert_eq!(second[0].cpu_time_seconds, 3);
+    }
+}
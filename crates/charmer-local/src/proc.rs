@@ -0,0 +1,174 @@
+//! Read process information from `/proc`.
+
+use camino::{Utf8Path, Utf8PathBuf};
+use std::fs;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ProcError {
+    #[error("Failed to read /proc: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse {path}: {message}")]
+    Parse { path: String, message: String },
+}
+
+/// Clock ticks per second for `/proc/<pid>/stat` times.
+///
+/// This is `sysconf(_SC_CLK_TCK)`, which is 100 on every Linux
+/// architecture charmer targets.
+pub const CLOCK_TICKS: u64 = 100;
+
+/// A snapshot of one process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Command line with arguments joined by spaces
+    pub cmdline: String,
+    /// Working directory (None if not readable)
+    pub cwd: Option<Utf8PathBuf>,
+    /// Resident set size in KB
+    pub rss_kb: u64,
+    /// User + system CPU time in clock ticks
+    pub cpu_ticks: u64,
+    /// Start time in clock ticks since boot
+    pub start_ticks: u64,
+}
+
+/// Fields parsed from `/proc/<pid>/stat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Stat {
+    pub ppid: u32,
+    pub cpu_ticks: u64,
+    pub start_ticks: u64,
+    pub rss_pages: u64,
+}
+
+/// Parse `/proc/<pid>/stat`.
+///
+/// The command name (field 2) is in parentheses and may itself contain
+/// spaces or parentheses, so fields are counted from the last `)`.
+pub(crate) fn parse_stat(content: &str) -> Option<Stat> {
+    let after_comm = &content[content.rfind(')')? + 1..];
+    let fields: Vec<&str> = after_comm.split_whitespace().collect();
+
+    // fields[0] is state (field 3), so field N is fields[N - 3]
+    let field = |n: usize| fields.get(n - 3).and_then(|v| v.parse::<u64>().ok());
+
+    Some(Stat {
+        ppid: field(4)? as u32,
+        cpu_ticks: field(14)? + field(15)?,
+        start_ticks: field(22)?,
+        rss_pages: field(24)?,
+    })
+}
+
+/// Read a single process. Returns None if it exited while being read.
+fn read_process(proc_dir: &Utf8Path, pid: u32, page_kb: u64) -> Option<ProcessInfo> {
+    let dir = proc_dir.join(pid.to_string());
+    let stat = parse_stat(&fs::read_to_string(dir.join("stat")).ok()?)?;
+
+    let cmdline = fs::read(dir.join("cmdline"))
+        .map(|raw| {
+            raw.split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+
+    let cwd = fs::read_link(dir.join("cwd"))
+        .ok()
+        .and_then(|p| Utf8PathBuf::from_path_buf(p).ok());
+
+    Some(ProcessInfo {
+        pid,
+        ppid: stat.ppid,
+        cmdline,
+        cwd,
+        rss_kb: stat.rss_pages * page_kb,
+        cpu_ticks: stat.cpu_ticks,
+        start_ticks: stat.start_ticks,
+    })
+}
+
+/// List all processes visible in `/proc`.
+pub fn list_processes() -> Result<Vec<ProcessInfo>, ProcError> {
+    let proc_dir = Utf8Path::new("/proc");
+    // Pages are 4 KB on the architectures charmer targets
+    let page_kb = 4;
+
+    let mut processes = Vec::new();
+    for entry in fs::read_dir(proc_dir)? {
+        let entry = entry?;
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse().ok()) else {
+            continue;
+        };
+        if let Some(info) = read_process(proc_dir, pid, page_kb) {
+            processes.push(info);
+        }
+    }
+
+    Ok(processes)
+}
+
+/// System boot time (seconds since the epoch) from `/proc/stat`.
+pub(crate) fn boot_time() -> Result<u64, ProcError> {
+    let content = fs::read_to_string("/proc/stat")?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or_else(|| ProcError::Parse {
+            path: "/proc/stat".to_string(),
+            message: "missing btime".to_string(),
+        })
+}
+
+/// This machine's hostname.
+pub fn local_hostname() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+/// Check whether a hostname (e.g., from the snakemake log) is this machine.
+///
+/// Only the short names are compared, since the log may hold a FQDN.
+pub fn is_local_host(host: &str) -> bool {
+    let short = |h: &str| h.split('.').next().unwrap_or(h).to_lowercase();
+    local_hostname().is_some_and(|local| short(&local) == short(host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        let content = "4242 (bash) S 4200 4242 4200 0 -1 4194304 1000 0 0 0 150 25 0 0 20 0 1 0 987654 12345678 2048 18446744073709551615";
+        let stat = parse_stat(content).unwrap();
+        assert_eq!(stat.ppid, 4200);
+        assert_eq!(stat.cpu_ticks, 175);
+        assert_eq!(stat.start_ticks, 987654);
+        assert_eq!(stat.rss_pages, 2048);
+    }
+
+    #[test]
+    fn test_parse_stat_odd_comm() {
+        let content = "7 (my (odd) cmd) R 1 7 7 0 -1 0 0 0 0 0 10 5 0 0 20 0 1 0 100 0 64 0";
+        let stat = parse_stat(content).unwrap();
+        assert_eq!(stat.ppid, 1);
+        assert_eq!(stat.cpu_ticks, 15);
+        assert_eq!(stat.rss_pages, 64);
+    }
+
+    #[test]
+    fn test_list_self() {
+        let processes = list_processes().unwrap();
+        let me = std::process::id();
+        assert!(processes.iter().any(|p| p.pid == me));
+    }
+}
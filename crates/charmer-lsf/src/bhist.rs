@@ -80,6 +80,7 @@ fn parse_bhist_long_output(
                     mem_used_mb: None,
                     run_limit: None,
                     description: None,
+                    pending_reason: None,
                 });
             }
         }
@@ -5,6 +5,7 @@ use charmer_parsers::{
     MemoryFormat, non_empty_string, parse_lsf_timestamp, parse_memory_mb,
     run_command_allow_failure, split_delimited,
 };
+use std::collections::HashMap;
 use std::time::Duration;
 use thiserror::Error;
 use tokio::process::Command;
@@ -60,6 +61,7 @@ fn parse_bjobs_line(line: &str) -> Result<LsfJob, BjobsError> {
         mem_used_mb: None,
         run_limit: None,
         description: non_empty_string(fields[9]),
+        pending_reason: None,
     })
 }
 
@@ -94,6 +96,49 @@ pub async fn query_bjobs(job_name_filter: Option<&str>) -> Result<Vec<LsfJob>, B
     Ok(jobs)
 }
 
+/// Parse `bjobs -p` output into the first pending reason per job ID.
+///
+/// Each job line is followed by indented reason lines ending in ';'.
+fn parse_pending_reasons(output: &str) -> HashMap<String, String> {
+    let mut reasons = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in output.lines() {
+        if line.trim().is_empty() || line.starts_with("JOBID") {
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            if let Some(job_id) = current.take() {
+                let reason = line.trim().trim_end_matches(';').trim();
+                if !reason.is_empty() {
+                    reasons.insert(job_id, reason.to_string());
+                }
+            }
+        } else {
+            current = line.split_whitespace().next().map(String::from);
+        }
+    }
+
+    reasons
+}
+
+/// Query pending reasons with `bjobs -p`.
+pub async fn query_pending_reasons(
+    job_name_filter: Option<&str>,
+) -> Result<HashMap<String, String>, BjobsError> {
+    let mut cmd = Command::new("bjobs");
+    cmd.arg("-p");
+    if let Some(name) = job_name_filter {
+        cmd.args(["-J", name]);
+    }
+
+    let stdout = run_command_allow_failure(&mut cmd, "bjobs")
+        .await
+        .map_err(|e| BjobsError::ExecutionError(e.to_string()))?;
+
+    Ok(parse_pending_reasons(&stdout))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,4 +168,20 @@ mod tests {
 
         assert!(parse_lsf_timestamp("-").is_none());
     }
+
+    #[test]
+    fn test_parse_pending_reasons() {
+        let output = "\
+JOBID   USER    STAT  QUEUE      FROM_HOST   JOB_NAME   SUBMIT_TIME
+4001    alice   PEND  normal     login1      run-uuid   Dec 18 10:30
+ New job is waiting for scheduling;
+4002    alice   PEND  normal     login1      run-uuid   Dec 18 10:31
+ Job slot limit reached;
+ Not enough job slot(s): 2 hosts;
+";
+        let reasons = parse_pending_reasons(output);
+        assert_eq!(reasons.len(), 2);
+        assert_eq!(reasons["4001"], "New job is waiting for scheduling");
+        assert_eq!(reasons["4002"], "Job slot limit reached");
+    }
 }
@@ -0,0 +1,71 @@
+//! Job control through bkill, bstop and bresume.
+
+use charmer_parsers::{CommandError, CommandRunner};
+use charmer_scheduler::JobAction;
+
+/// Command line for an action on the given jobs.
+pub fn control_command(action: JobAction, job_ids: &[String]) -> (&'static str, Vec<String>) {
+    let program = match action {
+        JobAction::Cancel => "bkill",
+        JobAction::Hold => "bstop",
+        JobAction::Release => "bresume",
+    };
+    (program, job_ids.to_vec())
+}
+
+/// Cancel, hold or release jobs. Does nothing when `job_ids` is empty.
+pub async fn control_jobs(
+    runner: &dyn CommandRunner,
+    action: JobAction,
+    job_ids: &[String],
+) -> Result<(), CommandError> {
+    if job_ids.is_empty() {
+        return Ok(());
+    }
+    let (program, args) = control_command(action, job_ids);
+    runner.run(program, &args).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Fails every command, echoing its argv in the error.
+    struct FailingRunner;
+
+    #[async_trait]
+    impl CommandRunner for FailingRunner {
+        async fn run(&self, program: &str, args: &[String]) -> Result<String, CommandError> {
+            Err(CommandError::Failed {
+                command: program.to_string(),
+                stderr: args.join(" "),
+            })
+        }
+    }
+
+    #[test]
+    fn test_control_command() {
+        let ids = vec!["881".to_string(), "882".to_string()];
+        assert_eq!(
+            control_command(JobAction::Hold, &ids),
+            ("bstop", ids.clone())
+        );
+        assert_eq!(control_command(JobAction::Release, &ids).0, "bresume");
+    }
+
+    #[tokio::test]
+    async fn test_control_jobs_reports_failure() {
+        let ids = vec!["881".to_string()];
+        let err = control_jobs(&FailingRunner, JobAction::Cancel, &ids)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Command bkill failed: 881");
+        assert!(
+            control_jobs(&FailingRunner, JobAction::Cancel, &[])
+                .await
+                .is_ok()
+        );
+    }
+}
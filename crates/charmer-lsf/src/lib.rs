@@ -4,10 +4,14 @@
 
 pub mod bhist;
 pub mod bjobs;
+pub mod control;
 pub mod failure;
+pub mod scheduler;
 pub mod types;
 
 pub use bhist::{BhistError, query_bhist};
 pub use bjobs::{BjobsError, query_bjobs};
+pub use control::{control_command, control_jobs};
 pub use failure::{FailureAnalysis, FailureError, FailureMode, analyze_failure};
+pub use scheduler::LsfScheduler;
 pub use types::{LsfJob, LsfJobState};
@@ -0,0 +1,216 @@
+//! `Scheduler` trait implementation for LSF.
+
+use crate::bhist;
+use crate::bjobs;
+use crate::control;
+use crate::failure::{self, FailureAnalysis, FailureMode};
+use crate::types::{LsfJob, LsfJobState};
+use async_trait::async_trait;
+use charmer_parsers::SystemRunner;
+use charmer_scheduler::{
+    JobAction, Scheduler, SchedulerCapabilities, SchedulerError, SchedulerJob, SchedulerJobState,
+};
+use chrono::{DateTime, Utc};
+
+/// LSF backend (bjobs/bhist).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LsfScheduler;
+
+#[async_trait]
+impl Scheduler for LsfScheduler {
+    fn name(&self) -> &'static str {
+        "lsf"
+    }
+
+    fn capabilities(&self) -> SchedulerCapabilities {
+        SchedulerCapabilities {
+            history: true,
+            resource_usage: false,
+            failure_analysis: true,
+            job_control: true,
+            run_names: true,
+        }
+    }
+
+    async fn is_available(&self) -> bool {
+        tokio::process::Command::new("bjobs")
+            .arg("-V")
+            .output()
+            .await
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    async fn query_active(
+        &self,
+        run_uuid: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let mut jobs = bjobs::query_bjobs(run_uuid)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+
+        if jobs.iter().any(|j| j.state == LsfJobState::Pending) {
+            match bjobs::query_pending_reasons(run_uuid).await {
+                Ok(reasons) => {
+                    for job in &mut jobs {
+                        job.pending_reason = reasons.get(&job.job_id).cloned();
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to query pending reasons: {}", e),
+            }
+        }
+
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_history(
+        &self,
+        run_uuid: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = bhist::query_bhist(run_uuid, since)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn analyze_failure(
+        &self,
+        job_id: &str,
+    ) -> Result<charmer_scheduler::FailureAnalysis, SchedulerError> {
+        let analysis = failure::analyze_failure(job_id)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok((&analysis).into())
+    }
+
+    async fn control(&self, action: JobAction, job_ids: &[String]) -> Result<(), SchedulerError> {
+        control::control_jobs(&SystemRunner, action, job_ids)
+            .await
+            .map_err(|e| SchedulerError::Control(e.to_string()))
+    }
+}
+
+impl From<LsfJobState> for SchedulerJobState {
+    fn from(state: LsfJobState) -> Self {
+        match state {
+            LsfJobState::Pending => Self::Pending,
+            LsfJobState::Running => Self::Running,
+            LsfJobState::Done { .. } => Self::Completed,
+            LsfJobState::Exit { exit_code, error } => Self::Failed {
+                exit_code,
+                message: error,
+            },
+            LsfJobState::UserSuspendedPending
+            | LsfJobState::UserSuspended
+            | LsfJobState::SystemSuspended => Self::Suspended,
+            LsfJobState::Zombie => Self::Unknown("ZOMBI".to_string()),
+            LsfJobState::Unknown(s) => Self::Unknown(s),
+        }
+    }
+}
+
+impl From<LsfJob> for SchedulerJob {
+    fn from(job: LsfJob) -> Self {
+        Self {
+            job_id: job.job_id,
+            name: job.name,
+            state: job.state.into(),
+            queue: job.queue,
+            submit_time: job.submit_time,
+            start_time: job.start_time,
+            end_time: job.end_time,
+            node: job.exec_host,
+            cpus: job.nprocs,
+            mem_mb: job.mem_limit_mb,
+            time_limit: job.run_limit,
+            comment: job.description,
+            array: None,
+            pending_reason: job.pending_reason,
+            priority: None,
+            estimated_start: None,
+        }
+    }
+}
+
+/// Convert LSF failure analysis to unified format.
+impl From<&FailureAnalysis> for charmer_scheduler::FailureAnalysis {
+    fn from(analysis: &FailureAnalysis) -> Self {
+        use charmer_scheduler::FailureMode as Unified;
+
+        let mode = match &analysis.mode {
+            FailureMode::OutOfMemory { .. } => Unified::OutOfMemory,
+            FailureMode::Timeout { .. } => Unified::Timeout,
+            FailureMode::ExitCode { .. } => Unified::ExitCode,
+            FailureMode::Killed { .. } => Unified::Cancelled,
+            FailureMode::HostFailure { .. } => Unified::NodeFailure,
+            FailureMode::Unknown { .. } => Unified::Unknown,
+        };
+
+        let exit_code = match &analysis.mode {
+            FailureMode::ExitCode { code, .. } => Some(*code),
+            _ => None,
+        };
+
+        let (memory_used_mb, memory_limit_mb) = match &analysis.mode {
+            FailureMode::OutOfMemory {
+                used_mb, limit_mb, ..
+            } => (Some(*used_mb), Some(*limit_mb)),
+            _ => (analysis.max_mem_mb, analysis.mem_limit_mb),
+        };
+
+        let (runtime_seconds, time_limit_seconds) = match &analysis.mode {
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                ..
+            } => (Some(*elapsed_seconds), Some(*limit_seconds)),
+            _ => (analysis.run_time_seconds, analysis.run_limit_seconds),
+        };
+
+        let (suggested_mem_mb, suggested_runtime_seconds) = match &analysis.mode {
+            FailureMode::OutOfMemory { suggested_mb, .. } => (Some(*suggested_mb), None),
+            FailureMode::Timeout {
+                suggested_seconds, ..
+            } => (None, Some(*suggested_seconds)),
+            _ => (None, None),
+        };
+
+        Self {
+            mode,
+            explanation: analysis.explanation.clone(),
+            suggestion: analysis.suggestion.clone(),
+            exit_code,
+            memory_used_mb,
+            memory_limit_mb,
+            runtime_seconds,
+            time_limit_seconds,
+            suggested_mem_mb,
+            suggested_runtime_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_conversion() {
+        assert_eq!(
+            SchedulerJobState::from(LsfJobState::Pending),
+            SchedulerJobState::Pending
+        );
+        assert_eq!(
+            SchedulerJobState::from(LsfJobState::UserSuspended),
+            SchedulerJobState::Suspended
+        );
+        assert!(matches!(
+            SchedulerJobState::from(LsfJobState::Exit {
+                exit_code: 2,
+                error: String::new()
+            }),
+            SchedulerJobState::Failed { exit_code: 2, .. }
+        ));
+    }
+}
@@ -67,4 +67,7 @@ pub struct LsfJob {
 
     /// Job description (used by snakemake for rule info)
     pub description: Option<String>,
+
+    /// First pending reason reported by `bjobs -p`
+    pub pending_reason: Option<String>,
 }
@@ -1,18 +1,23 @@
 //! Main TUI application.
 
 use crate::components::{
-    DependencyCache, Footer, Header, JobDetail, JobList, LogViewer, LogViewerState, RuleSummary,
-    compute_dependencies,
+    DagLayout, DagLevel, DagView, DependencyCache, EventLogView, Footer, Header, JobDetail,
+    JobList, LogViewer, LogViewerState, RuleSummary, Timeline, TimelineLanes, TimelineView,
+    array_group_id, array_group_parent, compute_dependencies, dag, timeline,
 };
 use crate::ui::Theme;
 use charmer_runs::RunInfo;
-use charmer_state::{JobStatus, MAIN_PIPELINE_JOB_ID, PipelineState};
+use charmer_state::{
+    JobAction, JobStatus, MAIN_PIPELINE_JOB_ID, PipelineState, ProfileOverlay, ResourceBump,
+    StateEvent, option_value, suggested_bumps,
+};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     widgets::Clear,
 };
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 /// Filter mode for job list.
@@ -76,6 +81,51 @@ pub enum ViewMode {
     Jobs,
     /// Show rule summary
     Rules,
+    /// Show recent state events
+    Events,
+    /// Show the dependency graph
+    Dag,
+    /// Show job execution over time
+    Timeline,
+}
+
+/// Which jobs a job-control action applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionScope {
+    /// The selected job (or every task of the selected array)
+    Job,
+    /// Every job of the selected rule
+    Rule,
+    /// Every job of the run
+    Run,
+}
+
+impl ActionScope {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Job => Self::Rule,
+            Self::Rule => Self::Run,
+            Self::Run => Self::Job,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Job => "Job",
+            Self::Rule => "Rule",
+            Self::Run => "Run",
+        }
+    }
+}
+
+/// A confirmed job-control action for the main loop to send to the scheduler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionRequest {
+    pub action: JobAction,
+    /// Scheduler job IDs
+    pub job_ids: Vec<String>,
+    /// What the action applies to (e.g., "rule align")
+    pub target: String,
 }
 
 impl SortMode {
@@ -116,6 +166,23 @@ pub struct App {
     status_message: Option<(String, Instant)>, // Temporary status message with timestamp
     command_expanded: bool,                    // Whether command section is expanded in details
     dependency_cache: DependencyCache,         // Cached dependency graph for job list
+    expanded_arrays: HashSet<String>,          // Array parent IDs shown task by task
+    pending_action: Option<(JobAction, ActionScope)>, // Action awaiting confirmation
+    action_requests: Vec<ActionRequest>,       // Confirmed actions not yet sent
+    rerun_plan: Option<Vec<ResourceBump>>,     // Resource bumps awaiting confirmation
+    rerun_request: Option<Vec<String>>,        // Snakemake args of a confirmed rerun
+    snakemake_args: Option<Vec<String>>,       // Args of the snakemake charmer started
+    main_log: Option<String>,                  // Log shown for the main pipeline row
+    restart_prompt: Option<(i32, bool)>,       // Exit code and lock state of a failed child
+    restart_request: Option<bool>,             // Confirmed restart; whether to unlock first
+    dag: DagLayout,                            // Cached graph for the DAG view
+    dag_level: DagLevel,                       // Rule or job nodes in the DAG view
+    dag_zoom: u8,                              // Label detail in the DAG view
+    dag_pan: (i32, i32),                       // DAG view offset from the selected node
+    timeline: Timeline,                        // Cached rows for the timeline view
+    timeline_lanes: TimelineLanes,             // Job rows or rule lanes in the timeline
+    timeline_zoom: u8,                         // Timeline zoom level (0 shows the whole run)
+    timeline_scroll: u32,                      // Quarter screens back from the timeline's end
 
     // Run management
     pub runs: Vec<RunInfo>,           // Available runs
@@ -156,6 +223,23 @@ impl App {
             status_message: None,
             command_expanded: false,
             dependency_cache: Vec::new(),
+            expanded_arrays: HashSet::new(),
+            pending_action: None,
+            action_requests: Vec::new(),
+            rerun_plan: None,
+            rerun_request: None,
+            snakemake_args: None,
+            main_log: None,
+            restart_prompt: None,
+            restart_request: None,
+            dag: DagLayout::default(),
+            dag_level: DagLevel::default(),
+            dag_zoom: 1,
+            dag_pan: (0, 0),
+            timeline: Timeline::default(),
+            timeline_lanes: TimelineLanes::default(),
+            timeline_zoom: 0,
+            timeline_scroll: 0,
             runs,
             selected_run,
             show_run_picker: false,
@@ -267,9 +351,28 @@ impl App {
             self.job_ids.push(MAIN_PIPELINE_JOB_ID.to_string());
         }
 
+        // Array tasks collapse into one group row at the position of the
+        // first task; the tasks follow it only when the group is expanded
+        let mut array_tasks: HashMap<&str, Vec<&String>> = HashMap::new();
+        for (id, job) in &jobs {
+            if let Some(array) = &job.array {
+                array_tasks.entry(&array.parent_id).or_default().push(id);
+            }
+        }
+
         // Add sorted job IDs
-        self.job_ids
-            .extend(jobs.into_iter().map(|(id, _)| id.clone()));
+        for (id, job) in &jobs {
+            let Some(array) = &job.array else {
+                self.job_ids.push((*id).clone());
+                continue;
+            };
+            if let Some(tasks) = array_tasks.remove(array.parent_id.as_str()) {
+                self.job_ids.push(array_group_id(&array.parent_id));
+                if self.expanded_arrays.contains(&array.parent_id) {
+                    self.job_ids.extend(tasks.into_iter().cloned());
+                }
+            }
+        }
 
         // Clamp selection
         if !self.job_ids.is_empty() {
@@ -293,9 +396,9 @@ impl App {
     }
 
     /// Get the currently selected job.
-    /// Returns None if the main pipeline job is selected (it's synthetic).
+    /// Returns None if the main pipeline job or an array group is selected (they're synthetic).
     pub fn selected_job(&self) -> Option<&charmer_state::Job> {
-        self.job_ids.get(self.selected_index).and_then(|id| {
+        self.selected_job_id().and_then(|id| {
             if id == MAIN_PIPELINE_JOB_ID {
                 None // Main pipeline job is synthetic
             } else {
@@ -306,15 +409,23 @@ impl App {
 
     /// Check if the main pipeline job is currently selected.
     pub fn is_main_pipeline_selected(&self) -> bool {
-        self.job_ids
-            .get(self.selected_index)
-            .map(|id| id == MAIN_PIPELINE_JOB_ID)
-            .unwrap_or(false)
+        self.selected_job_id() == Some(MAIN_PIPELINE_JOB_ID)
     }
 
-    /// Get the currently selected job ID.
+    /// Get the currently selected job ID (the job node's or row's job in the
+    /// DAG and timeline views).
     pub fn selected_job_id(&self) -> Option<&str> {
-        self.job_ids.get(self.selected_index).map(|s| s.as_str())
+        match self.view_mode {
+            ViewMode::Dag => self
+                .dag
+                .node(self.selected_index)
+                .and_then(|node| node.job_id.as_deref()),
+            ViewMode::Timeline => self
+                .timeline
+                .row(self.selected_index)
+                .and_then(|row| row.job_id.as_deref()),
+            _ => self.job_ids.get(self.selected_index).map(|s| s.as_str()),
+        }
     }
 
     /// Get filtered job IDs.
@@ -331,6 +442,9 @@ impl App {
         match self.view_mode {
             ViewMode::Jobs => self.job_ids.len(),
             ViewMode::Rules => self.rule_names.len(),
+            ViewMode::Events => self.state.events.len(),
+            ViewMode::Dag => self.dag.len(),
+            ViewMode::Timeline => self.timeline.len(),
         }
     }
 
@@ -395,6 +509,21 @@ impl App {
         }
     }
 
+    /// Expand or collapse the selected array group.
+    pub fn toggle_array_group(&mut self) {
+        let Some(parent) = self
+            .selected_job_id()
+            .and_then(array_group_parent)
+            .map(String::from)
+        else {
+            return;
+        };
+        if !self.expanded_arrays.remove(&parent) {
+            self.expanded_arrays.insert(parent);
+        }
+        self.update_job_list();
+    }
+
     /// Toggle between snakemake-only and all-jobs view.
     pub fn toggle_all_jobs(&mut self) {
         self.show_all_jobs = !self.show_all_jobs;
@@ -412,11 +541,14 @@ impl App {
         self.runs = runs;
     }
 
-    /// Toggle between jobs and rules view.
+    /// Cycle between jobs, rules, events, DAG and timeline view.
     pub fn toggle_view_mode(&mut self) {
         self.view_mode = match self.view_mode {
             ViewMode::Jobs => ViewMode::Rules,
-            ViewMode::Rules => ViewMode::Jobs,
+            ViewMode::Rules => ViewMode::Events,
+            ViewMode::Events => ViewMode::Dag,
+            ViewMode::Dag => ViewMode::Timeline,
+            ViewMode::Timeline => ViewMode::Jobs,
         };
         // Reset selection when switching views
         self.selected_index = 0;
@@ -424,6 +556,119 @@ impl App {
         if self.view_mode == ViewMode::Rules {
             self.update_rule_list();
         }
+        if self.view_mode == ViewMode::Dag {
+            self.dag_pan = (0, 0);
+            self.update_dag();
+        }
+        if self.view_mode == ViewMode::Timeline {
+            self.update_timeline();
+        }
+    }
+
+    /// Rebuild the cached graph for the DAG view.
+    fn update_dag(&mut self) {
+        self.dag = DagLayout::build(&self.state, self.dag_level);
+        self.selected_index = self.selected_index.min(self.dag.len().saturating_sub(1));
+    }
+
+    /// Switch the DAG view between rule and job nodes.
+    fn toggle_dag_level(&mut self) {
+        self.dag_level = self.dag_level.next();
+        let rule = self
+            .dag
+            .node(self.selected_index)
+            .map(|node| node.rule.clone());
+        self.update_dag();
+        // Stay on the same rule
+        self.selected_index = rule
+            .and_then(|rule| {
+                let nodes = &self.dag.nodes;
+                self.dag.order.iter().position(|&n| nodes[n].rule == rule)
+            })
+            .unwrap_or(0);
+        self.dag_pan = (0, 0);
+        if self.dag.level != self.dag_level {
+            self.set_status_message("Too many jobs for the job graph; showing rules");
+        }
+    }
+
+    /// Rebuild the cached rows for the timeline view.
+    fn update_timeline(&mut self) {
+        self.timeline = Timeline::build(&self.state, self.timeline_lanes);
+        self.selected_index = self
+            .selected_index
+            .min(self.timeline.len().saturating_sub(1));
+    }
+
+    /// Switch the timeline between job rows and rule lanes.
+    fn toggle_timeline_lanes(&mut self) {
+        self.timeline_lanes = self.timeline_lanes.next();
+        let rule = self
+            .timeline
+            .row(self.selected_index)
+            .map(|row| row.rule.clone());
+        self.update_timeline();
+        // Stay on the same rule
+        self.selected_index = rule
+            .and_then(|rule| self.timeline.rows.iter().position(|row| row.rule == rule))
+            .unwrap_or(0);
+    }
+
+    /// Zoom the timeline in or out, keeping the end of the window in place.
+    fn zoom_timeline(&mut self, zoom_in: bool) {
+        if zoom_in && self.timeline_zoom < timeline::MAX_ZOOM {
+            self.timeline_zoom += 1;
+            self.timeline_scroll *= 2;
+        } else if !zoom_in && self.timeline_zoom > 0 {
+            self.timeline_zoom -= 1;
+            self.timeline_scroll /= 2;
+        }
+        self.timeline_scroll = self
+            .timeline_scroll
+            .min(timeline::max_scroll(self.timeline_zoom));
+    }
+
+    /// Show the jobs of the selected DAG node in the jobs view.
+    fn jump_to_dag_jobs(&mut self) {
+        let Some(node) = self.dag.node(self.selected_index).cloned() else {
+            return;
+        };
+        self.view_mode = ViewMode::Jobs;
+        // Make sure the jobs are listed: no filter, and the array expanded
+        let is_target = |app: &Self, id: &str| match &node.job_id {
+            Some(job_id) => id == job_id,
+            None => app
+                .state
+                .jobs
+                .get(id)
+                .is_some_and(|job| job.rule == node.rule),
+        };
+        if !self.job_ids.iter().any(|id| is_target(self, id)) {
+            self.filter_mode = FilterMode::All;
+            if let Some(array) = node
+                .job_id
+                .as_ref()
+                .and_then(|id| self.state.jobs.get(id))
+                .and_then(|job| job.array.as_ref())
+            {
+                self.expanded_arrays.insert(array.parent_id.clone());
+            }
+            self.update_job_list();
+        }
+        self.selected_index = self
+            .job_ids
+            .iter()
+            .position(|id| is_target(self, id))
+            .unwrap_or(0);
+        self.update_dependency_cache();
+        if self.show_log_viewer {
+            self.update_log_viewer_for_selected();
+        }
+        let what = match &node.job_id {
+            Some(_) => format!("job {}", node.label),
+            None => format!("rule {} ({} jobs)", node.rule, node.counts.total),
+        };
+        self.set_status_message(format!("Showing {}", what));
     }
 
     /// Update the cached rule names list.
@@ -433,13 +678,38 @@ impl App {
         self.rule_names = rules;
     }
 
-    /// Get the currently selected rule name (in rules view).
+    /// Get the currently selected rule name (in rules, DAG and timeline view).
     pub fn selected_rule(&self) -> Option<&str> {
-        if self.view_mode == ViewMode::Rules {
-            self.rule_names.get(self.selected_index).map(|s| s.as_str())
-        } else {
-            None
+        match self.view_mode {
+            ViewMode::Rules => self.rule_names.get(self.selected_index).map(|s| s.as_str()),
+            // Rule nodes only; a job node selects its job
+            ViewMode::Dag => self
+                .dag
+                .node(self.selected_index)
+                .filter(|node| node.job_id.is_none())
+                .map(|node| node.rule.as_str()),
+            ViewMode::Timeline => self
+                .timeline
+                .row(self.selected_index)
+                .filter(|row| row.job_id.is_none())
+                .map(|row| row.rule.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Get the selected event (in events view).
+    pub fn selected_event(&self) -> Option<&StateEvent> {
+        if self.view_mode != ViewMode::Events {
+            return None;
         }
+        self.state.events.iter().rev().nth(self.selected_index)
+    }
+
+    /// Get the job of the selected event (in events view), following aliases.
+    pub fn selected_event_job(&self) -> Option<&charmer_state::Job> {
+        let id = self.selected_event()?.kind.job_id()?;
+        let id = self.state.job_aliases.get(id).map_or(id, |s| s.as_str());
+        self.state.jobs.get(id)
     }
 
     /// Toggle log viewer for the currently selected job.
@@ -554,11 +824,33 @@ impl App {
         latest.map(|(_, path)| path)
     }
 
+    /// Log shown for the main pipeline row: snakemake's own output when
+    /// charmer started it, otherwise the latest main log.
+    fn main_log_path(&self) -> Option<String> {
+        self.main_log
+            .clone()
+            .or_else(|| self.find_latest_snakemake_log())
+    }
+
+    /// Record the arguments charmer started snakemake with, so a rerun can
+    /// use the same targets and options.
+    pub fn set_snakemake_args(&mut self, args: Vec<String>) {
+        self.snakemake_args = Some(args);
+    }
+
+    /// Show this file instead of the latest main log for the main pipeline row.
+    pub fn set_main_log(&mut self, path: impl Into<String>) {
+        self.main_log = Some(path.into());
+        if self.show_log_viewer && self.is_main_pipeline_selected() {
+            self.update_log_viewer_for_selected();
+        }
+    }
+
     /// Open log viewer for the currently selected job.
     fn open_log_viewer(&mut self) {
         let log_path = if self.is_main_pipeline_selected() {
             // For main pipeline job, show the main snakemake log
-            self.find_latest_snakemake_log()
+            self.main_log_path()
                 .unwrap_or_else(|| "(no snakemake log found)".to_string())
         } else if let Some(job) = self.selected_job().cloned() {
             self.find_log_path(&job)
@@ -576,7 +868,7 @@ impl App {
     fn update_log_viewer_for_selected(&mut self) {
         let log_path = if self.is_main_pipeline_selected() {
             // For main pipeline job, show the main snakemake log
-            self.find_latest_snakemake_log()
+            self.main_log_path()
                 .unwrap_or_else(|| "(no snakemake log found)".to_string())
         } else if let Some(job) = self.selected_job().cloned() {
             self.find_log_path(&job)
@@ -610,8 +902,54 @@ impl App {
 
     /// Update app state from external source (polling service).
     pub fn update_from_state(&mut self, new_state: PipelineState) {
+        // New events push the list down; stay on the selected one
+        let event_seq = self.selected_event().map(|event| event.seq);
         self.state = new_state;
         self.update_job_list();
+        match self.view_mode {
+            ViewMode::Dag => {
+                // Nodes move as jobs appear; stay on the selected one
+                let selected = self
+                    .dag
+                    .node(self.selected_index)
+                    .map(|node| (node.rule.clone(), node.job_id.clone()));
+                self.update_dag();
+                if let Some(index) = selected.and_then(|(rule, job_id)| {
+                    let nodes = &self.dag.nodes;
+                    self.dag
+                        .order
+                        .iter()
+                        .position(|&n| nodes[n].rule == rule && nodes[n].job_id == job_id)
+                }) {
+                    self.selected_index = index;
+                }
+            }
+            ViewMode::Timeline => {
+                // Rows are reordered as jobs start; stay on the selected one
+                let selected = self
+                    .timeline
+                    .row(self.selected_index)
+                    .map(|row| (row.rule.clone(), row.job_id.clone()));
+                self.update_timeline();
+                if let Some(index) = selected.and_then(|(rule, job_id)| {
+                    self.timeline
+                        .rows
+                        .iter()
+                        .position(|row| row.rule == rule && row.job_id == job_id)
+                }) {
+                    self.selected_index = index;
+                }
+            }
+            ViewMode::Events => {
+                // Newest first: the event's row is the number of newer
+                // events, or the oldest row once it has been dropped
+                let newer = event_seq.map_or(self.selected_index, |seq| {
+                    self.state.events.since(seq + 1).count()
+                });
+                self.selected_index = newer.min(self.state.events.len().saturating_sub(1));
+            }
+            _ => {}
+        }
 
         // Refresh log viewer if in follow mode
         if self.show_log_viewer
@@ -653,6 +991,198 @@ impl App {
         }
     }
 
+    /// Show a temporary message in the footer.
+    pub fn set_status_message(&mut self, message: impl Into<String>) {
+        self.status_message = Some((message.into(), Instant::now()));
+    }
+
+    /// Take the confirmed job-control actions that haven't been sent yet.
+    pub fn take_action_requests(&mut self) -> Vec<ActionRequest> {
+        std::mem::take(&mut self.action_requests)
+    }
+
+    /// Offer to restart snakemake after it exited with `exit_code`,
+    /// unlocking the working directory first if it was `locked`.
+    pub fn offer_restart(&mut self, exit_code: i32, locked: bool) {
+        self.restart_prompt = Some((exit_code, locked));
+    }
+
+    /// Take a confirmed restart; true if the directory should be unlocked first.
+    pub fn take_restart_request(&mut self) -> Option<bool> {
+        self.restart_request.take()
+    }
+
+    /// Take the snakemake arguments of a confirmed rerun, if any.
+    pub fn take_rerun_request(&mut self) -> Option<Vec<String>> {
+        self.rerun_request.take()
+    }
+
+    /// Show the resource bumps suggested for failed jobs, for confirmation.
+    fn plan_rerun(&mut self) {
+        let bumps = suggested_bumps(&self.state);
+        if bumps.is_empty() {
+            self.set_status_message("No failed jobs with a suggested memory or time limit");
+            return;
+        }
+        self.rerun_plan = Some(bumps);
+    }
+
+    /// Write the planned bumps to the profile overlay, then copy the rerun
+    /// command or queue the rerun itself.
+    ///
+    /// Running it is refused unless charmer started snakemake: otherwise the
+    /// run's targets and options are unknown and the rerun would drop them.
+    fn confirm_rerun(&mut self, run: bool) {
+        if run && self.snakemake_args.is_none() {
+            self.set_status_message(
+                "Can't tell how the run was started; press w and run the command yourself",
+            );
+            return;
+        }
+        let Some(bumps) = self.rerun_plan.take() else {
+            return;
+        };
+        let args = self.snakemake_args.as_deref().unwrap_or_default();
+        let overlay = ProfileOverlay::new(&self.state.working_dir);
+        if let Err(e) = overlay.write(&bumps, option_value(args, "--workflow-profile")) {
+            self.set_status_message(format!("Failed to write {}: {}", overlay.config_path(), e));
+            return;
+        }
+        if run {
+            self.rerun_request = Some(overlay.rerun_args(args));
+            return;
+        }
+        let command = overlay.rerun_command(args);
+        let copied = arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(command.as_str()))
+            .is_ok();
+        self.set_status_message(if copied {
+            "Profile written; rerun command copied to clipboard".to_string()
+        } else {
+            format!("Profile written; run: {}", command)
+        });
+    }
+
+    /// Ask for confirmation of an action on the selected job, rule or run.
+    fn request_action(&mut self, action: JobAction) {
+        let scope = if self.selected_rule().is_some() {
+            ActionScope::Rule
+        } else if self.view_mode == ViewMode::Jobs && self.is_main_pipeline_selected() {
+            ActionScope::Run
+        } else {
+            ActionScope::Job
+        };
+        self.pending_action = Some((action, scope));
+    }
+
+    /// Queue the pending action for the main loop.
+    fn confirm_action(&mut self) {
+        let Some((action, scope)) = self.pending_action.take() else {
+            return;
+        };
+        let Some((target, job_ids)) = self.action_targets(action, scope) else {
+            self.set_status_message("Nothing selected");
+            return;
+        };
+        if job_ids.is_empty() {
+            self.set_status_message(format!("No jobs of {} to {}", target, verb(action)));
+            return;
+        }
+        self.set_status_message(format!(
+            "{} {} job(s) of {}...",
+            action.label(),
+            job_ids.len(),
+            target
+        ));
+        self.action_requests.push(ActionRequest {
+            action,
+            job_ids,
+            target,
+        });
+    }
+
+    /// Describe what `scope` covers and collect the scheduler job IDs the
+    /// action applies to. Returns None when nothing in that scope is selected.
+    fn action_targets(
+        &self,
+        action: JobAction,
+        scope: ActionScope,
+    ) -> Option<(String, Vec<String>)> {
+        let selected = self.selected_job().or_else(|| self.selected_event_job());
+        let (target, jobs): (String, Vec<&charmer_state::Job>) = match scope {
+            ActionScope::Job => {
+                if let Some(parent) = self
+                    .selected_job_id()
+                    .filter(|_| self.view_mode == ViewMode::Jobs)
+                    .and_then(array_group_parent)
+                {
+                    let tasks = self
+                        .state
+                        .jobs
+                        .values()
+                        .filter(|job| job.array.as_ref().is_some_and(|a| a.parent_id == parent))
+                        .collect();
+                    (format!("array {}", parent), tasks)
+                } else {
+                    let job = selected?;
+                    let label = match &job.wildcards {
+                        Some(wildcards) => format!("{} ({})", job.rule, wildcards),
+                        None => job.rule.clone(),
+                    };
+                    (format!("job {}", label), vec![job])
+                }
+            }
+            ActionScope::Rule => {
+                let rule = self
+                    .selected_rule()
+                    .or_else(|| selected.map(|job| job.rule.as_str()))?;
+                let jobs = self
+                    .state
+                    .jobs_by_rule
+                    .get(rule)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| self.state.jobs.get(id))
+                    .collect();
+                (format!("rule {}", rule), jobs)
+            }
+            ActionScope::Run => {
+                // Only snakemake's jobs of the selected run, whatever the
+                // display filter: the user's other jobs are never touched
+                let in_run = match (&self.selected_run, &self.state.run_uuid) {
+                    (Some(selected), Some(shown)) => selected == shown,
+                    _ => true,
+                };
+                let jobs = self
+                    .state
+                    .jobs
+                    .values()
+                    .filter(|job| in_run && job.is_snakemake_job)
+                    .collect();
+                ("the run".to_string(), jobs)
+            }
+        };
+
+        // Only jobs the scheduler still holds can be acted on; held jobs
+        // show as queued or pending
+        let mut job_ids: Vec<String> = jobs
+            .into_iter()
+            .filter(|job| match action {
+                JobAction::Cancel => matches!(
+                    job.status,
+                    JobStatus::Pending | JobStatus::Queued | JobStatus::Running
+                ),
+                JobAction::Hold | JobAction::Release => {
+                    matches!(job.status, JobStatus::Pending | JobStatus::Queued)
+                }
+            })
+            .filter_map(|job| job.scheduler_job_id.clone())
+            .collect();
+        job_ids.sort();
+        job_ids.dedup();
+        Some((target, job_ids))
+    }
+
     /// Handle a key event.
     pub fn handle_key(&mut self, key: KeyEvent) {
         // If help is showing, any key closes it
@@ -661,22 +1191,57 @@ impl App {
             return;
         }
 
+        // If a restart is on offer, only confirm/dismiss keys apply
+        if let Some((_, locked)) = self.restart_prompt {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.restart_prompt = None;
+                    self.restart_request = Some(locked);
+                }
+                KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
+                    self.restart_prompt = None
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        // If a rerun is awaiting confirmation, only write/run/cancel keys apply
+        if self.rerun_plan.is_some() {
+            match key.code {
+                KeyCode::Char('w') | KeyCode::Enter => self.confirm_rerun(false),
+                KeyCode::Char('r') => self.confirm_rerun(true),
+                KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => self.rerun_plan = None,
+                _ => {}
+            }
+            return;
+        }
+
+        // If an action is awaiting confirmation, only confirm/cancel/scope keys apply
+        if let Some((action, scope)) = self.pending_action {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => self.confirm_action(),
+                KeyCode::Tab => self.pending_action = Some((action, scope.next())),
+                KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
+                    self.pending_action = None
+                }
+                _ => {}
+            }
+            return;
+        }
+
         // If run picker is showing, handle picker navigation
         if self.show_run_picker {
             match key.code {
                 KeyCode::Esc | KeyCode::Char('q') => self.show_run_picker = false,
-                KeyCode::Char('j') | KeyCode::Down => {
-                    if !self.runs.is_empty() {
-                        self.run_picker_index = (self.run_picker_index + 1) % self.runs.len();
-                    }
+                KeyCode::Char('j') | KeyCode::Down if !self.runs.is_empty() => {
+                    self.run_picker_index = (self.run_picker_index + 1) % self.runs.len();
                 }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if !self.runs.is_empty() {
-                        self.run_picker_index = self
-                            .run_picker_index
-                            .checked_sub(1)
-                            .unwrap_or(self.runs.len() - 1);
-                    }
+                KeyCode::Char('k') | KeyCode::Up if !self.runs.is_empty() => {
+                    self.run_picker_index = self
+                        .run_picker_index
+                        .checked_sub(1)
+                        .unwrap_or(self.runs.len() - 1);
                 }
                 KeyCode::Enter => {
                     if let Some(run) = self.runs.get(self.run_picker_index) {
@@ -696,6 +1261,13 @@ impl App {
             return;
         }
 
+        if self.view_mode == ViewMode::Dag && self.handle_dag_key(key) {
+            return;
+        }
+        if self.view_mode == ViewMode::Timeline && self.handle_timeline_key(key) {
+            return;
+        }
+
         match key.code {
             KeyCode::Char('q') => self.quit(),
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit(),
@@ -720,6 +1292,7 @@ impl App {
             KeyCode::Char('r') => self.toggle_view_mode(),
             KeyCode::Char('R') => self.toggle_run_picker(),
             KeyCode::Char('a') => self.toggle_all_jobs(),
+            KeyCode::Char(' ') => self.toggle_array_group(),
             KeyCode::Char('l') | KeyCode::Enter => self.toggle_log_viewer(),
             KeyCode::Char('F') if self.show_log_viewer => {
                 // Toggle follow mode when log panel is open
@@ -730,10 +1303,69 @@ impl App {
             KeyCode::Char('?') => self.toggle_help(),
             KeyCode::Char('c') => self.copy_command(),
             KeyCode::Char('e') => self.command_expanded = !self.command_expanded,
+            KeyCode::Char('x') => self.request_action(JobAction::Cancel),
+            KeyCode::Char('h') => self.request_action(JobAction::Hold),
+            KeyCode::Char('u') => self.request_action(JobAction::Release),
+            KeyCode::Char('b') => self.plan_rerun(),
             _ => {}
         }
     }
 
+    /// Handle the DAG view's pan, zoom and jump keys. Returns false for keys
+    /// left to the common handling (its selection moves with the usual keys).
+    fn handle_dag_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('H') | KeyCode::Left => self.dag_pan.0 -= DAG_PAN_X,
+            KeyCode::Char('L') | KeyCode::Right => self.dag_pan.0 += DAG_PAN_X,
+            KeyCode::Char('K') | KeyCode::PageUp => self.dag_pan.1 -= DAG_PAN_Y,
+            KeyCode::Char('J') | KeyCode::PageDown => self.dag_pan.1 += DAG_PAN_Y,
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.dag_zoom = (self.dag_zoom + 1).min(dag::MAX_ZOOM)
+            }
+            KeyCode::Char('-') => self.dag_zoom = self.dag_zoom.saturating_sub(1),
+            KeyCode::Char('d') => self.toggle_dag_level(),
+            KeyCode::Enter => self.jump_to_dag_jobs(),
+            KeyCode::Char('j' | 'k' | 'g' | 'G')
+            | KeyCode::Down
+            | KeyCode::Up
+            | KeyCode::Home
+            | KeyCode::End => {
+                // Recentre on the newly selected node
+                self.dag_pan = (0, 0);
+                return false;
+            }
+            _ => return false,
+        }
+        // Don't let the pan run off the graph
+        self.dag_pan = self
+            .dag
+            .clamp_pan(Some(self.selected_index), self.dag_zoom, self.dag_pan);
+        true
+    }
+
+    /// Handle the timeline's scroll, zoom and lane keys. Returns false for
+    /// keys left to the common handling.
+    fn handle_timeline_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('H') | KeyCode::Left => {
+                self.timeline_scroll =
+                    (self.timeline_scroll + 1).min(timeline::max_scroll(self.timeline_zoom))
+            }
+            KeyCode::Char('L') | KeyCode::Right => {
+                self.timeline_scroll = self.timeline_scroll.saturating_sub(1)
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom_timeline(true),
+            KeyCode::Char('-') => self.zoom_timeline(false),
+            KeyCode::Char('0') => {
+                self.timeline_zoom = 0;
+                self.timeline_scroll = 0;
+            }
+            KeyCode::Char('d') => self.toggle_timeline_lanes(),
+            _ => return false,
+        }
+        true
+    }
+
     /// Poll for events and handle them.
     pub fn poll_events(&mut self, timeout: Duration) -> std::io::Result<bool> {
         if event::poll(timeout)?
@@ -821,6 +1453,64 @@ impl App {
                     self.render_rule_detail(frame, main_chunks[1], rule);
                 }
             }
+            ViewMode::Events => {
+                EventLogView::render(
+                    frame,
+                    main_chunks[0],
+                    &self.state,
+                    Some(self.selected_index),
+                );
+
+                // Show the job the selected event is about
+                match self.selected_event_job() {
+                    Some(job) => {
+                        JobDetail::render(frame, main_chunks[1], Some(job), self.command_expanded)
+                    }
+                    None => JobDetail::render_pipeline(frame, main_chunks[1], &self.state),
+                }
+            }
+            ViewMode::Dag => {
+                // The graph gets the wider share of the panel
+                let dag_chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                    .split(chunks[1]);
+                DagView::render(
+                    frame,
+                    dag_chunks[0],
+                    &self.dag,
+                    Some(self.selected_index),
+                    self.dag_zoom,
+                    self.dag_pan,
+                );
+
+                if let Some(rule) = self.selected_rule() {
+                    self.render_rule_detail(frame, dag_chunks[1], rule);
+                } else if let Some(job) = self.selected_job() {
+                    JobDetail::render(frame, dag_chunks[1], Some(job), self.command_expanded);
+                }
+            }
+            ViewMode::Timeline => {
+                // Bars need the width more than the details do
+                let timeline_chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                    .split(chunks[1]);
+                TimelineView::render(
+                    frame,
+                    timeline_chunks[0],
+                    &self.timeline,
+                    Some(self.selected_index),
+                    self.timeline_zoom,
+                    self.timeline_scroll,
+                );
+
+                if let Some(rule) = self.selected_rule() {
+                    self.render_rule_detail(frame, timeline_chunks[1], rule);
+                } else if let Some(job) = self.selected_job() {
+                    JobDetail::render(frame, timeline_chunks[1], Some(job), self.command_expanded);
+                }
+            }
         }
 
         // Log panel at bottom (if open)
@@ -838,7 +1528,12 @@ impl App {
         });
 
         // Footer with optional status message
-        Footer::render(frame, chunks[3], status_msg);
+        Footer::render(
+            frame,
+            chunks[3],
+            status_msg,
+            &self.state.pending_reason_counts(),
+        );
 
         // Overlays (on top of everything)
         if self.show_help {
@@ -847,6 +1542,201 @@ impl App {
         if self.show_run_picker {
             self.render_run_picker(frame);
         }
+        if let Some((action, scope)) = self.pending_action {
+            self.render_action_confirm(frame, action, scope);
+        }
+        if let Some(bumps) = &self.rerun_plan {
+            self.render_rerun_plan(frame, bumps);
+        }
+        if let Some((exit_code, locked)) = self.restart_prompt {
+            self.render_restart_prompt(frame, exit_code, locked);
+        }
+    }
+
+    /// Render the offer to restart a failed snakemake child.
+    fn render_restart_prompt(&self, frame: &mut Frame, exit_code: i32, locked: bool) {
+        use ratatui::style::{Color, Modifier, Style};
+        use ratatui::text::{Line, Span};
+        use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+        let area = centered_rect(50, 30, frame.area());
+        frame.render_widget(Clear, area);
+
+        let (reason, question) = if locked {
+            (
+                "The working directory is locked.".to_string(),
+                "Run snakemake --unlock, then restart with --rerun-incomplete?",
+            )
+        } else {
+            (
+                format!("Snakemake exited with status {}.", exit_code),
+                "Restart with --rerun-incomplete?",
+            )
+        };
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                reason,
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(question),
+        ];
+        if locked {
+            lines.push(Line::from(Span::styled(
+                "Only unlock if no other snakemake is running in this directory.",
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "y/Enter: restart  n/Esc: dismiss",
+            Style::default().fg(Color::Gray),
+        )));
+
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Snakemake stopped ")
+                    .style(Style::default().bg(Color::DarkGray)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::DarkGray));
+        frame.render_widget(paragraph, area);
+    }
+
+    /// Render the fix-and-rerun modal: planned bumps and the rerun command.
+    fn render_rerun_plan(&self, frame: &mut Frame, bumps: &[ResourceBump]) {
+        use ratatui::style::{Color, Modifier, Style};
+        use ratatui::text::{Line, Span};
+        use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+        let area = centered_rect(70, 50, frame.area());
+        frame.render_widget(Clear, area);
+
+        let overlay = ProfileOverlay::new(&self.state.working_dir);
+        let heading = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+        let mut lines = vec![Line::from(Span::styled("Resources", heading))];
+        for bump in bumps {
+            let previous = bump
+                .previous
+                .map_or_else(|| "?".to_string(), |v| v.to_string());
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {}", bump.rule), Style::default().fg(Color::Cyan)),
+                Span::styled(
+                    format!("  {}: {} → ", bump.resource, previous),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::styled(
+                    bump.value.to_string(),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Profile", heading)));
+        lines.push(Line::from(format!("  {}", overlay.config_path())));
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Command", heading)));
+        let args = self.snakemake_args.as_deref().unwrap_or_default();
+        lines.push(Line::from(Span::styled(
+            format!("  {}", overlay.rerun_command(args)),
+            Style::default().fg(Color::Yellow),
+        )));
+        if self.snakemake_args.is_none() {
+            lines.push(Line::from(Span::styled(
+                "  Add the run's targets and options (--profile, --executor, ...)",
+                Style::default().fg(Color::Gray),
+            )));
+        }
+        lines.push(Line::from(""));
+        let keys = if self.snakemake_args.is_some() {
+            "w/Enter: write profile and copy command  r: write and restart snakemake  Esc: cancel"
+        } else {
+            "w/Enter: write profile and copy command  Esc: cancel"
+        };
+        lines.push(Line::from(Span::styled(
+            keys,
+            Style::default().fg(Color::Gray),
+        )));
+
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Fix and rerun ")
+                    .style(Style::default().bg(Color::DarkGray)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::DarkGray));
+        frame.render_widget(paragraph, area);
+    }
+
+    /// Render the confirmation modal for a job-control action.
+    fn render_action_confirm(&self, frame: &mut Frame, action: JobAction, scope: ActionScope) {
+        use ratatui::style::{Color, Modifier, Style};
+        use ratatui::text::{Line, Span};
+        use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+        let area = centered_rect(50, 30, frame.area());
+        frame.render_widget(Clear, area);
+
+        let question = match self.action_targets(action, scope) {
+            None => format!("No {} selected", scope.label().to_lowercase()),
+            Some((target, ids)) if ids.is_empty() => {
+                format!("No jobs of {} to {}", target, verb(action))
+            }
+            Some((target, ids)) => {
+                format!("{} {} job(s) of {}?", action.label(), ids.len(), target)
+            }
+        };
+
+        let mut scope_spans = vec![Span::styled("Scope: ", Style::default().fg(Color::Gray))];
+        for option in [ActionScope::Job, ActionScope::Rule, ActionScope::Run] {
+            let style = if option == scope {
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            scope_spans.push(Span::styled(format!(" {} ", option.label()), style));
+            scope_spans.push(Span::raw(" "));
+        }
+
+        let lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                question,
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(scope_spans),
+            Line::from(""),
+            Line::from(Span::styled(
+                "y/Enter: confirm  Tab: change scope  n/Esc: cancel",
+                Style::default().fg(Color::Gray),
+            )),
+        ];
+
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} jobs ", action.label()))
+                    .style(Style::default().bg(Color::DarkGray)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::DarkGray));
+        frame.render_widget(paragraph, area);
     }
 
     /// Render run picker modal.
@@ -1142,7 +2032,7 @@ impl App {
         use ratatui::style::{Color, Style};
         use ratatui::widgets::{Block, Borders, Paragraph};
 
-        let area = centered_rect(60, 60, frame.area());
+        let area = centered_rect(60, 80, frame.area());
 
         let help_text = r#"
   Keyboard Shortcuts
@@ -1152,16 +2042,32 @@ impl App {
   k / ↑      Move up (also updates log panel)
   g / Home   Go to first item
   G / End    Go to last item
-  r          Toggle view (Jobs/Rules summary)
+  r          Cycle view (Jobs/Rules summary/Events/DAG/Timeline)
   R          Open run selector
   a          Toggle all jobs / snakemake only
+  Space      Expand/collapse array job
   f          Cycle filter (All/Running/Failed/Pending/Completed)
   s          Cycle sort (Status/Rule/Time/Recent)
   l / Enter  Toggle log panel
   F          Toggle follow mode (when logs open)
   c          Copy command to clipboard
   e          Expand/collapse command
+  x          Cancel jobs (Tab picks job/rule/run)
+  h          Hold queued jobs
+  u          Release held jobs
+  b          Fix and rerun failed jobs with more resources
   ?          Toggle this help
+
+  DAG view
+  H/J/K/L    Pan (also ←/→, PgUp/PgDn)
+  + / -      Zoom in/out
+  d          Toggle rule / job nodes
+  Enter      Show the node's jobs
+
+  Timeline
+  H/L, ←/→   Scroll back/forward in time
+  + / -      Zoom in/out (0 shows the whole run)
+  d          Toggle job rows / rule lanes
   q / Ctrl+C Quit
 
   Press any key to close
@@ -1181,6 +2087,10 @@ impl App {
     }
 }
 
+/// Columns and rows the DAG view pans per key press.
+const DAG_PAN_X: i32 = 8;
+const DAG_PAN_Y: i32 = 4;
+
 /// Create a centered rectangle.
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -1202,6 +2112,11 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+/// Lowercase verb for messages ("cancel").
+fn verb(action: JobAction) -> String {
+    action.label().to_lowercase()
+}
+
 /// Format seconds as human-readable duration.
 fn format_secs(secs: u64) -> String {
     if secs >= 3600 {
@@ -0,0 +1,821 @@
+//! DAG view: the rule graph (or job graph) drawn as layers, top to bottom.
+//!
+//! Edges come from the same output->producer map the job list uses for its
+//! chain gutter. Nodes are layered by longest path from the sources, edges
+//! that skip layers are routed through invisible pass-through nodes, and each
+//! layer is ordered by the positions of its parents to keep crossings down.
+
+use crate::app::ViewMode;
+use crate::components::ViewTabs;
+use crate::components::job_detail::status_style;
+use crate::components::job_list::output_producers;
+use charmer_state::{Job, JobCounts, JobStatus, PipelineState};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Rules with more jobs than this are shown as one node in the job-level graph.
+const COLLAPSE_THRESHOLD: usize = 8;
+
+/// Above this many jobs the job-level graph falls back to rule nodes only.
+const MAX_JOB_NODES: usize = 200;
+
+/// Rows from one layer to the next (node row plus three rows of edges).
+const LAYER_HEIGHT: usize = 4;
+
+/// Columns between neighbouring nodes of a layer.
+const NODE_GAP: usize = 2;
+
+/// Highest zoom level.
+pub const MAX_ZOOM: u8 = 2;
+
+/// What the nodes of the graph stand for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DagLevel {
+    /// One node per rule
+    #[default]
+    Rules,
+    /// One node per job; rules with many jobs stay collapsed
+    Jobs,
+}
+
+impl DagLevel {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Rules => Self::Jobs,
+            Self::Jobs => Self::Rules,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Rules => "rules",
+            Self::Jobs => "jobs",
+        }
+    }
+}
+
+/// A node of the laid-out graph.
+#[derive(Debug, Clone, Default)]
+pub struct DagNode {
+    /// Rule the node's jobs belong to
+    pub rule: String,
+    /// The job, for a node standing for a single job
+    pub job_id: Option<String>,
+    /// Rule name, or rule and wildcards for a job node
+    pub label: String,
+    /// Status counts of the node's jobs
+    pub counts: JobCounts,
+    /// Pass-through point of an edge that skips layers
+    pub dummy: bool,
+}
+
+impl DagNode {
+    /// Status shown for the node: failures first, then activity.
+    pub fn status(&self) -> JobStatus {
+        let c = &self.counts;
+        if c.failed > 0 {
+            JobStatus::Failed
+        } else if c.running > 0 {
+            JobStatus::Running
+        } else if c.total > 0 && c.completed == c.total {
+            JobStatus::Completed
+        } else if c.queued > 0 {
+            JobStatus::Queued
+        } else if c.pending > 0 {
+            JobStatus::Pending
+        } else if c.cancelled > 0 {
+            JobStatus::Cancelled
+        } else {
+            JobStatus::Unknown
+        }
+    }
+
+    fn add(&mut self, job: &Job) {
+        let c = &mut self.counts;
+        c.total += 1;
+        match job.status {
+            JobStatus::Pending => c.pending += 1,
+            JobStatus::Queued => c.queued += 1,
+            JobStatus::Running => c.running += 1,
+            JobStatus::Completed => c.completed += 1,
+            JobStatus::Failed => c.failed += 1,
+            JobStatus::Cancelled => c.cancelled += 1,
+            JobStatus::Unknown => c.unknown += 1,
+        }
+    }
+
+    /// Text drawn inside the node's brackets at a zoom level.
+    fn text(&self, zoom: u8) -> String {
+        let max_len = match zoom {
+            0 => 12,
+            1 => 24,
+            _ => usize::MAX,
+        };
+        let name = truncate(&self.label, max_len);
+        let c = &self.counts;
+        if zoom == 0 || self.job_id.is_some() {
+            name
+        } else if zoom == 1 {
+            format!("{} {}/{}", name, c.completed, c.total)
+        } else {
+            let mut text = format!("{} {}/{}", name, c.completed, c.total);
+            if c.running > 0 {
+                text.push_str(&format!(" ▶{}", c.running));
+            }
+            if c.failed > 0 {
+                text.push_str(&format!(" ✗{}", c.failed));
+            }
+            text
+        }
+    }
+}
+
+/// The graph with nodes assigned to layers.
+#[derive(Debug, Clone, Default)]
+pub struct DagLayout {
+    /// Real and pass-through nodes
+    pub nodes: Vec<DagNode>,
+    /// Node indices per layer, left to right
+    pub layers: Vec<Vec<usize>>,
+    /// Edges between nodes of adjacent layers
+    pub edges: Vec<(usize, usize)>,
+    /// Selectable (non pass-through) nodes, layer by layer
+    pub order: Vec<usize>,
+    /// Level actually shown (job level falls back to rules for large runs)
+    pub level: DagLevel,
+    /// Rules shown as a single node in the job-level graph
+    pub collapsed_rules: usize,
+}
+
+impl DagLayout {
+    /// Lay out the snakemake jobs of `state` at the requested level.
+    pub fn build(state: &PipelineState, level: DagLevel) -> Self {
+        let mut jobs: Vec<(&String, &Job)> = state
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.is_snakemake_job)
+            .collect();
+        jobs.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut rule_sizes: HashMap<&str, usize> = HashMap::new();
+        for (_, job) in &jobs {
+            *rule_sizes.entry(job.rule.as_str()).or_default() += 1;
+        }
+        let level = if level == DagLevel::Jobs && jobs.len() > MAX_JOB_NODES {
+            DagLevel::Rules
+        } else {
+            level
+        };
+
+        // Nodes, and the node each job is drawn as
+        let mut nodes: Vec<DagNode> = Vec::new();
+        let mut rule_nodes: HashMap<&str, usize> = HashMap::new();
+        let mut node_of: HashMap<&str, usize> = HashMap::new();
+        for (id, job) in &jobs {
+            let collapse =
+                level == DagLevel::Rules || rule_sizes[job.rule.as_str()] > COLLAPSE_THRESHOLD;
+            let idx = if collapse {
+                *rule_nodes.entry(job.rule.as_str()).or_insert_with(|| {
+                    nodes.push(DagNode {
+                        rule: job.rule.clone(),
+                        label: job.rule.clone(),
+                        ..Default::default()
+                    });
+                    nodes.len() - 1
+                })
+            } else {
+                nodes.push(DagNode {
+                    rule: job.rule.clone(),
+                    job_id: Some(id.to_string()),
+                    label: match &job.wildcards {
+                        Some(wildcards) => format!("{} ({})", job.rule, wildcards),
+                        None => job.rule.clone(),
+                    },
+                    ..Default::default()
+                });
+                nodes.len() - 1
+            };
+            nodes[idx].add(job);
+            node_of.insert(id.as_str(), idx);
+        }
+
+        // Producer -> consumer edges between nodes
+        let producers = output_producers(state);
+        let mut edges: BTreeSet<(usize, usize)> = BTreeSet::new();
+        for (id, job) in &jobs {
+            let to = node_of[id.as_str()];
+            for input in &job.inputs {
+                if let Some(producer) = producers.get(input.as_str())
+                    && let Some(&from) = node_of.get(producer)
+                    && from != to
+                {
+                    edges.insert((from, to));
+                }
+            }
+        }
+
+        let mut layer_of = assign_layers(nodes.len(), &edges);
+
+        // Route edges that skip layers through pass-through nodes
+        let mut adjacent = Vec::new();
+        for &(from, to) in &edges {
+            // Edges closing a cycle have nowhere to go
+            if layer_of[to] <= layer_of[from] {
+                continue;
+            }
+            let mut prev = from;
+            for layer in layer_of[from] + 1..layer_of[to] {
+                nodes.push(DagNode {
+                    rule: nodes[from].rule.clone(),
+                    dummy: true,
+                    ..Default::default()
+                });
+                layer_of.push(layer);
+                adjacent.push((prev, nodes.len() - 1));
+                prev = nodes.len() - 1;
+            }
+            adjacent.push((prev, to));
+        }
+
+        let layer_count = layer_of.iter().max().map_or(0, |max| max + 1);
+        let mut layers: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+        for (idx, &layer) in layer_of.iter().enumerate() {
+            layers[layer].push(idx);
+        }
+        for layer in &mut layers {
+            layer.sort_by(|&a, &b| nodes[a].label.cmp(&nodes[b].label));
+        }
+        order_layers(&mut layers, &adjacent);
+
+        let order = layers
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&idx| !nodes[idx].dummy)
+            .collect();
+        let collapsed_rules = if level == DagLevel::Jobs {
+            rule_nodes.len()
+        } else {
+            0
+        };
+
+        Self {
+            nodes,
+            layers,
+            edges: adjacent,
+            order,
+            level,
+            collapsed_rules,
+        }
+    }
+
+    /// Number of selectable nodes.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// The `index`th selectable node.
+    pub fn node(&self, index: usize) -> Option<&DagNode> {
+        self.order.get(index).map(|&idx| &self.nodes[idx])
+    }
+
+    /// Keep the point the view centres on (the selected node shifted by
+    /// `pan`) inside the canvas drawn at `zoom`.
+    pub fn clamp_pan(&self, selected: Option<usize>, zoom: u8, pan: (i32, i32)) -> (i32, i32) {
+        if self.is_empty() {
+            return (0, 0);
+        }
+        let placement = self.place(zoom);
+        let (focus_x, focus_row) = placement.focus(self, selected);
+        let (width, height) = (placement.width as i32, placement.height as i32);
+        (
+            pan.0.clamp(-focus_x, width - 1 - focus_x),
+            pan.1.clamp(-focus_row, height - 1 - focus_row),
+        )
+    }
+
+    /// Node texts and canvas positions at a zoom level. The layout must
+    /// have at least one layer.
+    fn place(&self, zoom: u8) -> Placement {
+        // Horizontal placement: each layer centred on the widest one
+        let texts: Vec<String> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                if node.dummy {
+                    String::new()
+                } else {
+                    format!("[{}]", node.text(zoom))
+                }
+            })
+            .collect();
+        let widths: Vec<usize> = texts
+            .iter()
+            .map(|text| text.chars().count().max(1))
+            .collect();
+        let layer_widths: Vec<usize> = self
+            .layers
+            .iter()
+            .map(|layer| {
+                layer.iter().map(|&n| widths[n]).sum::<usize>()
+                    + NODE_GAP * layer.len().saturating_sub(1)
+            })
+            .collect();
+        let width = layer_widths.iter().copied().max().unwrap_or(0);
+        let height = (self.layers.len() - 1) * LAYER_HEIGHT + 1;
+
+        let mut x_of = vec![0; self.nodes.len()];
+        let mut row_of = vec![0; self.nodes.len()];
+        for (l, layer) in self.layers.iter().enumerate() {
+            let mut x = (width - layer_widths[l]) / 2;
+            for &node in layer {
+                x_of[node] = x;
+                row_of[node] = l * LAYER_HEIGHT;
+                x += widths[node] + NODE_GAP;
+            }
+        }
+
+        Placement {
+            texts,
+            widths,
+            x_of,
+            row_of,
+            width,
+            height,
+        }
+    }
+}
+
+/// Where the nodes of a layout are drawn on the canvas.
+struct Placement {
+    /// Bracketed node texts (empty for pass-through nodes)
+    texts: Vec<String>,
+    /// Columns each node takes
+    widths: Vec<usize>,
+    /// Leftmost column of each node
+    x_of: Vec<usize>,
+    /// Row of each node
+    row_of: Vec<usize>,
+    /// Canvas size
+    width: usize,
+    height: usize,
+}
+
+impl Placement {
+    /// Column edges enter and leave a node at.
+    fn center(&self, node: usize) -> usize {
+        self.x_of[node] + self.widths[node] / 2
+    }
+
+    /// Canvas cell the view follows: the selected node, or the top left.
+    fn focus(&self, layout: &DagLayout, selected: Option<usize>) -> (i32, i32) {
+        selected
+            .and_then(|i| layout.order.get(i).copied())
+            .map_or((0, 0), |n| (self.center(n) as i32, self.row_of[n] as i32))
+    }
+}
+
+/// Layer of each node: the length of the longest path reaching it.
+///
+/// Nodes on a cycle (which snakemake doesn't allow, but partial metadata
+/// can fake) keep the layer their acyclic parents gave them.
+fn assign_layers(node_count: usize, edges: &BTreeSet<(usize, usize)>) -> Vec<usize> {
+    let mut children = vec![Vec::new(); node_count];
+    let mut indegree = vec![0usize; node_count];
+    for &(from, to) in edges {
+        children[from].push(to);
+        indegree[to] += 1;
+    }
+
+    let mut layer = vec![0; node_count];
+    let mut queue: VecDeque<usize> = (0..node_count).filter(|&n| indegree[n] == 0).collect();
+    while let Some(node) = queue.pop_front() {
+        for &child in &children[node] {
+            layer[child] = layer[child].max(layer[node] + 1);
+            indegree[child] -= 1;
+            if indegree[child] == 0 {
+                queue.push_back(child);
+            }
+        }
+    }
+    layer
+}
+
+/// Reorder each layer by the mean position of its neighbours in the layer
+/// above (then below, then above again), which untangles most edges.
+fn order_layers(layers: &mut [Vec<usize>], edges: &[(usize, usize)]) {
+    let sweep = |layers: &mut [Vec<usize>], down: bool| {
+        let count = layers.len();
+        for step in 1..count {
+            let (fixed, current) = if down {
+                (step - 1, step)
+            } else {
+                (count - step, count - step - 1)
+            };
+            let position: HashMap<usize, usize> = layers[fixed]
+                .iter()
+                .enumerate()
+                .map(|(pos, &node)| (node, pos))
+                .collect();
+            let key = |(own, node): (usize, usize)| {
+                let neighbours: Vec<usize> = edges
+                    .iter()
+                    .filter_map(|&(from, to)| {
+                        let other = if down {
+                            (to == node).then_some(from)
+                        } else {
+                            (from == node).then_some(to)
+                        };
+                        other.and_then(|other| position.get(&other).copied())
+                    })
+                    .collect();
+                if neighbours.is_empty() {
+                    own as f64
+                } else {
+                    neighbours.iter().sum::<usize>() as f64 / neighbours.len() as f64
+                }
+            };
+            let mut keyed: Vec<(f64, usize)> = layers[current]
+                .iter()
+                .copied()
+                .enumerate()
+                .map(|entry| (key(entry), entry.1))
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[current] = keyed.into_iter().map(|(_, node)| node).collect();
+        }
+    };
+    sweep(layers, true);
+    sweep(layers, false);
+    sweep(layers, true);
+}
+
+// Edge directions of a canvas cell
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// Box-drawing character joining the given edge directions.
+fn edge_char(mask: u8) -> char {
+    match mask {
+        0 => ' ',
+        m if m == UP | DOWN || m == UP || m == DOWN => '│',
+        m if m == LEFT | RIGHT || m == LEFT || m == RIGHT => '─',
+        m if m == UP | RIGHT => '└',
+        m if m == UP | LEFT => '┘',
+        m if m == DOWN | RIGHT => '┌',
+        m if m == DOWN | LEFT => '┐',
+        m if m == UP | DOWN | RIGHT => '├',
+        m if m == UP | DOWN | LEFT => '┤',
+        m if m == UP | LEFT | RIGHT => '┴',
+        m if m == DOWN | LEFT | RIGHT => '┬',
+        _ => '┼',
+    }
+}
+
+pub struct DagView;
+
+impl DagView {
+    /// Render the graph, centred on the selected node and shifted by `pan`.
+    pub fn render(
+        frame: &mut Frame,
+        area: Rect,
+        layout: &DagLayout,
+        selected: Option<usize>,
+        zoom: u8,
+        pan: (i32, i32),
+    ) {
+        let mut summary = format!(" {} ", layout.level.label());
+        if layout.collapsed_rules > 0 {
+            summary.push_str(&format!("· {} collapsed ", layout.collapsed_rules));
+        }
+        summary.push_str(&format!("· zoom {}/{} ", zoom + 1, MAX_ZOOM + 1));
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(ViewTabs::title_line(ViewMode::Dag))
+            .title_bottom(Line::from(Span::styled(
+                summary,
+                Style::default().fg(Color::DarkGray),
+            )));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if layout.is_empty() {
+            let empty = Paragraph::new(Span::styled(
+                " No jobs with known inputs and outputs yet",
+                Style::default().fg(Color::DarkGray),
+            ));
+            frame.render_widget(empty, inner);
+            return;
+        }
+
+        let selected_node = selected.and_then(|i| layout.order.get(i).copied());
+
+        let placement = layout.place(zoom);
+        let (texts, x_of, row_of) = (&placement.texts, &placement.x_of, &placement.row_of);
+        let (canvas_width, canvas_height) = (placement.width, placement.height);
+        let center = |node: usize| placement.center(node);
+
+        // Edges, drawn as masks so crossing and merging lines join up
+        let mut masks = vec![vec![0u8; canvas_width]; canvas_height];
+        let mut highlighted = vec![vec![false; canvas_width]; canvas_height];
+        for &(from, to) in &layout.edges {
+            let (x1, x2, row) = (center(from), center(to), row_of[from]);
+            let mut cells = vec![(row + 1, x1, UP | DOWN), (row + 3, x2, UP | DOWN)];
+            if x1 == x2 {
+                cells.push((row + 2, x1, UP | DOWN));
+            } else {
+                let (toward_child, toward_parent) = if x2 > x1 {
+                    (RIGHT, LEFT)
+                } else {
+                    (LEFT, RIGHT)
+                };
+                cells.push((row + 2, x1, UP | toward_child));
+                cells.push((row + 2, x2, DOWN | toward_parent));
+                for x in x1.min(x2) + 1..x1.max(x2) {
+                    cells.push((row + 2, x, LEFT | RIGHT));
+                }
+            }
+            let touches_selected = selected_node.is_some_and(|s| s == from || s == to);
+            for (r, x, mask) in cells {
+                masks[r][x] |= mask;
+                highlighted[r][x] |= touches_selected;
+            }
+        }
+        for (node, data) in layout.nodes.iter().enumerate() {
+            if data.dummy {
+                masks[row_of[node]][x_of[node]] |= UP | DOWN;
+            }
+        }
+
+        let edge_style = Style::default().fg(Color::DarkGray);
+        let highlight_style = Style::default().fg(Color::Cyan);
+        let mut canvas: Vec<Vec<(char, Style)>> = masks
+            .iter()
+            .zip(&highlighted)
+            .map(|(mask_row, hl_row)| {
+                mask_row
+                    .iter()
+                    .zip(hl_row)
+                    .map(|(&mask, &hl)| {
+                        (
+                            edge_char(mask),
+                            if hl { highlight_style } else { edge_style },
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Arrowheads into real nodes, then the nodes themselves
+        for &(_, to) in &layout.edges {
+            if !layout.nodes[to].dummy {
+                canvas[row_of[to] - 1][center(to)].0 = '▼';
+            }
+        }
+        for (node, data) in layout.nodes.iter().enumerate() {
+            if data.dummy {
+                continue;
+            }
+            let (_, color) = status_style(data.status());
+            let mut style = Style::default().fg(color);
+            if selected_node == Some(node) {
+                style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            for (i, ch) in texts[node].chars().enumerate() {
+                canvas[row_of[node]][x_of[node] + i] = (ch, style);
+            }
+        }
+
+        // Viewport: follow the selected node, offset by the pan
+        let (view_w, view_h) = (inner.width as i32, inner.height as i32);
+        let (focus_x, focus_row) = placement.focus(layout, selected);
+        let origin_x = viewport_origin(canvas_width as i32, view_w, focus_x, pan.0, true);
+        let origin_y = viewport_origin(canvas_height as i32, view_h, focus_row, pan.1, false);
+
+        let lines: Vec<Line> = (origin_y..origin_y + view_h)
+            .map(|y| {
+                let Some(row) = usize::try_from(y).ok().and_then(|y| canvas.get(y)) else {
+                    return Line::from("");
+                };
+                let mut spans: Vec<Span> = Vec::new();
+                let mut text = String::new();
+                let mut style = Style::default();
+                for x in origin_x..origin_x + view_w {
+                    let (ch, cell_style) = usize::try_from(x)
+                        .ok()
+                        .and_then(|x| row.get(x))
+                        .copied()
+                        .unwrap_or((' ', Style::default()));
+                    if cell_style != style && !text.is_empty() {
+                        spans.push(Span::styled(std::mem::take(&mut text), style));
+                    }
+                    style = cell_style;
+                    text.push(ch);
+                }
+                spans.push(Span::styled(text, style));
+                Line::from(spans)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+}
+
+/// First canvas coordinate shown: a canvas that fits is centred (or, for
+/// rows, top-aligned); otherwise the view centres on `focus` plus `pan`,
+/// clamped to the canvas.
+fn viewport_origin(canvas: i32, view: i32, focus: i32, pan: i32, center_small: bool) -> i32 {
+    if canvas <= view {
+        if center_small {
+            -(view - canvas) / 2
+        } else {
+            0
+        }
+    } else {
+        (focus - view / 2 + pan).clamp(0, canvas - view)
+    }
+}
+
+/// Truncate to `max_len` characters, marking the cut with an ellipsis.
+pub(crate) fn truncate(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(max_len.saturating_sub(1)).collect();
+        out.push('…');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charmer_state::{DataSources, JobResources, JobTiming};
+
+    fn job(id: &str, rule: &str, inputs: &[&str], outputs: &[&str]) -> Job {
+        Job {
+            id: id.to_string(),
+            rule: rule.to_string(),
+            wildcards: None,
+            outputs: outputs.iter().map(|s| s.to_string()).collect(),
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            status: JobStatus::Pending,
+            scheduler_job_id: None,
+            local_pid: None,
+            shellcmd: String::new(),
+            timing: JobTiming::default(),
+            resources: JobResources::default(),
+            usage: None,
+            log_files: vec![],
+            error: None,
+            conda_env: None,
+            container_img_url: None,
+            data_sources: DataSources::default(),
+            is_target: false,
+            is_snakemake_job: true,
+            array: None,
+            attempts: Vec::new(),
+        }
+    }
+
+    fn state(jobs: Vec<Job>) -> PipelineState {
+        let mut state = PipelineState::new("/work".into());
+        for job in jobs {
+            state.jobs.insert(job.id.clone(), job);
+        }
+        state
+    }
+
+    /// Layer of the node labelled `label`.
+    fn layer(layout: &DagLayout, label: &str) -> usize {
+        layout
+            .layers
+            .iter()
+            .position(|layer| {
+                layer
+                    .iter()
+                    .any(|&n| !layout.nodes[n].dummy && layout.nodes[n].label == label)
+            })
+            .unwrap()
+    }
+
+    /// align -> sort -> report, plus align -> report skipping a layer.
+    fn pipeline() -> PipelineState {
+        state(vec![
+            job("align", "align", &["reads.fq"], &["aligned.bam"]),
+            job("sort", "sort", &["aligned.bam"], &["sorted.bam"]),
+            job(
+                "report",
+                "report",
+                &["aligned.bam", "sorted.bam"],
+                &["report.html"],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_build_layers_by_longest_path() {
+        let layout = DagLayout::build(&pipeline(), DagLevel::Rules);
+
+        assert_eq!(layout.level, DagLevel::Rules);
+        assert_eq!(layout.layers.len(), 3);
+        assert_eq!(layer(&layout, "align"), 0);
+        assert_eq!(layer(&layout, "sort"), 1);
+        assert_eq!(layer(&layout, "report"), 2);
+
+        // Selectable nodes come layer by layer, without pass-through nodes
+        let order: Vec<&str> = (0..layout.len())
+            .map(|i| layout.node(i).unwrap().label.as_str())
+            .collect();
+        assert_eq!(order, vec!["align", "sort", "report"]);
+    }
+
+    #[test]
+    fn test_build_routes_through_pass_through_nodes() {
+        let layout = DagLayout::build(&pipeline(), DagLevel::Rules);
+
+        // align -> report skips the sort layer through one pass-through node
+        let dummies: Vec<usize> = (0..layout.nodes.len())
+            .filter(|&n| layout.nodes[n].dummy)
+            .collect();
+        assert_eq!(dummies.len(), 1);
+        let dummy = dummies[0];
+        assert_eq!(layout.nodes[dummy].rule, "align");
+        assert!(layout.layers[1].contains(&dummy));
+
+        let align = layout.order[0];
+        let report = layout.order[2];
+        assert!(layout.edges.contains(&(align, dummy)));
+        assert!(layout.edges.contains(&(dummy, report)));
+        assert!(!layout.edges.contains(&(align, report)));
+
+        // Every drawn edge joins adjacent layers
+        let layer_of = |node: usize| {
+            layout
+                .layers
+                .iter()
+                .position(|layer| layer.contains(&node))
+                .unwrap()
+        };
+        for &(from, to) in &layout.edges {
+            assert_eq!(layer_of(to), layer_of(from) + 1);
+        }
+    }
+
+    #[test]
+    fn test_build_job_level_falls_back_to_rules() {
+        // A few jobs per rule: one node per job
+        let jobs = vec![
+            job("align_a", "align", &[], &["a.bam"]),
+            job("align_b", "align", &[], &["b.bam"]),
+        ];
+        let layout = DagLayout::build(&state(jobs), DagLevel::Jobs);
+        assert_eq!(layout.level, DagLevel::Jobs);
+        assert_eq!(layout.len(), 2);
+        assert!(layout.nodes.iter().all(|node| node.job_id.is_some()));
+
+        // Too many jobs: the graph shows rules only
+        let jobs = (0..=MAX_JOB_NODES)
+            .map(|i| {
+                let rule = if i % 2 == 0 { "align" } else { "sort" };
+                job(&format!("job{i}"), rule, &[], &[])
+            })
+            .collect();
+        let layout = DagLayout::build(&state(jobs), DagLevel::Jobs);
+        assert_eq!(layout.level, DagLevel::Rules);
+        assert_eq!(layout.collapsed_rules, 0);
+        assert_eq!(layout.len(), 2);
+        let total: usize = layout.nodes.iter().map(|node| node.counts.total).sum();
+        assert_eq!(total, MAX_JOB_NODES + 1);
+    }
+
+    #[test]
+    fn test_clamp_pan_to_canvas() {
+        let layout = DagLayout::build(&pipeline(), DagLevel::Rules);
+        let placement = layout.place(0);
+        let (focus_x, focus_row) = placement.focus(&layout, Some(0));
+        let (width, height) = (placement.width as i32, placement.height as i32);
+
+        assert_eq!(layout.clamp_pan(Some(0), 0, (1, 1)), (1, 1));
+        assert_eq!(
+            layout.clamp_pan(Some(0), 0, (1000, 1000)),
+            (width - 1 - focus_x, height - 1 - focus_row)
+        );
+        assert_eq!(
+            layout.clamp_pan(Some(0), 0, (-1000, -1000)),
+            (-focus_x, -focus_row)
+        );
+        assert_eq!(DagLayout::default().clamp_pan(None, 0, (5, 5)), (0, 0));
+    }
+}
@@ -0,0 +1,148 @@
+//! Event log component listing recent state transitions.
+
+use crate::app::ViewMode;
+use crate::components::ViewTabs;
+use crate::components::job_detail::status_style;
+use charmer_state::{FailureMode, PipelineState, StateEvent, StateEventKind};
+use chrono::Local;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, TableState,
+    },
+};
+
+pub struct EventLogView;
+
+impl EventLogView {
+    /// Render recent events, newest first.
+    pub fn render(frame: &mut Frame, area: Rect, state: &PipelineState, selected: Option<usize>) {
+        let rows: Vec<Row> = state
+            .events
+            .iter()
+            .rev()
+            .map(|event| {
+                Row::new(vec![
+                    Line::from(Span::styled(
+                        event
+                            .timestamp
+                            .with_timezone(&Local)
+                            .format("%H:%M:%S")
+                            .to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    )),
+                    Line::from(Span::styled(
+                        event.kind.job_id().unwrap_or("pipeline").to_string(),
+                        Style::default().fg(Color::Cyan),
+                    )),
+                    describe(event),
+                ])
+            })
+            .collect();
+
+        let header_style = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        let header = Row::new(vec![
+            Span::styled("Time", header_style),
+            Span::styled("Job", header_style),
+            Span::styled("Event", header_style),
+        ])
+        .style(Style::default().add_modifier(Modifier::UNDERLINED));
+
+        let title = ViewTabs::title_line(ViewMode::Events);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),  // Time
+                Constraint::Min(15),    // Job
+                Constraint::Length(24), // Event
+            ],
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+        let mut table_state = TableState::default();
+        table_state.select(selected);
+
+        frame.render_stateful_widget(table, area, &mut table_state);
+
+        let table_height = area.height.saturating_sub(3) as usize; // header + borders
+        if state.events.len() > table_height {
+            let mut scrollbar_state =
+                ScrollbarState::new(state.events.len()).position(selected.unwrap_or(0));
+
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(Some("↑"))
+                .end_symbol(Some("↓"))
+                .track_symbol(Some("│"))
+                .thumb_symbol("█");
+
+            frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+        }
+    }
+}
+
+/// One-line description of an event ("Queued → Running", "added", ...).
+fn describe(event: &StateEvent) -> Line<'static> {
+    match &event.kind {
+        StateEventKind::JobAdded { status, .. } => {
+            let (label, color) = status_style(*status);
+            Line::from(vec![
+                Span::raw("added "),
+                Span::styled(label, Style::default().fg(color)),
+            ])
+        }
+        StateEventKind::JobStatusChanged { from, to, .. } => {
+            let (from_label, from_color) = status_style(*from);
+            let (to_label, to_color) = status_style(*to);
+            Line::from(vec![
+                Span::styled(from_label, Style::default().fg(from_color)),
+                Span::styled(" → ", Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    to_label,
+                    Style::default().fg(to_color).add_modifier(Modifier::BOLD),
+                ),
+            ])
+        }
+        StateEventKind::JobMerged { into, .. } => Line::from(Span::styled(
+            format!("merged into {}", into),
+            Style::default().fg(Color::Gray),
+        )),
+        StateEventKind::JobRemoved { .. } => {
+            Line::from(Span::styled("removed", Style::default().fg(Color::Gray)))
+        }
+        StateEventKind::FailureAnalyzed { mode, .. } => {
+            let label = match mode {
+                FailureMode::OutOfMemory => "Out of Memory",
+                FailureMode::Timeout => "Timeout",
+                FailureMode::ExitCode => "Exit Code Error",
+                FailureMode::Cancelled => "Cancelled",
+                FailureMode::NodeFailure => "Node Failure",
+                FailureMode::Unknown => "Unknown",
+            };
+            Line::from(Span::styled(
+                format!("failure: {}", label),
+                Style::default().fg(Color::Red),
+            ))
+        }
+        StateEventKind::PipelineFinished { success } => {
+            if *success {
+                Line::from(Span::styled(
+                    "pipeline finished",
+                    Style::default().fg(Color::Green),
+                ))
+            } else {
+                Line::from(Span::styled(
+                    "pipeline finished with errors",
+                    Style::default().fg(Color::Red),
+                ))
+            }
+        }
+    }
+}
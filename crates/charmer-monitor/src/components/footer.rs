@@ -14,15 +14,28 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub struct Footer;
 
 impl Footer {
-    pub fn render(frame: &mut Frame, area: Rect, status_message: Option<&str>) {
+    pub fn render(
+        frame: &mut Frame,
+        area: Rect,
+        status_message: Option<&str>,
+        pending_reasons: &[(String, usize)],
+    ) {
         let help = "j/k:nav  R:runs  a:all  l:logs  r:rules  f:filter  s:sort  ?:help  q:quit";
         let version = format!("v{}", VERSION);
 
-        // Split footer into left (help/status), right (version)
+        // Queued jobs per pending reason (e.g., "Priority:12 Resources:3")
+        let reasons = pending_reasons
+            .iter()
+            .map(|(reason, n)| format!("{}:{}", reason, n))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // Split footer into left (help/status), middle (pending reasons), right (version)
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
                 Constraint::Min(1),
+                Constraint::Length(reasons.chars().count() as u16 + 1),
                 Constraint::Length(version.len() as u16 + 1),
             ])
             .split(area);
@@ -42,10 +55,16 @@ impl Footer {
         let help_paragraph = Paragraph::new(left_content);
         frame.render_widget(help_paragraph, chunks[0]);
 
+        let reasons_paragraph = Paragraph::new(Line::from(Span::styled(
+            reasons,
+            Style::default().fg(Color::Blue),
+        )));
+        frame.render_widget(reasons_paragraph, chunks[1]);
+
         let version_paragraph = Paragraph::new(Line::from(Span::styled(
             version,
             Style::default().fg(Color::Gray),
         )));
-        frame.render_widget(version_paragraph, chunks[1]);
+        frame.render_widget(version_paragraph, chunks[2]);
     }
 }
@@ -1,6 +1,8 @@
 //! Job detail panel with rich formatting.
 
-use charmer_state::{EnvType, ExecutionEnvironment, FailureMode, Job, JobStatus, PipelineState};
+use charmer_state::{
+    EnvType, ExecutionEnvironment, FailureMode, Job, JobAttempt, JobStatus, PipelineState,
+};
 use chrono::Utc;
 use ratatui::{
     Frame,
@@ -300,15 +302,7 @@ fn build_detail_lines(job: &Job, command_expanded: bool) -> Vec<Line<'static>> {
     lines.push(Line::from(""));
 
     // Status with appropriate color
-    let (status_text, status_color) = match job.status {
-        JobStatus::Running => ("Running", Color::Yellow),
-        JobStatus::Completed => ("Completed", Color::Green),
-        JobStatus::Failed => ("Failed", Color::Red),
-        JobStatus::Queued => ("Queued", Color::Blue),
-        JobStatus::Pending => ("Pending", Color::White),
-        JobStatus::Cancelled => ("Cancelled", Color::Magenta),
-        JobStatus::Unknown => ("Unknown", Color::DarkGray),
-    };
+    let (status_text, status_color) = status_style(job.status);
     lines.push(Line::from(vec![
         Span::styled("Status: ", Style::default().fg(Color::Gray)),
         Span::styled(
@@ -327,6 +321,24 @@ fn build_detail_lines(job: &Job, command_expanded: bool) -> Vec<Line<'static>> {
         ]));
     }
 
+    // Process ID (local executor)
+    if let Some(pid) = job.local_pid {
+        lines.push(Line::from(vec![
+            Span::styled("PID: ", Style::default().fg(Color::Gray)),
+            Span::styled(pid.to_string(), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+
+    // Why a queued job is still waiting
+    if matches!(job.status, JobStatus::Queued | JobStatus::Pending)
+        && let Some(ref reason) = job.resources.pending_reason
+    {
+        lines.push(Line::from(vec![
+            Span::styled("Reason: ", Style::default().fg(Color::Gray)),
+            Span::styled(reason.clone(), Style::default().fg(Color::Yellow)),
+        ]));
+    }
+
     // Execution environment
     let env = ExecutionEnvironment::detect(
         &job.shellcmd,
@@ -413,7 +425,15 @@ fn build_detail_lines(job: &Job, command_expanded: bool) -> Vec<Line<'static>> {
         ]));
     }
 
-    // Usage section (actual consumption for finished jobs)
+    // Scheduling priority
+    if let Some(priority) = job.resources.priority {
+        lines.push(Line::from(vec![
+            Span::styled("  Priority: ", Style::default().fg(Color::Gray)),
+            Span::styled(priority.to_string(), Style::default().fg(Color::White)),
+        ]));
+    }
+
+    // Usage section (actual consumption for finished or local jobs)
     if let Some(ref usage) = job.usage {
         lines.push(Line::from(""));
         lines.push(Line::from(Span::styled(
@@ -490,6 +510,64 @@ fn build_detail_lines(job: &Job, command_expanded: bool) -> Vec<Line<'static>> {
                 Span::styled(time_str, Style::default().fg(Color::Cyan)),
             ]));
         }
+
+        // Live readings (local jobs while running)
+        if let Some(rss) = usage.rss_mb {
+            let mem_str = if rss >= 1024 {
+                format!("{:.1} GB", rss as f64 / 1024.0)
+            } else {
+                format!("{} MB", rss)
+            };
+            lines.push(Line::from(vec![
+                Span::styled("  Current RSS: ", Style::default().fg(Color::Gray)),
+                Span::styled(mem_str, Style::default().fg(Color::Green)),
+            ]));
+        }
+        if let Some(cpu) = usage.cpu_percent {
+            lines.push(Line::from(vec![
+                Span::styled("  CPU: ", Style::default().fg(Color::Gray)),
+                Span::styled(format!("{:.0}%", cpu), Style::default().fg(Color::Cyan)),
+            ]));
+        }
+
+        // Per-step breakdown (SLURM batch/extern/srun steps)
+        if !usage.steps.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  Steps:",
+                Style::default().fg(Color::Gray),
+            )));
+            let peak = usage.max_rss_mb;
+            for step in &usage.steps {
+                let step_name = step.step_id.rsplit('.').next().unwrap_or(&step.step_id);
+                let label = if step.name.is_empty() || step.name == step_name {
+                    step_name.to_string()
+                } else {
+                    format!("{} ({})", step_name, step.name)
+                };
+                let mem_str = match step.max_rss_mb {
+                    Some(rss) if rss >= 1024 => format!("{:.1} GB", rss as f64 / 1024.0),
+                    Some(rss) => format!("{} MB", rss),
+                    None => "-".to_string(),
+                };
+                // Highlight the step that reached the job's peak memory
+                let mem_color = if step.max_rss_mb.is_some() && step.max_rss_mb == peak {
+                    Color::Yellow
+                } else {
+                    Color::White
+                };
+                let mut spans = vec![
+                    Span::styled(format!("    {}: ", label), Style::default().fg(Color::Gray)),
+                    Span::styled(mem_str, Style::default().fg(mem_color)),
+                ];
+                if let Some(cpu_time) = step.cpu_time_seconds {
+                    spans.push(Span::styled(
+                        format!(", CPU {}", format_seconds(cpu_time)),
+                        Style::default().fg(Color::Gray),
+                    ));
+                }
+                lines.push(Line::from(spans));
+            }
+        }
     }
 
     lines.push(Line::from(""));
@@ -502,6 +580,25 @@ fn build_detail_lines(job: &Job, command_expanded: bool) -> Vec<Line<'static>> {
             .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
     )));
 
+    // Estimated start (while still queued)
+    if job.timing.started_at.is_none()
+        && let Some(estimated) = job.timing.estimated_start
+    {
+        let until = estimated - Utc::now();
+        let eta = if until > chrono::Duration::zero() {
+            format!(" (in {})", format_chrono_duration(&until))
+        } else {
+            String::new()
+        };
+        lines.push(Line::from(vec![
+            Span::styled("  Est. Start: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{}{}", estimated.format("%Y-%m-%d %H:%M:%S"), eta),
+                Style::default().fg(Color::Blue),
+            ),
+        ]));
+    }
+
     // Wait time (queued to started)
     if let (Some(queued), Some(started)) = (job.timing.queued_at, job.timing.started_at) {
         let wait = started - queued;
@@ -675,6 +772,38 @@ fn build_detail_lines(job: &Job, command_expanded: bool) -> Vec<Line<'static>> {
         }
     }
 
+    // Attempt timeline (retried jobs)
+    if job.attempts.len() > 1 {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Attempts",
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        )));
+        for (i, attempt) in job.attempts.iter().enumerate() {
+            let (outcome, color) = attempt_outcome(attempt);
+            let mut spans = vec![
+                Span::styled(format!("  #{} ", i + 1), Style::default().fg(Color::Gray)),
+                Span::styled(
+                    format!("{} {}", attempt.status.symbol(), outcome),
+                    Style::default().fg(color),
+                ),
+            ];
+            if let Some(mem) = attempt.resources.memory_mb {
+                spans.push(Span::styled(
+                    format!(" at {:.1} GB", mem as f64 / 1024.0),
+                    Style::default().fg(Color::White),
+                ));
+            }
+            spans.push(Span::styled(
+                format!(" ({})", attempt.scheduler_job_id),
+                Style::default().fg(Color::DarkGray),
+            ));
+            lines.push(Line::from(spans));
+        }
+    }
+
     // Output files
     if !job.outputs.is_empty() {
         lines.push(Line::from(""));
@@ -762,6 +891,42 @@ fn build_detail_lines(job: &Job, command_expanded: bool) -> Vec<Line<'static>> {
     lines
 }
 
+/// Label and color for a job status.
+pub(crate) fn status_style(status: JobStatus) -> (&'static str, Color) {
+    match status {
+        JobStatus::Running => ("Running", Color::Yellow),
+        JobStatus::Completed => ("Completed", Color::Green),
+        JobStatus::Failed => ("Failed", Color::Red),
+        JobStatus::Queued => ("Queued", Color::Blue),
+        JobStatus::Pending => ("Pending", Color::White),
+        JobStatus::Cancelled => ("Cancelled", Color::Magenta),
+        JobStatus::Unknown => ("Unknown", Color::DarkGray),
+    }
+}
+
+/// Short outcome of one attempt ("OOM", "Timeout", "Exit 1", ...).
+fn attempt_outcome(attempt: &JobAttempt) -> (String, Color) {
+    let (status_text, color) = status_style(attempt.status);
+    let Some(error) = attempt
+        .error
+        .as_ref()
+        .filter(|_| attempt.status == JobStatus::Failed)
+    else {
+        return (status_text.to_string(), color);
+    };
+    let outcome = match error.analysis.as_ref().map(|a| &a.mode) {
+        Some(FailureMode::OutOfMemory) => "OOM".to_string(),
+        Some(FailureMode::Timeout) => "Timeout".to_string(),
+        Some(FailureMode::Cancelled) => "Cancelled".to_string(),
+        Some(FailureMode::NodeFailure) => "Node failure".to_string(),
+        _ if error.message.contains("memory") => "OOM".to_string(),
+        _ if error.message.contains("time limit") => "Timeout".to_string(),
+        _ if error.exit_code > 0 => format!("Exit {}", error.exit_code),
+        _ => status_text.to_string(),
+    };
+    (outcome, color)
+}
+
 /// Extract sample name from output path patterns like "results/processed/sample1.txt"
 fn extract_sample_from_path(path: &str) -> Option<String> {
     // Common patterns: look for sample names between slashes
@@ -29,6 +29,19 @@ const CHAIN_WIDTH: u16 = 3; // Fixed width for dependency chain indicator
 const WILDCARDS_THRESHOLD: u16 = 45;
 const RUNTIME_THRESHOLD: u16 = 65;
 
+/// Prefix for synthetic rows that group the tasks of an array job.
+pub const ARRAY_GROUP_PREFIX: &str = "__array_";
+
+/// Job list row ID for an array job group.
+pub fn array_group_id(parent_id: &str) -> String {
+    format!("{}{}", ARRAY_GROUP_PREFIX, parent_id)
+}
+
+/// Parent job ID if this row is an array group.
+pub fn array_group_parent(row_id: &str) -> Option<&str> {
+    row_id.strip_prefix(ARRAY_GROUP_PREFIX)
+}
+
 /// Display options for job list items
 struct DisplayOptions {
     content_width: u16,
@@ -69,6 +82,17 @@ pub enum ChainPosition {
 /// Cached dependency relationships: Vec of (relation, chain_position) per job.
 pub type DependencyCache = Vec<(DepRelation, ChainPosition)>;
 
+/// Map each output file to the job that produces it.
+pub(crate) fn output_producers(state: &PipelineState) -> HashMap<&str, &str> {
+    let mut output_to_job = HashMap::new();
+    for (job_id, job) in &state.jobs {
+        for output in &job.outputs {
+            output_to_job.insert(output.as_str(), job_id.as_str());
+        }
+    }
+    output_to_job
+}
+
 /// Compute dependency relationships for all jobs relative to selected job.
 /// Returns (relation, chain_position) for each job.
 /// This finds the FULL transitive dependency chain (all ancestors and descendants).
@@ -100,12 +124,7 @@ pub fn compute_dependencies(
     relations[sel_idx].0 = DepRelation::Selected;
 
     // Build output->job_id map for finding upstream dependencies
-    let mut output_to_job: HashMap<&str, &str> = HashMap::new();
-    for (job_id, job) in &state.jobs {
-        for output in &job.outputs {
-            output_to_job.insert(output.as_str(), job_id.as_str());
-        }
-    }
+    let output_to_job = output_producers(state);
 
     // Build input->job_ids map for finding downstream dependencies
     let mut input_to_jobs: HashMap<&str, Vec<&str>> = HashMap::new();
@@ -221,6 +240,7 @@ pub struct JobList;
 
 impl JobList {
     /// Render the job list using filtered job IDs.
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         frame: &mut Frame,
         area: Rect,
@@ -233,10 +253,11 @@ impl JobList {
     ) {
         let counts = state.job_counts();
 
-        // Calculate visible job count (exclude main pipeline pseudo-job)
+        // Calculate visible job count (exclude main pipeline and array group rows)
         let visible = filtered_job_ids
             .iter()
             .filter(|id| id.as_str() != MAIN_PIPELINE_JOB_ID)
+            .filter(|id| array_group_parent(id).is_none())
             .count();
 
         // Split area: progress bar on top, column headers, list below
@@ -282,6 +303,20 @@ impl JobList {
             .map(|(i, job_id)| {
                 let row_num = if job_id == MAIN_PIPELINE_JOB_ID {
                     0 // Main pipeline uses special display, row num not shown
+                } else if let Some(parent) = array_group_parent(job_id) {
+                    // Expanded groups are followed by their tasks
+                    let expanded = filtered_job_ids
+                        .get(i + 1)
+                        .and_then(|next| state.jobs.get(next))
+                        .and_then(|job| job.array.as_ref())
+                        .is_some_and(|a| a.parent_id == parent);
+                    return build_array_group_item(
+                        parent,
+                        state,
+                        expanded,
+                        selected == Some(i),
+                        &opts,
+                    );
                 } else {
                     display_row += 1;
                     display_row
@@ -648,6 +683,79 @@ fn build_main_pipeline_item(
     ]))
 }
 
+/// Build the row for an array job group.
+fn build_array_group_item(
+    parent_id: &str,
+    state: &PipelineState,
+    expanded: bool,
+    is_selected: bool,
+    opts: &DisplayOptions,
+) -> ListItem<'static> {
+    let tasks: Vec<&Job> = state
+        .jobs
+        .values()
+        .filter(|job| job.array.as_ref().is_some_and(|a| a.parent_id == parent_id))
+        .collect();
+
+    // Task counts per status, with pending lines counting all their tasks
+    let mut counts = JobCounts::default();
+    for job in &tasks {
+        let n = job.array.as_ref().map_or(1, |a| a.task_count as usize);
+        match job.status {
+            JobStatus::Pending => counts.pending += n,
+            JobStatus::Queued => counts.queued += n,
+            JobStatus::Running => counts.running += n,
+            JobStatus::Completed => counts.completed += n,
+            JobStatus::Failed => counts.failed += n,
+            JobStatus::Cancelled => counts.cancelled += n,
+            JobStatus::Unknown => counts.unknown += n,
+        }
+        counts.total += n;
+    }
+
+    let rule = tasks.first().map(|job| job.rule.as_str()).unwrap_or("?");
+    let marker = if expanded { "▾" } else { "▸" };
+
+    let mut label_style = Style::default()
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    if is_selected {
+        label_style = label_style.add_modifier(Modifier::REVERSED);
+    }
+
+    let rule_width = (MAX_RULE_WIDTH as usize).min(opts.content_width as usize / 3);
+    let mut spans = vec![
+        Span::styled(format!("  {} ", marker), Style::default().fg(Color::Cyan)),
+        Span::styled(format!("{} ", truncate_str(rule, rule_width)), label_style),
+        Span::styled(
+            format!("array {} ", parent_id),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ];
+
+    for (count, status) in [
+        (counts.running, JobStatus::Running),
+        (counts.failed, JobStatus::Failed),
+        (counts.queued, JobStatus::Queued),
+        (counts.pending, JobStatus::Pending),
+        (counts.completed, JobStatus::Completed),
+        (counts.cancelled, JobStatus::Cancelled),
+    ] {
+        if count > 0 {
+            spans.push(Span::styled(
+                format!("{}{} ", status.symbol(), count),
+                get_status_style(status),
+            ));
+        }
+    }
+    spans.push(Span::styled(
+        format!("/{}", counts.total),
+        Style::default().fg(Color::Gray),
+    ));
+
+    ListItem::new(Line::from(spans))
+}
+
 /// Get the style for a job status.
 fn get_status_style(status: JobStatus) -> Style {
     match status {
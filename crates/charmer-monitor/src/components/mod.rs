@@ -1,17 +1,26 @@
 //! TUI components.
 
+pub mod dag;
+pub mod event_log;
 pub mod footer;
 pub mod header;
 pub mod job_detail;
 pub mod job_list;
 pub mod log_viewer;
 pub mod rule_summary;
+pub mod timeline;
 pub mod view_tabs;
 
+pub use dag::{DagLayout, DagLevel, DagNode, DagView};
+pub use event_log::EventLogView;
 pub use footer::Footer;
 pub use header::Header;
 pub use job_detail::JobDetail;
-pub use job_list::{ChainPosition, DepRelation, DependencyCache, JobList, compute_dependencies};
+pub use job_list::{
+    ChainPosition, DepRelation, DependencyCache, JobList, array_group_id, array_group_parent,
+    compute_dependencies,
+};
 pub use log_viewer::{LogViewer, LogViewerState};
 pub use rule_summary::RuleSummary;
+pub use timeline::{Timeline, TimelineLanes, TimelineRow, TimelineView};
 pub use view_tabs::ViewTabs;
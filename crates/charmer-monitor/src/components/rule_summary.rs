@@ -99,10 +99,9 @@ impl RuleSummary {
                 };
 
                 // Progress bar for this rule
-                let progress = if s.total > 0 {
-                    format!("{}%", s.completed * 100 / s.total)
-                } else {
-                    "-".to_string()
+                let progress = match (s.completed * 100).checked_div(s.total) {
+                    Some(pct) => format!("{}%", pct),
+                    None => "-".to_string(),
                 };
 
                 Row::new(vec![
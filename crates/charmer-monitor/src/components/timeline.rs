@@ -0,0 +1,498 @@
+//! Timeline view: queued and run time of jobs as bars on a shared time axis.
+//!
+//! Each row is a job, or a lane with every job of a rule. Queued time is drawn
+//! light and run time solid, so idle gaps, stragglers and jobs starved in the
+//! queue stand out.
+
+use crate::app::ViewMode;
+use crate::components::ViewTabs;
+use crate::components::dag::truncate;
+use crate::components::job_detail::status_style;
+use charmer_state::{Job, JobStatus, PipelineState};
+use chrono::{DateTime, Local, TimeZone, Utc};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::collections::HashMap;
+
+/// Highest zoom level; each level halves the time shown.
+pub const MAX_ZOOM: u8 = 8;
+
+/// Widest the row label column gets.
+const MAX_LABEL_WIDTH: usize = 28;
+
+/// Fewest columns between two time axis ticks.
+const MIN_TICK_SPACING: f64 = 12.0;
+
+/// Tick intervals to choose from, in seconds.
+const TICK_STEPS: [i64; 17] = [
+    1, 2, 5, 10, 15, 30, 60, 120, 300, 600, 900, 1800, 3600, 7200, 10800, 21600, 43200,
+];
+
+/// Scroll positions (quarter screens back from the end) available at a zoom level.
+pub fn max_scroll(zoom: u8) -> u32 {
+    ((1u32 << zoom) - 1) * 4
+}
+
+/// What a row of the timeline stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimelineLanes {
+    /// One row per job
+    #[default]
+    Jobs,
+    /// One lane per rule holding all its jobs
+    Rules,
+}
+
+impl TimelineLanes {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Jobs => Self::Rules,
+            Self::Rules => Self::Jobs,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Jobs => "jobs",
+            Self::Rules => "rules",
+        }
+    }
+}
+
+/// Timestamps of one job, resolved against "now" when drawn.
+#[derive(Debug, Clone)]
+struct Bar {
+    queued_at: Option<DateTime<Utc>>,
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+    status: JobStatus,
+}
+
+impl Bar {
+    fn new(job: &Job) -> Self {
+        Self {
+            queued_at: job.timing.queued_at,
+            started_at: job.timing.started_at,
+            completed_at: job.timing.completed_at,
+            status: job.status,
+        }
+    }
+
+    /// Whether the bar still grows with time.
+    fn is_open(&self) -> bool {
+        self.completed_at.is_none()
+            && match self.status {
+                JobStatus::Running => self.started_at.is_some(),
+                JobStatus::Queued | JobStatus::Pending => self.queued_at.is_some(),
+                _ => false,
+            }
+    }
+
+    /// Queued and running intervals.
+    fn intervals(&self, now: DateTime<Utc>) -> [Option<(DateTime<Utc>, DateTime<Utc>)>; 2] {
+        let open_end = |start| if self.is_open() { now } else { start };
+        let queued = self.queued_at.map(|queued| {
+            let end = self
+                .started_at
+                .or(self.completed_at)
+                .unwrap_or_else(|| open_end(queued));
+            (queued, end)
+        });
+        let running = self.started_at.map(|started| {
+            (
+                started,
+                self.completed_at.unwrap_or_else(|| open_end(started)),
+            )
+        });
+        [queued, running]
+    }
+}
+
+/// A row of the timeline.
+#[derive(Debug, Clone)]
+pub struct TimelineRow {
+    /// Rule of the row's jobs
+    pub rule: String,
+    /// The job, for a row standing for a single job
+    pub job_id: Option<String>,
+    /// Job label or rule name
+    pub label: String,
+    /// Job status, or the most pressing status of the lane's jobs
+    pub status: JobStatus,
+    bars: Vec<Bar>,
+}
+
+/// Rows of the timeline with the time range they cover.
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    pub rows: Vec<TimelineRow>,
+    pub lanes: TimelineLanes,
+    /// Earliest queue or start time
+    start: Option<DateTime<Utc>>,
+    /// Latest known timestamp
+    end: Option<DateTime<Utc>>,
+    /// Whether any job is still queued or running
+    open: bool,
+}
+
+impl Timeline {
+    /// Collect the snakemake jobs of `state` that have been queued or started.
+    pub fn build(state: &PipelineState, lanes: TimelineLanes) -> Self {
+        let mut jobs: Vec<(&String, &Job)> = state
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.is_snakemake_job)
+            .filter(|(_, job)| job.timing.queued_at.is_some() || job.timing.started_at.is_some())
+            .collect();
+        jobs.sort_by_key(|(id, job)| {
+            (
+                job.timing.started_at.or(job.timing.queued_at),
+                id.to_string(),
+            )
+        });
+
+        let mut rows: Vec<TimelineRow> = Vec::new();
+        let mut lane_of: HashMap<&str, usize> = HashMap::new();
+        for (id, job) in &jobs {
+            let row = match lanes {
+                TimelineLanes::Jobs => {
+                    rows.push(TimelineRow {
+                        rule: job.rule.clone(),
+                        job_id: Some(id.to_string()),
+                        label: match &job.wildcards {
+                            Some(wildcards) => format!("{} ({})", job.rule, wildcards),
+                            None => job.rule.clone(),
+                        },
+                        status: job.status,
+                        bars: Vec::new(),
+                    });
+                    rows.len() - 1
+                }
+                TimelineLanes::Rules => *lane_of.entry(job.rule.as_str()).or_insert_with(|| {
+                    rows.push(TimelineRow {
+                        rule: job.rule.clone(),
+                        job_id: None,
+                        label: job.rule.clone(),
+                        status: job.status,
+                        bars: Vec::new(),
+                    });
+                    rows.len() - 1
+                }),
+            };
+            let row = &mut rows[row];
+            if status_rank(job.status) < status_rank(row.status) {
+                row.status = job.status;
+            }
+            row.bars.push(Bar::new(job));
+        }
+        if lanes == TimelineLanes::Rules {
+            for row in &mut rows {
+                row.label = format!("{} ×{}", row.rule, row.bars.len());
+            }
+        }
+
+        let bars = || rows.iter().flat_map(|row| &row.bars);
+        let start = bars()
+            .filter_map(|bar| bar.queued_at.or(bar.started_at))
+            .min();
+        let end = bars()
+            .flat_map(|bar| [bar.queued_at, bar.started_at, bar.completed_at])
+            .flatten()
+            .max();
+        let open = bars().any(Bar::is_open);
+
+        Self {
+            rows,
+            lanes,
+            start,
+            end,
+            open,
+        }
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The `index`th row.
+    pub fn row(&self, index: usize) -> Option<&TimelineRow> {
+        self.rows.get(index)
+    }
+}
+
+/// Lower is more pressing: what a lane with mixed jobs is shown as.
+fn status_rank(status: JobStatus) -> u8 {
+    match status {
+        JobStatus::Failed => 0,
+        JobStatus::Running => 1,
+        JobStatus::Queued => 2,
+        JobStatus::Pending => 3,
+        JobStatus::Cancelled => 4,
+        JobStatus::Unknown => 5,
+        JobStatus::Completed => 6,
+    }
+}
+
+/// What a chart cell shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Idle,
+    Queued,
+    Running(JobStatus),
+}
+
+impl Cell {
+    /// Running beats queued; among running bars, the most pressing status wins.
+    fn rank(self) -> u8 {
+        match self {
+            Self::Idle => 0,
+            Self::Queued => 1,
+            Self::Running(status) => 10 - status_rank(status),
+        }
+    }
+}
+
+pub struct TimelineView;
+
+impl TimelineView {
+    /// Render the timeline. `zoom` halves the time shown per level and
+    /// `scroll` moves the window back from the end by quarter screens.
+    pub fn render(
+        frame: &mut Frame,
+        area: Rect,
+        timeline: &Timeline,
+        selected: Option<usize>,
+        zoom: u8,
+        scroll: u32,
+    ) {
+        let now = Utc::now();
+        let (Some(start), Some(last)) = (timeline.start, timeline.end) else {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(ViewTabs::title_line(ViewMode::Timeline));
+            let empty = Paragraph::new(Span::styled(
+                " No jobs have been queued yet",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(block);
+            frame.render_widget(empty, area);
+            return;
+        };
+        let end = if timeline.open { now.max(last) } else { last };
+        let seconds = |t: DateTime<Utc>| (t - start).num_milliseconds() as f64 / 1000.0;
+
+        // Window in seconds since the start
+        let span = seconds(end).max(1.0);
+        let visible = span / f64::from(1u32 << zoom);
+        let view_end = span - f64::from(scroll) * visible / 4.0;
+        let view_start = view_end - visible;
+
+        let label_width = timeline
+            .rows
+            .iter()
+            .map(|row| row.label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .clamp(4, MAX_LABEL_WIDTH)
+            .min(area.width as usize / 3);
+        let chart_width = (area.width as usize).saturating_sub(label_width + 3);
+        let scale = visible / chart_width.max(1) as f64;
+
+        let mut summary = format!(" {} · {}/col ", timeline.lanes.label(), format_span(scale));
+        summary.push_str(if scroll == 0 {
+            "· following "
+        } else {
+            "· scrolled back "
+        });
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(ViewTabs::title_line(ViewMode::Timeline))
+            .title_bottom(Line::from(Span::styled(
+                summary,
+                Style::default().fg(Color::DarkGray),
+            )));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        if chart_width == 0 || inner.height < 2 {
+            return;
+        }
+
+        let column = |t: f64| ((t - view_start) / scale).floor();
+        // "now" is the very end of a window that follows the run
+        let now_column = Some(column(seconds(now)))
+            .filter(|&c| c >= 0.0 && c <= chart_width as f64)
+            .map(|c| (c as usize).min(chart_width - 1));
+        let marker_style = Style::default().fg(Color::Magenta);
+
+        let mut lines = vec![axis_line(
+            start,
+            view_start,
+            scale,
+            label_width,
+            chart_width,
+            now_column,
+        )];
+
+        let visible_rows = inner.height as usize - 1;
+        let selected_row = selected.unwrap_or(0);
+        let offset = (selected_row + 1).saturating_sub(visible_rows);
+        for (index, row) in timeline
+            .rows
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible_rows)
+        {
+            let mut cells = vec![Cell::Idle; chart_width];
+            for bar in &row.bars {
+                let [queued, running] = bar.intervals(now);
+                let painted = [
+                    queued.map(|span| (span, Cell::Queued)),
+                    running.map(|span| (span, Cell::Running(bar.status))),
+                ];
+                for ((from, to), cell) in painted.into_iter().flatten() {
+                    // Every interval covers at least one column
+                    let first = column(seconds(from));
+                    let last = column(seconds(to)).max(first);
+                    if last < 0.0 || first >= chart_width as f64 {
+                        continue;
+                    }
+                    let first = first.max(0.0) as usize;
+                    let last = (last as usize).min(chart_width - 1);
+                    for slot in &mut cells[first..=last] {
+                        if cell.rank() > slot.rank() {
+                            *slot = cell;
+                        }
+                    }
+                }
+            }
+
+            let (_, color) = status_style(row.status);
+            let mut label_style = Style::default().fg(color);
+            if index == selected_row {
+                label_style = label_style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            let label = truncate(&row.label, label_width);
+            let mut spans = vec![
+                Span::styled(
+                    format!("{:<width$}", label, width = label_width),
+                    label_style,
+                ),
+                Span::raw(" "),
+            ];
+            let mut text = String::new();
+            let mut style = Style::default();
+            for (col, cell) in cells.into_iter().enumerate() {
+                let (ch, cell_style) = match cell {
+                    Cell::Idle if now_column == Some(col) => ('│', marker_style),
+                    Cell::Idle => (' ', Style::default()),
+                    Cell::Queued => ('░', Style::default().fg(status_style(JobStatus::Queued).1)),
+                    Cell::Running(status) => ('█', Style::default().fg(status_style(status).1)),
+                };
+                if cell_style != style && !text.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut text), style));
+                }
+                style = cell_style;
+                text.push(ch);
+            }
+            spans.push(Span::styled(text, style));
+            lines.push(Line::from(spans));
+        }
+
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+}
+
+/// The time axis: local clock times at regular ticks, and the "now" marker.
+fn axis_line(
+    start: DateTime<Utc>,
+    view_start: f64,
+    scale: f64,
+    label_width: usize,
+    chart_width: usize,
+    now_column: Option<usize>,
+) -> Line<'static> {
+    let step = TICK_STEPS
+        .iter()
+        .copied()
+        .find(|&step| step as f64 / scale >= MIN_TICK_SPACING)
+        .unwrap_or_else(|| {
+            let days = (MIN_TICK_SPACING * scale / 86400.0).ceil() as i64;
+            days.max(1) * 86400
+        });
+    let format = if step < 60 {
+        "%H:%M:%S"
+    } else if step < 86400 {
+        "%H:%M"
+    } else {
+        "%m-%d"
+    };
+
+    let mut axis = vec![' '; chart_width];
+    let origin = start.timestamp_millis() as f64 / 1000.0 + view_start;
+    let mut tick = (origin / step as f64).ceil() as i64 * step;
+    let mut free_from = 0;
+    loop {
+        let col = ((tick as f64 - origin) / scale).round() as usize;
+        if col >= chart_width {
+            break;
+        }
+        if col >= free_from
+            && let Some(time) = Utc.timestamp_opt(tick, 0).single()
+        {
+            let label = format!("┊{}", time.with_timezone(&Local).format(format));
+            for (i, ch) in label.chars().enumerate() {
+                if let Some(slot) = axis.get_mut(col + i) {
+                    *slot = ch;
+                }
+            }
+            free_from = col + label.chars().count() + 1;
+        }
+        tick += step;
+    }
+
+    let mut spans = vec![Span::raw(" ".repeat(label_width + 1))];
+    match now_column {
+        Some(col) => {
+            // The arrow sits on the marker; the word goes where there is room
+            let (label, at) = if col + 4 <= chart_width {
+                ("▼now", col)
+            } else {
+                ("now▼", col.saturating_sub(3))
+            };
+            let before: String = axis[..at].iter().collect();
+            let after: String = axis.iter().skip(at + label.chars().count()).collect();
+            spans.push(Span::styled(before, Style::default().fg(Color::DarkGray)));
+            spans.push(Span::styled(label, Style::default().fg(Color::Magenta)));
+            spans.push(Span::styled(after, Style::default().fg(Color::DarkGray)));
+        }
+        None => spans.push(Span::styled(
+            axis.into_iter().collect::<String>(),
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+    Line::from(spans)
+}
+
+/// Format a number of seconds compactly ("500ms", "12s", "5m", "2.5h").
+fn format_span(secs: f64) -> String {
+    if secs < 1.0 {
+        format!("{}ms", (secs * 1000.0).round())
+    } else if secs < 60.0 {
+        format!("{:.0}s", secs)
+    } else if secs < 3600.0 {
+        format!("{:.0}m", secs / 60.0)
+    } else {
+        format!("{:.1}h", secs / 3600.0)
+    }
+}
@@ -10,9 +10,15 @@ pub struct ViewTabs;
 
 impl ViewTabs {
     /// Generate a title Line with inline tab selection.
-    /// Returns something like: " \[Jobs\] Rules "
+    /// Returns something like: " \[Jobs\] Rules Events DAG Timeline "
     pub fn title_line(view_mode: ViewMode) -> Line<'static> {
-        let tabs = [("Jobs", ViewMode::Jobs), ("Rules", ViewMode::Rules)];
+        let tabs = [
+            ("Jobs", ViewMode::Jobs),
+            ("Rules", ViewMode::Rules),
+            ("Events", ViewMode::Events),
+            ("DAG", ViewMode::Dag),
+            ("Timeline", ViewMode::Timeline),
+        ];
 
         let mut spans = Vec::new();
         spans.push(Span::raw(" "));
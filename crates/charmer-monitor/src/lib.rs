@@ -4,4 +4,4 @@ pub mod app;
 pub mod components;
 pub mod ui;
 
-pub use app::App;
+pub use app::{ActionRequest, ActionScope, App};
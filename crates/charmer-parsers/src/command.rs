@@ -1,5 +1,6 @@
 //! Command execution utilities for scheduler queries.
 
+use async_trait::async_trait;
 use thiserror::Error;
 use tokio::process::Command;
 
@@ -48,6 +49,27 @@ pub async fn run_command_allow_failure(
     Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
+/// Runs scheduler commands that change job state.
+///
+/// Job control goes through this trait so tests can record the commands
+/// instead of running them.
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    /// Run `program` with `args` and return stdout; non-zero exit is an error.
+    async fn run(&self, program: &str, args: &[String]) -> Result<String, CommandError>;
+}
+
+/// Runs commands on this host.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemRunner;
+
+#[async_trait]
+impl CommandRunner for SystemRunner {
+    async fn run(&self, program: &str, args: &[String]) -> Result<String, CommandError> {
+        run_command(Command::new(program).args(args), program).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +88,13 @@ mod tests {
         let result = run_command(&mut cmd, "nonexistent").await;
         assert!(matches!(result, Err(CommandError::Execution { .. })));
     }
+
+    #[tokio::test]
+    async fn test_system_runner() {
+        let output = SystemRunner
+            .run("echo", &["a".to_string(), "b".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(output.trim(), "a b");
+    }
 }
@@ -7,11 +7,14 @@ pub mod command;
 pub mod memory;
 pub mod time;
 
-pub use command::{CommandError, run_command, run_command_allow_failure};
+pub use command::{
+    CommandError, CommandRunner, SystemRunner, run_command, run_command_allow_failure,
+};
 pub use memory::{MemoryFormat, parse_memory_mb};
 pub use time::{
     format_duration, format_duration_lsf, format_duration_slurm, parse_duration,
-    parse_duration_secs, parse_exit_code, parse_lsf_timestamp, parse_slurm_timestamp,
+    parse_duration_secs, parse_exit_code, parse_lsf_timestamp, parse_pbs_timestamp,
+    parse_slurm_timestamp,
 };
 
 /// Filter helper for optional string fields.
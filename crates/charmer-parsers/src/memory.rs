@@ -9,6 +9,10 @@ pub enum MemoryFormat {
     SlurmSacct,
     /// LSF format: "4 GB", "1000 MB" (with spaces)
     Lsf,
+    /// PBS format: "4gb", "1048576kb" (lowercase units, no spaces)
+    Pbs,
+    /// SGE format: "8G", "2.000G", "512M" (fractional, bare number is bytes)
+    Sge,
 }
 
 /// Parse memory string to megabytes.
@@ -17,6 +21,8 @@ pub enum MemoryFormat {
 /// - SLURM: "4G", "1000M", "4096K", "4096" (no spaces)
 /// - SLURM sacct: "4Gn", "1000Mc" (n=per node, c=per core)
 /// - LSF: "4 GB", "1000 MB" (with spaces)
+/// - PBS: "4gb", "1048576kb", "512mb"
+/// - SGE: "8G", "2.000G", "512M"
 ///
 /// Returns None for empty strings or placeholder values.
 pub fn parse_memory_mb(s: &str, format: MemoryFormat) -> Option<u64> {
@@ -28,6 +34,8 @@ pub fn parse_memory_mb(s: &str, format: MemoryFormat) -> Option<u64> {
         MemoryFormat::Slurm => parse_slurm_memory(s),
         MemoryFormat::SlurmSacct => parse_slurm_sacct_memory(s),
         MemoryFormat::Lsf => parse_lsf_memory(s),
+        MemoryFormat::Pbs => parse_pbs_memory(s),
+        MemoryFormat::Sge => parse_sge_memory(s),
     }
 }
 
@@ -72,6 +80,49 @@ fn parse_lsf_memory(s: &str) -> Option<u64> {
     }
 }
 
+/// Parse PBS memory format (e.g., "4gb", "1048576kb", "512mb", "1024").
+fn parse_pbs_memory(s: &str) -> Option<u64> {
+    let s = s.trim().to_lowercase();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value.parse().ok()?;
+
+    match unit {
+        "tb" | "t" => Some(value * 1024 * 1024),
+        "gb" | "g" => Some(value * 1024),
+        "mb" | "m" => Some(value),
+        "kb" | "k" => Some(value / 1024),
+        // PBS treats a bare number as bytes
+        "b" | "" => Some(value / (1024 * 1024)),
+        _ => None,
+    }
+}
+
+/// Parse SGE memory format (e.g., "8G", "2.000G", "512M", "1073741824").
+fn parse_sge_memory(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: f64 = value.parse().ok()?;
+
+    // Upper-case suffixes are powers of 1024, lower-case powers of 1000
+    let mb = match unit {
+        "T" => value * 1024.0 * 1024.0,
+        "t" => value * 1e12 / 1_048_576.0,
+        "G" => value * 1024.0,
+        "g" => value * 1e9 / 1_048_576.0,
+        "M" => value,
+        "m" => value * 1e6 / 1_048_576.0,
+        "K" => value / 1024.0,
+        "k" => value * 1e3 / 1_048_576.0,
+        "" => value / 1_048_576.0,
+        _ => return None,
+    };
+    Some(mb as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +156,22 @@ mod tests {
         assert_eq!(parse_memory_mb("1000", MemoryFormat::Lsf), Some(1000));
         assert_eq!(parse_memory_mb("-", MemoryFormat::Lsf), None);
     }
+
+    #[test]
+    fn test_parse_pbs_memory() {
+        assert_eq!(parse_memory_mb("4gb", MemoryFormat::Pbs), Some(4096));
+        assert_eq!(parse_memory_mb("512mb", MemoryFormat::Pbs), Some(512));
+        assert_eq!(parse_memory_mb("1048576kb", MemoryFormat::Pbs), Some(1024));
+        assert_eq!(parse_memory_mb("4GB", MemoryFormat::Pbs), Some(4096));
+        assert_eq!(parse_memory_mb("1mib", MemoryFormat::Pbs), None);
+    }
+
+    #[test]
+    fn test_parse_sge_memory() {
+        assert_eq!(parse_memory_mb("8G", MemoryFormat::Sge), Some(8192));
+        assert_eq!(parse_memory_mb("2.000G", MemoryFormat::Sge), Some(2048));
+        assert_eq!(parse_memory_mb("512M", MemoryFormat::Sge), Some(512));
+        assert_eq!(parse_memory_mb("1073741824", MemoryFormat::Sge), Some(1024));
+        assert_eq!(parse_memory_mb("4X", MemoryFormat::Sge), None);
+    }
 }
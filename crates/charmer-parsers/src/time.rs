@@ -40,6 +40,20 @@ pub fn parse_lsf_timestamp(s: &str) -> Option<DateTime<Utc>> {
     None
 }
 
+/// Parse a PBS timestamp (ctime format, e.g., "Thu Dec 19 10:30:00 2024").
+///
+/// Returns None for empty strings or unparseable values.
+pub fn parse_pbs_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    // Day-of-month is space padded ("Jan  5"), so normalize whitespace first
+    let normalized = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.is_empty() {
+        return None;
+    }
+    NaiveDateTime::parse_from_str(&normalized, "%a %b %d %H:%M:%S %Y")
+        .ok()
+        .and_then(|dt| Utc.from_local_datetime(&dt).single())
+}
+
 /// Parse a duration in various formats.
 ///
 /// Supports:
@@ -161,6 +175,17 @@ mod tests {
         assert!(parse_lsf_timestamp("").is_none());
     }
 
+    #[test]
+    fn test_parse_pbs_timestamp() {
+        let dt = parse_pbs_timestamp("Thu Dec 19 10:30:00 2024").unwrap();
+        assert_eq!(dt.format("%Y-%m-%d").to_string(), "2024-12-19");
+
+        let dt = parse_pbs_timestamp("Sun Jan  5 08:00:00 2025").unwrap();
+        assert_eq!(dt.format("%Y-%m-%d").to_string(), "2025-01-05");
+
+        assert!(parse_pbs_timestamp("").is_none());
+    }
+
     #[test]
     fn test_parse_duration() {
         assert_eq!(parse_duration("1:00:00"), Some(Duration::from_secs(3600)));
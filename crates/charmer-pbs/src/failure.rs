@@ -0,0 +1,290 @@
+//! PBS job failure analysis.
+//!
+//! Classify failures from `Exit_status` and `resources_used` and provide
+//! actionable suggestions.
+
+use crate::qstat::{
+    JOB_EXEC_KILL_MEM, JOB_EXEC_KILL_VMEM, JOB_EXEC_KILL_WALLTIME, QstatError, query_qstat_job,
+};
+use crate::types::PbsJob;
+use charmer_parsers::format_duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FailureError {
+    #[error("Failed to execute qstat: {0}")]
+    ExecutionError(String),
+    #[error("Job not found: {0}")]
+    NotFound(String),
+    #[error("Parse error: {0}")]
+    ParseError(String),
+}
+
+/// Failure mode classification for PBS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureMode {
+    /// Job ran out of memory
+    OutOfMemory {
+        used_mb: u64,
+        limit_mb: u64,
+        suggested_mb: u64,
+    },
+    /// Job exceeded walltime limit
+    Timeout {
+        elapsed_seconds: u64,
+        limit_seconds: u64,
+        suggested_seconds: u64,
+    },
+    /// Job failed with non-zero exit code
+    ExitCode { code: i32, signal: Option<i32> },
+    /// Job was killed by a signal (qdel or admin)
+    Killed { signal: i32 },
+    /// PBS could not start or run the job on the execution host
+    HostFailure { host: Option<String> },
+    /// Unknown failure mode
+    Unknown { exit_status: Option<i32> },
+}
+
+/// Detailed failure analysis result for PBS.
+#[derive(Debug, Clone)]
+pub struct FailureAnalysis {
+    /// PBS job ID
+    pub job_id: String,
+    /// Classified failure mode
+    pub mode: FailureMode,
+    /// Human-readable explanation
+    pub explanation: String,
+    /// Suggested fix
+    pub suggestion: String,
+    /// Raw Exit_status
+    pub exit_status: Option<i32>,
+    /// Memory used (MB, resources_used.mem)
+    pub mem_used_mb: Option<u64>,
+    /// Memory requested (MB, Resource_List.mem)
+    pub mem_limit_mb: Option<u64>,
+    /// Walltime used (seconds)
+    pub walltime_seconds: Option<u64>,
+    /// Walltime limit (seconds)
+    pub walltime_limit_seconds: Option<u64>,
+}
+
+impl FailureAnalysis {
+    /// Generate explanation and suggestion based on failure mode.
+    fn generate_messages(mode: &FailureMode) -> (String, String) {
+        match mode {
+            FailureMode::OutOfMemory {
+                used_mb,
+                limit_mb,
+                suggested_mb,
+            } => {
+                let explanation = format!(
+                    "Job exceeded memory limit. Used {:.1} GB but limit was {:.1} GB.",
+                    *used_mb as f64 / 1024.0,
+                    *limit_mb as f64 / 1024.0
+                );
+                let suggestion = format!(
+                    "Increase memory to at least {:.1} GB. In your Snakefile, add:\n  resources: mem_mb={}",
+                    *suggested_mb as f64 / 1024.0,
+                    suggested_mb
+                );
+                (explanation, suggestion)
+            }
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                suggested_seconds,
+            } => {
+                let explanation = format!(
+                    "Job exceeded walltime limit. Ran for {} but limit was {}.",
+                    format_duration(*elapsed_seconds),
+                    format_duration(*limit_seconds)
+                );
+                let suggestion = format!(
+                    "Increase walltime to at least {}. In your Snakefile, add:\n  resources: runtime={}",
+                    format_duration(*suggested_seconds),
+                    suggested_seconds.div_ceil(60)
+                );
+                (explanation, suggestion)
+            }
+            FailureMode::ExitCode { code, signal } => {
+                let explanation = if let Some(sig) = signal {
+                    format!("Job exited with code {} and signal {}", code, sig)
+                } else {
+                    match code {
+                        1 => "Job failed with exit code 1 (general error)".to_string(),
+                        137 => {
+                            "Job killed (likely OOM). Exit code 137 = 128 + 9 (SIGKILL)".to_string()
+                        }
+                        _ => format!("Job failed with exit code {}", code),
+                    }
+                };
+                let suggestion = if *code == 137 {
+                    "This is likely an out-of-memory error. Try increasing memory allocation."
+                        .to_string()
+                } else {
+                    "Check the job's stderr log for error details.".to_string()
+                };
+                (explanation, suggestion)
+            }
+            FailureMode::Killed { signal } => (
+                format!("Job was killed by signal {}", signal),
+                "Check if the job was deleted with qdel or by an administrator.".to_string(),
+            ),
+            FailureMode::HostFailure { host } => {
+                let explanation = if let Some(h) = host {
+                    format!("PBS could not run the job on {}", h)
+                } else {
+                    "PBS could not run the job on its execution host".to_string()
+                };
+                (
+                    explanation,
+                    "Re-run the job. If persistent, contact cluster admin.".to_string(),
+                )
+            }
+            FailureMode::Unknown { exit_status } => (
+                match exit_status {
+                    Some(code) => format!("Job failed with PBS exit status {}", code),
+                    None => "Job failed".to_string(),
+                },
+                "Check the PBS server logs (tracejob) for details.".to_string(),
+            ),
+        }
+    }
+}
+
+/// Query detailed failure information for a PBS job.
+pub async fn analyze_failure(job_id: &str) -> Result<FailureAnalysis, FailureError> {
+    let job = query_qstat_job(job_id)
+        .await
+        .map_err(|e| match e {
+            QstatError::ExecutionError(msg) => FailureError::ExecutionError(msg),
+            QstatError::ParseError(msg) => FailureError::ParseError(msg),
+        })?
+        .ok_or_else(|| FailureError::NotFound(job_id.to_string()))?;
+
+    Ok(analyze_job(&job))
+}
+
+/// Classify a finished PBS job.
+pub fn analyze_job(job: &PbsJob) -> FailureAnalysis {
+    let mem_used_mb = job.mem_used_mb;
+    let mem_limit_mb = job.mem_limit_mb;
+    let walltime_seconds = job.walltime_used.map(|d| d.as_secs());
+    let walltime_limit_seconds = job.walltime_limit.map(|d| d.as_secs());
+
+    let out_of_memory = || {
+        let used = mem_used_mb.unwrap_or(0);
+        let limit = mem_limit_mb.unwrap_or(0);
+        let suggested = ((used as f64 * 1.5) / 1024.0).ceil() as u64 * 1024;
+        FailureMode::OutOfMemory {
+            used_mb: used,
+            limit_mb: limit,
+            suggested_mb: suggested.max(limit + 1024),
+        }
+    };
+    let timeout = || {
+        let elapsed = walltime_seconds.unwrap_or(0);
+        let limit = walltime_limit_seconds.unwrap_or(0);
+        let suggested = (elapsed as f64 * 1.5) as u64;
+        FailureMode::Timeout {
+            elapsed_seconds: elapsed,
+            limit_seconds: limit,
+            suggested_seconds: suggested.max(limit + 3600),
+        }
+    };
+    let walltime_exceeded = matches!(
+        (walltime_seconds, walltime_limit_seconds),
+        (Some(used), Some(limit)) if limit > 0 && used >= limit
+    );
+
+    let mode = match job.exit_status {
+        Some(JOB_EXEC_KILL_MEM | JOB_EXEC_KILL_VMEM) => out_of_memory(),
+        Some(JOB_EXEC_KILL_WALLTIME) => timeout(),
+        // Torque kills walltime overruns with SIGTERM/SIGKILL instead
+        Some(code) if code > 256 && walltime_exceeded => timeout(),
+        Some(code) if code > 256 => FailureMode::Killed { signal: code - 256 },
+        Some(137) => out_of_memory(),
+        Some(code) if code > 128 => FailureMode::ExitCode {
+            code,
+            signal: Some(code - 128),
+        },
+        Some(code) if code > 0 => FailureMode::ExitCode { code, signal: None },
+        // JOB_EXEC_FAIL1/FAIL2/RETRY and other execution-host errors
+        Some(-3..=-1 | -20) => FailureMode::HostFailure {
+            host: job.exec_host.clone(),
+        },
+        exit_status => FailureMode::Unknown { exit_status },
+    };
+
+    let (explanation, suggestion) = FailureAnalysis::generate_messages(&mode);
+
+    FailureAnalysis {
+        job_id: job.job_id.clone(),
+        mode,
+        explanation,
+        suggestion,
+        exit_status: job.exit_status,
+        mem_used_mb,
+        mem_limit_mb,
+        walltime_seconds,
+        walltime_limit_seconds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qstat::parse_qstat_json;
+
+    fn history_job(job_id: &str) -> PbsJob {
+        parse_qstat_json(include_str!("../tests/fixtures/qstat_x.json"))
+            .unwrap()
+            .into_iter()
+            .find(|j| j.job_id == job_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_analyze_oom() {
+        let analysis = analyze_job(&history_job("991.pbs01"));
+        assert_eq!(
+            analysis.mode,
+            FailureMode::OutOfMemory {
+                used_mb: 4100,
+                limit_mb: 4096,
+                suggested_mb: 7168,
+            }
+        );
+        assert!(analysis.suggestion.contains("mem_mb=7168"));
+    }
+
+    #[test]
+    fn test_analyze_walltime() {
+        let analysis = analyze_job(&history_job("992.pbs01"));
+        assert!(matches!(
+            analysis.mode,
+            FailureMode::Timeout {
+                elapsed_seconds: 3605,
+                limit_seconds: 3600,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_analyze_signal_and_exit_code() {
+        let mut job = history_job("990.pbs01");
+
+        job.exit_status = Some(271);
+        assert_eq!(analyze_job(&job).mode, FailureMode::Killed { signal: 15 });
+
+        job.exit_status = Some(2);
+        assert_eq!(
+            analyze_job(&job).mode,
+            FailureMode::ExitCode {
+                code: 2,
+                signal: None
+            }
+        );
+    }
+}
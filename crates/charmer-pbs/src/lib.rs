@@ -0,0 +1,13 @@
+//! PBS Pro / Torque integration for charmer.
+//!
+//! Query job status via `qstat -f -F json` and `qstat -x`.
+
+pub mod failure;
+pub mod qstat;
+pub mod scheduler;
+pub mod types;
+
+pub use failure::{FailureAnalysis, FailureError, FailureMode, analyze_failure, analyze_job};
+pub use qstat::{QstatError, parse_qstat_json, query_qstat, query_qstat_history, query_qstat_job};
+pub use scheduler::PbsScheduler;
+pub use types::{PbsJob, PbsJobState};
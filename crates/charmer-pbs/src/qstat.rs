@@ -0,0 +1,306 @@
+//! Query PBS jobs via `qstat -f -F json`.
+
+use crate::types::{PbsJob, PbsJobState};
+use charmer_parsers::{
+    MemoryFormat, non_empty_string, parse_duration, parse_memory_mb, parse_pbs_timestamp,
+    run_command_allow_failure,
+};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Error, Debug)]
+pub enum QstatError {
+    #[error("Failed to execute qstat: {0}")]
+    ExecutionError(String),
+    #[error("Failed to parse qstat output: {0}")]
+    ParseError(String),
+}
+
+/// PBS Pro exit status: job killed for exceeding its vmem limit.
+pub(crate) const JOB_EXEC_KILL_VMEM: i32 = -26;
+/// PBS Pro exit status: job killed for exceeding its mem limit.
+pub(crate) const JOB_EXEC_KILL_MEM: i32 = -27;
+/// PBS Pro exit status: job killed for exceeding its walltime.
+pub(crate) const JOB_EXEC_KILL_WALLTIME: i32 = -29;
+
+/// Describe a PBS exit status for display.
+pub(crate) fn describe_exit_status(code: i32) -> String {
+    match code {
+        JOB_EXEC_KILL_MEM | JOB_EXEC_KILL_VMEM => "Job exceeded memory limit".to_string(),
+        JOB_EXEC_KILL_WALLTIME => "Job exceeded walltime limit".to_string(),
+        c if c < 0 => format!("Job could not be executed (PBS exit status {})", c),
+        // PBS reports death by signal as 256 + signal
+        c if c > 256 => format!("Job killed by signal {}", c - 256),
+        c => format!("Job exited with code {}", c),
+    }
+}
+
+/// Parse PBS state letter, using the exit status for finished jobs.
+fn parse_state(s: &str, exit_status: Option<i32>) -> PbsJobState {
+    match s.to_uppercase().as_str() {
+        "Q" | "T" => PbsJobState::Queued,
+        "R" | "B" => PbsJobState::Running,
+        "H" => PbsJobState::Held,
+        "W" => PbsJobState::Waiting,
+        "S" | "U" => PbsJobState::Suspended,
+        "E" => PbsJobState::Exiting,
+        // F is PBS Pro history, C is Torque completed, X is a finished subjob
+        "F" | "C" | "X" => match exit_status {
+            Some(0) => PbsJobState::Completed,
+            Some(code) => PbsJobState::Failed {
+                exit_code: code,
+                error: describe_exit_status(code),
+            },
+            None => PbsJobState::Cancelled,
+        },
+        other => PbsJobState::Unknown(other.to_string()),
+    }
+}
+
+/// Get a string attribute; qstat emits some numeric resources as JSON numbers.
+fn attr_string(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => non_empty_string(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Strip CPU slot suffixes from an exec_host string ("n1/0*4+n2/0*4" -> "n1,n2").
+fn parse_exec_host(s: &str) -> String {
+    let mut hosts: Vec<&str> = Vec::new();
+    for part in s.split('+') {
+        let host = part.split('/').next().unwrap_or(part);
+        if !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    hosts.join(",")
+}
+
+/// Parse a single job object from qstat JSON.
+fn parse_job(job_id: &str, attrs: &Value) -> PbsJob {
+    let resource_list = attrs.get("Resource_List");
+    let resources_used = attrs.get("resources_used");
+    let resource = |group: Option<&Value>, key: &str| attr_string(group.and_then(|g| g.get(key)));
+
+    let exit_status = attrs
+        .get("Exit_status")
+        .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()))
+        .map(|v| v as i32);
+
+    let state = attr_string(attrs.get("job_state")).unwrap_or_default();
+
+    PbsJob {
+        job_id: job_id.to_string(),
+        name: attr_string(attrs.get("Job_Name")).unwrap_or_default(),
+        state: parse_state(&state, exit_status),
+        queue: attr_string(attrs.get("queue")),
+        submit_time: attr_string(attrs.get("ctime")).and_then(|s| parse_pbs_timestamp(&s)),
+        start_time: attr_string(attrs.get("stime")).and_then(|s| parse_pbs_timestamp(&s)),
+        end_time: attr_string(attrs.get("obittime")).and_then(|s| parse_pbs_timestamp(&s)),
+        exec_host: attr_string(attrs.get("exec_host")).map(|s| parse_exec_host(&s)),
+        ncpus: resource(resource_list, "ncpus").and_then(|s| s.parse().ok()),
+        mem_limit_mb: resource(resource_list, "mem")
+            .and_then(|s| parse_memory_mb(&s, MemoryFormat::Pbs)),
+        mem_used_mb: resource(resources_used, "mem")
+            .and_then(|s| parse_memory_mb(&s, MemoryFormat::Pbs)),
+        walltime_limit: resource(resource_list, "walltime").and_then(|s| parse_duration(&s)),
+        walltime_used: resource(resources_used, "walltime").and_then(|s| parse_duration(&s)),
+        cput_used: resource(resources_used, "cput").and_then(|s| parse_duration(&s)),
+        exit_status,
+        comment: attr_string(attrs.get("comment")),
+    }
+}
+
+/// Parse `qstat -f -F json` output into jobs.
+pub fn parse_qstat_json(output: &str) -> Result<Vec<PbsJob>, QstatError> {
+    let root: Value =
+        serde_json::from_str(output).map_err(|e| QstatError::ParseError(e.to_string()))?;
+
+    // "Jobs" is omitted entirely when there are no jobs
+    let Some(jobs) = root.get("Jobs") else {
+        return Ok(Vec::new());
+    };
+    let jobs = jobs
+        .as_object()
+        .ok_or_else(|| QstatError::ParseError("\"Jobs\" is not an object".to_string()))?;
+
+    Ok(jobs
+        .iter()
+        .map(|(job_id, attrs)| parse_job(job_id, attrs))
+        .collect())
+}
+
+/// Run qstat with the given extra arguments and parse the JSON output.
+async fn run_qstat(extra_args: &[&str]) -> Result<Vec<PbsJob>, QstatError> {
+    let user = std::env::var("USER").unwrap_or_default();
+
+    let mut cmd = Command::new("qstat");
+    cmd.args(extra_args);
+    cmd.args(["-f", "-F", "json", "-u", &user]);
+
+    // qstat returns non-zero if no jobs match, which is OK
+    let stdout = run_command_allow_failure(&mut cmd, "qstat")
+        .await
+        .map_err(|e| QstatError::ExecutionError(e.to_string()))?;
+
+    if stdout.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    parse_qstat_json(&stdout)
+}
+
+/// Keep the jobs of the run `run_uuid`, if given.
+///
+/// The PBS executor names jobs after their rule (`rule_align_...`), not the
+/// run, so the filter keeps every rule-marked job and any named for the run;
+/// the polling service narrows them down with the main log's job IDs.
+fn retain_run(jobs: &mut Vec<PbsJob>, run_uuid: Option<&str>) {
+    if let Some(run_uuid) = run_uuid {
+        jobs.retain(|j| j.name.starts_with("rule_") || j.name == run_uuid);
+    }
+}
+
+/// Query active jobs with `qstat -f -F json`.
+pub async fn query_qstat(run_uuid: Option<&str>) -> Result<Vec<PbsJob>, QstatError> {
+    let mut jobs = run_qstat(&[]).await?;
+    retain_run(&mut jobs, run_uuid);
+    Ok(jobs)
+}
+
+/// Query finished jobs with `qstat -x -f -F json`.
+pub async fn query_qstat_history(
+    run_uuid: Option<&str>,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<PbsJob>, QstatError> {
+    let mut jobs = run_qstat(&["-x"]).await?;
+    retain_run(&mut jobs, run_uuid);
+    if let Some(since) = since {
+        jobs.retain(|j| j.end_time.or(j.submit_time).is_none_or(|t| t >= since));
+    }
+
+    Ok(jobs)
+}
+
+/// Query a single job (active or finished) with `qstat -x -f -F json <job_id>`.
+pub async fn query_qstat_job(job_id: &str) -> Result<Option<PbsJob>, QstatError> {
+    let mut cmd = Command::new("qstat");
+    cmd.args(["-x", "-f", "-F", "json", job_id]);
+
+    let stdout = run_command_allow_failure(&mut cmd, "qstat")
+        .await
+        .map_err(|e| QstatError::ExecutionError(e.to_string()))?;
+
+    if stdout.trim().is_empty() {
+        return Ok(None);
+    }
+
+    // The server suffix may be omitted from job_id ("1234" vs "1234.pbs01")
+    Ok(parse_qstat_json(&stdout)?
+        .into_iter()
+        .find(|j| j.job_id == job_id || j.job_id.split('.').next() == Some(job_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const QSTAT_F_JSON: &str = include_str!("../tests/fixtures/qstat_f.json");
+    const QSTAT_X_JSON: &str = include_str!("../tests/fixtures/qstat_x.json");
+
+    #[test]
+    fn test_parse_state() {
+        assert_eq!(parse_state("Q", None), PbsJobState::Queued);
+        assert_eq!(parse_state("R", None), PbsJobState::Running);
+        assert_eq!(parse_state("F", Some(0)), PbsJobState::Completed);
+        assert_eq!(parse_state("F", None), PbsJobState::Cancelled);
+        assert!(matches!(
+            parse_state("F", Some(-29)),
+            PbsJobState::Failed { exit_code: -29, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_exec_host() {
+        assert_eq!(parse_exec_host("node01/0*4"), "node01");
+        assert_eq!(parse_exec_host("n1/0*4+n1/1*4+n2/0*4"), "n1,n2");
+    }
+
+    #[test]
+    fn test_parse_active_fixture() {
+        let mut jobs = parse_qstat_json(QSTAT_F_JSON).unwrap();
+        jobs.sort_by(|a, b| a.job_id.cmp(&b.job_id));
+        assert_eq!(jobs.len(), 2);
+
+        let running = &jobs[0];
+        assert_eq!(running.job_id, "1001.pbs01");
+        assert_eq!(running.name, "rule_align_wildcards_sample=S1");
+        assert_eq!(running.state, PbsJobState::Running);
+        assert_eq!(running.queue.as_deref(), Some("workq"));
+        assert_eq!(running.exec_host.as_deref(), Some("node07"));
+        assert_eq!(running.ncpus, Some(4));
+        assert_eq!(running.mem_limit_mb, Some(8192));
+        assert_eq!(running.mem_used_mb, Some(2048));
+        assert_eq!(running.walltime_limit, Some(Duration::from_secs(7200)));
+        assert!(running.start_time.is_some());
+
+        assert_eq!(jobs[1].state, PbsJobState::Queued);
+        assert!(jobs[1].exec_host.is_none());
+    }
+
+    #[test]
+    fn test_parse_history_fixture() {
+        let mut jobs = parse_qstat_json(QSTAT_X_JSON).unwrap();
+        jobs.sort_by(|a, b| a.job_id.cmp(&b.job_id));
+        assert_eq!(jobs.len(), 3);
+
+        assert_eq!(jobs[0].state, PbsJobState::Completed);
+        assert!(jobs[0].end_time.is_some());
+        assert!(matches!(
+            jobs[1].state,
+            PbsJobState::Failed {
+                exit_code: JOB_EXEC_KILL_MEM,
+                ..
+            }
+        ));
+        assert_eq!(jobs[1].mem_used_mb, Some(4100));
+        assert_eq!(jobs[2].exit_status, Some(JOB_EXEC_KILL_WALLTIME));
+    }
+
+    #[test]
+    fn test_retain_run() {
+        let mut jobs = parse_qstat_json(QSTAT_F_JSON).unwrap();
+        let mut other = jobs[0].clone();
+        other.job_id = "1003.pbs01".to_string();
+        other.name = "interactive".to_string();
+        jobs.push(other);
+
+        let mut all = jobs.clone();
+        retain_run(&mut all, None);
+        assert_eq!(all.len(), 3);
+
+        // Rule-marked jobs are kept even though no name is the run UUID
+        retain_run(&mut jobs, Some("3f2a9c1e-run-uuid"));
+        let mut names: Vec<&str> = jobs.iter().map(|j| j.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "rule_align_wildcards_sample=S1",
+                "rule_align_wildcards_sample=S2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_output() {
+        let jobs =
+            parse_qstat_json(r#"{"timestamp": 1734600000, "pbs_version": "2022.1.0"}"#).unwrap();
+        assert!(jobs.is_empty());
+    }
+}
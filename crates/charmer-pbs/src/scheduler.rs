@@ -0,0 +1,214 @@
+//! `Scheduler` trait implementation for PBS Pro / Torque.
+
+use crate::failure::{self, FailureAnalysis, FailureMode};
+use crate::qstat;
+use crate::types::{PbsJob, PbsJobState};
+use async_trait::async_trait;
+use charmer_scheduler::{
+    ResourceUsage, Scheduler, SchedulerCapabilities, SchedulerError, SchedulerJob,
+    SchedulerJobState,
+};
+use chrono::{DateTime, Utc};
+
+/// PBS backend (qstat / qstat -x).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PbsScheduler;
+
+#[async_trait]
+impl Scheduler for PbsScheduler {
+    fn name(&self) -> &'static str {
+        "pbs"
+    }
+
+    fn capabilities(&self) -> SchedulerCapabilities {
+        SchedulerCapabilities {
+            history: true,
+            resource_usage: true,
+            failure_analysis: true,
+            job_control: false,
+            run_names: false,
+        }
+    }
+
+    async fn is_available(&self) -> bool {
+        // SGE also ships a qstat, but only PBS answers --version
+        tokio::process::Command::new("qstat")
+            .arg("--version")
+            .output()
+            .await
+            .map(|o| {
+                o.status.success()
+                    && String::from_utf8_lossy(&o.stdout)
+                        .to_lowercase()
+                        .contains("version")
+            })
+            .unwrap_or(false)
+    }
+
+    async fn query_active(
+        &self,
+        run_uuid: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = qstat::query_qstat(run_uuid)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_history(
+        &self,
+        run_uuid: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = qstat::query_qstat_history(run_uuid, since)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_resource_usage(
+        &self,
+        job_id: &str,
+    ) -> Result<Option<ResourceUsage>, SchedulerError> {
+        let job = qstat::query_qstat_job(job_id)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(job.map(|j| ResourceUsage {
+            max_rss_mb: j.mem_used_mb,
+            elapsed_seconds: j.walltime_used.map(|d| d.as_secs()),
+            cpu_time_seconds: j.cput_used.map(|d| d.as_secs()),
+            ..Default::default()
+        }))
+    }
+
+    async fn analyze_failure(
+        &self,
+        job_id: &str,
+    ) -> Result<charmer_scheduler::FailureAnalysis, SchedulerError> {
+        let analysis = failure::analyze_failure(job_id)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok((&analysis).into())
+    }
+}
+
+impl From<PbsJobState> for SchedulerJobState {
+    fn from(state: PbsJobState) -> Self {
+        match state {
+            PbsJobState::Queued | PbsJobState::Held | PbsJobState::Waiting => Self::Pending,
+            PbsJobState::Running | PbsJobState::Exiting => Self::Running,
+            PbsJobState::Suspended => Self::Suspended,
+            PbsJobState::Completed => Self::Completed,
+            PbsJobState::Failed { exit_code, error } => Self::Failed {
+                exit_code,
+                message: error,
+            },
+            PbsJobState::Cancelled => Self::Cancelled,
+            PbsJobState::Unknown(s) => Self::Unknown(s),
+        }
+    }
+}
+
+impl From<PbsJob> for SchedulerJob {
+    fn from(job: PbsJob) -> Self {
+        // PBS has no free-form comment field for users, so the snakemake
+        // "rule_..." marker is carried in the job name (-N) instead.
+        let comment = job.name.starts_with("rule_").then(|| job.name.clone());
+
+        Self {
+            job_id: job.job_id,
+            name: job.name,
+            state: job.state.into(),
+            queue: job.queue,
+            submit_time: job.submit_time,
+            start_time: job.start_time,
+            end_time: job.end_time,
+            node: job.exec_host,
+            cpus: job.ncpus,
+            mem_mb: job.mem_limit_mb,
+            time_limit: job.walltime_limit,
+            comment,
+            array: None,
+            pending_reason: None,
+            priority: None,
+            estimated_start: None,
+        }
+    }
+}
+
+/// Convert PBS failure analysis to unified format.
+impl From<&FailureAnalysis> for charmer_scheduler::FailureAnalysis {
+    fn from(analysis: &FailureAnalysis) -> Self {
+        use charmer_scheduler::FailureMode as Unified;
+
+        let mode = match &analysis.mode {
+            FailureMode::OutOfMemory { .. } => Unified::OutOfMemory,
+            FailureMode::Timeout { .. } => Unified::Timeout,
+            FailureMode::ExitCode { .. } => Unified::ExitCode,
+            FailureMode::Killed { .. } => Unified::Cancelled,
+            FailureMode::HostFailure { .. } => Unified::NodeFailure,
+            FailureMode::Unknown { .. } => Unified::Unknown,
+        };
+
+        let (memory_used_mb, memory_limit_mb) = match &analysis.mode {
+            FailureMode::OutOfMemory {
+                used_mb, limit_mb, ..
+            } => (Some(*used_mb), Some(*limit_mb)),
+            _ => (analysis.mem_used_mb, analysis.mem_limit_mb),
+        };
+
+        let (runtime_seconds, time_limit_seconds) = match &analysis.mode {
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                ..
+            } => (Some(*elapsed_seconds), Some(*limit_seconds)),
+            _ => (analysis.walltime_seconds, analysis.walltime_limit_seconds),
+        };
+
+        let (suggested_mem_mb, suggested_runtime_seconds) = match &analysis.mode {
+            FailureMode::OutOfMemory { suggested_mb, .. } => (Some(*suggested_mb), None),
+            FailureMode::Timeout {
+                suggested_seconds, ..
+            } => (None, Some(*suggested_seconds)),
+            _ => (None, None),
+        };
+
+        Self {
+            mode,
+            explanation: analysis.explanation.clone(),
+            suggestion: analysis.suggestion.clone(),
+            exit_code: analysis.exit_status,
+            memory_used_mb,
+            memory_limit_mb,
+            runtime_seconds,
+            time_limit_seconds,
+            suggested_mem_mb,
+            suggested_runtime_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_conversion() {
+        assert_eq!(
+            SchedulerJobState::from(PbsJobState::Held),
+            SchedulerJobState::Pending
+        );
+        assert_eq!(
+            SchedulerJobState::from(PbsJobState::Exiting),
+            SchedulerJobState::Running
+        );
+        assert!(matches!(
+            SchedulerJobState::from(PbsJobState::Failed {
+                exit_code: -27,
+                error: String::new()
+            }),
+            SchedulerJobState::Failed { exit_code: -27, .. }
+        ));
+    }
+}
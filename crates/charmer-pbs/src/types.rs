@@ -0,0 +1,83 @@
+//! PBS job types.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// PBS job status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PbsJobState {
+    /// Q - queued, eligible to run
+    Queued,
+    /// R - running
+    Running,
+    /// H - held
+    Held,
+    /// W - waiting for its execution time
+    Waiting,
+    /// S/U - suspended
+    Suspended,
+    /// E - exiting after having run
+    Exiting,
+    /// F/C - finished with exit status 0
+    Completed,
+    /// F/C - finished with non-zero exit status
+    Failed {
+        exit_code: i32,
+        error: String,
+    },
+    /// F/C - finished without ever running (deleted)
+    Cancelled,
+    Unknown(String),
+}
+
+/// PBS job information from qstat.
+#[derive(Debug, Clone)]
+pub struct PbsJob {
+    /// PBS job ID (e.g., "1234.server")
+    pub job_id: String,
+
+    /// Job name (-N)
+    pub name: String,
+
+    /// Job state
+    pub state: PbsJobState,
+
+    /// Queue
+    pub queue: Option<String>,
+
+    /// Submit time (ctime)
+    pub submit_time: Option<DateTime<Utc>>,
+
+    /// Start time (stime)
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// End time (obittime)
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Execution host(s), comma separated
+    pub exec_host: Option<String>,
+
+    /// Requested CPUs (Resource_List.ncpus)
+    pub ncpus: Option<u32>,
+
+    /// Requested memory in MB (Resource_List.mem)
+    pub mem_limit_mb: Option<u64>,
+
+    /// Memory used in MB (resources_used.mem)
+    pub mem_used_mb: Option<u64>,
+
+    /// Requested walltime (Resource_List.walltime)
+    pub walltime_limit: Option<Duration>,
+
+    /// Walltime used (resources_used.walltime)
+    pub walltime_used: Option<Duration>,
+
+    /// CPU time used (resources_used.cput)
+    pub cput_used: Option<Duration>,
+
+    /// Exit status (finished jobs only)
+    pub exit_status: Option<i32>,
+
+    /// Scheduler comment attribute
+    pub comment: Option<String>,
+}
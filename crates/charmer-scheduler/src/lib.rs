@@ -0,0 +1,13 @@
+//! Scheduler abstraction for charmer.
+//!
+//! Defines the `Scheduler` trait implemented by each backend crate
+//! (charmer-slurm, charmer-lsf) and the scheduler-neutral types it returns.
+
+pub mod scheduler;
+pub mod types;
+
+pub use scheduler::{Scheduler, SchedulerError};
+pub use types::{
+    ArrayTask, FailureAnalysis, FailureMode, JobAction, ResourceUsage, SchedulerCapabilities,
+    SchedulerJob, SchedulerJobState, StepUsage,
+};
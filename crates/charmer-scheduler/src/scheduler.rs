@@ -0,0 +1,67 @@
+//! The `Scheduler` trait implemented by each backend.
+
+use crate::types::{
+    FailureAnalysis, JobAction, ResourceUsage, SchedulerCapabilities, SchedulerJob,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SchedulerError {
+    #[error("Scheduler query failed: {0}")]
+    Query(String),
+    #[error("Scheduler command failed: {0}")]
+    Control(String),
+    #[error("{0} is not supported by this scheduler")]
+    Unsupported(&'static str),
+}
+
+/// A cluster scheduler backend.
+///
+/// The polling service only talks to schedulers through this trait, so a
+/// new backend can be added without touching the polling loop.
+#[async_trait]
+pub trait Scheduler: Send + Sync {
+    /// Short lowercase name (e.g., "slurm", "lsf").
+    fn name(&self) -> &'static str;
+
+    /// Optional features this backend supports.
+    fn capabilities(&self) -> SchedulerCapabilities;
+
+    /// Check whether the scheduler's tools are available on this host.
+    async fn is_available(&self) -> bool;
+
+    /// Query active (queued and running) jobs.
+    async fn query_active(
+        &self,
+        run_uuid: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError>;
+
+    /// Query job history since the given time.
+    async fn query_history(
+        &self,
+        _run_uuid: Option<&str>,
+        _since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        Err(SchedulerError::Unsupported("history query"))
+    }
+
+    /// Query actual resource usage for a finished job.
+    async fn query_resource_usage(
+        &self,
+        _job_id: &str,
+    ) -> Result<Option<ResourceUsage>, SchedulerError> {
+        Err(SchedulerError::Unsupported("resource usage"))
+    }
+
+    /// Analyze why a job failed.
+    async fn analyze_failure(&self, _job_id: &str) -> Result<FailureAnalysis, SchedulerError> {
+        Err(SchedulerError::Unsupported("failure analysis"))
+    }
+
+    /// Cancel, hold or release jobs by scheduler job ID.
+    async fn control(&self, _action: JobAction, _job_ids: &[String]) -> Result<(), SchedulerError> {
+        Err(SchedulerError::Unsupported("job control"))
+    }
+}
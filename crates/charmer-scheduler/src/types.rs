@@ -0,0 +1,214 @@
+//! Scheduler-neutral job, usage, and failure types.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Scheduler job state, normalized across backends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchedulerJobState {
+    /// Waiting in the scheduler queue
+    Pending,
+    /// Currently running
+    Running,
+    /// Suspended by the user or the system
+    Suspended,
+    /// Finished successfully
+    Completed,
+    /// Finished unsuccessfully
+    Failed { exit_code: i32, message: String },
+    /// Cancelled or killed before completion
+    Cancelled,
+    /// Unknown or unmapped state
+    Unknown(String),
+}
+
+/// Scheduler job information from an active or history query.
+#[derive(Debug, Clone)]
+pub struct SchedulerJob {
+    /// Scheduler job ID
+    pub job_id: String,
+
+    /// Job name (run_uuid for snakemake executor plugins)
+    pub name: String,
+
+    /// Job state
+    pub state: SchedulerJobState,
+
+    /// Partition or queue
+    pub queue: Option<String>,
+
+    /// Submit time
+    pub submit_time: Option<DateTime<Utc>>,
+
+    /// Start time
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// End time
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Execution node(s)
+    pub node: Option<String>,
+
+    /// Allocated CPUs
+    pub cpus: Option<u32>,
+
+    /// Requested memory (MB)
+    pub mem_mb: Option<u64>,
+
+    /// Time limit
+    pub time_limit: Option<Duration>,
+
+    /// Comment or description field (contains rule info for snakemake)
+    pub comment: Option<String>,
+
+    /// Position within an array job (if part of one)
+    pub array: Option<ArrayTask>,
+
+    /// Why a queued job hasn't started (e.g., "Priority", "Resources")
+    pub pending_reason: Option<String>,
+
+    /// Scheduling priority
+    pub priority: Option<u64>,
+
+    /// Scheduler's estimated start time for a queued job
+    pub estimated_start: Option<DateTime<Utc>>,
+}
+
+/// Position of a job within an array job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArrayTask {
+    /// Parent array job ID
+    pub parent_id: String,
+    /// Task index, or None for a line covering several pending tasks
+    pub task_id: Option<u32>,
+    /// Number of tasks this entry stands for (1 for a single task)
+    pub task_count: u32,
+}
+
+/// Actual resource usage (from sacct/bhist for finished jobs, or sampled
+/// live for local jobs).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Maximum resident set size (actual memory used) in MB
+    pub max_rss_mb: Option<u64>,
+    /// Actual elapsed runtime in seconds
+    pub elapsed_seconds: Option<u64>,
+    /// Total CPU time in seconds
+    pub cpu_time_seconds: Option<u64>,
+    /// Current resident set size in MB (running local jobs only)
+    #[serde(default)]
+    pub rss_mb: Option<u64>,
+    /// Current CPU usage, 100% = one core (running local jobs only)
+    #[serde(default)]
+    pub cpu_percent: Option<f64>,
+    /// Per-step breakdown (SLURM batch/extern/srun steps)
+    #[serde(default)]
+    pub steps: Vec<StepUsage>,
+}
+
+/// Resource usage of a single job step.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepUsage {
+    /// Step ID (e.g., "12345.batch", "12345.0")
+    pub step_id: String,
+    /// Step name (e.g., "batch", or the srun command)
+    pub name: String,
+    /// Maximum resident set size in MB
+    pub max_rss_mb: Option<u64>,
+    /// Elapsed runtime in seconds
+    pub elapsed_seconds: Option<u64>,
+    /// CPU time in seconds
+    pub cpu_time_seconds: Option<u64>,
+}
+
+/// Detailed failure analysis from SLURM/LSF.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureAnalysis {
+    /// Classified failure mode
+    pub mode: FailureMode,
+    /// Human-readable explanation
+    pub explanation: String,
+    /// Suggested fix
+    pub suggestion: String,
+    /// Exit code reported by the scheduler (if known)
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Memory used (MB) if available
+    pub memory_used_mb: Option<u64>,
+    /// Memory limit (MB) if available
+    pub memory_limit_mb: Option<u64>,
+    /// Runtime (seconds) if available
+    pub runtime_seconds: Option<u64>,
+    /// Time limit (seconds) if available
+    pub time_limit_seconds: Option<u64>,
+    /// Memory (MB) to request on retry, for out-of-memory failures
+    #[serde(default)]
+    pub suggested_mem_mb: Option<u64>,
+    /// Time limit (seconds) to request on retry, for timeouts
+    #[serde(default)]
+    pub suggested_runtime_seconds: Option<u64>,
+}
+
+/// Failure mode classification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailureMode {
+    /// Job ran out of memory
+    OutOfMemory,
+    /// Job exceeded time limit
+    Timeout,
+    /// Job failed with exit code
+    ExitCode,
+    /// Job was cancelled/killed
+    Cancelled,
+    /// Node/host failure
+    NodeFailure,
+    /// Unknown failure
+    Unknown,
+}
+
+/// A change to the state of submitted jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobAction {
+    /// Remove queued jobs and kill running ones
+    Cancel,
+    /// Keep queued jobs from starting
+    Hold,
+    /// Let held jobs start again
+    Release,
+}
+
+impl JobAction {
+    /// Imperative label ("Cancel").
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Cancel => "Cancel",
+            Self::Hold => "Hold",
+            Self::Release => "Release",
+        }
+    }
+
+    /// Past participle for result messages ("cancelled").
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            Self::Cancel => "cancelled",
+            Self::Hold => "held",
+            Self::Release => "released",
+        }
+    }
+}
+
+/// Optional features a scheduler backend supports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerCapabilities {
+    /// Can query finished jobs (sacct/bhist)
+    pub history: bool,
+    /// Can report actual resource usage for finished jobs
+    pub resource_usage: bool,
+    /// Can classify why a job failed
+    pub failure_analysis: bool,
+    /// Can cancel, hold and release jobs
+    pub job_control: bool,
+    /// Job names carry the run UUID, so queries can filter on it
+    pub run_names: bool,
+}
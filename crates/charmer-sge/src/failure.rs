@@ -0,0 +1,297 @@
+//! SGE job failure analysis.
+//!
+//! Classify failures from qacct's `failed`, `exit_status` and `maxvmem`
+//! fields and provide actionable suggestions.
+
+use crate::qacct::{QacctError, query_qacct_job};
+use crate::types::SgeJob;
+use charmer_parsers::format_duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FailureError {
+    #[error("Failed to execute qacct: {0}")]
+    ExecutionError(String),
+    #[error("Job not found: {0}")]
+    NotFound(String),
+}
+
+/// Failure mode classification for SGE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureMode {
+    /// Job exceeded h_vmem
+    OutOfMemory {
+        used_mb: u64,
+        requested_mb: u64,
+        suggested_mb: u64,
+    },
+    /// Job exceeded h_rt
+    Timeout {
+        elapsed_seconds: u64,
+        limit_seconds: u64,
+        suggested_seconds: u64,
+    },
+    /// Job failed with non-zero exit code
+    ExitCode { code: i32, signal: Option<i32> },
+    /// Job was deleted (qdel) while running
+    Killed,
+    /// SGE could not start the job on the execution host (failed 1-36)
+    HostFailure {
+        host: Option<String>,
+        reason: String,
+    },
+    /// Unknown failure mode
+    Unknown { failed: String },
+}
+
+/// Detailed failure analysis result for SGE.
+#[derive(Debug, Clone)]
+pub struct FailureAnalysis {
+    /// SGE job number
+    pub job_id: String,
+    /// Classified failure mode
+    pub mode: FailureMode,
+    /// Human-readable explanation
+    pub explanation: String,
+    /// Suggested fix
+    pub suggestion: String,
+    /// Job exit status
+    pub exit_status: Option<i32>,
+    /// Peak virtual memory (MB)
+    pub max_vmem_mb: Option<u64>,
+    /// Requested memory (MB)
+    pub req_mem_mb: Option<u64>,
+    /// Wallclock runtime (seconds)
+    pub wallclock_seconds: Option<u64>,
+    /// h_rt limit (seconds)
+    pub time_limit_seconds: Option<u64>,
+}
+
+impl FailureAnalysis {
+    /// Generate explanation and suggestion based on failure mode.
+    fn generate_messages(mode: &FailureMode) -> (String, String) {
+        match mode {
+            FailureMode::OutOfMemory {
+                used_mb,
+                requested_mb,
+                suggested_mb,
+            } => {
+                let explanation = format!(
+                    "Job exceeded memory limit. Used {:.1} GB but only {:.1} GB was allocated.",
+                    *used_mb as f64 / 1024.0,
+                    *requested_mb as f64 / 1024.0
+                );
+                let suggestion = format!(
+                    "Increase memory to at least {:.1} GB. In your Snakefile, add:\n  resources: mem_mb={}",
+                    *suggested_mb as f64 / 1024.0,
+                    suggested_mb
+                );
+                (explanation, suggestion)
+            }
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                suggested_seconds,
+            } => {
+                let explanation = format!(
+                    "Job exceeded time limit. Ran for {} but limit was {}.",
+                    format_duration(*elapsed_seconds),
+                    format_duration(*limit_seconds)
+                );
+                let suggestion = format!(
+                    "Increase time limit to at least {}. In your Snakefile, add:\n  resources: runtime={}",
+                    format_duration(*suggested_seconds),
+                    suggested_seconds.div_ceil(60)
+                );
+                (explanation, suggestion)
+            }
+            FailureMode::ExitCode { code, signal } => {
+                let explanation = match (code, signal) {
+                    (_, Some(sig)) => format!("Job exited with code {} and signal {}", code, sig),
+                    (1, None) => "Job failed with exit code 1 (general error)".to_string(),
+                    (137, None) => {
+                        "Job killed (likely OOM killer). Exit code 137 = 128 + 9 (SIGKILL)"
+                            .to_string()
+                    }
+                    (_, None) => format!("Job failed with exit code {}", code),
+                };
+                let suggestion = if *code == 137 {
+                    "This is likely an out-of-memory error. Try increasing memory allocation."
+                        .to_string()
+                } else {
+                    "Check the job's stderr log for error details.".to_string()
+                };
+                (explanation, suggestion)
+            }
+            FailureMode::Killed => (
+                "Job was deleted while running".to_string(),
+                "Consider if this was intentional or due to dependency failure.".to_string(),
+            ),
+            FailureMode::HostFailure { host, reason } => {
+                let explanation = match host {
+                    Some(h) => format!("SGE could not run the job on {}: {}", h, reason),
+                    None => format!("SGE could not run the job: {}", reason),
+                };
+                (
+                    explanation,
+                    "Re-run the job. If persistent, contact cluster admin.".to_string(),
+                )
+            }
+            FailureMode::Unknown { failed } => (
+                format!("Job failed: {}", failed),
+                "Check the SGE messages file for details.".to_string(),
+            ),
+        }
+    }
+}
+
+/// Query detailed failure information for an SGE job.
+pub async fn analyze_failure(job_id: &str) -> Result<FailureAnalysis, FailureError> {
+    let job = query_qacct_job(job_id)
+        .await
+        .map_err(|e| match e {
+            QacctError::ExecutionError(msg) | QacctError::ParseError(msg) => {
+                FailureError::ExecutionError(msg)
+            }
+        })?
+        .ok_or_else(|| FailureError::NotFound(job_id.to_string()))?;
+
+    Ok(analyze_job(&job))
+}
+
+/// Classify a finished SGE job from its accounting record.
+pub fn analyze_job(job: &SgeJob) -> FailureAnalysis {
+    let failed = job.failed.unwrap_or(0);
+    let exit_status = job.exit_status.unwrap_or(0);
+    let time_limit_seconds = job.time_limit.map(|d| d.as_secs());
+
+    // SGE kills h_rt/h_vmem overruns with SIGKILL, so compare usage with limits
+    let exceeded_memory = matches!(
+        (job.max_vmem_mb, job.mem_limit_mb),
+        (Some(used), Some(limit)) if used >= limit
+    );
+    let exceeded_time = matches!(
+        (job.wallclock_seconds, time_limit_seconds),
+        (Some(used), Some(limit)) if limit > 0 && used >= limit
+    );
+
+    let mode = if exceeded_memory && (failed != 0 || exit_status != 0) {
+        let used = job.max_vmem_mb.unwrap_or(0);
+        let requested = job.mem_limit_mb.unwrap_or(0);
+        let suggested = ((used as f64 * 1.5) / 1024.0).ceil() as u64 * 1024;
+        FailureMode::OutOfMemory {
+            used_mb: used,
+            requested_mb: requested,
+            suggested_mb: suggested.max(requested + 1024),
+        }
+    } else if exceeded_time && (failed != 0 || exit_status != 0) {
+        let elapsed = job.wallclock_seconds.unwrap_or(0);
+        let limit = time_limit_seconds.unwrap_or(0);
+        let suggested = (elapsed as f64 * 1.5) as u64;
+        FailureMode::Timeout {
+            elapsed_seconds: elapsed,
+            limit_seconds: limit,
+            suggested_seconds: suggested.max(limit + 3600),
+        }
+    } else if (1..37).contains(&failed) {
+        FailureMode::HostFailure {
+            host: job.host.clone(),
+            reason: job
+                .failed_reason
+                .clone()
+                .unwrap_or_else(|| format!("failed code {}", failed)),
+        }
+    } else if failed == 100 && exit_status == 137 {
+        // "assumedly after job" with SIGKILL and no limit hit: qdel
+        FailureMode::Killed
+    } else if exit_status > 128 && exit_status != 137 {
+        FailureMode::ExitCode {
+            code: exit_status,
+            signal: Some(exit_status - 128),
+        }
+    } else if exit_status != 0 {
+        FailureMode::ExitCode {
+            code: exit_status,
+            signal: None,
+        }
+    } else {
+        FailureMode::Unknown {
+            failed: job
+                .failed_reason
+                .clone()
+                .unwrap_or_else(|| format!("failed code {}", failed)),
+        }
+    };
+
+    let (explanation, suggestion) = FailureAnalysis::generate_messages(&mode);
+
+    FailureAnalysis {
+        job_id: job.job_id.clone(),
+        mode,
+        explanation,
+        suggestion,
+        exit_status: job.exit_status,
+        max_vmem_mb: job.max_vmem_mb,
+        req_mem_mb: job.mem_limit_mb,
+        wallclock_seconds: job.wallclock_seconds,
+        time_limit_seconds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qacct::parse_qacct_output;
+
+    fn fixture_jobs() -> Vec<SgeJob> {
+        parse_qacct_output(include_str!("../tests/fixtures/qacct.txt"))
+    }
+
+    #[test]
+    fn test_analyze_oom() {
+        let analysis = analyze_job(&fixture_jobs()[1]);
+        assert_eq!(
+            analysis.mode,
+            FailureMode::OutOfMemory {
+                used_mb: 4097,
+                requested_mb: 4096,
+                suggested_mb: 7168,
+            }
+        );
+        assert!(analysis.explanation.contains("exceeded memory limit"));
+    }
+
+    #[test]
+    fn test_analyze_h_rt() {
+        let analysis = analyze_job(&fixture_jobs()[2]);
+        assert!(matches!(
+            analysis.mode,
+            FailureMode::Timeout {
+                elapsed_seconds: 3605,
+                limit_seconds: 3600,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_analyze_exit_code_and_host_failure() {
+        let mut job = fixture_jobs()[0].clone();
+        job.exit_status = Some(1);
+        assert_eq!(
+            analyze_job(&job).mode,
+            FailureMode::ExitCode {
+                code: 1,
+                signal: None
+            }
+        );
+
+        job.exit_status = Some(0);
+        job.failed = Some(26);
+        job.failed_reason = Some("opening input/output file".to_string());
+        assert!(matches!(
+            analyze_job(&job).mode,
+            FailureMode::HostFailure { .. }
+        ));
+    }
+}
@@ -0,0 +1,15 @@
+//! SGE / Univa Grid Engine integration for charmer.
+//!
+//! Query job status via `qstat -xml` and `qacct -j`.
+
+pub mod failure;
+pub mod qacct;
+pub mod qstat;
+pub mod scheduler;
+pub mod types;
+
+pub use failure::{FailureAnalysis, FailureError, FailureMode, analyze_failure, analyze_job};
+pub use qacct::{QacctError, parse_qacct_output, query_qacct};
+pub use qstat::{QstatError, parse_qstat_xml, query_qstat};
+pub use scheduler::SgeScheduler;
+pub use types::{SgeJob, SgeJobState};
@@ -0,0 +1,241 @@
+//! Query finished SGE jobs via `qacct -j`.
+
+use crate::types::{SgeJob, SgeJobState};
+use charmer_parsers::{
+    MemoryFormat, non_empty_string, parse_duration, parse_memory_mb, parse_pbs_timestamp,
+    run_command_allow_failure,
+};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Error, Debug)]
+pub enum QacctError {
+    #[error("Failed to execute qacct: {0}")]
+    ExecutionError(String),
+    #[error("Failed to parse qacct output: {0}")]
+    ParseError(String),
+}
+
+/// Parse a qacct timestamp.
+///
+/// Older releases print ctime format ("Thu Dec 19 10:00:00 2024"), newer
+/// ones "12/19/2024 10:00:00.123".
+fn parse_qacct_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    parse_pbs_timestamp(s).or_else(|| {
+        NaiveDateTime::parse_from_str(s.trim(), "%m/%d/%Y %H:%M:%S%.f")
+            .ok()
+            .and_then(|dt| Utc.from_local_datetime(&dt).single())
+    })
+}
+
+/// Parse a qacct duration ("1080", "1080s" or "1080.123s") to whole seconds.
+fn parse_qacct_seconds(s: &str) -> Option<u64> {
+    s.trim()
+        .trim_end_matches('s')
+        .parse::<f64>()
+        .ok()
+        .map(|v| v as u64)
+}
+
+/// Extract a `-l name=value` resource request from the qacct `category` field.
+fn category_resource(category: &str, name: &str) -> Option<String> {
+    let mut words = category.split_whitespace();
+    while let Some(word) = words.next() {
+        if word != "-l" && word != "-hard" {
+            continue;
+        }
+        let Some(list) = words.next() else { break };
+        for pair in list.split(',') {
+            if let Some((key, value)) = pair.split_once('=')
+                && key == name
+            {
+                return Some(value.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Build a job from one qacct record.
+fn parse_record(fields: &HashMap<&str, &str>) -> SgeJob {
+    let field = |name: &str| fields.get(name).and_then(|v| non_empty_string(v));
+
+    // "100 : assumedly after job" -> (100, "assumedly after job")
+    let (failed, failed_reason) = match field("failed") {
+        Some(f) => match f.split_once(':') {
+            Some((code, reason)) => (code.trim().parse().ok(), non_empty_string(reason)),
+            None => (f.trim().parse().ok(), None),
+        },
+        None => (None, None),
+    };
+    let exit_status: Option<i32> = field("exit_status").and_then(|s| s.parse().ok());
+
+    let state = match (failed, exit_status) {
+        (Some(0) | None, Some(0)) => SgeJobState::Completed,
+        (failed, exit_status) => SgeJobState::Failed {
+            exit_code: exit_status.unwrap_or(-1),
+            error: match (&failed_reason, failed) {
+                (Some(reason), _) => reason.clone(),
+                (None, Some(code)) if code != 0 => format!("SGE failed code {}", code),
+                _ => format!("Job exited with code {}", exit_status.unwrap_or(-1)),
+            },
+        },
+    };
+
+    let category = field("category").unwrap_or_default();
+
+    let job_id = match field("taskid").filter(|t| t != "undefined") {
+        Some(task) => format!("{}.{}", field("jobnumber").unwrap_or_default(), task),
+        None => field("jobnumber").unwrap_or_default(),
+    };
+
+    SgeJob {
+        job_id,
+        name: field("jobname").unwrap_or_default(),
+        state,
+        queue: field("qname"),
+        host: field("hostname"),
+        submit_time: field("qsub_time").and_then(|s| parse_qacct_timestamp(&s)),
+        start_time: field("start_time").and_then(|s| parse_qacct_timestamp(&s)),
+        end_time: field("end_time").and_then(|s| parse_qacct_timestamp(&s)),
+        slots: field("slots").and_then(|s| s.parse().ok()),
+        mem_limit_mb: category_resource(&category, "h_vmem")
+            .or_else(|| category_resource(&category, "mem_free"))
+            .and_then(|s| parse_memory_mb(&s, MemoryFormat::Sge)),
+        time_limit: category_resource(&category, "h_rt").and_then(|s| parse_duration(&s)),
+        max_vmem_mb: field("maxvmem").and_then(|s| parse_memory_mb(&s, MemoryFormat::Sge)),
+        wallclock_seconds: field("ru_wallclock").and_then(|s| parse_qacct_seconds(&s)),
+        cpu_seconds: field("cpu").and_then(|s| parse_qacct_seconds(&s)),
+        failed,
+        failed_reason,
+        exit_status,
+    }
+}
+
+/// Parse `qacct -j` output into jobs.
+///
+/// Records are separated by lines of `=` characters; each line is a
+/// whitespace-separated key and value.
+pub fn parse_qacct_output(output: &str) -> Vec<SgeJob> {
+    let mut jobs = Vec::new();
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+
+    for line in output.lines() {
+        if line.starts_with("====") {
+            if !fields.is_empty() {
+                jobs.push(parse_record(&fields));
+                fields.clear();
+            }
+            continue;
+        }
+        if let Some((key, value)) = line.trim().split_once(char::is_whitespace) {
+            fields.insert(key, value.trim());
+        }
+    }
+    if !fields.is_empty() {
+        jobs.push(parse_record(&fields));
+    }
+
+    jobs
+}
+
+/// Query finished jobs with `qacct -j`.
+///
+/// `since` is rounded up to whole days for `-d`. As with qstat, there is
+/// no run filter: job names don't carry the run.
+pub async fn query_qacct(since: Option<DateTime<Utc>>) -> Result<Vec<SgeJob>, QacctError> {
+    let user = std::env::var("USER").unwrap_or_default();
+
+    let mut cmd = Command::new("qacct");
+    cmd.args(["-o", &user]);
+
+    if let Some(since) = since {
+        let days = (Utc::now() - since).num_hours().max(0) / 24 + 1;
+        cmd.args(["-d", &days.to_string()]);
+    }
+
+    cmd.arg("-j");
+
+    // qacct exits non-zero when no jobs match, which is OK
+    let stdout = run_command_allow_failure(&mut cmd, "qacct")
+        .await
+        .map_err(|e| QacctError::ExecutionError(e.to_string()))?;
+
+    let mut jobs = parse_qacct_output(&stdout);
+    if let Some(since) = since {
+        jobs.retain(|j| j.end_time.is_none_or(|t| t >= since));
+    }
+
+    Ok(jobs)
+}
+
+/// Query the accounting record for a single job.
+pub async fn query_qacct_job(job_id: &str) -> Result<Option<SgeJob>, QacctError> {
+    let mut cmd = Command::new("qacct");
+    cmd.args(["-j", job_id]);
+
+    let stdout = run_command_allow_failure(&mut cmd, "qacct")
+        .await
+        .map_err(|e| QacctError::ExecutionError(e.to_string()))?;
+
+    if stdout.contains("not found") {
+        return Ok(None);
+    }
+
+    // A rerun job has several records; the last one is the latest attempt
+    Ok(parse_qacct_output(&stdout).pop())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_category_resource() {
+        let category = "-U lab -u alice -l h_rt=7200,h_vmem=4G -pe smp 4";
+        assert_eq!(category_resource(category, "h_rt").as_deref(), Some("7200"));
+        assert_eq!(category_resource(category, "h_vmem").as_deref(), Some("4G"));
+        assert!(category_resource(category, "mem_free").is_none());
+    }
+
+    #[test]
+    fn test_parse_qacct_fixture() {
+        let jobs = parse_qacct_output(include_str!("../tests/fixtures/qacct.txt"));
+        assert_eq!(jobs.len(), 3);
+
+        let ok = &jobs[0];
+        assert_eq!(ok.job_id, "4990");
+        assert_eq!(ok.name, "snakejob.index.1.sh");
+        assert_eq!(ok.state, SgeJobState::Completed);
+        assert_eq!(ok.host.as_deref(), Some("node03"));
+        assert_eq!(ok.wallclock_seconds, Some(1200));
+        assert_eq!(ok.cpu_seconds, Some(2280));
+        assert_eq!(ok.max_vmem_mb, Some(1024));
+        assert_eq!(ok.mem_limit_mb, Some(4096));
+        assert_eq!(ok.time_limit, Some(Duration::from_secs(3600)));
+        assert!(ok.end_time.is_some());
+
+        assert!(matches!(
+            jobs[1].state,
+            SgeJobState::Failed { exit_code: 137, .. }
+        ));
+        assert_eq!(jobs[1].failed, Some(100));
+        assert_eq!(
+            jobs[1].failed_reason.as_deref(),
+            Some("assumedly after job")
+        );
+
+        // Newer date format
+        assert!(jobs[2].start_time.is_some());
+    }
+
+    #[test]
+    fn test_parse_qacct_seconds() {
+        assert_eq!(parse_qacct_seconds("1080"), Some(1080));
+        assert_eq!(parse_qacct_seconds("1080s"), Some(1080));
+        assert_eq!(parse_qacct_seconds("12.500s"), Some(12));
+    }
+}
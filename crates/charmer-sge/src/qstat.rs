@@ -0,0 +1,179 @@
+//! Query active SGE jobs via `qstat -xml`.
+
+use crate::types::{SgeJob, SgeJobState};
+use charmer_parsers::{
+    MemoryFormat, non_empty_string, parse_duration, parse_memory_mb, run_command,
+};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Error, Debug)]
+pub enum QstatError {
+    #[error("Failed to execute qstat: {0}")]
+    ExecutionError(String),
+    #[error("Failed to parse qstat output: {0}")]
+    ParseError(String),
+}
+
+/// Parse SGE state code (e.g., "qw", "hqw", "r", "Eqw", "dr").
+fn parse_state(s: &str) -> SgeJobState {
+    if s.contains('E') {
+        SgeJobState::Error
+    } else if s.contains('d') {
+        SgeJobState::Deleting
+    } else if s.contains('h') {
+        SgeJobState::Held
+    } else if s.contains(['s', 'S', 'T']) {
+        SgeJobState::Suspended
+    } else if s.contains(['r', 't', 'R']) {
+        SgeJobState::Running
+    } else if s.contains(['q', 'w']) {
+        SgeJobState::Pending
+    } else {
+        SgeJobState::Unknown(s.to_string())
+    }
+}
+
+/// Parse a qstat XML timestamp (e.g., "2024-12-19T10:12:00" or with ".123").
+fn parse_xml_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .and_then(|dt| Utc.from_local_datetime(&dt).single())
+}
+
+/// Parse a single `<job_list>` element.
+fn parse_job_list(node: roxmltree::Node) -> SgeJob {
+    let child_text = |name: &str| {
+        node.children()
+            .find(|c| c.has_tag_name(name))
+            .and_then(|c| c.text())
+            .and_then(non_empty_string)
+    };
+    let hard_request = |name: &str| {
+        node.children()
+            .find(|c| c.has_tag_name("hard_request") && c.attribute("name") == Some(name))
+            .and_then(|c| c.text())
+            .and_then(non_empty_string)
+    };
+
+    // "all.q@node07" -> ("all.q", "node07")
+    let (queue, host) = match child_text("queue_name") {
+        Some(q) => match q.split_once('@') {
+            Some((queue, host)) => (Some(queue.to_string()), Some(host.to_string())),
+            None => (Some(q), None),
+        },
+        None => (None, None),
+    };
+
+    let job_id = match child_text("tasks") {
+        Some(task) => format!(
+            "{}.{}",
+            child_text("JB_job_number").unwrap_or_default(),
+            task
+        ),
+        None => child_text("JB_job_number").unwrap_or_default(),
+    };
+
+    SgeJob {
+        job_id,
+        name: child_text("JB_name").unwrap_or_default(),
+        state: parse_state(&child_text("state").unwrap_or_default()),
+        queue,
+        host,
+        submit_time: child_text("JB_submission_time").and_then(|s| parse_xml_timestamp(&s)),
+        start_time: child_text("JAT_start_time").and_then(|s| parse_xml_timestamp(&s)),
+        end_time: None,
+        slots: child_text("slots").and_then(|s| s.parse().ok()),
+        mem_limit_mb: hard_request("h_vmem")
+            .or_else(|| hard_request("mem_free"))
+            .and_then(|s| parse_memory_mb(&s, MemoryFormat::Sge)),
+        time_limit: hard_request("h_rt").and_then(|s| parse_duration(&s)),
+        max_vmem_mb: None,
+        wallclock_seconds: None,
+        cpu_seconds: None,
+        failed: None,
+        failed_reason: None,
+        exit_status: None,
+    }
+}
+
+/// Parse `qstat -xml -r` output into jobs.
+///
+/// Running jobs are listed under `<queue_info>` and pending jobs under the
+/// nested `<job_info>`; both use `<job_list>` elements.
+pub fn parse_qstat_xml(output: &str) -> Result<Vec<SgeJob>, QstatError> {
+    let doc =
+        roxmltree::Document::parse(output).map_err(|e| QstatError::ParseError(e.to_string()))?;
+
+    Ok(doc
+        .descendants()
+        .filter(|n| n.has_tag_name("job_list"))
+        .map(parse_job_list)
+        .collect())
+}
+
+/// Query active jobs with `qstat -xml -r`.
+///
+/// Jobs are named `snakejob.{rule}.{id}.sh`, not after the run, so there is
+/// no run filter here; the polling service matches jobs to the run through
+/// the main log.
+pub async fn query_qstat() -> Result<Vec<SgeJob>, QstatError> {
+    let user = std::env::var("USER").unwrap_or_default();
+
+    let mut cmd = Command::new("qstat");
+    cmd.args(["-xml", "-r", "-u", &user]);
+
+    let stdout = run_command(&mut cmd, "qstat")
+        .await
+        .map_err(|e| QstatError::ExecutionError(e.to_string()))?;
+
+    parse_qstat_xml(&stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_state() {
+        assert_eq!(parse_state("qw"), SgeJobState::Pending);
+        assert_eq!(parse_state("hqw"), SgeJobState::Held);
+        assert_eq!(parse_state("r"), SgeJobState::Running);
+        assert_eq!(parse_state("Rr"), SgeJobState::Running);
+        assert_eq!(parse_state("Eqw"), SgeJobState::Error);
+        assert_eq!(parse_state("dr"), SgeJobState::Deleting);
+        assert_eq!(parse_state("S"), SgeJobState::Suspended);
+    }
+
+    #[test]
+    fn test_parse_qstat_fixture() {
+        let jobs = parse_qstat_xml(include_str!("../tests/fixtures/qstat.xml")).unwrap();
+        assert_eq!(jobs.len(), 3);
+
+        let running = &jobs[0];
+        assert_eq!(running.job_id, "5001");
+        assert_eq!(running.name, "snakejob.align.3.sh");
+        assert_eq!(running.state, SgeJobState::Running);
+        assert_eq!(running.queue.as_deref(), Some("all.q"));
+        assert_eq!(running.host.as_deref(), Some("node07"));
+        assert_eq!(running.slots, Some(4));
+        assert_eq!(running.mem_limit_mb, Some(8192));
+        assert_eq!(running.time_limit, Some(Duration::from_secs(7200)));
+        assert!(running.start_time.is_some());
+
+        assert_eq!(jobs[1].state, SgeJobState::Pending);
+        assert!(jobs[1].submit_time.is_some());
+        assert!(jobs[1].host.is_none());
+
+        assert_eq!(jobs[2].job_id, "5003.2");
+        assert_eq!(jobs[2].state, SgeJobState::Held);
+    }
+
+    #[test]
+    fn test_parse_empty_qstat() {
+        let xml = "<?xml version='1.0'?><job_info><queue_info/><job_info/></job_info>";
+        assert!(parse_qstat_xml(xml).unwrap().is_empty());
+    }
+}
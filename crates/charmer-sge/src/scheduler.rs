@@ -0,0 +1,205 @@
+//! `Scheduler` trait implementation for SGE / Univa Grid Engine.
+
+use crate::failure::{self, FailureAnalysis, FailureMode};
+use crate::qacct;
+use crate::qstat;
+use crate::types::{SgeJob, SgeJobState};
+use async_trait::async_trait;
+use charmer_scheduler::{
+    ResourceUsage, Scheduler, SchedulerCapabilities, SchedulerError, SchedulerJob,
+    SchedulerJobState,
+};
+use chrono::{DateTime, Utc};
+
+/// SGE backend (qstat -xml / qacct).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SgeScheduler;
+
+#[async_trait]
+impl Scheduler for SgeScheduler {
+    fn name(&self) -> &'static str {
+        "sge"
+    }
+
+    fn capabilities(&self) -> SchedulerCapabilities {
+        SchedulerCapabilities {
+            history: true,
+            resource_usage: true,
+            failure_analysis: true,
+            job_control: false,
+            run_names: false,
+        }
+    }
+
+    async fn is_available(&self) -> bool {
+        // qconf only ships with Grid Engine, unlike qstat which PBS also has
+        tokio::process::Command::new("qconf")
+            .arg("-sq")
+            .output()
+            .await
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    async fn query_active(
+        &self,
+        _run_uuid: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = qstat::query_qstat()
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_history(
+        &self,
+        _run_uuid: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = qacct::query_qacct(since)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_resource_usage(
+        &self,
+        job_id: &str,
+    ) -> Result<Option<ResourceUsage>, SchedulerError> {
+        let job = qacct::query_qacct_job(job_id)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok(job.map(|j| ResourceUsage {
+            max_rss_mb: j.max_vmem_mb,
+            elapsed_seconds: j.wallclock_seconds,
+            cpu_time_seconds: j.cpu_seconds,
+            ..Default::default()
+        }))
+    }
+
+    async fn analyze_failure(
+        &self,
+        job_id: &str,
+    ) -> Result<charmer_scheduler::FailureAnalysis, SchedulerError> {
+        let analysis = failure::analyze_failure(job_id)
+            .await
+            .map_err(|e| SchedulerError::Query(e.to_string()))?;
+        Ok((&analysis).into())
+    }
+}
+
+impl From<SgeJobState> for SchedulerJobState {
+    fn from(state: SgeJobState) -> Self {
+        match state {
+            SgeJobState::Pending | SgeJobState::Held => Self::Pending,
+            SgeJobState::Running | SgeJobState::Deleting => Self::Running,
+            SgeJobState::Suspended => Self::Suspended,
+            SgeJobState::Error => Self::Failed {
+                exit_code: -1,
+                message: "Job is in error state (Eqw)".to_string(),
+            },
+            SgeJobState::Completed => Self::Completed,
+            SgeJobState::Failed { exit_code, error } => Self::Failed {
+                exit_code,
+                message: error,
+            },
+            SgeJobState::Unknown(s) => Self::Unknown(s),
+        }
+    }
+}
+
+impl From<SgeJob> for SchedulerJob {
+    fn from(job: SgeJob) -> Self {
+        Self {
+            job_id: job.job_id,
+            // Rule info lives in the job name; see `parse_snakejob_name`
+            name: job.name,
+            state: job.state.into(),
+            queue: job.queue,
+            submit_time: job.submit_time,
+            start_time: job.start_time,
+            end_time: job.end_time,
+            node: job.host,
+            cpus: job.slots,
+            mem_mb: job.mem_limit_mb,
+            time_limit: job.time_limit,
+            comment: None,
+            array: None,
+            pending_reason: None,
+            priority: None,
+            estimated_start: None,
+        }
+    }
+}
+
+/// Convert SGE failure analysis to unified format.
+impl From<&FailureAnalysis> for charmer_scheduler::FailureAnalysis {
+    fn from(analysis: &FailureAnalysis) -> Self {
+        use charmer_scheduler::FailureMode as Unified;
+
+        let mode = match &analysis.mode {
+            FailureMode::OutOfMemory { .. } => Unified::OutOfMemory,
+            FailureMode::Timeout { .. } => Unified::Timeout,
+            FailureMode::ExitCode { .. } => Unified::ExitCode,
+            FailureMode::Killed => Unified::Cancelled,
+            FailureMode::HostFailure { .. } => Unified::NodeFailure,
+            FailureMode::Unknown { .. } => Unified::Unknown,
+        };
+
+        let (memory_used_mb, memory_limit_mb) = match &analysis.mode {
+            FailureMode::OutOfMemory {
+                used_mb,
+                requested_mb,
+                ..
+            } => (Some(*used_mb), Some(*requested_mb)),
+            _ => (analysis.max_vmem_mb, analysis.req_mem_mb),
+        };
+
+        let (runtime_seconds, time_limit_seconds) = match &analysis.mode {
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                ..
+            } => (Some(*elapsed_seconds), Some(*limit_seconds)),
+            _ => (analysis.wallclock_seconds, analysis.time_limit_seconds),
+        };
+
+        let (suggested_mem_mb, suggested_runtime_seconds) = match &analysis.mode {
+            FailureMode::OutOfMemory { suggested_mb, .. } => (Some(*suggested_mb), None),
+            FailureMode::Timeout {
+                suggested_seconds, ..
+            } => (None, Some(*suggested_seconds)),
+            _ => (None, None),
+        };
+
+        Self {
+            mode,
+            explanation: analysis.explanation.clone(),
+            suggestion: analysis.suggestion.clone(),
+            exit_code: analysis.exit_status,
+            memory_used_mb,
+            memory_limit_mb,
+            runtime_seconds,
+            time_limit_seconds,
+            suggested_mem_mb,
+            suggested_runtime_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_conversion() {
+        assert_eq!(
+            SchedulerJobState::from(SgeJobState::Held),
+            SchedulerJobState::Pending
+        );
+        assert!(matches!(
+            SchedulerJobState::from(SgeJobState::Error),
+            SchedulerJobState::Failed { exit_code: -1, .. }
+        ));
+    }
+}
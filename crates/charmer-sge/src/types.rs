@@ -0,0 +1,84 @@
+//! SGE job types.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// SGE job status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SgeJobState {
+    /// qw - queued and waiting
+    Pending,
+    /// hqw - held
+    Held,
+    /// r, t, Rr - running or being transferred to a host
+    Running,
+    /// s, S, T - suspended
+    Suspended,
+    /// Eqw - error state, will not be scheduled until cleared
+    Error,
+    /// dr - being deleted
+    Deleting,
+    /// Finished with exit status 0 (qacct)
+    Completed,
+    /// Finished unsuccessfully (qacct)
+    Failed {
+        exit_code: i32,
+        error: String,
+    },
+    Unknown(String),
+}
+
+/// SGE job information from qstat or qacct.
+#[derive(Debug, Clone)]
+pub struct SgeJob {
+    /// SGE job number (with ".taskid" for array tasks)
+    pub job_id: String,
+
+    /// Job name (-N; "snakejob.{rule}.{jobid}.sh" for cluster-generic)
+    pub name: String,
+
+    /// Job state
+    pub state: SgeJobState,
+
+    /// Cluster queue (e.g., "all.q")
+    pub queue: Option<String>,
+
+    /// Execution host
+    pub host: Option<String>,
+
+    /// Submit time
+    pub submit_time: Option<DateTime<Utc>>,
+
+    /// Start time
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// End time (qacct only)
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Granted slots
+    pub slots: Option<u32>,
+
+    /// Requested memory in MB (h_vmem or mem_free)
+    pub mem_limit_mb: Option<u64>,
+
+    /// Requested runtime (h_rt)
+    pub time_limit: Option<Duration>,
+
+    /// Peak virtual memory in MB (qacct maxvmem)
+    pub max_vmem_mb: Option<u64>,
+
+    /// Wallclock time in seconds (qacct ru_wallclock)
+    pub wallclock_seconds: Option<u64>,
+
+    /// CPU time in seconds (qacct cpu)
+    pub cpu_seconds: Option<u64>,
+
+    /// qacct `failed` code (0 = no failure)
+    pub failed: Option<i32>,
+
+    /// Text after the `failed` code (e.g., "assumedly after job")
+    pub failed_reason: Option<String>,
+
+    /// Job exit status (qacct only)
+    pub exit_status: Option<i32>,
+}
@@ -0,0 +1,128 @@
+//! Parse SLURM array job IDs ("12345_7", "12345_[1-100%10]").
+
+/// Tasks covered by an array job ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArrayTasks {
+    /// A single task index
+    Task(u32),
+    /// Pending tasks that squeue/sacct still show as one line
+    Pending { spec: String, count: u32 },
+}
+
+/// A parsed array job ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayJobId {
+    /// Parent (array master) job ID
+    pub parent_id: u64,
+    pub tasks: ArrayTasks,
+}
+
+impl ArrayJobId {
+    /// Task index, if this ID names a single task.
+    pub fn task_id(&self) -> Option<u32> {
+        match self.tasks {
+            ArrayTasks::Task(id) => Some(id),
+            ArrayTasks::Pending { .. } => None,
+        }
+    }
+
+    /// Number of pending tasks, if this ID names a pending range.
+    pub fn pending_count(&self) -> Option<u32> {
+        match self.tasks {
+            ArrayTasks::Task(_) => None,
+            ArrayTasks::Pending { count, .. } => Some(count),
+        }
+    }
+}
+
+/// Parse an array job ID. Returns None for plain job IDs.
+pub fn parse_array_job_id(s: &str) -> Option<ArrayJobId> {
+    let (parent, tasks) = s.trim().split_once('_')?;
+    let parent_id = parent.parse().ok()?;
+
+    let tasks = if let Some(spec) = tasks.strip_prefix('[') {
+        let spec = spec.strip_suffix(']')?;
+        ArrayTasks::Pending {
+            spec: spec.to_string(),
+            count: count_array_tasks(spec)?,
+        }
+    } else {
+        ArrayTasks::Task(tasks.parse().ok()?)
+    };
+
+    Some(ArrayJobId { parent_id, tasks })
+}
+
+/// Count the tasks in an array spec such as "1-100%10", "1,3,5-7" or "1-99:2".
+///
+/// Returns None for malformed specs and counts that don't fit in a `u32`.
+pub fn count_array_tasks(spec: &str) -> Option<u32> {
+    // Drop the concurrency limit ("%10")
+    let spec = spec.split('%').next().unwrap_or(spec);
+
+    let mut count = 0u32;
+    for part in spec.split(',').filter(|p| !p.is_empty()) {
+        let (range, step) = match part.split_once(':') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            None => (part, 1),
+        };
+        let tasks = match range.split_once('-') {
+            Some((start, end)) => {
+                let start: u32 = start.parse().ok()?;
+                let end: u32 = end.parse().ok()?;
+                if end < start {
+                    return None;
+                }
+                ((end - start) / step).checked_add(1)?
+            }
+            None => {
+                range.parse::<u32>().ok()?;
+                1
+            }
+        };
+        count = count.checked_add(tasks)?;
+    }
+
+    (count > 0).then_some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_array_job_id() {
+        assert_eq!(
+            parse_array_job_id("12345_7"),
+            Some(ArrayJobId {
+                parent_id: 12345,
+                tasks: ArrayTasks::Task(7)
+            })
+        );
+        assert_eq!(
+            parse_array_job_id("12345_[8-100%10]"),
+            Some(ArrayJobId {
+                parent_id: 12345,
+                tasks: ArrayTasks::Pending {
+                    spec: "8-100%10".to_string(),
+                    count: 93
+                }
+            })
+        );
+        assert_eq!(parse_array_job_id("12345"), None);
+        assert_eq!(parse_array_job_id("12345.batch"), None);
+    }
+
+    #[test]
+    fn test_count_array_tasks() {
+        assert_eq!(count_array_tasks("1-100"), Some(100));
+        assert_eq!(count_array_tasks("1,3,5-7"), Some(5));
+        assert_eq!(count_array_tasks("1-99:2"), Some(50));
+        assert_eq!(count_array_tasks("0-9%2"), Some(10));
+        assert_eq!(count_array_tasks("9-1"), None);
+        assert_eq!(count_array_tasks(""), None);
+        // Counts past u32::MAX
+        assert_eq!(count_array_tasks("0-4294967295"), None);
+        assert_eq!(count_array_tasks("0-4000000000,0-4000000000"), None);
+    }
+}
@@ -0,0 +1,72 @@
+//! Job control through scancel and scontrol.
+
+use charmer_parsers::{CommandError, CommandRunner};
+use charmer_scheduler::JobAction;
+
+/// Command line for an action on the given jobs.
+pub fn control_command(action: JobAction, job_ids: &[String]) -> (&'static str, Vec<String>) {
+    match action {
+        JobAction::Cancel => ("scancel", job_ids.to_vec()),
+        JobAction::Hold => ("scontrol", vec!["hold".to_string(), job_ids.join(",")]),
+        JobAction::Release => ("scontrol", vec!["release".to_string(), job_ids.join(",")]),
+    }
+}
+
+/// Cancel, hold or release jobs. Does nothing when `job_ids` is empty.
+pub async fn control_jobs(
+    runner: &dyn CommandRunner,
+    action: JobAction,
+    job_ids: &[String],
+) -> Result<(), CommandError> {
+    if job_ids.is_empty() {
+        return Ok(());
+    }
+    let (program, args) = control_command(action, job_ids);
+    runner.run(program, &args).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockRunner {
+        calls: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl CommandRunner for MockRunner {
+        async fn run(&self, program: &str, args: &[String]) -> Result<String, CommandError> {
+            let mut argv = vec![program.to_string()];
+            argv.extend_from_slice(args);
+            self.calls.lock().unwrap().push(argv);
+            Ok(String::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_control_jobs() {
+        let runner = MockRunner::default();
+        let ids = vec!["101".to_string(), "102_3".to_string()];
+        control_jobs(&runner, JobAction::Cancel, &ids)
+            .await
+            .unwrap();
+        control_jobs(&runner, JobAction::Hold, &ids).await.unwrap();
+        control_jobs(&runner, JobAction::Release, &ids[..1])
+            .await
+            .unwrap();
+        control_jobs(&runner, JobAction::Cancel, &[]).await.unwrap();
+
+        assert_eq!(
+            *runner.calls.lock().unwrap(),
+            vec![
+                vec!["scancel", "101", "102_3"],
+                vec!["scontrol", "hold", "101,102_3"],
+                vec!["scontrol", "release", "101"],
+            ]
+        );
+    }
+}
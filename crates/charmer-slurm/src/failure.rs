@@ -67,7 +67,106 @@ pub struct FailureAnalysis {
     pub time_limit_seconds: Option<u64>,
 }
 
+/// Accounting details a failure is classified from.
+#[derive(Debug, Clone)]
+pub(crate) struct FailureDetails {
+    pub raw_state: String,
+    pub exit_code: i32,
+    pub signal: Option<i32>,
+    pub node: Option<String>,
+    pub max_rss_mb: Option<u64>,
+    pub req_mem_mb: Option<u64>,
+    pub elapsed_seconds: Option<u64>,
+    pub time_limit_seconds: Option<u64>,
+}
+
 impl FailureAnalysis {
+    /// Classify a failure from the job's accounting details.
+    pub(crate) fn classify(job_id: &str, details: FailureDetails) -> Self {
+        let FailureDetails {
+            raw_state,
+            exit_code,
+            signal,
+            node,
+            max_rss_mb,
+            req_mem_mb,
+            elapsed_seconds,
+            time_limit_seconds,
+        } = details;
+
+        // Determine failure mode
+        let base_state = raw_state.split_whitespace().next().unwrap_or(&raw_state);
+        let mode = match base_state.to_uppercase().as_str() {
+            "OUT_OF_MEMORY" => {
+                let used = max_rss_mb.unwrap_or(0);
+                let requested = req_mem_mb.unwrap_or(0);
+                // Suggest 50% more than used, rounded up to nearest GB
+                let suggested = ((used as f64 * 1.5) / 1024.0).ceil() as u64 * 1024;
+                FailureMode::OutOfMemory {
+                    used_mb: used,
+                    requested_mb: requested,
+                    suggested_mb: suggested.max(requested + 1024),
+                }
+            }
+            "TIMEOUT" => {
+                let elapsed = elapsed_seconds.unwrap_or(0);
+                let limit = time_limit_seconds.unwrap_or(0);
+                // Suggest 50% more time
+                let suggested = (elapsed as f64 * 1.5) as u64;
+                FailureMode::Timeout {
+                    elapsed_seconds: elapsed,
+                    limit_seconds: limit,
+                    suggested_seconds: suggested.max(limit + 3600),
+                }
+            }
+            "CANCELLED" => {
+                // Check if cancelled by someone
+                let by_user = if raw_state.contains("by ") {
+                    raw_state.split("by ").nth(1).map(|s| s.trim().to_string())
+                } else {
+                    None
+                };
+                FailureMode::Cancelled { by_user }
+            }
+            "NODE_FAIL" => FailureMode::NodeFailure { node },
+            "FAILED" | "BOOT_FAIL" | "DEADLINE" => {
+                // Check for common exit codes that indicate OOM
+                if exit_code == 137 || (signal == Some(9) && max_rss_mb.is_some()) {
+                    let used = max_rss_mb.unwrap_or(0);
+                    let requested = req_mem_mb.unwrap_or(0);
+                    let suggested = ((used as f64 * 1.5) / 1024.0).ceil() as u64 * 1024;
+                    FailureMode::OutOfMemory {
+                        used_mb: used,
+                        requested_mb: requested,
+                        suggested_mb: suggested.max(requested + 1024),
+                    }
+                } else {
+                    FailureMode::ExitCode {
+                        code: exit_code,
+                        signal,
+                    }
+                }
+            }
+            other => FailureMode::Unknown {
+                state: other.to_string(),
+            },
+        };
+
+        let (explanation, suggestion) = FailureAnalysis::generate_messages(&mode);
+
+        FailureAnalysis {
+            job_id: job_id.to_string(),
+            mode,
+            explanation,
+            suggestion,
+            raw_state,
+            max_rss_mb,
+            req_mem_mb,
+            elapsed_seconds,
+            time_limit_seconds,
+        }
+    }
+
     /// Generate explanation and suggestion based on failure mode.
     fn generate_messages(mode: &FailureMode) -> (String, String) {
         match mode {
@@ -173,13 +272,13 @@ impl FailureAnalysis {
 
 /// Query detailed failure information for a SLURM job.
 pub async fn analyze_failure(job_id: &str) -> Result<FailureAnalysis, FailureError> {
-    // Query sacct with detailed memory and time info
+    // Query sacct with detailed memory and time info for the job and its
+    // steps (MaxRSS is only recorded on steps)
     // Format: State, ExitCode, MaxRSS, ReqMem, Elapsed, Timelimit, NodeList
     let mut cmd = Command::new("sacct");
     cmd.args([
         "-j",
         job_id,
-        "-X",
         "--parsable2",
         "--noheader",
         "--format",
@@ -190,16 +289,23 @@ pub async fn analyze_failure(job_id: &str) -> Result<FailureAnalysis, FailureErr
         .await
         .map_err(|e| FailureError::ExecutionError(e.to_string()))?;
 
-    let line = stdout
-        .lines()
+    parse_failure_output(job_id, &stdout)
+}
+
+/// Parse sacct output for failure analysis.
+///
+/// The first line is the job allocation; the remaining step lines only
+/// contribute their MaxRSS.
+fn parse_failure_output(job_id: &str, output: &str) -> Result<FailureAnalysis, FailureError> {
+    let mut lines = output.lines().filter(|l| !l.trim().is_empty());
+    let line = lines
         .next()
         .ok_or_else(|| FailureError::NotFound(job_id.to_string()))?;
+    let step_max_rss_mb = lines
+        .filter_map(|l| l.split('|').nth(2))
+        .filter_map(|rss| parse_memory_mb(rss, MemoryFormat::SlurmSacct))
+        .max();
 
-    parse_failure_line(job_id, line)
-}
-
-/// Parse sacct output line for failure analysis.
-fn parse_failure_line(job_id: &str, line: &str) -> Result<FailureAnalysis, FailureError> {
     let fields: Vec<&str> = line.split('|').collect();
     if fields.len() < 7 {
         return Err(FailureError::ParseError(format!(
@@ -209,100 +315,26 @@ fn parse_failure_line(job_id: &str, line: &str) -> Result<FailureAnalysis, Failu
         )));
     }
 
-    let raw_state = fields[0].to_string();
-    let exit_code_str = fields[1];
-    let max_rss_str = fields[2];
-    let req_mem_str = fields[3];
-    let elapsed_str = fields[4];
-    let time_limit_str = fields[5];
+    let (exit_code, signal) = parse_exit_code_signal(fields[1]);
     let node = if fields[6].is_empty() || fields[6] == "None" {
         None
     } else {
         Some(fields[6].to_string())
     };
 
-    // Parse exit code (format: "exit_code:signal")
-    let (exit_code, signal) = parse_exit_code_signal(exit_code_str);
-
-    // Parse memory values
-    let max_rss_mb = parse_memory_mb(max_rss_str, MemoryFormat::SlurmSacct);
-    let req_mem_mb = parse_memory_mb(req_mem_str, MemoryFormat::SlurmSacct);
-
-    // Parse time values
-    let elapsed_seconds = parse_duration_secs(elapsed_str);
-    let time_limit_seconds = parse_duration_secs(time_limit_str);
-
-    // Determine failure mode
-    let base_state = raw_state.split_whitespace().next().unwrap_or(&raw_state);
-    let mode = match base_state.to_uppercase().as_str() {
-        "OUT_OF_MEMORY" => {
-            let used = max_rss_mb.unwrap_or(0);
-            let requested = req_mem_mb.unwrap_or(0);
-            // Suggest 50% more than used, rounded up to nearest GB
-            let suggested = ((used as f64 * 1.5) / 1024.0).ceil() as u64 * 1024;
-            FailureMode::OutOfMemory {
-                used_mb: used,
-                requested_mb: requested,
-                suggested_mb: suggested.max(requested + 1024),
-            }
-        }
-        "TIMEOUT" => {
-            let elapsed = elapsed_seconds.unwrap_or(0);
-            let limit = time_limit_seconds.unwrap_or(0);
-            // Suggest 50% more time
-            let suggested = (elapsed as f64 * 1.5) as u64;
-            FailureMode::Timeout {
-                elapsed_seconds: elapsed,
-                limit_seconds: limit,
-                suggested_seconds: suggested.max(limit + 3600),
-            }
-        }
-        "CANCELLED" => {
-            // Check if cancelled by someone
-            let by_user = if raw_state.contains("by ") {
-                raw_state.split("by ").nth(1).map(|s| s.trim().to_string())
-            } else {
-                None
-            };
-            FailureMode::Cancelled { by_user }
-        }
-        "NODE_FAIL" => FailureMode::NodeFailure { node },
-        "FAILED" | "BOOT_FAIL" | "DEADLINE" => {
-            // Check for common exit codes that indicate OOM
-            if exit_code == 137 || (signal == Some(9) && max_rss_mb.is_some()) {
-                let used = max_rss_mb.unwrap_or(0);
-                let requested = req_mem_mb.unwrap_or(0);
-                let suggested = ((used as f64 * 1.5) / 1024.0).ceil() as u64 * 1024;
-                FailureMode::OutOfMemory {
-                    used_mb: used,
-                    requested_mb: requested,
-                    suggested_mb: suggested.max(requested + 1024),
-                }
-            } else {
-                FailureMode::ExitCode {
-                    code: exit_code,
-                    signal,
-                }
-            }
-        }
-        other => FailureMode::Unknown {
-            state: other.to_string(),
+    Ok(FailureAnalysis::classify(
+        job_id,
+        FailureDetails {
+            raw_state: fields[0].to_string(),
+            exit_code,
+            signal,
+            node,
+            max_rss_mb: parse_memory_mb(fields[2], MemoryFormat::SlurmSacct).max(step_max_rss_mb),
+            req_mem_mb: parse_memory_mb(fields[3], MemoryFormat::SlurmSacct),
+            elapsed_seconds: parse_duration_secs(fields[4]),
+            time_limit_seconds: parse_duration_secs(fields[5]),
         },
-    };
-
-    let (explanation, suggestion) = FailureAnalysis::generate_messages(&mode);
-
-    Ok(FailureAnalysis {
-        job_id: job_id.to_string(),
-        mode,
-        explanation,
-        suggestion,
-        raw_state,
-        max_rss_mb,
-        req_mem_mb,
-        elapsed_seconds,
-        time_limit_seconds,
-    })
+    ))
 }
 
 /// Parse exit code string "code:signal" into (code, signal).
@@ -326,4 +358,17 @@ mod tests {
         assert_eq!(parse_exit_code_signal("1:0"), (1, None));
         assert_eq!(parse_exit_code_signal("137:9"), (137, Some(9)));
     }
+
+    #[test]
+    fn test_parse_failure_output_uses_step_rss() {
+        let output = "OUT_OF_MEMORY|0:125||4Gn|00:10:00|01:00:00|node01\n\
+                      OUT_OF_MEMORY|0:125|4194304K||00:10:00||node01\n\
+                      COMPLETED|0:0|1024K||00:10:00||node01\n";
+        let analysis = parse_failure_output("12345", output).unwrap();
+        assert_eq!(analysis.max_rss_mb, Some(4096));
+        assert!(matches!(
+            analysis.mode,
+            FailureMode::OutOfMemory { used_mb: 4096, .. }
+        ));
+    }
 }
@@ -0,0 +1,798 @@
+//! Parse `squeue --json` and `sacct --json` output (SLURM 21.08+).
+//!
+//! The JSON schema changed between data_parser versions: plain integers
+//! became `{set, infinite, number}` objects and state strings became lists.
+//! The types below accept both shapes.
+
+use crate::array::count_array_tasks;
+use crate::failure::{FailureAnalysis, FailureDetails};
+use crate::sacct::{SlurmResourceUsage, SlurmStepUsage, state_from_str};
+use crate::squeue::pending_reason;
+use crate::types::{SlurmJob, SlurmJobState};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+/// First SLURM release with `--json` output.
+const MIN_JSON_VERSION: (u32, u32) = (21, 8);
+
+/// Integer that is either plain or wrapped as `{set, infinite, number}`.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Plain(i64),
+    Wrapped {
+        #[serde(default)]
+        set: bool,
+        #[serde(default)]
+        infinite: bool,
+        #[serde(default)]
+        number: i64,
+    },
+    #[default]
+    Missing,
+}
+
+impl Number {
+    fn get(self) -> Option<i64> {
+        match self {
+            Number::Plain(n) => Some(n),
+            Number::Wrapped {
+                set: true,
+                infinite: false,
+                number,
+            } => Some(number),
+            _ => None,
+        }
+    }
+
+    /// Positive values only (SLURM uses 0 for "not set" in many fields).
+    fn positive(self) -> Option<u64> {
+        self.get().filter(|n| *n > 0).map(|n| n as u64)
+    }
+
+    fn timestamp(self) -> Option<DateTime<Utc>> {
+        self.get()
+            .filter(|n| *n > 0)
+            .and_then(|n| DateTime::from_timestamp(n, 0))
+    }
+}
+
+/// State that is either a string or a list of flags (base state first).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(untagged)]
+enum StateList {
+    One(String),
+    Many(Vec<String>),
+    #[default]
+    Missing,
+}
+
+impl StateList {
+    fn base(&self) -> &str {
+        match self {
+            StateList::One(s) => s,
+            StateList::Many(v) => v.first().map(String::as_str).unwrap_or(""),
+            StateList::Missing => "",
+        }
+    }
+}
+
+/// Exit code as a plain integer or `{status, return_code, signal}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(untagged)]
+enum ExitCode {
+    Plain(i64),
+    Detailed {
+        #[serde(default)]
+        return_code: Number,
+        #[serde(default)]
+        signal: Option<Signal>,
+    },
+    #[default]
+    Missing,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct Signal {
+    #[serde(default)]
+    id: Number,
+}
+
+impl ExitCode {
+    /// Return code, or 128 + signal when killed by a signal.
+    fn code(&self) -> Option<i32> {
+        match self {
+            ExitCode::Plain(n) => Some(*n as i32),
+            ExitCode::Detailed {
+                return_code,
+                signal,
+            } => {
+                let signal = signal.as_ref().and_then(|s| s.id.positive());
+                match (return_code.get(), signal) {
+                    (Some(0) | None, Some(sig)) => Some(128 + sig as i32),
+                    (code, _) => code.map(|c| c as i32),
+                }
+            }
+            ExitCode::Missing => None,
+        }
+    }
+
+    /// Return code and terminating signal, as reported by `sacct`.
+    fn parts(&self) -> (i32, Option<i32>) {
+        match self {
+            ExitCode::Plain(n) => (*n as i32, None),
+            ExitCode::Detailed {
+                return_code,
+                signal,
+            } => (
+                return_code.get().unwrap_or(0) as i32,
+                signal
+                    .as_ref()
+                    .and_then(|s| s.id.positive())
+                    .map(|s| s as i32),
+            ),
+            ExitCode::Missing => (0, None),
+        }
+    }
+}
+
+/// One trackable resource (TRES) entry, e.g. `{"type": "mem", "count": ...}`.
+#[derive(Debug, Clone, Deserialize)]
+struct Tres {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    count: Option<i64>,
+}
+
+fn tres_count(tres: &[Tres], kind: &str) -> Option<u64> {
+    tres.iter()
+        .find(|t| t.kind == kind)
+        .and_then(|t| t.count)
+        .filter(|c| *c >= 0)
+        .map(|c| c as u64)
+}
+
+#[derive(Debug, Deserialize)]
+struct SqueueResponse {
+    #[serde(default)]
+    jobs: Vec<SqueueJob>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SqueueJob {
+    job_id: u64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    user_name: String,
+    #[serde(default)]
+    job_state: StateList,
+    #[serde(default)]
+    partition: String,
+    #[serde(default)]
+    submit_time: Number,
+    #[serde(default)]
+    start_time: Number,
+    #[serde(default)]
+    end_time: Number,
+    #[serde(default)]
+    nodes: String,
+    #[serde(default)]
+    cpus: Number,
+    #[serde(default)]
+    memory_per_node: Number,
+    #[serde(default)]
+    memory_per_cpu: Number,
+    /// Minutes
+    #[serde(default)]
+    time_limit: Number,
+    #[serde(default)]
+    comment: String,
+    #[serde(default)]
+    exit_code: ExitCode,
+    #[serde(default)]
+    derived_exit_code: ExitCode,
+    #[serde(default)]
+    array_job_id: Number,
+    #[serde(default)]
+    array_task_id: Number,
+    /// Pending task range (e.g., "1-100%10")
+    #[serde(default)]
+    array_task_string: Option<String>,
+    #[serde(default)]
+    het_job_id: Number,
+    #[serde(default)]
+    het_job_offset: Number,
+    #[serde(default)]
+    state_reason: String,
+    #[serde(default)]
+    priority: Number,
+}
+
+#[derive(Debug, Deserialize)]
+struct SacctResponse {
+    #[serde(default)]
+    jobs: Vec<SacctJob>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SacctJob {
+    job_id: u64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    state: SacctState,
+    #[serde(default)]
+    partition: String,
+    #[serde(default)]
+    time: SacctTime,
+    #[serde(default)]
+    nodes: String,
+    #[serde(default)]
+    required: SacctRequired,
+    #[serde(default)]
+    tres: SacctTres,
+    #[serde(default)]
+    comment: SacctComment,
+    #[serde(default)]
+    exit_code: ExitCode,
+    #[serde(default)]
+    derived_exit_code: ExitCode,
+    #[serde(default)]
+    array: SacctArray,
+    #[serde(default)]
+    het: SacctHet,
+    #[serde(default)]
+    steps: Vec<SacctStep>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SacctState {
+    #[serde(default)]
+    current: StateList,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SacctTime {
+    #[serde(default)]
+    submission: Number,
+    #[serde(default)]
+    start: Number,
+    #[serde(default)]
+    end: Number,
+    #[serde(default)]
+    elapsed: Number,
+    /// Minutes
+    #[serde(default)]
+    limit: Number,
+    #[serde(default)]
+    total: CpuTime,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CpuTime {
+    #[serde(default)]
+    seconds: Number,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SacctRequired {
+    #[serde(default, rename = "CPUs")]
+    cpus: Number,
+    #[serde(default)]
+    memory_per_node: Number,
+    #[serde(default)]
+    memory_per_cpu: Number,
+    /// Older data_parser versions (MB)
+    #[serde(default)]
+    memory: Number,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SacctTres {
+    #[serde(default)]
+    allocated: Vec<Tres>,
+}
+
+/// `comment` is a string in old versions and `{job, ...}` in newer ones.
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum SacctComment {
+    Plain(String),
+    Split {
+        #[serde(default)]
+        job: Option<String>,
+    },
+    #[default]
+    Missing,
+}
+
+impl SacctComment {
+    fn job(&self) -> Option<&str> {
+        match self {
+            SacctComment::Plain(s) => Some(s),
+            SacctComment::Split { job } => job.as_deref(),
+            SacctComment::Missing => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SacctArray {
+    #[serde(default)]
+    job_id: Number,
+    #[serde(default)]
+    task_id: Number,
+    /// Pending task range (e.g., "1-100%10")
+    #[serde(default)]
+    task: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SacctHet {
+    #[serde(default)]
+    job_id: Number,
+    #[serde(default)]
+    job_offset: Number,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SacctStep {
+    #[serde(default)]
+    step: StepInfo,
+    #[serde(default)]
+    time: StepTime,
+    #[serde(default)]
+    tres: StepTres,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StepInfo {
+    #[serde(default)]
+    id: StepId,
+    #[serde(default)]
+    name: String,
+}
+
+/// Step ID as "12345.batch", or `{job_id, step_id}` in older versions.
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum StepId {
+    Plain(String),
+    Split {
+        #[serde(default)]
+        job_id: Number,
+        #[serde(default)]
+        step_id: serde_json::Value,
+    },
+    #[default]
+    Missing,
+}
+
+impl StepId {
+    fn format(&self) -> String {
+        match self {
+            StepId::Plain(s) => s.clone(),
+            StepId::Split { job_id, step_id } => {
+                let step = match step_id {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                match job_id.get() {
+                    Some(job) => format!("{}.{}", job, step),
+                    None => step,
+                }
+            }
+            StepId::Missing => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StepTime {
+    #[serde(default)]
+    elapsed: Number,
+    #[serde(default)]
+    total: CpuTime,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StepTres {
+    #[serde(default)]
+    requested: StepTresUsage,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StepTresUsage {
+    #[serde(default)]
+    max: Vec<Tres>,
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// Memory request in MB from per-node or per-CPU values.
+fn memory_mb(per_node: Number, per_cpu: Number, cpus: Option<u32>) -> Option<u64> {
+    per_node
+        .positive()
+        .or_else(|| per_cpu.positive().map(|m| m * u64::from(cpus.unwrap_or(1))))
+}
+
+/// Number of pending tasks for an array line without a task index.
+fn pending_task_count(task_id: Number, spec: Option<&str>) -> Option<u32> {
+    if task_id.get().is_some() {
+        return None;
+    }
+    spec.and_then(count_array_tasks)
+}
+
+fn job_state(state: &str, exit_code: Option<i32>) -> SlurmJobState {
+    state_from_str(state, exit_code.unwrap_or(0))
+}
+
+impl From<SqueueJob> for SlurmJob {
+    fn from(job: SqueueJob) -> Self {
+        let cpus = job.cpus.positive().map(|c| c as u32);
+        let state = job_state(job.job_state.base(), job.exit_code.code());
+        // For pending jobs start_time is the scheduler's estimate
+        let (start_time, estimated_start) = if state == SlurmJobState::Pending {
+            (None, job.start_time.timestamp())
+        } else {
+            (job.start_time.timestamp(), None)
+        };
+        Self {
+            job_id: job.job_id.to_string(),
+            name: job.name,
+            state,
+            partition: non_empty(&job.partition),
+            submit_time: job.submit_time.timestamp(),
+            start_time,
+            end_time: job.end_time.timestamp(),
+            nodelist: non_empty(&job.nodes),
+            cpus,
+            mem_mb: memory_mb(job.memory_per_node, job.memory_per_cpu, cpus),
+            time_limit: job
+                .time_limit
+                .positive()
+                .map(|m| Duration::from_secs(m * 60)),
+            comment: non_empty(&job.comment),
+            array_job_id: job.array_job_id.positive(),
+            array_task_id: job.array_task_id.get().map(|t| t as u32),
+            array_task_count: pending_task_count(
+                job.array_task_id,
+                job.array_task_string.as_deref(),
+            ),
+            het_job_id: job.het_job_id.positive(),
+            het_job_offset: job.het_job_offset.get().map(|o| o as u32),
+            derived_exit_code: job.derived_exit_code.code(),
+            reason: pending_reason(&job.state_reason),
+            priority: job.priority.positive(),
+            estimated_start,
+        }
+    }
+}
+
+impl From<SacctJob> for SlurmJob {
+    fn from(job: SacctJob) -> Self {
+        let cpus = tres_count(&job.tres.allocated, "cpu")
+            .or(job.required.cpus.positive())
+            .map(|c| c as u32);
+        let mem_mb = tres_count(&job.tres.allocated, "mem").or_else(|| {
+            job.required.memory.positive().or_else(|| {
+                memory_mb(
+                    job.required.memory_per_node,
+                    job.required.memory_per_cpu,
+                    cpus,
+                )
+            })
+        });
+        Self {
+            job_id: job.job_id.to_string(),
+            name: job.name,
+            state: job_state(job.state.current.base(), job.exit_code.code()),
+            partition: non_empty(&job.partition),
+            submit_time: job.time.submission.timestamp(),
+            start_time: job.time.start.timestamp(),
+            end_time: job.time.end.timestamp(),
+            nodelist: non_empty(&job.nodes).filter(|n| n != "None assigned"),
+            cpus,
+            mem_mb,
+            time_limit: job
+                .time
+                .limit
+                .positive()
+                .map(|m| Duration::from_secs(m * 60)),
+            comment: job.comment.job().and_then(non_empty),
+            array_job_id: job.array.job_id.positive(),
+            array_task_id: job.array.task_id.get().map(|t| t as u32),
+            array_task_count: pending_task_count(job.array.task_id, job.array.task.as_deref()),
+            het_job_id: job.het.job_id.positive(),
+            het_job_offset: job.het.job_offset.get().map(|o| o as u32),
+            derived_exit_code: job.derived_exit_code.code(),
+            reason: None,
+            priority: None,
+            estimated_start: None,
+        }
+    }
+}
+
+/// Parse `squeue --json` output.
+pub fn parse_squeue_json(output: &str) -> Result<Vec<SlurmJob>, serde_json::Error> {
+    parse_squeue_json_filtered(output, "", None)
+}
+
+/// Parse `squeue --json` output, keeping only `user`'s jobs (all if empty)
+/// and, if set, jobs named `run_uuid`. Older SLURM ignores filter options
+/// when `--json` is given, so they are applied here.
+pub fn parse_squeue_json_filtered(
+    output: &str,
+    user: &str,
+    run_uuid: Option<&str>,
+) -> Result<Vec<SlurmJob>, serde_json::Error> {
+    let response: SqueueResponse = serde_json::from_str(output)?;
+    Ok(response
+        .jobs
+        .into_iter()
+        .filter(|j| user.is_empty() || j.user_name.is_empty() || j.user_name == user)
+        .filter(|j| run_uuid.is_none_or(|uuid| j.name == uuid))
+        .map(SlurmJob::from)
+        .collect())
+}
+
+/// Parse `sacct --json` output.
+pub fn parse_sacct_json(output: &str) -> Result<Vec<SlurmJob>, serde_json::Error> {
+    let response: SacctResponse = serde_json::from_str(output)?;
+    Ok(response.jobs.into_iter().map(SlurmJob::from).collect())
+}
+
+/// Resource usage from `sacct --json -j <id>`, aggregated over steps.
+pub fn parse_sacct_usage_json(
+    output: &str,
+) -> Result<Option<SlurmResourceUsage>, serde_json::Error> {
+    let response: SacctResponse = serde_json::from_str(output)?;
+    Ok(response.jobs.into_iter().next().map(|job| {
+        let steps = job
+            .steps
+            .iter()
+            .map(|s| SlurmStepUsage {
+                step_id: s.step.id.format(),
+                name: s.step.name.clone(),
+                max_rss_mb: tres_count(&s.tres.requested.max, "mem").map(|b| b / (1024 * 1024)),
+                elapsed_seconds: s.time.elapsed.get().map(|e| e.max(0) as u64),
+                cpu_time_seconds: s.time.total.seconds.get().map(|t| t.max(0) as u64),
+            })
+            .collect();
+        SlurmResourceUsage::from_steps(
+            job.job_id.to_string(),
+            job.time.elapsed.get().map(|e| e.max(0) as u64),
+            None,
+            job.time.total.seconds.get().map(|s| s.max(0) as u64),
+            steps,
+        )
+    }))
+}
+
+/// Failure analysis from `sacct --json -j <id>`, using the same accounting
+/// fields as the text query.
+pub fn parse_sacct_failure_json(
+    output: &str,
+) -> Result<Option<FailureAnalysis>, serde_json::Error> {
+    let response: SacctResponse = serde_json::from_str(output)?;
+    Ok(response.jobs.into_iter().next().map(|job| {
+        let (exit_code, signal) = job.exit_code.parts();
+        let cpus = tres_count(&job.tres.allocated, "cpu")
+            .or(job.required.cpus.positive())
+            .map(|c| c as u32);
+        let req_mem_mb = job.required.memory.positive().or_else(|| {
+            memory_mb(
+                job.required.memory_per_node,
+                job.required.memory_per_cpu,
+                cpus,
+            )
+        });
+        let max_rss_mb = job
+            .steps
+            .iter()
+            .filter_map(|s| tres_count(&s.tres.requested.max, "mem"))
+            .max()
+            .map(|b| b / (1024 * 1024));
+        let details = FailureDetails {
+            raw_state: job.state.current.base().to_string(),
+            exit_code,
+            signal,
+            node: non_empty(&job.nodes).filter(|n| n != "None assigned"),
+            max_rss_mb,
+            req_mem_mb,
+            elapsed_seconds: job.time.elapsed.get().map(|e| e.max(0) as u64),
+            time_limit_seconds: job.time.limit.positive().map(|m| m * 60),
+        };
+        FailureAnalysis::classify(&job.job_id.to_string(), details)
+    }))
+}
+
+/// Parse the version from `squeue --version` (e.g., "slurm 23.02.6").
+fn parse_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.split_whitespace().nth(1)?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Whether the installed SLURM supports `--json` (checked once).
+pub async fn json_supported() -> bool {
+    static SUPPORTED: OnceCell<bool> = OnceCell::const_new();
+    *SUPPORTED
+        .get_or_init(|| async {
+            Command::new("squeue")
+                .arg("--version")
+                .output()
+                .await
+                .ok()
+                .and_then(|o| parse_version(&String::from_utf8_lossy(&o.stdout)))
+                .is_some_and(|v| v >= MIN_JSON_VERSION)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::failure::FailureMode;
+
+    #[test]
+    fn test_parse_squeue_json() {
+        let jobs = parse_squeue_json(include_str!("../tests/fixtures/squeue.json")).unwrap();
+        assert_eq!(jobs.len(), 3);
+
+        let running = &jobs[0];
+        assert_eq!(running.job_id, "12345");
+        assert_eq!(running.state, SlurmJobState::Running);
+        assert_eq!(running.partition.as_deref(), Some("short"));
+        assert_eq!(running.cpus, Some(4));
+        assert_eq!(running.mem_mb, Some(8192));
+        assert_eq!(running.time_limit, Some(Duration::from_secs(3600)));
+        assert_eq!(running.nodelist.as_deref(), Some("node01"));
+        // A `|` in the comment no longer breaks parsing
+        assert_eq!(
+            running.comment.as_deref(),
+            Some("rule_align_wildcards_sample=S1|lane=2")
+        );
+        assert!(running.start_time.is_some());
+        assert!(running.end_time.is_some());
+
+        // Array task from the older (plain integer) schema
+        let task = &jobs[1];
+        assert_eq!(task.state, SlurmJobState::Pending);
+        assert_eq!(task.array_job_id, Some(12340));
+        assert_eq!(task.array_task_id, Some(7));
+        assert_eq!(task.mem_mb, Some(2048));
+        assert!(task.start_time.is_none());
+        assert!(task.estimated_start.is_some());
+        assert_eq!(task.reason.as_deref(), Some("Priority"));
+        assert_eq!(task.priority, Some(4294));
+
+        assert_eq!(task.array_task_count, None);
+
+        // Het-job component
+        let het = &jobs[2];
+        assert_eq!(het.het_job_id, Some(12350));
+        assert_eq!(het.het_job_offset, Some(1));
+    }
+
+    #[test]
+    fn test_parse_squeue_json_pending_array() {
+        let output = r#"{"jobs": [{
+            "job_id": 12400,
+            "name": "run-uuid",
+            "job_state": ["PENDING"],
+            "array_job_id": {"set": true, "infinite": false, "number": 12400},
+            "array_task_id": {"set": false, "infinite": false, "number": 0},
+            "array_task_string": "3-100%10"
+        }]}"#;
+        let jobs = parse_squeue_json(output).unwrap();
+        assert_eq!(jobs[0].array_job_id, Some(12400));
+        assert_eq!(jobs[0].array_task_id, None);
+        assert_eq!(jobs[0].array_task_count, Some(98));
+    }
+
+    #[test]
+    fn test_parse_squeue_json_filtered() {
+        let output = include_str!("../tests/fixtures/squeue.json");
+        let jobs = parse_squeue_json_filtered(output, "alice", None).unwrap();
+        assert_eq!(jobs.len(), 2);
+        let jobs = parse_squeue_json_filtered(output, "alice", Some("run-uuid")).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].job_id, "12345");
+    }
+
+    #[test]
+    fn test_parse_sacct_json() {
+        let jobs = parse_sacct_json(include_str!("../tests/fixtures/sacct.json")).unwrap();
+        assert_eq!(jobs.len(), 2);
+
+        let done = &jobs[0];
+        assert_eq!(done.job_id, "12345");
+        assert!(matches!(
+            done.state,
+            SlurmJobState::Completed { exit_code: 0, .. }
+        ));
+        assert_eq!(done.cpus, Some(4));
+        assert_eq!(done.mem_mb, Some(8192));
+        assert_eq!(
+            done.comment.as_deref(),
+            Some("rule_align_wildcards_sample=S1")
+        );
+        assert_eq!(done.derived_exit_code, Some(0));
+
+        let failed = &jobs[1];
+        assert!(matches!(
+            failed.state,
+            SlurmJobState::Failed { exit_code: 137, .. }
+        ));
+        assert_eq!(failed.derived_exit_code, Some(2));
+        assert!(failed.nodelist.is_none());
+    }
+
+    #[test]
+    fn test_parse_sacct_usage_json() {
+        let usage = parse_sacct_usage_json(include_str!("../tests/fixtures/sacct.json"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage.job_id, "12345");
+        assert_eq!(usage.max_rss_mb, Some(3072));
+        assert_eq!(usage.elapsed_seconds, Some(300));
+        assert_eq!(usage.cpu_time_seconds, Some(1100));
+        assert_eq!(usage.steps.len(), 2);
+        assert_eq!(usage.steps[0].step_id, "12345.batch");
+        assert_eq!(usage.steps[1].name, "bwa");
+        assert_eq!(usage.steps[1].max_rss_mb, Some(1024));
+        assert_eq!(usage.steps[1].cpu_time_seconds, Some(1000));
+    }
+
+    #[test]
+    fn test_parse_sacct_failure_json() {
+        let output = r#"{"jobs": [{
+            "job_id": 12347,
+            "state": {"current": ["OUT_OF_MEMORY"]},
+            "nodes": "node02",
+            "time": {"elapsed": 600, "limit": {"set": true, "infinite": false, "number": 60}},
+            "required": {"memory_per_node": {"set": true, "infinite": false, "number": 4096}},
+            "exit_code": {"return_code": {"set": true, "infinite": false, "number": 0},
+                          "signal": {"id": {"set": true, "infinite": false, "number": 9}}},
+            "steps": [{"tres": {"requested": {"max": [
+                {"type": "mem", "count": 4294967296}
+            ]}}}]
+        }]}"#;
+        let analysis = parse_sacct_failure_json(output).unwrap().unwrap();
+        assert_eq!(analysis.job_id, "12347");
+        assert_eq!(analysis.max_rss_mb, Some(4096));
+        assert_eq!(analysis.req_mem_mb, Some(4096));
+        assert_eq!(analysis.time_limit_seconds, Some(3600));
+        assert!(matches!(
+            analysis.mode,
+            FailureMode::OutOfMemory {
+                used_mb: 4096,
+                requested_mb: 4096,
+                ..
+            }
+        ));
+
+        let failed = parse_sacct_failure_json(r#"{"jobs": []}"#).unwrap();
+        assert!(failed.is_none());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("slurm 23.02.6\n"), Some((23, 2)));
+        assert_eq!(parse_version("slurm 20.11.9"), Some((20, 11)));
+        assert!(parse_version("slurm 20.11.9").unwrap() < MIN_JSON_VERSION);
+        assert_eq!(parse_version("garbage"), None);
+    }
+}
@@ -1,13 +1,26 @@
 //! SLURM integration for charmer.
 //!
-//! Query job status via squeue and sacct.
+//! Query job status via squeue and sacct (JSON output on SLURM 21.08+),
+//! or through slurmrestd.
 
+pub mod array;
+pub mod control;
 pub mod failure;
+pub mod json;
+pub mod restd;
 pub mod sacct;
+pub mod scheduler;
 pub mod squeue;
 pub mod types;
 
+pub use array::{ArrayJobId, ArrayTasks, count_array_tasks, parse_array_job_id};
+pub use control::{control_command, control_jobs};
 pub use failure::{FailureAnalysis, FailureError, FailureMode, analyze_failure};
-pub use sacct::{SacctError, SlurmResourceUsage, query_resource_usage, query_sacct};
+pub use json::{parse_sacct_json, parse_squeue_json};
+pub use restd::{RestClient, RestEndpoint, RestError};
+pub use sacct::{
+    SacctError, SlurmResourceUsage, SlurmStepUsage, query_resource_usage, query_sacct,
+};
+pub use scheduler::SlurmScheduler;
 pub use squeue::{SqueueError, query_squeue};
 pub use types::{SlurmJob, SlurmJobState};
@@ -0,0 +1,440 @@
+//! Query jobs through slurmrestd instead of running squeue/sacct.
+//!
+//! slurmrestd serves the same JSON as `squeue --json` and `sacct --json`,
+//! so responses go through the parsers in `crate::json`. Only plain HTTP
+//! is supported, over TCP or a Unix socket.
+
+use crate::failure::FailureAnalysis;
+use crate::json::{
+    parse_sacct_failure_json, parse_sacct_json, parse_sacct_usage_json, parse_squeue_json_filtered,
+};
+use crate::sacct::SlurmResourceUsage;
+use crate::types::SlurmJob;
+use chrono::{DateTime, Duration, Utc};
+use http_body_util::{BodyExt, Empty};
+use hyper::Request;
+use hyper::body::Bytes;
+use hyper_util::rt::TokioIo;
+use std::path::PathBuf;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+/// Default OpenAPI version of the slurmrestd endpoints.
+pub const DEFAULT_API_VERSION: &str = "v0.0.40";
+
+#[derive(Error, Debug)]
+pub enum RestError {
+    #[error("Invalid slurmrestd URL: {0}")]
+    InvalidUrl(String),
+    #[error("Failed to connect to slurmrestd: {0}")]
+    Connect(String),
+    #[error("slurmrestd request failed: {0}")]
+    Request(String),
+    #[error("slurmrestd returned HTTP {status}: {body}")]
+    Status { status: u16, body: String },
+    #[error("Failed to parse slurmrestd response: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Where slurmrestd listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestEndpoint {
+    Tcp { host: String, port: u16 },
+    Unix(PathBuf),
+}
+
+impl RestEndpoint {
+    /// Parse `http://host[:port]`, `http://[addr][:port]` or
+    /// `unix:/path/to/socket`.
+    pub fn parse(url: &str) -> Result<Self, RestError> {
+        if let Some(path) = url.strip_prefix("unix:") {
+            // Accept unix:/path and unix:///path
+            let path = path.trim_start_matches("//");
+            if path.is_empty() {
+                return Err(RestError::InvalidUrl(url.to_string()));
+            }
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| RestError::InvalidUrl(url.to_string()))?;
+        let authority = rest.split('/').next().unwrap_or(rest);
+        // IPv6 literals are bracketed: [::1]:6820
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(bracketed) => {
+                let (host, rest) = bracketed
+                    .split_once(']')
+                    .ok_or_else(|| RestError::InvalidUrl(url.to_string()))?;
+                match rest {
+                    "" => (host, None),
+                    _ => (
+                        host,
+                        Some(
+                            rest.strip_prefix(':')
+                                .ok_or_else(|| RestError::InvalidUrl(url.to_string()))?,
+                        ),
+                    ),
+                }
+            }
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| RestError::InvalidUrl(url.to_string()))?,
+            None => 6820,
+        };
+        if host.is_empty() {
+            return Err(RestError::InvalidUrl(url.to_string()));
+        }
+        Ok(Self::Tcp {
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+/// slurmrestd client using JWT authentication.
+#[derive(Debug, Clone)]
+pub struct RestClient {
+    endpoint: RestEndpoint,
+    api_version: String,
+    user: String,
+    token: Option<String>,
+}
+
+impl RestClient {
+    pub fn new(endpoint: RestEndpoint, user: impl Into<String>) -> Self {
+        Self {
+            endpoint,
+            api_version: DEFAULT_API_VERSION.to_string(),
+            user: user.into(),
+            token: None,
+        }
+    }
+
+    /// Create a client for `url`, taking the token from `SLURM_JWT` and the
+    /// user from `SLURM_USER_NAME` or `USER`.
+    pub fn from_url(url: &str) -> Result<Self, RestError> {
+        let user = std::env::var("SLURM_USER_NAME")
+            .or_else(|_| std::env::var("USER"))
+            .unwrap_or_default();
+        let mut client = Self::new(RestEndpoint::parse(url)?, user);
+        client.token = std::env::var("SLURM_JWT").ok().filter(|t| !t.is_empty());
+        Ok(client)
+    }
+
+    /// Set the JWT sent in `X-SLURM-USER-TOKEN`.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Use a different OpenAPI version (e.g., "v0.0.39").
+    pub fn with_api_version(mut self, version: impl Into<String>) -> Self {
+        self.api_version = version.into();
+        self
+    }
+
+    /// Check that slurmrestd answers and accepts our credentials.
+    pub async fn ping(&self) -> bool {
+        let path = format!("/slurm/{}/ping", self.api_version);
+        match self.get(&path).await {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::debug!("slurmrestd ping failed: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Query active jobs (same as `squeue --json`).
+    pub async fn query_jobs(&self, run_uuid: Option<&str>) -> Result<Vec<SlurmJob>, RestError> {
+        let body = self
+            .get(&format!("/slurm/{}/jobs", self.api_version))
+            .await?;
+        Ok(parse_squeue_json_filtered(&body, &self.user, run_uuid)?)
+    }
+
+    /// Query job history from slurmdbd (same as `sacct --json`).
+    pub async fn query_history(
+        &self,
+        run_uuid: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SlurmJob>, RestError> {
+        let since = since.unwrap_or_else(|| Utc::now() - Duration::hours(24));
+        let mut path = format!(
+            "/slurmdb/{}/jobs?start_time={}",
+            self.api_version,
+            since.timestamp()
+        );
+        if !self.user.is_empty() {
+            path.push_str(&format!("&users={}", encode(&self.user)));
+        }
+        if let Some(uuid) = run_uuid {
+            path.push_str(&format!("&job_name={}", encode(uuid)));
+        }
+        let body = self.get(&path).await?;
+        Ok(parse_sacct_json(&body)?)
+    }
+
+    /// Query resource usage for a finished job.
+    pub async fn query_resource_usage(
+        &self,
+        job_id: &str,
+    ) -> Result<Option<SlurmResourceUsage>, RestError> {
+        let body = self
+            .get(&format!(
+                "/slurmdb/{}/job/{}",
+                self.api_version,
+                encode(job_id)
+            ))
+            .await?;
+        Ok(parse_sacct_usage_json(&body)?)
+    }
+
+    /// Analyze why a finished job failed.
+    pub async fn query_failure(&self, job_id: &str) -> Result<Option<FailureAnalysis>, RestError> {
+        let body = self
+            .get(&format!(
+                "/slurmdb/{}/job/{}",
+                self.api_version,
+                encode(job_id)
+            ))
+            .await?;
+        Ok(parse_sacct_failure_json(&body)?)
+    }
+
+    /// GET a path and return the response body.
+    async fn get(&self, path: &str) -> Result<String, RestError> {
+        match &self.endpoint {
+            RestEndpoint::Tcp { host, port } => {
+                let stream = TcpStream::connect((host.as_str(), *port))
+                    .await
+                    .map_err(|e| RestError::Connect(format!("{}:{}: {}", host, port, e)))?;
+                if host.contains(':') {
+                    self.send(stream, &format!("[{}]", host), path).await
+                } else {
+                    self.send(stream, host, path).await
+                }
+            }
+            RestEndpoint::Unix(socket) => {
+                let stream = tokio::net::UnixStream::connect(socket)
+                    .await
+                    .map_err(|e| RestError::Connect(format!("{}: {}", socket.display(), e)))?;
+                self.send(stream, "localhost", path).await
+            }
+        }
+    }
+
+    async fn send<S>(&self, stream: S, host: &str, path: &str) -> Result<String, RestError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| RestError::Connect(e.to_string()))?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::debug!("slurmrestd connection closed: {}", e);
+            }
+        });
+
+        let mut request = Request::get(path)
+            .header(hyper::header::HOST, host)
+            .header(hyper::header::ACCEPT, "application/json");
+        if !self.user.is_empty() {
+            request = request.header("X-SLURM-USER-NAME", &self.user);
+        }
+        if let Some(token) = &self.token {
+            request = request.header("X-SLURM-USER-TOKEN", token);
+        }
+        let request = request
+            .body(Empty::<Bytes>::new())
+            .map_err(|e| RestError::Request(e.to_string()))?;
+
+        let response = sender
+            .send_request(request)
+            .await
+            .map_err(|e| RestError::Request(e.to_string()))?;
+        let status = response.status();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| RestError::Request(e.to_string()))?
+            .to_bytes();
+        let body = String::from_utf8_lossy(&body).into_owned();
+
+        if !status.is_success() {
+            return Err(RestError::Status {
+                status: status.as_u16(),
+                body,
+            });
+        }
+        Ok(body)
+    }
+}
+
+/// Percent-encode a query or path value.
+fn encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Reply to one request with a recorded response, saving the request head.
+    async fn respond<S>(mut stream: S, body: &str, requests: &Mutex<Vec<String>>)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut buf = vec![0u8; 4096];
+        let n = stream.read(&mut buf).await.unwrap_or(0);
+        requests
+            .lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(&buf[..n]).into_owned());
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            RestEndpoint::parse("http://slurm-ctl:6820").unwrap(),
+            RestEndpoint::Tcp {
+                host: "slurm-ctl".to_string(),
+                port: 6820
+            }
+        );
+        assert_eq!(
+            RestEndpoint::parse("http://slurm-ctl/").unwrap(),
+            RestEndpoint::Tcp {
+                host: "slurm-ctl".to_string(),
+                port: 6820
+            }
+        );
+        assert_eq!(
+            RestEndpoint::parse("unix:///run/slurmrestd.sock").unwrap(),
+            RestEndpoint::Unix(PathBuf::from("/run/slurmrestd.sock"))
+        );
+        assert_eq!(
+            RestEndpoint::parse("http://[::1]:6821").unwrap(),
+            RestEndpoint::Tcp {
+                host: "::1".to_string(),
+                port: 6821
+            }
+        );
+        assert_eq!(
+            RestEndpoint::parse("http://[fe80::1]/").unwrap(),
+            RestEndpoint::Tcp {
+                host: "fe80::1".to_string(),
+                port: 6820
+            }
+        );
+        assert!(RestEndpoint::parse("http://[::1").is_err());
+        assert!(RestEndpoint::parse("http://[::1]6820").is_err());
+        assert!(RestEndpoint::parse("https://slurm-ctl:6820").is_err());
+        assert!(RestEndpoint::parse("http://slurm-ctl:abc").is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("run-uuid_1.2"), "run-uuid_1.2");
+        assert_eq!(encode("a b&c"), "a%20b%26c");
+    }
+
+    #[tokio::test]
+    async fn test_query_jobs_over_tcp() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                respond(
+                    stream,
+                    include_str!("../tests/fixtures/squeue.json"),
+                    &recorded,
+                )
+                .await;
+            }
+        });
+
+        let client = RestClient::new(
+            RestEndpoint::Tcp {
+                host: "127.0.0.1".to_string(),
+                port,
+            },
+            "alice",
+        )
+        .with_token("secret-jwt");
+        let jobs = client.query_jobs(Some("run-uuid")).await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].job_id, "12345");
+
+        let request = requests.lock().unwrap()[0].to_lowercase();
+        assert!(request.starts_with("get /slurm/v0.0.40/jobs "));
+        assert!(request.contains("x-slurm-user-name: alice"));
+        assert!(request.contains("x-slurm-user-token: secret-jwt"));
+    }
+
+    #[tokio::test]
+    async fn test_query_history_over_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("slurmrestd.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                respond(
+                    stream,
+                    include_str!("../tests/fixtures/sacct.json"),
+                    &recorded,
+                )
+                .await;
+            }
+        });
+
+        let client = RestClient::new(RestEndpoint::Unix(socket), "alice");
+        let jobs = client.query_history(Some("run-uuid"), None).await.unwrap();
+        assert_eq!(jobs.len(), 2);
+
+        let usage = client.query_resource_usage("12345").await.unwrap().unwrap();
+        assert_eq!(usage.max_rss_mb, Some(3072));
+
+        let analysis = client.query_failure("12345").await.unwrap().unwrap();
+        assert_eq!(analysis.job_id, "12345");
+        assert_eq!(analysis.max_rss_mb, Some(3072));
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /slurmdb/v0.0.40/jobs?start_time="));
+        assert!(requests[0].contains("&users=alice&job_name=run-uuid"));
+        assert!(requests[1].starts_with("GET /slurmdb/v0.0.40/job/12345 "));
+        assert!(requests[2].starts_with("GET /slurmdb/v0.0.40/job/12345 "));
+    }
+}
@@ -1,5 +1,7 @@
 //! Query SLURM job history via sacct.
 
+use crate::array::parse_array_job_id;
+use crate::json::{json_supported, parse_sacct_json, parse_sacct_usage_json};
 use crate::types::{SlurmJob, SlurmJobState};
 use charmer_parsers::{
     MemoryFormat, non_empty_string, parse_duration, parse_duration_secs, parse_exit_code,
@@ -19,13 +21,18 @@ pub enum SacctError {
 }
 
 /// sacct output format (--parsable2 uses | delimiter)
-/// JobIDRaw, JobName, State, Partition, Submit, Start, End, NodeList, AllocCPUS, ReqMem, Timelimit, Comment, ExitCode
-const SACCT_FORMAT: &str = "JobIDRaw,JobName,State,Partition,Submit,Start,End,NodeList,AllocCPUS,ReqMem,Timelimit,Comment,ExitCode";
+/// JobIDRaw, JobName, State, Partition, Submit, Start, End, NodeList, AllocCPUS, ReqMem, Timelimit, ExitCode, JobID, Comment
+///
+/// Comment is free text and may contain `|`, so it comes last.
+const SACCT_FORMAT: &str = "JobIDRaw,JobName,State,Partition,Submit,Start,End,NodeList,AllocCPUS,ReqMem,Timelimit,ExitCode,JobID,Comment";
 
 /// Parse sacct state string with exit code info.
 fn parse_state(state_str: &str, exit_code_str: &str) -> SlurmJobState {
-    let exit_code = parse_exit_code(exit_code_str);
+    state_from_str(state_str, parse_exit_code(exit_code_str))
+}
 
+/// Map a SLURM state name to a job state, given the job's exit code.
+pub(crate) fn state_from_str(state_str: &str, exit_code: i32) -> SlurmJobState {
     // sacct states can have suffixes like "CANCELLED by 12345"
     let base_state = state_str.split_whitespace().next().unwrap_or(state_str);
 
@@ -53,9 +60,10 @@ fn parse_state(state_str: &str, exit_code_str: &str) -> SlurmJobState {
 
 /// Parse a single line of sacct output.
 fn parse_sacct_line(line: &str) -> Result<SlurmJob, SacctError> {
-    let fields = split_delimited(line, 13).map_err(SacctError::ParseError)?;
+    let fields = split_delimited(line, 14).map_err(SacctError::ParseError)?;
 
-    let state = parse_state(fields[2], fields[12]);
+    let state = parse_state(fields[2], fields[11]);
+    let array = parse_array_job_id(fields[12]);
 
     Ok(SlurmJob {
         job_id: fields[0].to_string(),
@@ -69,52 +77,134 @@ fn parse_sacct_line(line: &str) -> Result<SlurmJob, SacctError> {
         cpus: fields[8].parse().ok(),
         mem_mb: parse_memory_mb(fields[9], MemoryFormat::SlurmSacct),
         time_limit: parse_duration(fields[10]),
-        comment: non_empty_string(fields[11]),
+        comment: non_empty_string(&fields[13..].join("|")),
+        array_job_id: array.as_ref().map(|a| a.parent_id),
+        array_task_id: array.as_ref().and_then(|a| a.task_id()),
+        array_task_count: array.as_ref().and_then(|a| a.pending_count()),
+        het_job_id: None,
+        het_job_offset: None,
+        derived_exit_code: None,
+        reason: None,
+        priority: None,
+        estimated_start: None,
     })
 }
 
-/// Resource usage data from sacct.
+/// Resource usage data from sacct, aggregated over the job's steps.
 #[derive(Debug, Clone)]
 pub struct SlurmResourceUsage {
     pub job_id: String,
+    /// Largest MaxRSS of any step
+    pub max_rss_mb: Option<u64>,
+    pub elapsed_seconds: Option<u64>,
+    /// TotalCPU summed over steps
+    pub cpu_time_seconds: Option<u64>,
+    /// Per-step usage (batch, extern, and numbered srun steps)
+    pub steps: Vec<SlurmStepUsage>,
+}
+
+/// Resource usage of one job step from sacct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlurmStepUsage {
+    /// Step ID (e.g., "12345.batch", "12345.0")
+    pub step_id: String,
+    pub name: String,
     pub max_rss_mb: Option<u64>,
     pub elapsed_seconds: Option<u64>,
     pub cpu_time_seconds: Option<u64>,
 }
 
+impl SlurmResourceUsage {
+    /// Aggregate step usage: the largest step RSS and the summed step CPU
+    /// time, falling back to the allocation's values when no step has them.
+    pub(crate) fn from_steps(
+        job_id: String,
+        elapsed_seconds: Option<u64>,
+        alloc_max_rss_mb: Option<u64>,
+        alloc_cpu_time_seconds: Option<u64>,
+        steps: Vec<SlurmStepUsage>,
+    ) -> Self {
+        let max_rss_mb = steps
+            .iter()
+            .filter_map(|s| s.max_rss_mb)
+            .max()
+            .or(alloc_max_rss_mb);
+        let step_cpu: Vec<u64> = steps.iter().filter_map(|s| s.cpu_time_seconds).collect();
+        let cpu_time_seconds = if step_cpu.is_empty() {
+            alloc_cpu_time_seconds
+        } else {
+            Some(step_cpu.iter().sum())
+        };
+        Self {
+            job_id,
+            max_rss_mb,
+            elapsed_seconds,
+            cpu_time_seconds,
+            steps,
+        }
+    }
+}
+
 /// Query resource usage for a specific job.
 pub async fn query_resource_usage(job_id: &str) -> Result<Option<SlurmResourceUsage>, SacctError> {
+    if json_supported().await {
+        let mut cmd = Command::new("sacct");
+        cmd.args(["-j", job_id, "--json"]);
+        let stdout = run_command(&mut cmd, "sacct")
+            .await
+            .map_err(|e| SacctError::ExecutionError(e.to_string()))?;
+        match parse_sacct_usage_json(&stdout) {
+            Ok(usage) => return Ok(usage),
+            Err(e) => tracing::warn!("Failed to parse sacct JSON, falling back: {}", e),
+        }
+    }
+
+    // No -X: MaxRSS is only recorded on the steps
     let mut cmd = Command::new("sacct");
     cmd.args([
         "-j",
         job_id,
-        "-X",
         "--parsable2",
         "--noheader",
         "--format",
-        "JobIDRaw,MaxRSS,Elapsed,TotalCPU",
+        "JobIDRaw,JobName,MaxRSS,Elapsed,TotalCPU",
     ]);
 
     let stdout = run_command(&mut cmd, "sacct")
         .await
         .map_err(|e| SacctError::ExecutionError(e.to_string()))?;
 
-    let line = match stdout.lines().next() {
-        Some(l) if !l.trim().is_empty() => l,
-        _ => return Ok(None),
-    };
+    Ok(parse_usage_output(&stdout))
+}
+
+/// Parse `JobIDRaw,JobName,MaxRSS,Elapsed,TotalCPU` lines for a job and its
+/// steps. The allocation line comes first.
+fn parse_usage_output(output: &str) -> Option<SlurmResourceUsage> {
+    let mut lines = output
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.split('|').collect::<Vec<_>>())
+        .filter(|fields| fields.len() >= 5);
 
-    let fields: Vec<&str> = line.split('|').collect();
-    if fields.len() < 4 {
-        return Ok(None);
-    }
+    let alloc = lines.next()?;
+    let steps = lines
+        .filter(|fields| fields[0].contains('.'))
+        .map(|fields| SlurmStepUsage {
+            step_id: fields[0].to_string(),
+            name: fields[1].to_string(),
+            max_rss_mb: parse_memory_mb(fields[2], MemoryFormat::SlurmSacct),
+            elapsed_seconds: parse_elapsed_time(fields[3]),
+            cpu_time_seconds: parse_elapsed_time(fields[4]),
+        })
+        .collect();
 
-    Ok(Some(SlurmResourceUsage {
-        job_id: fields[0].to_string(),
-        max_rss_mb: parse_memory_mb(fields[1], MemoryFormat::SlurmSacct),
-        elapsed_seconds: parse_elapsed_time(fields[2]),
-        cpu_time_seconds: parse_elapsed_time(fields[3]),
-    }))
+    Some(SlurmResourceUsage::from_steps(
+        alloc[0].to_string(),
+        parse_elapsed_time(alloc[3]),
+        parse_memory_mb(alloc[2], MemoryFormat::SlurmSacct),
+        parse_elapsed_time(alloc[4]),
+        steps,
+    ))
 }
 
 /// Parse elapsed time string, stripping any milliseconds before parsing.
@@ -127,13 +217,9 @@ fn parse_elapsed_time(s: &str) -> Option<u64> {
     parse_duration_secs(clean)
 }
 
-/// Query job history with sacct.
-pub async fn query_sacct(
-    run_uuid: Option<&str>,
-    since: Option<DateTime<Utc>>,
-) -> Result<Vec<SlurmJob>, SacctError> {
+/// Build a sacct command with the time and job name filters applied.
+fn sacct_command(run_uuid: Option<&str>, since: Option<DateTime<Utc>>) -> Command {
     let mut cmd = Command::new("sacct");
-    cmd.args(["-X", "--parsable2", "--noheader", "--format", SACCT_FORMAT]);
 
     // Add time filter
     if let Some(since_time) = since {
@@ -149,6 +235,31 @@ pub async fn query_sacct(
         cmd.args(["--name", uuid]);
     }
 
+    cmd
+}
+
+/// Query job history with sacct.
+///
+/// Uses `--json` when supported, falling back to delimited output.
+pub async fn query_sacct(
+    run_uuid: Option<&str>,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<SlurmJob>, SacctError> {
+    if json_supported().await {
+        let mut cmd = sacct_command(run_uuid, since);
+        cmd.arg("--json");
+        let stdout = run_command(&mut cmd, "sacct")
+            .await
+            .map_err(|e| SacctError::ExecutionError(e.to_string()))?;
+        match parse_sacct_json(&stdout) {
+            Ok(jobs) => return Ok(jobs),
+            Err(e) => tracing::warn!("Failed to parse sacct JSON, falling back: {}", e),
+        }
+    }
+
+    let mut cmd = sacct_command(run_uuid, since);
+    cmd.args(["-X", "--parsable2", "--noheader", "--format", SACCT_FORMAT]);
+
     let stdout = run_command(&mut cmd, "sacct")
         .await
         .map_err(|e| SacctError::ExecutionError(e.to_string()))?;
@@ -197,10 +308,45 @@ mod tests {
 
     #[test]
     fn test_parse_sacct_line() {
-        let line = "12345|test_job|COMPLETED|short|2024-01-15T10:00:00|2024-01-15T10:05:00|2024-01-15T10:10:00|node01|4|4Gn|1:00:00|rule_align_wildcards_sample=S1|0:0";
+        let line = "12345|test_job|COMPLETED|short|2024-01-15T10:00:00|2024-01-15T10:05:00|2024-01-15T10:10:00|node01|4|4Gn|1:00:00|0:0|12345|rule_align|a|b";
         let job = parse_sacct_line(line).unwrap();
         assert_eq!(job.job_id, "12345");
         assert_eq!(job.name, "test_job");
         assert!(matches!(job.state, SlurmJobState::Completed { .. }));
+        // A `|` in the comment doesn't shift other fields
+        assert_eq!(job.comment.as_deref(), Some("rule_align|a|b"));
+        assert_eq!(job.array_job_id, None);
+    }
+
+    #[test]
+    fn test_parse_sacct_array_task() {
+        let line = "12350|run|COMPLETED|short|2024-01-15T10:00:00|2024-01-15T10:05:00|2024-01-15T10:10:00|node01|1|1Gn|1:00:00|0:0|12345_3|rule_align";
+        let job = parse_sacct_line(line).unwrap();
+        assert_eq!(job.job_id, "12350");
+        assert_eq!(job.array_job_id, Some(12345));
+        assert_eq!(job.array_task_id, Some(3));
+    }
+
+    #[test]
+    fn test_parse_usage_output() {
+        let output = "12345|align||00:10:00|00:35:00\n\
+                      12345.batch|batch|2048K|00:10:00|00:00:05\n\
+                      12345.extern|extern|1024K|00:10:00|00:00:00\n\
+                      12345.0|bwa|6291456K|00:09:00|00:30:00\n\
+                      12345.1|samtools|1048576K|00:01:00|00:04:55\n";
+        let usage = parse_usage_output(output).unwrap();
+        assert_eq!(usage.job_id, "12345");
+        assert_eq!(usage.max_rss_mb, Some(6144));
+        assert_eq!(usage.elapsed_seconds, Some(600));
+        assert_eq!(usage.cpu_time_seconds, Some(35 * 60));
+        assert_eq!(usage.steps.len(), 4);
+        assert_eq!(usage.steps[2].name, "bwa");
+        assert_eq!(usage.steps[2].max_rss_mb, Some(6144));
+
+        // Allocation line only (e.g., job never started a step)
+        let usage = parse_usage_output("12346|sort||00:00:01|00:00:00\n").unwrap();
+        assert!(usage.steps.is_empty());
+        assert_eq!(usage.max_rss_mb, None);
+        assert_eq!(usage.cpu_time_seconds, Some(0));
     }
 }
@@ -0,0 +1,287 @@
+//! `Scheduler` trait implementation for SLURM.
+
+use crate::control;
+use crate::failure::{self, FailureAnalysis, FailureMode};
+use crate::restd::RestClient;
+use crate::sacct::{self, SlurmResourceUsage, SlurmStepUsage};
+use crate::squeue;
+use crate::types::{SlurmJob, SlurmJobState};
+use async_trait::async_trait;
+use charmer_parsers::SystemRunner;
+use charmer_scheduler::{
+    ArrayTask, JobAction, ResourceUsage, Scheduler, SchedulerCapabilities, SchedulerError,
+    SchedulerJob, SchedulerJobState, StepUsage,
+};
+use chrono::{DateTime, Utc};
+
+/// SLURM backend (squeue/sacct, or slurmrestd when configured).
+#[derive(Debug, Clone, Default)]
+pub struct SlurmScheduler {
+    rest: Option<RestClient>,
+}
+
+impl SlurmScheduler {
+    /// Query through slurmrestd instead of running squeue/sacct.
+    pub fn with_rest(client: RestClient) -> Self {
+        Self { rest: Some(client) }
+    }
+}
+
+#[async_trait]
+impl Scheduler for SlurmScheduler {
+    fn name(&self) -> &'static str {
+        "slurm"
+    }
+
+    fn capabilities(&self) -> SchedulerCapabilities {
+        SchedulerCapabilities {
+            history: true,
+            resource_usage: true,
+            failure_analysis: true,
+            job_control: true,
+            run_names: true,
+        }
+    }
+
+    async fn is_available(&self) -> bool {
+        if let Some(rest) = &self.rest {
+            return rest.ping().await;
+        }
+        tokio::process::Command::new("squeue")
+            .arg("--version")
+            .output()
+            .await
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    async fn query_active(
+        &self,
+        run_uuid: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = match &self.rest {
+            Some(rest) => rest.query_jobs(run_uuid).await.map_err(query_error)?,
+            None => squeue::query_squeue(run_uuid).await.map_err(query_error)?,
+        };
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_history(
+        &self,
+        run_uuid: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SchedulerJob>, SchedulerError> {
+        let jobs = match &self.rest {
+            Some(rest) => rest
+                .query_history(run_uuid, since)
+                .await
+                .map_err(query_error)?,
+            None => sacct::query_sacct(run_uuid, since)
+                .await
+                .map_err(query_error)?,
+        };
+        Ok(jobs.into_iter().map(SchedulerJob::from).collect())
+    }
+
+    async fn query_resource_usage(
+        &self,
+        job_id: &str,
+    ) -> Result<Option<ResourceUsage>, SchedulerError> {
+        let usage = match &self.rest {
+            Some(rest) => rest
+                .query_resource_usage(job_id)
+                .await
+                .map_err(query_error)?,
+            None => sacct::query_resource_usage(job_id)
+                .await
+                .map_err(query_error)?,
+        };
+        Ok(usage.map(ResourceUsage::from))
+    }
+
+    async fn analyze_failure(
+        &self,
+        job_id: &str,
+    ) -> Result<charmer_scheduler::FailureAnalysis, SchedulerError> {
+        let analysis = match &self.rest {
+            Some(rest) => rest
+                .query_failure(job_id)
+                .await
+                .map_err(query_error)?
+                .ok_or_else(|| query_error(failure::FailureError::NotFound(job_id.to_string())))?,
+            None => failure::analyze_failure(job_id)
+                .await
+                .map_err(query_error)?,
+        };
+        Ok((&analysis).into())
+    }
+
+    async fn control(&self, action: JobAction, job_ids: &[String]) -> Result<(), SchedulerError> {
+        control::control_jobs(&SystemRunner, action, job_ids)
+            .await
+            .map_err(|e| SchedulerError::Control(e.to_string()))
+    }
+}
+
+fn query_error(e: impl std::fmt::Display) -> SchedulerError {
+    SchedulerError::Query(e.to_string())
+}
+
+impl From<SlurmJobState> for SchedulerJobState {
+    fn from(state: SlurmJobState) -> Self {
+        match state {
+            SlurmJobState::Pending => Self::Pending,
+            SlurmJobState::Running => Self::Running,
+            SlurmJobState::Completed { .. } => Self::Completed,
+            SlurmJobState::Failed { exit_code, error } => Self::Failed {
+                exit_code,
+                message: error,
+            },
+            SlurmJobState::Cancelled => Self::Cancelled,
+            SlurmJobState::Timeout => Self::Failed {
+                exit_code: -1,
+                message: "Job exceeded time limit".to_string(),
+            },
+            SlurmJobState::OutOfMemory => Self::Failed {
+                exit_code: -1,
+                message: "Job exceeded memory limit".to_string(),
+            },
+            SlurmJobState::Unknown(s) => Self::Unknown(s),
+        }
+    }
+}
+
+impl From<SlurmJob> for SchedulerJob {
+    fn from(job: SlurmJob) -> Self {
+        Self {
+            job_id: job.job_id,
+            name: job.name,
+            state: job.state.into(),
+            queue: job.partition,
+            submit_time: job.submit_time,
+            start_time: job.start_time,
+            end_time: job.end_time,
+            node: job.nodelist,
+            cpus: job.cpus,
+            mem_mb: job.mem_mb,
+            time_limit: job.time_limit,
+            comment: job.comment,
+            array: job.array_job_id.map(|parent| ArrayTask {
+                parent_id: parent.to_string(),
+                task_id: job.array_task_id,
+                task_count: match job.array_task_id {
+                    Some(_) => 1,
+                    None => job.array_task_count.unwrap_or(1),
+                },
+            }),
+            pending_reason: job.reason,
+            priority: job.priority,
+            estimated_start: job.estimated_start,
+        }
+    }
+}
+
+impl From<SlurmResourceUsage> for ResourceUsage {
+    fn from(usage: SlurmResourceUsage) -> Self {
+        Self {
+            max_rss_mb: usage.max_rss_mb,
+            elapsed_seconds: usage.elapsed_seconds,
+            cpu_time_seconds: usage.cpu_time_seconds,
+            steps: usage.steps.into_iter().map(StepUsage::from).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<SlurmStepUsage> for StepUsage {
+    fn from(step: SlurmStepUsage) -> Self {
+        Self {
+            step_id: step.step_id,
+            name: step.name,
+            max_rss_mb: step.max_rss_mb,
+            elapsed_seconds: step.elapsed_seconds,
+            cpu_time_seconds: step.cpu_time_seconds,
+        }
+    }
+}
+
+/// Convert SLURM failure analysis to unified format.
+impl From<&FailureAnalysis> for charmer_scheduler::FailureAnalysis {
+    fn from(analysis: &FailureAnalysis) -> Self {
+        use charmer_scheduler::FailureMode as Unified;
+
+        let mode = match &analysis.mode {
+            FailureMode::OutOfMemory { .. } => Unified::OutOfMemory,
+            FailureMode::Timeout { .. } => Unified::Timeout,
+            FailureMode::ExitCode { .. } => Unified::ExitCode,
+            FailureMode::Cancelled { .. } => Unified::Cancelled,
+            FailureMode::NodeFailure { .. } => Unified::NodeFailure,
+            FailureMode::Unknown { .. } => Unified::Unknown,
+        };
+
+        let exit_code = match &analysis.mode {
+            FailureMode::ExitCode { code, .. } => Some(*code),
+            _ => None,
+        };
+
+        let (memory_used_mb, memory_limit_mb) = match &analysis.mode {
+            FailureMode::OutOfMemory {
+                used_mb,
+                requested_mb,
+                ..
+            } => (Some(*used_mb), Some(*requested_mb)),
+            _ => (analysis.max_rss_mb, analysis.req_mem_mb),
+        };
+
+        let (runtime_seconds, time_limit_seconds) = match &analysis.mode {
+            FailureMode::Timeout {
+                elapsed_seconds,
+                limit_seconds,
+                ..
+            } => (Some(*elapsed_seconds), Some(*limit_seconds)),
+            _ => (analysis.elapsed_seconds, analysis.time_limit_seconds),
+        };
+
+        let (suggested_mem_mb, suggested_runtime_seconds) = match &analysis.mode {
+            FailureMode::OutOfMemory { suggested_mb, .. } => (Some(*suggested_mb), None),
+            FailureMode::Timeout {
+                suggested_seconds, ..
+            } => (None, Some(*suggested_seconds)),
+            _ => (None, None),
+        };
+
+        Self {
+            mode,
+            explanation: analysis.explanation.clone(),
+            suggestion: analysis.suggestion.clone(),
+            exit_code,
+            memory_used_mb,
+            memory_limit_mb,
+            runtime_seconds,
+            time_limit_seconds,
+            suggested_mem_mb,
+            suggested_runtime_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_conversion() {
+        assert_eq!(
+            SchedulerJobState::from(SlurmJobState::Pending),
+            SchedulerJobState::Pending
+        );
+        assert!(matches!(
+            SchedulerJobState::from(SlurmJobState::OutOfMemory),
+            SchedulerJobState::Failed { exit_code: -1, .. }
+        ));
+        assert!(matches!(
+            SchedulerJobState::from(SlurmJobState::Timeout),
+            SchedulerJobState::Failed { exit_code: -1, .. }
+        ));
+    }
+}
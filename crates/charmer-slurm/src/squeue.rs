@@ -1,10 +1,14 @@
 //! Query active SLURM jobs via squeue.
 
+use crate::array::parse_array_job_id;
+use crate::json::{json_supported, parse_squeue_json_filtered};
 use crate::types::{SlurmJob, SlurmJobState};
 use charmer_parsers::{
     MemoryFormat, non_empty_string, parse_duration, parse_memory_mb, parse_slurm_timestamp,
     run_command, split_delimited,
 };
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::time::Duration;
 use thiserror::Error;
 use tokio::process::Command;
@@ -29,8 +33,22 @@ pub enum SqueueError {
 /// %C - CPUs
 /// %m - Memory
 /// %l - Time limit
-/// %k - Comment
-const SQUEUE_FORMAT: &str = "%A|%j|%T|%P|%V|%S|%e|%N|%C|%m|%l|%k";
+/// %i - Job ID with array index (e.g., "12345_7", "12345_[8-100]")
+/// %r - Reason a job is pending
+/// %Q - Priority
+/// %k - Comment (free text that may contain `|`, so it comes last)
+const SQUEUE_FORMAT: &str = "%A|%j|%T|%P|%V|%S|%e|%N|%C|%m|%l|%i|%r|%Q|%k";
+
+/// `squeue --start` output format: job ID and estimated start time.
+const SQUEUE_START_FORMAT: &str = "%A|%S";
+
+/// Pending reason, or None for the placeholder SLURM reports on running jobs.
+pub(crate) fn pending_reason(s: &str) -> Option<String> {
+    match s.trim() {
+        "" | "None" | "(null)" => None,
+        reason => Some(reason.to_string()),
+    }
+}
 
 /// Parse SLURM state string.
 fn parse_state(s: &str) -> SlurmJobState {
@@ -54,28 +72,96 @@ fn parse_state(s: &str) -> SlurmJobState {
 
 /// Parse a single line of squeue output.
 fn parse_squeue_line(line: &str) -> Result<SlurmJob, SqueueError> {
-    let fields = split_delimited(line, 12).map_err(SqueueError::ParseError)?;
+    let fields = split_delimited(line, 15).map_err(SqueueError::ParseError)?;
+    let array = parse_array_job_id(fields[11]);
+    let state = parse_state(fields[2]);
+
+    // For pending jobs %S is the scheduler's estimate, not an actual start
+    let start = parse_slurm_timestamp(fields[5]);
+    let (start_time, estimated_start) = if state == SlurmJobState::Pending {
+        (None, start)
+    } else {
+        (start, None)
+    };
 
     Ok(SlurmJob {
         job_id: fields[0].to_string(),
         name: fields[1].to_string(),
-        state: parse_state(fields[2]),
+        state,
         partition: non_empty_string(fields[3]),
         submit_time: parse_slurm_timestamp(fields[4]),
-        start_time: parse_slurm_timestamp(fields[5]),
+        start_time,
         end_time: parse_slurm_timestamp(fields[6]),
         nodelist: non_empty_string(fields[7]),
         cpus: fields[8].parse().ok(),
         mem_mb: parse_memory_mb(fields[9], MemoryFormat::Slurm),
         time_limit: parse_duration(fields[10]),
-        comment: non_empty_string(fields[11]),
+        comment: non_empty_string(&fields[14..].join("|")),
+        array_job_id: array.as_ref().map(|a| a.parent_id),
+        array_task_id: array.as_ref().and_then(|a| a.task_id()),
+        array_task_count: array.as_ref().and_then(|a| a.pending_count()),
+        het_job_id: None,
+        het_job_offset: None,
+        derived_exit_code: None,
+        reason: pending_reason(fields[12]),
+        priority: fields[13].trim().parse().ok(),
+        estimated_start,
     })
 }
 
+/// Parse `squeue --start` output into estimated start times by job ID.
+fn parse_start_output(output: &str) -> HashMap<String, DateTime<Utc>> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('|'))
+        .filter_map(|(id, start)| Some((id.trim().to_string(), parse_slurm_timestamp(start)?)))
+        .collect()
+}
+
+/// Fill in estimated start times for pending jobs from `squeue --start`.
+async fn fill_estimated_starts(jobs: &mut [SlurmJob], user: &str) {
+    if !jobs.iter().any(|j| j.state == SlurmJobState::Pending) {
+        return;
+    }
+
+    let mut cmd = Command::new("squeue");
+    cmd.args(["-u", user, "-h", "--start", "-o", SQUEUE_START_FORMAT]);
+    let starts = match run_command(&mut cmd, "squeue").await {
+        Ok(stdout) => parse_start_output(&stdout),
+        Err(e) => {
+            tracing::warn!("Failed to query estimated start times: {}", e);
+            return;
+        }
+    };
+
+    for job in jobs {
+        if let Some(start) = starts.get(&job.job_id) {
+            job.estimated_start = Some(*start);
+        }
+    }
+}
+
 /// Query active jobs with squeue.
+///
+/// Uses `--json` when supported, falling back to delimited output.
 pub async fn query_squeue(run_uuid: Option<&str>) -> Result<Vec<SlurmJob>, SqueueError> {
     let user = std::env::var("USER").unwrap_or_default();
 
+    if json_supported().await {
+        let mut cmd = Command::new("squeue");
+        cmd.args(["-u", &user, "--json"]);
+        let stdout = run_command(&mut cmd, "squeue")
+            .await
+            .map_err(|e| SqueueError::ExecutionError(e.to_string()))?;
+        match parse_squeue_json_filtered(&stdout, &user, run_uuid) {
+            Ok(mut jobs) => {
+                fill_estimated_starts(&mut jobs, &user).await;
+                return Ok(jobs);
+            }
+            Err(e) => tracing::warn!("Failed to parse squeue JSON, falling back: {}", e),
+        }
+    }
+
     let mut cmd = Command::new("squeue");
     cmd.args(["-u", &user, "-h", "-o", SQUEUE_FORMAT]);
 
@@ -100,6 +186,7 @@ pub async fn query_squeue(run_uuid: Option<&str>) -> Result<Vec<SlurmJob>, Squeu
         }
     }
 
+    fill_estimated_starts(&mut jobs, &user).await;
     Ok(jobs)
 }
 
@@ -144,7 +231,7 @@ mod tests {
 
     #[test]
     fn test_parse_squeue_line() {
-        let line = "12345|test_job|RUNNING|short|2024-01-15T10:00:00|2024-01-15T10:05:00|N/A|node01|4|4G|1:00:00|rule_align_wildcards_sample=S1";
+        let line = "12345|test_job|RUNNING|short|2024-01-15T10:00:00|2024-01-15T10:05:00|N/A|node01|4|4G|1:00:00|12345|None|4294|rule_align_wildcards_sample=S1";
         let job = parse_squeue_line(line).unwrap();
         assert_eq!(job.job_id, "12345");
         assert_eq!(job.name, "test_job");
@@ -154,5 +241,49 @@ mod tests {
             job.comment,
             Some("rule_align_wildcards_sample=S1".to_string())
         );
+        assert_eq!(job.array_job_id, None);
+    }
+
+    #[test]
+    fn test_parse_squeue_array_lines() {
+        let task = "12346|run|RUNNING|short|2024-01-15T10:00:00|2024-01-15T10:05:00|N/A|node01|1|1G|1:00:00|12345_7|None|4294|rule_align";
+        let job = parse_squeue_line(task).unwrap();
+        assert_eq!(job.array_job_id, Some(12345));
+        assert_eq!(job.array_task_id, Some(7));
+        assert_eq!(job.array_task_count, None);
+
+        let pending = "12345|run|PENDING|short|2024-01-15T10:00:00|N/A|N/A||1|1G|1:00:00|12345_[8-100%10]|JobArrayTaskLimit|4294|rule_align";
+        let job = parse_squeue_line(pending).unwrap();
+        assert_eq!(job.array_job_id, Some(12345));
+        assert_eq!(job.array_task_id, None);
+        assert_eq!(job.array_task_count, Some(93));
+    }
+
+    #[test]
+    fn test_parse_squeue_pending_reason() {
+        let line = "12347|run|PENDING|short|2024-01-15T10:00:00|2024-01-15T12:30:00|N/A||1|1G|1:00:00|12347|QOSMaxJobsPerUser|4294|rule_align|a|b";
+        let job = parse_squeue_line(line).unwrap();
+        assert_eq!(job.reason.as_deref(), Some("QOSMaxJobsPerUser"));
+        assert_eq!(job.priority, Some(4294));
+        // A `|` in the comment doesn't shift the reason or priority
+        assert_eq!(job.comment.as_deref(), Some("rule_align|a|b"));
+        assert_eq!(job.start_time, None);
+        assert_eq!(
+            job.estimated_start,
+            parse_slurm_timestamp("2024-01-15T12:30:00")
+        );
+
+        let line = "12348|run|RUNNING|short|2024-01-15T10:00:00|2024-01-15T10:05:00|N/A|node01|1|1G|1:00:00|12348|None|4294|rule_align";
+        let job = parse_squeue_line(line).unwrap();
+        assert_eq!(job.reason, None);
+        assert!(job.start_time.is_some());
+    }
+
+    #[test]
+    fn test_parse_start_output() {
+        let output = "12347|2024-01-15T12:30:00\n12349|N/A\n";
+        let starts = parse_start_output(output);
+        assert_eq!(starts.len(), 1);
+        assert!(starts.contains_key("12347"));
     }
 }
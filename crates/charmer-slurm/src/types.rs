@@ -54,4 +54,31 @@ pub struct SlurmJob {
 
     /// Comment field (contains rule info for snakemake)
     pub comment: Option<String>,
+
+    /// Parent job ID of an array task
+    pub array_job_id: Option<u64>,
+
+    /// Array task index
+    pub array_task_id: Option<u32>,
+
+    /// Number of tasks on a pending array line ("12345_[1-100]")
+    pub array_task_count: Option<u32>,
+
+    /// Leader job ID of a heterogeneous job (JSON output only)
+    pub het_job_id: Option<u64>,
+
+    /// Component offset within a heterogeneous job (JSON output only)
+    pub het_job_offset: Option<u32>,
+
+    /// Highest exit code of all job steps (JSON output only)
+    pub derived_exit_code: Option<i32>,
+
+    /// Why a pending job hasn't started (e.g., "Priority", "Resources")
+    pub reason: Option<String>,
+
+    /// Scheduling priority
+    pub priority: Option<u64>,
+
+    /// Estimated start time of a pending job
+    pub estimated_start: Option<DateTime<Utc>>,
 }
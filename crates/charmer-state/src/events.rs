@@ -0,0 +1,190 @@
+//! Typed record of what changed in the pipeline state.
+//!
+//! Merge functions push a [`StateEvent`] for every job they add, remove or
+//! move to another status. Consumers remember the last `seq` they saw and
+//! read newer events with [`EventLog::since`] instead of diffing states.
+
+use crate::types::{FailureMode, Job, JobStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Number of events kept by default; older ones are dropped.
+pub const DEFAULT_EVENT_CAPACITY: usize = 1000;
+
+/// What changed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateEventKind {
+    /// A job entry was created
+    JobAdded {
+        job_id: String,
+        rule: String,
+        status: JobStatus,
+    },
+    /// A job moved to another status
+    JobStatusChanged {
+        job_id: String,
+        from: JobStatus,
+        to: JobStatus,
+    },
+    /// A job entry was merged into another one (same job, other source)
+    JobMerged { job_id: String, into: String },
+    /// A job entry was dropped
+    JobRemoved { job_id: String },
+    /// A failed job's logs were analyzed
+    FailureAnalyzed { job_id: String, mode: FailureMode },
+    /// Snakemake reported the end of the run
+    PipelineFinished { success: bool },
+}
+
+impl StateEventKind {
+    /// The job this event is about, if any.
+    pub fn job_id(&self) -> Option<&str> {
+        match self {
+            Self::JobAdded { job_id, .. }
+            | Self::JobStatusChanged { job_id, .. }
+            | Self::JobMerged { job_id, .. }
+            | Self::JobRemoved { job_id }
+            | Self::FailureAnalyzed { job_id, .. } => Some(job_id),
+            Self::PipelineFinished { .. } => None,
+        }
+    }
+}
+
+/// One recorded change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateEvent {
+    /// Position in the log, increasing by one per event
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub kind: StateEventKind,
+}
+
+/// Bounded log of the most recent state events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLog {
+    events: VecDeque<StateEvent>,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+impl EventLog {
+    /// Create a log keeping at most `capacity` events.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity.min(DEFAULT_EVENT_CAPACITY)),
+            capacity,
+            next_seq: 0,
+        }
+    }
+
+    /// Record an event, dropping the oldest one when full.
+    pub fn push(&mut self, kind: StateEventKind) {
+        if self.capacity == 0 {
+            self.next_seq += 1;
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(StateEvent {
+            seq: self.next_seq,
+            timestamp: Utc::now(),
+            kind,
+        });
+        self.next_seq += 1;
+    }
+
+    /// Record a new job entry.
+    pub(crate) fn job_added(&mut self, job: &Job) {
+        self.push(StateEventKind::JobAdded {
+            job_id: job.id.clone(),
+            rule: job.rule.clone(),
+            status: job.status,
+        });
+    }
+
+    /// Record a status transition; nothing is recorded if `from == to`.
+    pub(crate) fn status_changed(&mut self, job_id: &str, from: JobStatus, to: JobStatus) {
+        if from != to {
+            self.push(StateEventKind::JobStatusChanged {
+                job_id: job_id.to_string(),
+                from,
+                to,
+            });
+        }
+    }
+
+    /// Events still in the log, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &StateEvent> + ExactSizeIterator {
+        self.events.iter()
+    }
+
+    /// Events with `seq >= seq`, oldest first.
+    pub fn since(&self, seq: u64) -> impl Iterator<Item = &StateEvent> {
+        let skip = self.events.partition_point(|e| e.seq < seq);
+        self.events.range(skip..)
+    }
+
+    /// Sequence number the next event will get.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn removed(id: &str) -> StateEventKind {
+        StateEventKind::JobRemoved {
+            job_id: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_event_log_ring_buffer() {
+        let mut log = EventLog::with_capacity(3);
+        for i in 0..5 {
+            log.push(removed(&i.to_string()));
+        }
+
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.next_seq(), 5);
+        let seqs: Vec<u64> = log.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![2, 3, 4]);
+
+        let newer: Vec<u64> = log.since(4).map(|e| e.seq).collect();
+        assert_eq!(newer, vec![4]);
+        // Events that were dropped are simply missing
+        assert_eq!(log.since(0).count(), 3);
+        assert_eq!(log.since(5).count(), 0);
+
+        log.status_changed("a", JobStatus::Running, JobStatus::Running);
+        assert_eq!(log.next_seq(), 5);
+        log.status_changed("a", JobStatus::Running, JobStatus::Failed);
+        assert_eq!(
+            log.iter().last().unwrap().kind,
+            StateEventKind::JobStatusChanged {
+                job_id: "a".to_string(),
+                from: JobStatus::Running,
+                to: JobStatus::Failed,
+            }
+        );
+    }
+}
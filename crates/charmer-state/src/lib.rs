@@ -2,15 +2,24 @@
 //!
 //! Merges data from SLURM and snakemake sources.
 
+pub mod events;
 pub mod merge;
+pub mod rerun;
+pub mod snapshot;
 pub mod types;
 
+pub use events::{DEFAULT_EVENT_CAPACITY, EventLog, StateEvent, StateEventKind};
 pub use merge::{
-    correlate_jobs, merge_lsf_jobs, merge_slurm_jobs, merge_snakemake_jobs, parse_lsf_description,
-    parse_slurm_comment,
+    correlate_jobs, merge_htcondor_jobs, merge_local_processes, merge_log_events,
+    merge_logger_events, merge_lsf_jobs, merge_pbs_jobs, merge_scheduler_jobs,
+    merge_scheduler_logs, merge_sge_jobs, merge_slurm_jobs, merge_snakemake_jobs,
+    parse_lsf_description, parse_slurm_comment, parse_snakejob_name, retain_logged_jobs,
 };
+pub use rerun::{ProfileOverlay, ResourceBump, option_value, suggested_bumps};
+pub use snapshot::{SnapshotError, SnapshotStore};
 pub use types::{
-    DataSources, EnvType, ExecutionEnvironment, FailureAnalysis, FailureMode, Job, JobCounts,
-    JobError, JobResources, JobStatus, JobTiming, MAIN_PIPELINE_JOB_ID, PipelineError,
-    PipelineErrorType, PipelineState, ResourceUsage,
+    ArrayTask, DataSources, EnvType, ExecutionEnvironment, FailureAnalysis, FailureMode, Job,
+    JobAction, JobAttempt, JobCounts, JobError, JobResources, JobStatus, JobTiming, LogJob,
+    MAIN_PIPELINE_JOB_ID, PipelineError, PipelineErrorType, PipelineState, ResourceUsage,
+    UNSCHEDULED_JOB_PREFIX,
 };
@@ -31,6 +31,25 @@ pub fn parse_lsf_description(desc: &str) -> Option<(String, Option<String>)> {
     parse_slurm_comment(desc)
 }
 
+/// Parse a cluster-generic executor job name: "snakejob.{rule}.{jobid}.sh".
+///
+/// Returns the rule name and snakemake's numeric job ID. Wildcards are not
+/// part of the name, so the job ID is needed to tell jobs of a rule apart.
+pub fn parse_snakejob_name(name: &str) -> Option<(String, u64)> {
+    let rest = name.strip_prefix("snakejob.")?;
+    let rest = rest.strip_suffix(".sh").unwrap_or(rest);
+    let (rule, jobid) = rest.rsplit_once('.')?;
+    if rule.is_empty() {
+        return None;
+    }
+    Some((rule.to_string(), jobid.parse().ok()?))
+}
+
+/// Generate a job ID for a job known only by rule and snakemake job ID.
+pub fn make_snakejob_id(rule: &str, jobid: u64) -> String {
+    format!("{}#{}", rule, jobid)
+}
+
 /// Generate a job ID from rule and wildcards.
 pub fn make_job_id(rule: &str, wildcards: Option<&str>) -> String {
     match wildcards {
@@ -59,6 +78,20 @@ mod tests {
         assert!(parse_slurm_comment("not_a_rule").is_none());
     }
 
+    #[test]
+    fn test_parse_snakejob_name() {
+        assert_eq!(
+            parse_snakejob_name("snakejob.align_reads.12.sh"),
+            Some(("align_reads".to_string(), 12))
+        );
+        assert_eq!(
+            parse_snakejob_name("snakejob.align.3"),
+            Some(("align".to_string(), 3))
+        );
+        assert!(parse_snakejob_name("snakejob.align.sh").is_none());
+        assert!(parse_snakejob_name("rule_align").is_none());
+    }
+
     #[test]
     fn test_make_job_id() {
         assert_eq!(make_job_id("align", None), "align");
@@ -1,49 +1,416 @@
 //! Job correlation between different data sources.
+//!
+//! Snakemake metadata jobs are keyed by output path, scheduler jobs by
+//! `rule[wildcards]` (or `rule#jobid`), so the same job can show up twice.
+//! This pass folds each scheduler entry into its metadata entry.
 
-use crate::types::PipelineState;
+use crate::events::StateEventKind;
+use crate::types::{Job, JobStatus, PipelineState};
+use std::collections::{HashMap, HashSet};
 
-/// Attempt to correlate jobs that couldn't be matched by comment field.
-/// Uses timing windows and rule name matching.
+/// Maximum start time difference for a rule/timing match.
+const TIMING_WINDOW_SECS: i64 = 60;
+
+/// Merge scheduler-only jobs into the matching snakemake-metadata jobs.
+///
+/// Jobs are paired through the main log's submission lines where possible,
+/// then by rule, wildcards and a start-time window. Ambiguous matches are
+/// left alone.
 pub fn correlate_jobs(state: &mut PipelineState) {
-    // Find jobs that have SLURM data but no snakemake metadata
-    let slurm_only: Vec<_> = state
+    // Jobs that have scheduler data but no snakemake metadata or log entry
+    let mut scheduler_only: Vec<String> = state
         .jobs
         .values()
-        .filter(|j| j.data_sources.has_slurm_squeue && !j.data_sources.has_snakemake_metadata)
+        .filter(|j| j.data_sources.has_scheduler() && !is_snakemake_side(j))
         .map(|j| j.id.clone())
         .collect();
+    scheduler_only.sort();
+
+    let mut matches: Vec<(String, String)> = Vec::new();
+    let mut unmatched = Vec::new();
+
+    // Exact matches through the log's jobid and external ID lines
+    for sched_id in scheduler_only {
+        let target = logged_outputs(state, &state.jobs[&sched_id])
+            .and_then(|outputs| outputs.iter().find(|o| snakemake_only(state, o)).cloned());
+        match target {
+            Some(target) => matches.push((sched_id, target)),
+            None => unmatched.push(sched_id),
+        }
+    }
+
+    // Rule, wildcards and timing for the rest
+    let mut candidates: HashMap<String, Vec<String>> = HashMap::new();
+    for sched_id in &unmatched {
+        let sched_job = &state.jobs[sched_id];
+        let found: Vec<String> = state
+            .jobs_by_rule
+            .get(&sched_job.rule)
+            .into_iter()
+            .flatten()
+            .filter(|id| snakemake_only(state, id))
+            .filter(|id| is_candidate(sched_job, &state.jobs[*id]))
+            .filter(|id| !matches.iter().any(|(_, t)| t == *id))
+            .cloned()
+            .collect();
+        if let [target] = found.as_slice() {
+            candidates
+                .entry(target.clone())
+                .or_default()
+                .push(sched_id.clone());
+        }
+    }
+    // A metadata job claimed by several scheduler jobs is ambiguous too
+    for (target, sched_ids) in candidates {
+        if let [sched_id] = sched_ids.as_slice() {
+            matches.push((sched_id.clone(), target));
+        }
+    }
 
-    // Find jobs that have snakemake metadata but no SLURM data
-    let snakemake_only: Vec<_> = state
+    for (sched_id, target) in matches {
+        merge_pair(state, &sched_id, &target);
+    }
+}
+
+/// Merge the scheduler entries of the given main log jobs into their
+/// snakemake entries, after new job blocks or submission lines.
+///
+/// Only exact matches through the log are made; rule and timing matches
+/// are left to `correlate_jobs` when scheduler data arrives.
+pub(crate) fn correlate_log_jobs(state: &mut PipelineState, jobids: &HashSet<u64>) {
+    let mut scheduler_only: Vec<String> = state
         .jobs
         .values()
-        .filter(|j| j.data_sources.has_snakemake_metadata && !j.data_sources.has_slurm_squeue)
+        .filter(|j| j.data_sources.has_scheduler() && !is_snakemake_side(j))
+        .filter(|j| logged_jobid(state, j).is_some_and(|id| jobids.contains(&id)))
         .map(|j| j.id.clone())
         .collect();
+    scheduler_only.sort();
+
+    for sched_id in scheduler_only {
+        let target = logged_outputs(state, &state.jobs[&sched_id])
+            .and_then(|outputs| outputs.iter().find(|o| snakemake_only(state, o)).cloned());
+        if let Some(target) = target {
+            merge_pair(state, &sched_id, &target);
+        }
+    }
+}
+
+/// Whether snakemake knows about the entry (metadata or main log) but no
+/// scheduler has reported it.
+fn snakemake_only(state: &PipelineState, id: &str) -> bool {
+    state
+        .jobs
+        .get(id)
+        .is_some_and(|j| is_snakemake_side(j) && !j.data_sources.has_scheduler())
+}
+
+/// Whether the entry comes from snakemake's own records of a concrete job.
+fn is_snakemake_side(job: &Job) -> bool {
+    (job.data_sources.has_snakemake_metadata || job.data_sources.has_main_log)
+        && !job.outputs.is_empty()
+}
+
+/// Snakemake job ID of a scheduler job, from its submission line or ID.
+fn logged_jobid(state: &PipelineState, job: &Job) -> Option<u64> {
+    job.scheduler_job_id
+        .as_ref()
+        .and_then(|ext| state.external_ids.get(ext).copied())
+        .or_else(|| snakejob_number(job))
+}
+
+/// Outputs of a scheduler job according to the main log, if it's listed.
+fn logged_outputs<'a>(state: &'a PipelineState, job: &Job) -> Option<&'a Vec<String>> {
+    state
+        .log_jobs
+        .get(&logged_jobid(state, job)?)
+        .map(|j| &j.outputs)
+}
+
+/// Snakemake job ID from a `rule#jobid` entry.
+fn snakejob_number(job: &Job) -> Option<u64> {
+    job.id
+        .strip_prefix(&job.rule)?
+        .strip_prefix('#')?
+        .parse()
+        .ok()
+}
+
+/// Whether a metadata job is a plausible match for a scheduler job.
+fn is_candidate(sched_job: &Job, sm_job: &Job) -> bool {
+    if let (Some(a), Some(b)) = (&sched_job.wildcards, &sm_job.wildcards)
+        && normalize_wildcards(a) != normalize_wildcards(b)
+    {
+        return false;
+    }
+
+    match (sched_job.timing.started_at, sm_job.timing.started_at) {
+        (Some(a), Some(b)) => (a - b).num_seconds().abs() <= TIMING_WINDOW_SECS,
+        // Without both start times only rule and wildcards can decide
+        _ => true,
+    }
+}
+
+/// Sorted `key=value` pairs, ignoring spacing and order.
+fn normalize_wildcards(wildcards: &str) -> Vec<String> {
+    let mut pairs: Vec<String> = wildcards
+        .split(',')
+        .map(|p| p.split_whitespace().collect::<String>())
+        .filter(|p| !p.is_empty())
+        .collect();
+    pairs.sort();
+    pairs
+}
+
+/// Fold the scheduler entry into the metadata entry and retire its ID.
+fn merge_pair(state: &mut PipelineState, sched_id: &str, target_id: &str) {
+    let Some(sched_job) = state.jobs.remove(sched_id) else {
+        return;
+    };
+    if let Some(ids) = state.jobs_by_rule.get_mut(&sched_job.rule) {
+        ids.retain(|id| id != sched_id);
+    }
+
+    let Some(target) = state.jobs.get_mut(target_id) else {
+        return;
+    };
+
+    // Metadata is authoritative for completion; otherwise the scheduler
+    // knows more (queued, running, failed)
+    if target.status != JobStatus::Completed {
+        state
+            .events
+            .status_changed(target_id, target.status, sched_job.status);
+        target.status = sched_job.status;
+    }
+    target.scheduler_job_id = sched_job.scheduler_job_id;
+    target.resources = sched_job.resources;
+    target.error = target.error.take().or(sched_job.error);
+    target.usage = target.usage.take().or(sched_job.usage);
+    if sched_job.wildcards.is_some() {
+        target.wildcards = sched_job.wildcards;
+    }
+    if target.timing.queued_at.is_none() {
+        target.timing.queued_at = sched_job.timing.queued_at;
+    }
+    if target.timing.started_at.is_none() {
+        target.timing.started_at = sched_job.timing.started_at;
+    }
+    if target.timing.completed_at.is_none() {
+        target.timing.completed_at = sched_job.timing.completed_at;
+    }
+    target.timing.estimated_start = sched_job.timing.estimated_start;
+    target.array = sched_job.array;
+    target.data_sources.merge(&sched_job.data_sources);
+    for attempt in sched_job.attempts {
+        target.record_attempt(attempt);
+    }
 
-    // Try to match by rule name and timing
-    for slurm_id in slurm_only {
-        if let Some(slurm_job) = state.jobs.get(&slurm_id) {
-            let slurm_start = slurm_job.timing.started_at;
-
-            for snakemake_id in &snakemake_only {
-                if let Some(sm_job) = state.jobs.get(snakemake_id) {
-                    // Match by rule name
-                    if slurm_job.rule != sm_job.rule {
-                        continue;
-                    }
-
-                    // Match by timing (within 60 second window)
-                    if let (Some(slurm_t), Some(sm_t)) = (slurm_start, sm_job.timing.started_at) {
-                        let diff = (slurm_t - sm_t).num_seconds().abs();
-                        if diff <= 60 {
-                            // Found a match - merge the data
-                            // In a real implementation, we'd merge these entries
-                            // For now, just log the correlation
-                        }
-                    }
-                }
-            }
+    let rule = target.rule.clone();
+    let ids = state.jobs_by_rule.entry(rule).or_default();
+    if !ids.iter().any(|id| id == target_id) {
+        ids.push(target_id.to_string());
+    }
+
+    state
+        .job_aliases
+        .insert(sched_id.to_string(), target_id.to_string());
+    state.events.push(StateEventKind::JobMerged {
+        job_id: sched_id.to_string(),
+        into: target_id.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge::{merge_scheduler_jobs, merge_snakemake_jobs};
+    use crate::types::LogJob;
+    use camino::Utf8PathBuf;
+    use charmer_core::{SnakemakeJob, SnakemakeMetadata};
+    use charmer_scheduler::{SchedulerJob, SchedulerJobState};
+
+    fn sm_job(output: &str, rule: &str, start: f64) -> SnakemakeJob {
+        SnakemakeJob {
+            output_path: output.to_string(),
+            metadata: SnakemakeMetadata {
+                rule: rule.to_string(),
+                input: vec![],
+                log: vec![],
+                params: vec![],
+                shellcmd: String::new(),
+                incomplete: true,
+                starttime: Some(start),
+                endtime: None,
+                job_hash: 0,
+                conda_env: None,
+                container_img_url: None,
+                input_checksums: Default::default(),
+            },
         }
     }
+
+    fn sched_job(job_id: &str, name: &str, comment: Option<&str>, start: i64) -> SchedulerJob {
+        SchedulerJob {
+            job_id: job_id.to_string(),
+            name: name.to_string(),
+            state: SchedulerJobState::Running,
+            queue: None,
+            submit_time: None,
+            start_time: chrono::DateTime::from_timestamp(start, 0),
+            end_time: None,
+            node: Some("node01".to_string()),
+            cpus: Some(2),
+            mem_mb: None,
+            time_limit: None,
+            comment: comment.map(String::from),
+            array: None,
+            pending_reason: None,
+            priority: None,
+            estimated_start: None,
+        }
+    }
+
+    #[test]
+    fn test_correlate_by_external_id() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_snakemake_jobs(
+            &mut state,
+            vec![
+                sm_job("results/a.bam", "align", 1000.0),
+                sm_job("results/b.bam", "align", 1000.0),
+            ],
+        );
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![sched_job("4102", "snakejob.align.7.sh", None, 1000)],
+            false,
+        );
+        state.log_jobs.insert(
+            7,
+            LogJob {
+                jobid: 7,
+                rule: "align".into(),
+                outputs: vec!["results/b.bam".into()],
+                ..Default::default()
+            },
+        );
+        state.external_ids.insert("4102".into(), 7);
+
+        correlate_jobs(&mut state);
+
+        assert_eq!(state.jobs.len(), 2);
+        let job = &state.jobs["results/b.bam"];
+        assert_eq!(job.scheduler_job_id.as_deref(), Some("4102"));
+        assert_eq!(job.resources.node.as_deref(), Some("node01"));
+        assert!(job.data_sources.has_snakemake_metadata);
+        assert!(job.data_sources.has_slurm_squeue);
+        assert_eq!(state.jobs_by_rule["align"].len(), 2);
+        assert_eq!(state.job_aliases["align#7"], "results/b.bam");
+
+        // Later scheduler updates land on the merged entry
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![sched_job("4102", "snakejob.align.7.sh", None, 1000)],
+            true,
+        );
+        assert_eq!(state.jobs.len(), 2);
+        assert!(state.jobs["results/b.bam"].data_sources.has_slurm_sacct);
+    }
+
+    #[test]
+    fn test_correlate_by_rule_wildcards_and_timing() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_snakemake_jobs(
+            &mut state,
+            vec![
+                sm_job("results/aligned/S1.bam", "align", 1000.0),
+                sm_job("results/aligned/S2.bam", "align", 1000.0),
+            ],
+        );
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![sched_job(
+                "501",
+                "run-uuid",
+                Some("rule_align_wildcards_sample=S2"),
+                1030,
+            )],
+            false,
+        );
+
+        correlate_jobs(&mut state);
+
+        assert_eq!(state.jobs.len(), 2);
+        let job = &state.jobs["results/aligned/S2.bam"];
+        assert_eq!(job.scheduler_job_id.as_deref(), Some("501"));
+        assert!(!state.jobs.contains_key("align[sample=S2]"));
+    }
+
+    #[test]
+    fn test_correlate_skips_ambiguous_matches() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        // Two metadata jobs fit the one scheduler job
+        merge_snakemake_jobs(
+            &mut state,
+            vec![
+                sm_job("out/x1.txt", "count", 1000.0),
+                sm_job("out/x2.txt", "count", 1010.0),
+            ],
+        );
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![sched_job("601", "run-uuid", Some("rule_count"), 1005)],
+            false,
+        );
+        correlate_jobs(&mut state);
+        assert_eq!(state.jobs.len(), 3);
+        assert!(state.job_aliases.is_empty());
+
+        // Two scheduler jobs fit the one metadata job
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_snakemake_jobs(&mut state, vec![sm_job("out/y.txt", "merge", 1000.0)]);
+        merge_scheduler_jobs(
+            &mut state,
+            "sge",
+            vec![
+                sched_job("701", "snakejob.merge.3.sh", None, 1000),
+                sched_job("702", "snakejob.merge.4.sh", None, 1020),
+            ],
+            false,
+        );
+        correlate_jobs(&mut state);
+        assert_eq!(state.jobs.len(), 3);
+        assert!(state.job_aliases.is_empty());
+    }
+
+    #[test]
+    fn test_correlate_respects_timing_window() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_snakemake_jobs(&mut state, vec![sm_job("out/z.txt", "plot", 1000.0)]);
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![sched_job("801", "run-uuid", Some("rule_plot"), 5000)],
+            false,
+        );
+        correlate_jobs(&mut state);
+        assert_eq!(state.jobs.len(), 2);
+    }
+
+    #[test]
+    fn test_normalize_wildcards() {
+        assert_eq!(
+            normalize_wildcards("sample=S1, lane=2"),
+            normalize_wildcards("lane=2,sample=S1")
+        );
+        assert_ne!(
+            normalize_wildcards("sample=S1"),
+            normalize_wildcards("sample=S2")
+        );
+    }
 }
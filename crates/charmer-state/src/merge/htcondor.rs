@@ -0,0 +1,15 @@
+//! HTCondor job merging into unified state.
+
+use super::scheduler::merge_scheduler_jobs;
+use crate::types::PipelineState;
+use charmer_htcondor::HtcondorJob;
+use charmer_scheduler::SchedulerJob;
+
+/// Merge HTCondor jobs into pipeline state.
+///
+/// Rule info comes from the Snakemake ClassAd attributes; see
+/// `charmer_htcondor::snakemake_comment`.
+pub fn merge_htcondor_jobs(state: &mut PipelineState, jobs: Vec<HtcondorJob>, from_history: bool) {
+    let jobs = jobs.into_iter().map(SchedulerJob::from).collect();
+    merge_scheduler_jobs(state, "htcondor", jobs, from_history);
+}
@@ -0,0 +1,217 @@
+//! Local process merging into unified state.
+
+use crate::types::{JobStatus, PipelineState, ResourceUsage};
+use charmer_local::{LocalProcess, normalize_command};
+use chrono::{Duration, Utc};
+
+/// Merge sampled local processes into pipeline state.
+///
+/// Each unfinished job with a known shell command is matched to the first
+/// process whose command line contains it. `processes` must be ordered
+/// parents before children (as `LocalMonitor::sample` returns them) so the
+/// match is the job's top-level process. `node` is the local hostname.
+/// The generation only advances when a job's status, PID or usage changed.
+pub fn merge_local_processes(
+    state: &mut PipelineState,
+    processes: &[LocalProcess],
+    node: Option<&str>,
+) {
+    let commands: Vec<String> = processes
+        .iter()
+        .map(|p| normalize_command(&p.cmdline))
+        .collect();
+    let now = Utc::now();
+    let mut changed = false;
+
+    for job in state.jobs.values_mut() {
+        if job.shellcmd.trim().is_empty()
+            || matches!(
+                job.status,
+                JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+            )
+        {
+            continue;
+        }
+
+        let before = (job.status, job.local_pid, job.usage.clone());
+        let shellcmd = normalize_command(&job.shellcmd);
+        let Some(process) = commands
+            .iter()
+            .position(|cmd| cmd.contains(&shellcmd))
+            .map(|i| &processes[i])
+        else {
+            // Process gone: keep peak usage but drop the live readings
+            job.local_pid = None;
+            if job.data_sources.has_local_proc
+                && let Some(usage) = job.usage.as_mut()
+            {
+                usage.rss_mb = None;
+                usage.cpu_percent = None;
+            }
+            changed |= before != (job.status, job.local_pid, job.usage.clone());
+            continue;
+        };
+
+        job.local_pid = Some(process.pid);
+        if matches!(
+            job.status,
+            JobStatus::Pending | JobStatus::Queued | JobStatus::Unknown
+        ) {
+            state
+                .events
+                .status_changed(&job.id, job.status, JobStatus::Running);
+            job.status = JobStatus::Running;
+        }
+        if job.timing.started_at.is_none() {
+            job.timing.started_at = Some(now - Duration::seconds(process.elapsed_seconds as i64));
+        }
+        if let Some(node) = node {
+            job.resources.node = Some(node.to_string());
+        }
+
+        let peak = job
+            .usage
+            .as_ref()
+            .and_then(|u| u.max_rss_mb)
+            .unwrap_or(0)
+            .max(process.peak_rss_mb);
+        job.usage = Some(ResourceUsage {
+            max_rss_mb: Some(peak),
+            elapsed_seconds: Some(process.elapsed_seconds),
+            cpu_time_seconds: Some(process.cpu_time_seconds),
+            rss_mb: Some(process.rss_mb),
+            cpu_percent: process.cpu_percent,
+            steps: vec![],
+        });
+        job.data_sources.mark_scheduler("local", false);
+        changed |= before != (job.status, job.local_pid, job.usage.clone());
+    }
+
+    state.last_updated = now;
+    if changed {
+        state.generation = state.generation.wrapping_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DataSources, Job, JobResources, JobTiming};
+    use camino::Utf8PathBuf;
+
+    fn job(id: &str, shellcmd: &str, status: JobStatus) -> Job {
+        Job {
+            id: id.to_string(),
+            rule: id.to_string(),
+            wildcards: None,
+            outputs: vec![],
+            inputs: vec![],
+            status,
+            scheduler_job_id: None,
+            local_pid: None,
+            shellcmd: shellcmd.to_string(),
+            timing: JobTiming::default(),
+            resources: JobResources::default(),
+            usage: None,
+            log_files: vec![],
+            error: None,
+            conda_env: None,
+            container_img_url: None,
+            data_sources: DataSources::default(),
+            is_target: false,
+            is_snakemake_job: true,
+            array: None,
+            attempts: Vec::new(),
+        }
+    }
+
+    fn process(pid: u32, cmdline: &str, rss_mb: u64) -> LocalProcess {
+        LocalProcess {
+            pid,
+            cmdline: cmdline.to_string(),
+            rss_mb,
+            peak_rss_mb: rss_mb,
+            cpu_percent: Some(95.0),
+            cpu_time_seconds: 30,
+            elapsed_seconds: 60,
+        }
+    }
+
+    #[test]
+    fn test_merge_local_processes() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/work"));
+        state.jobs.insert(
+            "align".to_string(),
+            job(
+                "align",
+                "bwa mem ref.fa\n    S1.fq > S1.sam",
+                JobStatus::Pending,
+            ),
+        );
+        state.jobs.insert(
+            "sort".to_string(),
+            job("sort", "samtools sort S1.sam", JobStatus::Pending),
+        );
+
+        let processes = vec![
+            process(
+                200,
+                "/bin/bash -c set -euo pipefail;  bwa mem ref.fa S1.fq > S1.sam",
+                1500,
+            ),
+            process(201, "bwa mem ref.fa S1.fq", 1400),
+        ];
+        merge_local_processes(&mut state, &processes, Some("laptop"));
+
+        let align = &state.jobs["align"];
+        assert_eq!(align.status, JobStatus::Running);
+        assert_eq!(align.local_pid, Some(200));
+        assert!(align.scheduler_job_id.is_none());
+        assert_eq!(align.resources.node.as_deref(), Some("laptop"));
+        assert!(align.timing.started_at.is_some());
+        assert!(align.data_sources.has_local_proc);
+        let usage = align.usage.as_ref().unwrap();
+        assert_eq!(usage.rss_mb, Some(1500));
+        assert_eq!(usage.max_rss_mb, Some(1500));
+
+        assert_eq!(state.jobs["sort"].status, JobStatus::Pending);
+        assert!(state.jobs["sort"].usage.is_none());
+
+        // Once the process exits the live readings are cleared, the peak stays
+        merge_local_processes(&mut state, &[], Some("laptop"));
+        let usage = state.jobs["align"].usage.as_ref().unwrap();
+        assert_eq!(usage.rss_mb, None);
+        assert_eq!(usage.cpu_percent, None);
+        assert_eq!(usage.max_rss_mb, Some(1500));
+        assert_eq!(state.jobs["align"].local_pid, None);
+
+        // Polls that change nothing leave the generation alone
+        let generation = state.generation;
+        merge_local_processes(&mut state, &[], Some("laptop"));
+        assert_eq!(state.generation, generation);
+    }
+
+    #[test]
+    fn test_merge_local_processes_generation() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/work"));
+        state.jobs.insert(
+            "align".to_string(),
+            job("align", "bwa mem ref.fa S1.fq", JobStatus::Pending),
+        );
+        let processes = vec![process(200, "bwa mem ref.fa S1.fq", 1500)];
+
+        let generation = state.generation;
+        merge_local_processes(&mut state, &processes, None);
+        assert_ne!(state.generation, generation);
+
+        // Same process, same readings: nothing to redraw
+        let generation = state.generation;
+        merge_local_processes(&mut state, &processes, None);
+        assert_eq!(state.generation, generation);
+
+        // New readings bump it again
+        let processes = vec![process(200, "bwa mem ref.fa S1.fq", 1600)];
+        merge_local_processes(&mut state, &processes, None);
+        assert_ne!(state.generation, generation);
+    }
+}
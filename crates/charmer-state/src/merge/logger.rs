@@ -0,0 +1,215 @@
+//! Structured logger plugin events merging into unified state.
+
+use crate::types::{JobError, JobStatus, PipelineError, PipelineErrorType, PipelineState};
+use charmer_core::{LogJob, LoggerEvent};
+use chrono::Utc;
+
+/// Apply logger plugin events to pipeline state.
+///
+/// Once any event has been applied, job status and progress no longer come
+/// from the text of the main log; its other information still applies.
+pub fn merge_logger_events(state: &mut PipelineState, events: &[LoggerEvent]) {
+    if events.is_empty() {
+        return;
+    }
+    state.has_logger_events = true;
+
+    for event in events {
+        match event {
+            LoggerEvent::RunInfo {
+                per_rule_job_counts,
+                total_job_count,
+            } => {
+                if total_job_count.is_some() {
+                    state.total_jobs = *total_job_count;
+                }
+                for (rule, count) in per_rule_job_counts {
+                    state.sync_unscheduled_placeholders(rule, *count);
+                }
+            }
+            LoggerEvent::JobInfo(info) => {
+                let log_job = info.to_log_job();
+                // Replaces one of the rule's unscheduled placeholders
+                let planned = state.jobs_by_rule.get(&log_job.rule).map_or(0, Vec::len);
+                if log_job.outputs.is_empty() {
+                    state.log_jobs.insert(log_job.jobid, log_job.clone());
+                    if !state.jobs.contains_key(&entry_id(&log_job)) {
+                        state.add_target_job(&log_job.rule, JobStatus::Pending);
+                    }
+                } else {
+                    state.record_log_job(&log_job);
+                }
+                if planned > 0 {
+                    state.sync_unscheduled_placeholders(&log_job.rule, planned);
+                }
+            }
+            LoggerEvent::JobFinished { job_id } => {
+                if let Some(id) = entry_for_jobid(state, *job_id)
+                    && let Some(job) = state.jobs.get_mut(&id)
+                {
+                    state
+                        .events
+                        .status_changed(&id, job.status, JobStatus::Completed);
+                    job.status = JobStatus::Completed;
+                    job.error = None;
+                    if job.timing.completed_at.is_none() {
+                        job.timing.completed_at = Some(Utc::now());
+                    }
+                }
+            }
+            LoggerEvent::JobError { jobid, msg } => {
+                let message = msg.clone().unwrap_or_else(|| "Job failed".to_string());
+                let rule = state.log_jobs.get(jobid).map(|j| j.rule.clone());
+                if let Some(id) = entry_for_jobid(state, *jobid)
+                    && let Some(job) = state.jobs.get_mut(&id)
+                {
+                    state
+                        .events
+                        .status_changed(&id, job.status, JobStatus::Failed);
+                    job.status = JobStatus::Failed;
+                    if job.error.is_none() {
+                        job.error = Some(JobError {
+                            exit_code: -1,
+                            message: message.clone(),
+                            analysis: None,
+                        });
+                    }
+                    if job.timing.completed_at.is_none() {
+                        job.timing.completed_at = Some(Utc::now());
+                    }
+                }
+                let mut error = PipelineError::new(PipelineErrorType::RuleError, message);
+                if let Some(rule) = rule {
+                    error = error.with_rule(rule);
+                }
+                state.pipeline_errors.push(error);
+            }
+            LoggerEvent::Progress { done, total } => {
+                state.total_jobs = Some(*total);
+                state.set_pipeline_finished(*total > 0 && done >= total);
+            }
+            LoggerEvent::Other => {}
+        }
+    }
+
+    // Scheduled jobs may now pair up with scheduler entries
+    crate::merge::correlate_jobs(state);
+
+    state.last_updated = Utc::now();
+    state.generation = state.generation.wrapping_add(1);
+}
+
+/// Entry ID for a logged job: its first output, or the synthetic target
+/// entry for rules without outputs.
+fn entry_id(log_job: &LogJob) -> String {
+    match log_job.outputs.first() {
+        Some(output) => output.clone(),
+        None => format!("__target_{}__", log_job.rule),
+    }
+}
+
+/// Entry ID for a snakemake job ID, following correlation aliases.
+pub(super) fn entry_for_jobid(state: &PipelineState, jobid: u64) -> Option<String> {
+    let id = entry_id(state.log_jobs.get(&jobid)?);
+    Some(state.job_aliases.get(&id).cloned().unwrap_or(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::StateEventKind;
+    use camino::Utf8PathBuf;
+    use charmer_core::{LoggerJobInfo, SnakemakeLogInfo};
+    use std::collections::HashMap;
+
+    fn job_info(jobid: u64, rule: &str, output: &[&str]) -> LoggerEvent {
+        LoggerEvent::JobInfo(LoggerJobInfo {
+            jobid,
+            rule_name: rule.to_string(),
+            output: output.iter().map(|o| o.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_merge_logger_events() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_logger_events(
+            &mut state,
+            &[LoggerEvent::RunInfo {
+                per_rule_job_counts: HashMap::from([
+                    ("align".to_string(), 2),
+                    ("all".to_string(), 1),
+                ]),
+                total_job_count: Some(3),
+            }],
+        );
+        assert_eq!(state.total_jobs, Some(3));
+        assert_eq!(state.job_counts().pending, 3);
+
+        merge_logger_events(
+            &mut state,
+            &[
+                job_info(1, "align", &["aligned/S1.bam"]),
+                job_info(2, "align", &["aligned/S2.bam"]),
+                LoggerEvent::JobFinished { job_id: 1 },
+                LoggerEvent::JobError {
+                    jobid: 2,
+                    msg: Some("exit 1".to_string()),
+                },
+                LoggerEvent::Progress { done: 1, total: 3 },
+            ],
+        );
+        assert_eq!(state.jobs_by_rule["align"].len(), 2);
+        assert_eq!(state.jobs["aligned/S1.bam"].status, JobStatus::Completed);
+        let failed = &state.jobs["aligned/S2.bam"];
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert_eq!(failed.error.as_ref().unwrap().message, "exit 1");
+        assert_eq!(state.pipeline_errors[0].rule.as_deref(), Some("align"));
+        assert!(!state.pipeline_finished);
+
+        // The target rule's entry replaces its placeholder
+        merge_logger_events(
+            &mut state,
+            &[
+                job_info(3, "all", &[]),
+                LoggerEvent::JobFinished { job_id: 3 },
+            ],
+        );
+        assert_eq!(state.jobs_by_rule["all"], vec!["__target_all__"]);
+        assert_eq!(state.jobs["__target_all__"].status, JobStatus::Completed);
+        assert_eq!(state.jobs.len(), 3);
+
+        merge_logger_events(&mut state, &[LoggerEvent::Progress { done: 3, total: 3 }]);
+        assert!(state.pipeline_finished);
+        assert_eq!(
+            state.events.iter().last().unwrap().kind,
+            StateEventKind::PipelineFinished { success: false }
+        );
+    }
+
+    #[test]
+    fn test_logger_events_take_precedence() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_logger_events(&mut state, &[LoggerEvent::Progress { done: 0, total: 5 }]);
+
+        let info = SnakemakeLogInfo {
+            total_jobs: Some(9),
+            finished: true,
+            cores: Some(8),
+            host: Some("login01".to_string()),
+            errors: vec!["Error in rule align:".to_string()],
+            external_ids: HashMap::from([("4102".to_string(), 3)]),
+            ..Default::default()
+        };
+        state.update_from_log_info(&info);
+        assert_eq!(state.total_jobs, Some(5));
+        assert!(!state.pipeline_finished);
+
+        // The rest of the log still applies
+        assert_eq!(state.cores, Some(8));
+        assert_eq!(state.host.as_deref(), Some("login01"));
+        assert_eq!(state.pipeline_errors.len(), 1);
+        assert_eq!(state.external_ids.get("4102"), Some(&3));
+    }
+}
@@ -0,0 +1,184 @@
+//! Main log events merging into unified state.
+
+use super::correlation::correlate_log_jobs;
+use super::logger::entry_for_jobid;
+use crate::types::{JobError, JobStatus, PipelineState};
+use charmer_core::LogEvent;
+use std::collections::HashSet;
+
+/// Apply main log events to pipeline state.
+///
+/// Job blocks and submission lines are indexed as they arrive, and the job
+/// outcomes ("Finished job", "Error in rule") set the status of the matching
+/// entries. Only the jobs the events name are correlated with scheduler
+/// entries, so a followed log can be applied poll by poll. Run-level
+/// information (totals, errors, completion) comes from `update_log_summary`.
+pub fn merge_log_events(state: &mut PipelineState, events: &[LogEvent]) {
+    let mut logged = HashSet::new();
+    for event in events {
+        let (jobid, status, rule) = match event {
+            // Reading started over: the previous run's blocks no longer apply
+            LogEvent::LogOpened { .. } => {
+                state.reset_log_run();
+                logged.clear();
+                continue;
+            }
+            LogEvent::JobScheduled(log_job) => {
+                state.record_log_job(log_job);
+                logged.insert(log_job.jobid);
+                continue;
+            }
+            LogEvent::JobSubmitted { jobid, external_id } => {
+                state.external_ids.insert(external_id.clone(), *jobid);
+                if let Some(log_job) = state.log_jobs.get_mut(jobid) {
+                    log_job.external_id = Some(external_id.clone());
+                }
+                logged.insert(*jobid);
+                continue;
+            }
+            LogEvent::JobFinished { jobid } => (*jobid, JobStatus::Completed, None),
+            LogEvent::JobFailed { jobid, rule } => (*jobid, JobStatus::Failed, Some(rule)),
+            LogEvent::Progress { .. } | LogEvent::Error(_) => continue,
+        };
+        // Structured logger events decide job status once received
+        if state.has_logger_events {
+            continue;
+        }
+        let Some(id) = entry_for_jobid(state, jobid) else {
+            continue;
+        };
+        let Some(job) = state.jobs.get_mut(&id) else {
+            continue;
+        };
+
+        state.events.status_changed(&id, job.status, status);
+        job.status = status;
+        match rule {
+            Some(rule) if job.error.is_none() => {
+                job.error = Some(JobError {
+                    exit_code: -1,
+                    message: format!("Error in rule {}", rule),
+                    analysis: None,
+                });
+            }
+            Some(_) => {}
+            // A retry succeeded
+            None => job.error = None,
+        }
+    }
+
+    if !logged.is_empty() {
+        correlate_log_jobs(state, &logged);
+    }
+    state.generation = state.generation.wrapping_add(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge::merge_scheduler_jobs;
+    use camino::Utf8PathBuf;
+    use charmer_core::{LogJob, SnakemakeLogInfo};
+    use charmer_scheduler::{SchedulerJob, SchedulerJobState};
+
+    fn log_job(jobid: u64, output: &str) -> LogJob {
+        LogJob {
+            jobid,
+            rule: "align".to_string(),
+            outputs: vec![output.to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_log_events_sets_outcomes() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        state.update_from_log_info(&SnakemakeLogInfo {
+            jobs: vec![log_job(1, "a.bam"), log_job(2, "b.bam")],
+            ..Default::default()
+        });
+
+        merge_log_events(
+            &mut state,
+            &[
+                LogEvent::JobFinished { jobid: 1 },
+                LogEvent::JobFailed {
+                    jobid: 2,
+                    rule: "align".to_string(),
+                },
+                LogEvent::JobFinished { jobid: 99 },
+            ],
+        );
+
+        assert_eq!(state.jobs["a.bam"].status, JobStatus::Completed);
+        let failed = &state.jobs["b.bam"];
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert_eq!(
+            failed.error.as_ref().unwrap().message,
+            "Error in rule align"
+        );
+    }
+
+    #[test]
+    fn test_merge_log_events_follows_log() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![SchedulerJob {
+                job_id: "4102".to_string(),
+                name: "snakejob.align.7.sh".to_string(),
+                state: SchedulerJobState::Running,
+                queue: None,
+                submit_time: None,
+                start_time: None,
+                end_time: None,
+                node: None,
+                cpus: None,
+                mem_mb: None,
+                time_limit: None,
+                comment: None,
+                array: None,
+                pending_reason: None,
+                priority: None,
+                estimated_start: None,
+            }],
+            false,
+        );
+
+        merge_log_events(
+            &mut state,
+            &[
+                LogEvent::LogOpened {
+                    path: Utf8PathBuf::from("/tmp/.snakemake/log/run.snakemake.log"),
+                },
+                LogEvent::JobScheduled(log_job(7, "b.bam")),
+                LogEvent::JobSubmitted {
+                    jobid: 7,
+                    external_id: "4102".to_string(),
+                },
+            ],
+        );
+
+        // The scheduler entry was folded into the logged job
+        let job = &state.jobs["b.bam"];
+        assert_eq!(job.scheduler_job_id.as_deref(), Some("4102"));
+        assert_eq!(job.status, JobStatus::Running);
+        assert_eq!(state.jobs.len(), 1);
+        assert_eq!(state.log_jobs[&7].external_id.as_deref(), Some("4102"));
+
+        merge_log_events(&mut state, &[LogEvent::JobFinished { jobid: 7 }]);
+        assert_eq!(state.jobs["b.bam"].status, JobStatus::Completed);
+
+        // A new run starts over
+        merge_log_events(
+            &mut state,
+            &[LogEvent::LogOpened {
+                path: Utf8PathBuf::from("/tmp/.snakemake/log/next.snakemake.log"),
+            }],
+        );
+        assert!(state.log_jobs.is_empty());
+        assert!(state.log_job_by_output.is_empty());
+        assert!(state.external_ids.is_empty());
+    }
+}
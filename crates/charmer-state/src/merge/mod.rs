@@ -1,13 +1,31 @@
-//! Merge SLURM, LSF, and snakemake data into unified state.
+//! Merge scheduler (SLURM, LSF, PBS, SGE, HTCondor) and snakemake data into unified state.
 
 mod comment;
 mod correlation;
+mod htcondor;
+mod local;
+mod logger;
 mod lsf;
+mod main_log;
+mod pbs;
+mod scheduler;
+mod scheduler_logs;
+mod sge;
 mod slurm;
 mod snakemake;
 
-pub use comment::{make_job_id, parse_lsf_description, parse_slurm_comment};
+pub use comment::{
+    make_job_id, make_snakejob_id, parse_lsf_description, parse_slurm_comment, parse_snakejob_name,
+};
 pub use correlation::correlate_jobs;
+pub use htcondor::merge_htcondor_jobs;
+pub use local::merge_local_processes;
+pub use logger::merge_logger_events;
 pub use lsf::merge_lsf_jobs;
+pub use main_log::merge_log_events;
+pub use pbs::merge_pbs_jobs;
+pub use scheduler::{merge_scheduler_jobs, retain_logged_jobs};
+pub use scheduler_logs::merge_scheduler_logs;
+pub use sge::merge_sge_jobs;
 pub use slurm::merge_slurm_jobs;
 pub use snakemake::merge_snakemake_jobs;
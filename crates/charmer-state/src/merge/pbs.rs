@@ -0,0 +1,12 @@
+//! PBS job merging into unified state.
+
+use super::scheduler::merge_scheduler_jobs;
+use crate::types::PipelineState;
+use charmer_pbs::PbsJob;
+use charmer_scheduler::SchedulerJob;
+
+/// Merge PBS jobs into pipeline state.
+pub fn merge_pbs_jobs(state: &mut PipelineState, jobs: Vec<PbsJob>, from_history: bool) {
+    let jobs = jobs.into_iter().map(SchedulerJob::from).collect();
+    merge_scheduler_jobs(state, "pbs", jobs, from_history);
+}
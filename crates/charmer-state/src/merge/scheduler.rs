@@ -0,0 +1,525 @@
+//! Scheduler job merging into unified state.
+
+use super::comment::{make_job_id, make_snakejob_id, parse_slurm_comment, parse_snakejob_name};
+use crate::events::StateEventKind;
+use crate::types::{
+    DataSources, Job, JobAttempt, JobResources, JobTiming, PipelineState, ToJobStatus,
+};
+use charmer_scheduler::SchedulerJob;
+use chrono::Utc;
+use std::collections::HashSet;
+
+/// Merge jobs reported by a scheduler backend into pipeline state.
+///
+/// `scheduler` is the backend name from `Scheduler::name`, used to set
+/// the matching `DataSources` flag.
+pub fn merge_scheduler_jobs(
+    state: &mut PipelineState,
+    scheduler: &str,
+    jobs: Vec<SchedulerJob>,
+    from_history: bool,
+) {
+    // Pending array lines seen in this batch, to drop ones that have since
+    // been split into tasks
+    let mut pending_arrays = HashSet::new();
+
+    for sched_job in jobs {
+        // Try to parse rule info from comment/description
+        let parsed = sched_job
+            .comment
+            .as_ref()
+            .and_then(|c| parse_slurm_comment(c));
+
+        // Fall back to the job name for executors that put the rule there
+        let from_name = if parsed.is_none() {
+            parse_snakejob_name(&sched_job.name)
+        } else {
+            None
+        };
+
+        // Job is a snakemake job if comment or name parsing succeeded.
+        // Backends without a comment field (PBS) copy a rule-marked name
+        // into the comment, so that name isn't a run UUID either.
+        let named_by_rule = from_name.is_some()
+            || (parsed.is_some() && sched_job.comment.as_ref() == Some(&sched_job.name));
+        let is_snakemake_job = parsed.is_some() || named_by_rule;
+
+        let (rule, wildcards, job_id) = match (parsed, from_name) {
+            (Some((rule, wildcards)), _) => {
+                let job_id = make_job_id(&rule, wildcards.as_deref());
+                (rule, wildcards, job_id)
+            }
+            (None, Some((rule, jobid))) => {
+                let job_id = make_snakejob_id(&rule, jobid);
+                (rule, None, job_id)
+            }
+            (None, None) => {
+                let rule = sched_job.name.clone();
+                let job_id = make_job_id(&rule, None);
+                (rule, None, job_id)
+            }
+        };
+
+        // Array tasks share the rule/wildcards of their parent, so each task
+        // (and the line for still-pending tasks) gets its own entry
+        let job_id = match &sched_job.array {
+            Some(array) => match array.task_id {
+                Some(task) => format!("{}#{}_{}", job_id, array.parent_id, task),
+                None => {
+                    let id = format!("{}#{}_[pending]", job_id, array.parent_id);
+                    pending_arrays.insert(id.clone());
+                    id
+                }
+            },
+            None => job_id,
+        };
+
+        // Follow entries that correlation folded into another job
+        let job_id = state.job_aliases.get(&job_id).cloned().unwrap_or(job_id);
+
+        // Update run_uuid if this is the first job (the name is only the
+        // run UUID when it isn't carrying the rule)
+        if state.run_uuid.is_none() && !named_by_rule {
+            state.run_uuid = Some(sched_job.name.clone());
+        }
+
+        // Convert scheduler state using the trait
+        let status = sched_job.state.to_job_status();
+        let error = sched_job.state.to_job_error();
+
+        // Build timing
+        let timing = JobTiming {
+            queued_at: sched_job.submit_time,
+            started_at: sched_job.start_time,
+            completed_at: sched_job.end_time,
+            estimated_start: sched_job.estimated_start,
+        };
+
+        // Build resources
+        let resources = JobResources {
+            cpus: sched_job.cpus,
+            memory_mb: sched_job.mem_mb,
+            time_limit: sched_job.time_limit,
+            partition: sched_job.queue.clone(),
+            node: sched_job.node.clone(),
+            pending_reason: sched_job.pending_reason.clone(),
+            priority: sched_job.priority,
+        };
+
+        let attempt = JobAttempt {
+            scheduler_job_id: sched_job.job_id.clone(),
+            status,
+            resources: resources.clone(),
+            timing: timing.clone(),
+            error: error.clone(),
+        };
+
+        // Check if job already exists
+        if let Some(existing) = state.jobs.get_mut(&job_id) {
+            existing
+                .data_sources
+                .mark_scheduler(scheduler, from_history);
+
+            // Earlier attempts (from history) don't overwrite a retry
+            if !existing.record_attempt(attempt) {
+                continue;
+            }
+
+            // Update with scheduler data
+            existing.scheduler_job_id = Some(sched_job.job_id.clone());
+            state
+                .events
+                .status_changed(&job_id, existing.status, status);
+            existing.status = status;
+            existing.resources = resources;
+            existing.error = existing.attempts.last().and_then(|a| a.error.clone());
+            existing.array = sched_job.array.clone();
+            if existing.timing.queued_at.is_none() {
+                existing.timing.queued_at = timing.queued_at;
+            }
+            existing.timing.estimated_start = timing.estimated_start;
+        } else {
+            let mut data_sources = DataSources::default();
+            data_sources.mark_scheduler(scheduler, from_history);
+
+            // Create new job entry
+            let job = Job {
+                id: job_id.clone(),
+                rule,
+                wildcards,
+                outputs: vec![],
+                inputs: vec![],
+                status,
+                scheduler_job_id: Some(sched_job.job_id.clone()),
+                local_pid: None,
+                shellcmd: String::new(),
+                timing,
+                resources,
+                usage: None,
+                log_files: vec![],
+                error,
+                conda_env: None,
+                container_img_url: None,
+                data_sources,
+                is_target: false,
+                is_snakemake_job,
+                array: sched_job.array.clone(),
+                attempts: vec![attempt],
+            };
+
+            let rule_name = job.rule.clone();
+            state.events.job_added(&job);
+            state.jobs.insert(job_id.clone(), job);
+
+            // Update jobs_by_rule index
+            state
+                .jobs_by_rule
+                .entry(rule_name)
+                .or_default()
+                .push(job_id);
+        }
+    }
+
+    if !from_history {
+        remove_stale_pending_arrays(state, &pending_arrays);
+    }
+
+    state.last_updated = Utc::now();
+    state.generation = state.generation.wrapping_add(1);
+}
+
+/// Drop pending array lines that the active queue no longer reports.
+fn remove_stale_pending_arrays(state: &mut PipelineState, seen: &HashSet<String>) {
+    let stale: Vec<String> = state
+        .jobs
+        .values()
+        .filter(|job| job.array.as_ref().is_some_and(|a| a.task_id.is_none()))
+        .filter(|job| !seen.contains(&job.id))
+        .map(|job| job.id.clone())
+        .collect();
+
+    for id in stale {
+        let Some(job) = state.jobs.remove(&id) else {
+            continue;
+        };
+        if let Some(ids) = state.jobs_by_rule.get_mut(&job.rule) {
+            ids.retain(|j| j != &id);
+        }
+        state.events.push(StateEventKind::JobRemoved { job_id: id });
+    }
+}
+
+/// Keep the scheduler jobs the main log says the run submitted.
+///
+/// This is the run filter for backends whose job names don't carry the run
+/// UUID. Until the log has named a submission there is nothing to go by,
+/// and every job is kept.
+pub fn retain_logged_jobs(state: &PipelineState, jobs: &mut Vec<SchedulerJob>) {
+    if state.external_ids.is_empty() {
+        return;
+    }
+    // The log may leave off a server suffix or task index ("1001.pbs01")
+    let logged = |id: &str| {
+        state.external_ids.contains_key(id)
+            || id
+                .split('.')
+                .next()
+                .is_some_and(|base| state.external_ids.contains_key(base))
+    };
+    jobs.retain(|job| {
+        logged(&job.job_id)
+            || job
+                .array
+                .as_ref()
+                .is_some_and(|array| logged(&array.parent_id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::JobStatus;
+    use camino::Utf8PathBuf;
+    use charmer_scheduler::{ArrayTask, SchedulerJobState};
+
+    fn sched_job(job_id: &str, name: &str, comment: Option<&str>) -> SchedulerJob {
+        SchedulerJob {
+            job_id: job_id.to_string(),
+            name: name.to_string(),
+            state: SchedulerJobState::Running,
+            queue: Some("all.q".to_string()),
+            submit_time: None,
+            start_time: None,
+            end_time: None,
+            node: Some("node07".to_string()),
+            cpus: Some(4),
+            mem_mb: None,
+            time_limit: None,
+            comment: comment.map(String::from),
+            array: None,
+            pending_reason: None,
+            priority: None,
+            estimated_start: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_by_comment() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let jobs = vec![sched_job(
+            "101",
+            "run-uuid",
+            Some("rule_align_wildcards_sample=S1"),
+        )];
+        merge_scheduler_jobs(&mut state, "slurm", jobs, false);
+
+        let job = &state.jobs["align[sample=S1]"];
+        assert_eq!(job.scheduler_job_id.as_deref(), Some("101"));
+        assert!(job.is_snakemake_job);
+        assert!(job.data_sources.has_slurm_squeue);
+        assert_eq!(state.run_uuid.as_deref(), Some("run-uuid"));
+    }
+
+    #[test]
+    fn test_merge_by_job_name() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let jobs = vec![
+            sched_job("5001", "snakejob.align.3.sh", None),
+            sched_job("5002", "snakejob.align.4.sh", None),
+        ];
+        merge_scheduler_jobs(&mut state, "sge", jobs, false);
+
+        assert_eq!(state.jobs.len(), 2);
+        let job = &state.jobs["align#3"];
+        assert_eq!(job.rule, "align");
+        assert!(job.is_snakemake_job);
+        assert!(job.data_sources.has_sge_qstat);
+        assert_eq!(state.jobs_by_rule["align"].len(), 2);
+        assert!(state.run_uuid.is_none());
+    }
+
+    #[test]
+    fn test_merge_rule_marker_in_name() {
+        // PBS: the rule marker is the job name, copied into the comment
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let name = "rule_align_wildcards_sample=S1";
+        merge_scheduler_jobs(
+            &mut state,
+            "pbs",
+            vec![sched_job("1001", name, Some(name))],
+            false,
+        );
+
+        let job = &state.jobs["align[sample=S1]"];
+        assert!(job.is_snakemake_job);
+        assert!(state.run_uuid.is_none());
+    }
+
+    #[test]
+    fn test_retain_logged_jobs() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let name = "rule_align_wildcards_sample=S1";
+        let mut jobs = vec![
+            sched_job("1001.pbs01", name, Some(name)),
+            sched_job("1002.pbs01", name, Some(name)),
+        ];
+
+        // Nothing logged yet: nothing to filter on
+        retain_logged_jobs(&state, &mut jobs);
+        assert_eq!(jobs.len(), 2);
+
+        state.external_ids.insert("1002".to_string(), 4);
+        retain_logged_jobs(&state, &mut jobs);
+        let ids: Vec<&str> = jobs.iter().map(|j| j.job_id.as_str()).collect();
+        assert_eq!(ids, vec!["1002.pbs01"]);
+
+        // SGE: the names only say which rule, the log says which run
+        let mut jobs = vec![
+            sched_job("5001", "snakejob.align.3.sh", None),
+            sched_job("6001", "snakejob.align.3.sh", None),
+        ];
+        state.external_ids.insert("5001".to_string(), 3);
+        retain_logged_jobs(&state, &mut jobs);
+        merge_scheduler_jobs(&mut state, "sge", jobs, false);
+        assert_eq!(
+            state.jobs["align#3"].scheduler_job_id.as_deref(),
+            Some("5001")
+        );
+        assert!(state.run_uuid.is_none());
+    }
+
+    #[test]
+    fn test_merge_array_tasks() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let comment = Some("rule_align_wildcards_sample=S1");
+        let task = |job_id: &str, task_id: Option<u32>, task_count: u32| SchedulerJob {
+            array: Some(ArrayTask {
+                parent_id: "700".to_string(),
+                task_id,
+                task_count,
+            }),
+            ..sched_job(job_id, "run-uuid", comment)
+        };
+
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![task("700_1", Some(1), 1), task("700_[2-4]", None, 3)],
+            false,
+        );
+        assert_eq!(state.jobs.len(), 2);
+        assert!(state.jobs.contains_key("align[sample=S1]#700_1"));
+        assert!(state.jobs.contains_key("align[sample=S1]#700_[pending]"));
+        assert_eq!(state.job_counts().total, 4);
+
+        // Remaining tasks started: the pending line disappears
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![
+                task("700_1", Some(1), 1),
+                task("700_2", Some(2), 1),
+                task("700_3", Some(3), 1),
+                task("700_4", Some(4), 1),
+            ],
+            false,
+        );
+        assert_eq!(state.jobs.len(), 4);
+        assert!(!state.jobs.contains_key("align[sample=S1]#700_[pending]"));
+        assert_eq!(state.jobs_by_rule["align"].len(), 4);
+    }
+
+    #[test]
+    fn test_merge_retry_attempts() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let comment = Some("rule_align_wildcards_sample=S1");
+        let attempt = |job_id: &str, mem_mb: u64, state: SchedulerJobState| SchedulerJob {
+            state,
+            mem_mb: Some(mem_mb),
+            ..sched_job(job_id, "run-uuid", comment)
+        };
+        let oom = || SchedulerJobState::Failed {
+            exit_code: -1,
+            message: "Job exceeded memory limit".to_string(),
+        };
+
+        merge_scheduler_jobs(&mut state, "slurm", vec![attempt("101", 4096, oom())], true);
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![attempt("102", 8192, SchedulerJobState::Running)],
+            false,
+        );
+        // History lists every attempt, oldest last
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![
+                attempt("102", 8192, oom()),
+                attempt("103", 16384, SchedulerJobState::Completed),
+                attempt("101", 4096, oom()),
+            ],
+            true,
+        );
+
+        let job = &state.jobs["align[sample=S1]"];
+        let ids: Vec<&str> = job
+            .attempts
+            .iter()
+            .map(|a| a.scheduler_job_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["101", "102", "103"]);
+        assert_eq!(job.attempts[1].status, JobStatus::Failed);
+        assert_eq!(job.attempts[1].resources.memory_mb, Some(8192));
+        assert_eq!(job.scheduler_job_id.as_deref(), Some("103"));
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.resources.memory_mb, Some(16384));
+        assert!(job.error.is_none());
+    }
+
+    #[test]
+    fn test_merge_pending_reason() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let queued = |job_id: &str, reason: &str| SchedulerJob {
+            state: SchedulerJobState::Pending,
+            pending_reason: Some(reason.to_string()),
+            priority: Some(100),
+            ..sched_job(job_id, &format!("snakejob.align.{}.sh", job_id), None)
+        };
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![
+                queued("1", "Priority"),
+                queued("2", "Priority"),
+                queued("3", "Resources"),
+            ],
+            false,
+        );
+
+        let job = &state.jobs["align#1"];
+        assert_eq!(job.resources.pending_reason.as_deref(), Some("Priority"));
+        assert_eq!(job.resources.priority, Some(100));
+        assert_eq!(
+            state.pending_reason_counts(),
+            vec![("Priority".to_string(), 2), ("Resources".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_merge_records_state_events() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        let job = |state: SchedulerJobState| SchedulerJob {
+            state,
+            ..sched_job("101", "snakejob.align.1.sh", None)
+        };
+
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![job(SchedulerJobState::Pending)],
+            false,
+        );
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![job(SchedulerJobState::Pending)],
+            false,
+        );
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![job(SchedulerJobState::Running)],
+            false,
+        );
+
+        let kinds: Vec<_> = state.events.iter().map(|e| e.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                StateEventKind::JobAdded {
+                    job_id: "align#1".to_string(),
+                    rule: "align".to_string(),
+                    status: JobStatus::Queued,
+                },
+                StateEventKind::JobStatusChanged {
+                    job_id: "align#1".to_string(),
+                    from: JobStatus::Queued,
+                    to: JobStatus::Running,
+                },
+            ]
+        );
+
+        let seen = state.events.next_seq();
+        merge_scheduler_jobs(
+            &mut state,
+            "slurm",
+            vec![job(SchedulerJobState::Failed {
+                exit_code: 1,
+                message: "exit 1".to_string(),
+            })],
+            false,
+        );
+        assert_eq!(state.events.since(seen).count(), 1);
+    }
+}
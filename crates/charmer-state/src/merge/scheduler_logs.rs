@@ -0,0 +1,194 @@
+//! Executor job logs merging into unified state.
+
+use super::logger::entry_for_jobid;
+use crate::types::{FailureAnalysis, FailureMode, PipelineState, leading_number};
+use charmer_core::SchedulerLog;
+use std::fs;
+
+/// Link executor job logs to their jobs and classify failures from them.
+///
+/// Each log names its scheduler job ID, which the main log's submission
+/// lines map to a snakemake job; `update_from_log_info` should be applied
+/// first. The newest log of a job becomes its scheduler job ID. Failed jobs
+/// get a failure analysis when their log shows why the scheduler killed them.
+pub fn merge_scheduler_logs(state: &mut PipelineState, logs: &[SchedulerLog]) {
+    let mut linked = Vec::new();
+    for log in logs {
+        let entry = state
+            .external_ids
+            .get(&log.job_id)
+            .and_then(|jobid| entry_for_jobid(state, *jobid))
+            .or_else(|| {
+                state
+                    .jobs
+                    .iter()
+                    .find(|(_, job)| job.scheduler_job_id.as_deref() == Some(&log.job_id))
+                    .map(|(id, _)| id.clone())
+            });
+        let Some(job) = entry.as_ref().and_then(|id| state.jobs.get_mut(id)) else {
+            continue;
+        };
+
+        let newer = job
+            .scheduler_job_id
+            .as_deref()
+            .is_none_or(|current| leading_number(&log.job_id) > leading_number(current));
+        if newer {
+            job.scheduler_job_id = Some(log.job_id.clone());
+        }
+        if !job.log_files.contains(&log.path.to_string()) {
+            job.log_files.push(log.path.to_string());
+        }
+        linked.extend(entry.map(|id| (id, log)));
+    }
+
+    for (id, log) in linked {
+        let Some(job) = state.jobs.get(&id) else {
+            continue;
+        };
+        let unanalyzed = job.error.as_ref().is_some_and(|e| e.analysis.is_none());
+        if job.scheduler_job_id.as_deref() != Some(&log.job_id) || !unanalyzed {
+            continue;
+        }
+        let exit_code = job.error.as_ref().map(|e| e.exit_code);
+        let Some(analysis) = fs::read_to_string(&log.path)
+            .ok()
+            .and_then(|text| analyze_log(&text, log.scheduler))
+        else {
+            continue;
+        };
+        state.set_failure_analysis(
+            &id,
+            &log.job_id,
+            FailureAnalysis {
+                exit_code: exit_code.filter(|c| *c >= 0),
+                ..analysis
+            },
+        );
+    }
+
+    state.generation = state.generation.wrapping_add(1);
+}
+
+/// Classify a failure from the messages the scheduler appends to job output.
+fn analyze_log(text: &str, scheduler: &str) -> Option<FailureAnalysis> {
+    let has = |patterns: &[&str]| patterns.iter().any(|p| text.contains(p));
+
+    let (mode, explanation, suggestion) = if has(&["oom-kill", "Out Of Memory", "TERM_MEMLIMIT"]) {
+        (
+            FailureMode::OutOfMemory,
+            "Job was killed for exceeding its memory limit.",
+            "Increase memory. In your Snakefile, add:\n  resources: mem_mb=<more>",
+        )
+    } else if has(&["DUE TO TIME LIMIT", "TERM_RUNLIMIT"]) {
+        (
+            FailureMode::Timeout,
+            "Job was killed for exceeding its time limit.",
+            "Increase the time limit. In your Snakefile, add:\n  resources: runtime=<more>",
+        )
+    } else if has(&["NODE FAILURE", "TERM_HOST"]) {
+        (
+            FailureMode::NodeFailure,
+            "The node running the job failed.",
+            "Resubmit the job; consider excluding the failed node.",
+        )
+    } else if has(&["CANCELLED AT", "TERM_OWNER", "TERM_ADMIN"]) {
+        (
+            FailureMode::Cancelled,
+            "Job was cancelled.",
+            "Resubmit the job if the cancellation was unintended.",
+        )
+    } else {
+        return None;
+    };
+
+    Some(FailureAnalysis {
+        mode,
+        explanation: format!("{} (from the {} job log)", explanation, scheduler),
+        suggestion: suggestion.to_string(),
+        exit_code: None,
+        memory_used_mb: None,
+        memory_limit_mb: None,
+        runtime_seconds: None,
+        time_limit_seconds: None,
+        suggested_mem_mb: None,
+        suggested_runtime_seconds: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge::merge_log_events;
+    use camino::Utf8Path;
+    use charmer_core::{LogEvent, LogJob, SnakemakeLogInfo};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merge_scheduler_logs_links_and_analyzes() {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(temp.path()).unwrap();
+        let log = |job_id: &str, text: &str| {
+            let path = dir.join(format!("{}.log", job_id));
+            fs::write(&path, text).unwrap();
+            SchedulerLog {
+                scheduler: "slurm",
+                rule: Some("align".to_string()),
+                job_id: job_id.to_string(),
+                path,
+            }
+        };
+
+        let mut state = PipelineState::new(dir.to_path_buf());
+        state.update_from_log_info(&SnakemakeLogInfo {
+            jobs: vec![LogJob {
+                jobid: 4,
+                rule: "align".to_string(),
+                outputs: vec!["a.bam".to_string()],
+                ..Default::default()
+            }],
+            external_ids: HashMap::from([("4101".to_string(), 4), ("4102".to_string(), 4)]),
+            ..Default::default()
+        });
+        merge_log_events(
+            &mut state,
+            &[LogEvent::JobFailed {
+                jobid: 4,
+                rule: "align".to_string(),
+            }],
+        );
+
+        merge_scheduler_logs(
+            &mut state,
+            &[
+                log("4102", "slurmstepd: error: Detected 1 oom-kill event(s)"),
+                log(
+                    "4101",
+                    "slurmstepd: error: *** JOB 4101 CANCELLED AT 2024 DUE TO TIME LIMIT ***",
+                ),
+                log("999", "unrelated"),
+            ],
+        );
+
+        let job = &state.jobs["a.bam"];
+        assert_eq!(job.scheduler_job_id.as_deref(), Some("4102"));
+        assert_eq!(job.log_files.len(), 2);
+        let analysis = job.error.as_ref().unwrap().analysis.as_ref().unwrap();
+        assert_eq!(analysis.mode, FailureMode::OutOfMemory);
+    }
+
+    #[test]
+    fn test_analyze_log() {
+        let mode = |text: &str| analyze_log(text, "lsf").map(|a| a.mode);
+        assert_eq!(
+            mode("TERM_RUNLIMIT: job killed"),
+            Some(FailureMode::Timeout)
+        );
+        assert_eq!(
+            mode("*** JOB 1 ON c1 CANCELLED AT 2024-01-01 ***"),
+            Some(FailureMode::Cancelled)
+        );
+        assert_eq!(mode("Traceback (most recent call last)"), None);
+    }
+}
@@ -0,0 +1,14 @@
+//! SGE job merging into unified state.
+
+use super::scheduler::merge_scheduler_jobs;
+use crate::types::PipelineState;
+use charmer_scheduler::SchedulerJob;
+use charmer_sge::SgeJob;
+
+/// Merge SGE jobs into pipeline state.
+///
+/// SGE jobs are correlated by job name ("snakejob.{rule}.{jobid}.sh").
+pub fn merge_sge_jobs(state: &mut PipelineState, jobs: Vec<SgeJob>, from_qacct: bool) {
+    let jobs = jobs.into_iter().map(SchedulerJob::from).collect();
+    merge_scheduler_jobs(state, "sge", jobs, from_qacct);
+}
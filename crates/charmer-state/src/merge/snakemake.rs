@@ -11,9 +11,10 @@ fn timestamp_to_datetime(ts: f64) -> DateTime<Utc> {
         .unwrap_or_else(Utc::now)
 }
 
-/// Extract wildcards from output path.
+/// Guess wildcards from an output path.
 /// For paths like "results/aligned/sample1.bam" with rule "align_sample",
-/// tries to extract sample=sample1 based on common patterns.
+/// tries to extract sample=sample1 based on common patterns. Only used when
+/// the main log has no `wildcards:` line for the job.
 fn extract_wildcards(output_path: &str, _rule: &str) -> Option<String> {
     let parts: Vec<&str> = output_path.split('/').collect();
     if parts.len() < 2 {
@@ -60,8 +61,12 @@ pub fn merge_snakemake_jobs(state: &mut PipelineState, jobs: Vec<SnakemakeJob>)
     for snakemake_job in jobs {
         let meta = &snakemake_job.metadata;
 
-        // Generate job ID from output path or rule
-        let job_id = snakemake_job.output_path.clone();
+        // Generate job ID from output path, using the job's first output when
+        // the main log lists it so multi-output jobs stay one entry
+        let job_id = state
+            .log_job_for_output(&snakemake_job.output_path)
+            .and_then(|j| j.outputs.first().cloned())
+            .unwrap_or_else(|| snakemake_job.output_path.clone());
 
         // Determine status from metadata
         let status = if meta.incomplete {
@@ -77,10 +82,13 @@ pub fn merge_snakemake_jobs(state: &mut PipelineState, jobs: Vec<SnakemakeJob>)
             queued_at: None,
             started_at: meta.starttime.map(timestamp_to_datetime),
             completed_at: meta.endtime.map(timestamp_to_datetime),
+            ..Default::default()
         };
 
-        // Extract wildcards from output path
-        let wildcards = extract_wildcards(&snakemake_job.output_path, &meta.rule);
+        // Wildcards from the main log's job block, if it has been parsed
+        let log_wildcards = state
+            .log_job_for_output(&snakemake_job.output_path)
+            .and_then(|j| j.wildcards.clone());
 
         // Check if job already exists (from SLURM data)
         if let Some(existing) = state.jobs.get_mut(&job_id) {
@@ -90,8 +98,10 @@ pub fn merge_snakemake_jobs(state: &mut PipelineState, jobs: Vec<SnakemakeJob>)
             existing.log_files = meta.log.clone();
             existing.conda_env = meta.conda_env.clone();
             existing.container_img_url = meta.container_img_url.clone();
-            if existing.wildcards.is_none() {
-                existing.wildcards = wildcards;
+            if log_wildcards.is_some() {
+                existing.wildcards = log_wildcards;
+            } else if existing.wildcards.is_none() {
+                existing.wildcards = extract_wildcards(&snakemake_job.output_path, &meta.rule);
             }
             if existing.timing.started_at.is_none() {
                 existing.timing.started_at = timing.started_at;
@@ -99,6 +109,14 @@ pub fn merge_snakemake_jobs(state: &mut PipelineState, jobs: Vec<SnakemakeJob>)
             if existing.timing.completed_at.is_none() {
                 existing.timing.completed_at = timing.completed_at;
             }
+            // Without scheduler data (e.g., a main-log placeholder) metadata
+            // is the only status source
+            if !existing.data_sources.has_scheduler() {
+                state
+                    .events
+                    .status_changed(&job_id, existing.status, status);
+                existing.status = status;
+            }
             existing.data_sources.has_snakemake_metadata = true;
             existing.is_snakemake_job = true; // Mark as snakemake job when metadata is found
         } else {
@@ -106,11 +124,13 @@ pub fn merge_snakemake_jobs(state: &mut PipelineState, jobs: Vec<SnakemakeJob>)
             let job = Job {
                 id: job_id.clone(),
                 rule: meta.rule.clone(),
-                wildcards,
+                wildcards: log_wildcards
+                    .or_else(|| extract_wildcards(&snakemake_job.output_path, &meta.rule)),
                 outputs: vec![snakemake_job.output_path.clone()],
                 inputs: meta.input.clone(),
                 status,
                 scheduler_job_id: None,
+                local_pid: None,
                 shellcmd: meta.shellcmd.clone(),
                 timing,
                 resources: JobResources::default(),
@@ -121,14 +141,14 @@ pub fn merge_snakemake_jobs(state: &mut PipelineState, jobs: Vec<SnakemakeJob>)
                 container_img_url: meta.container_img_url.clone(),
                 data_sources: DataSources {
                     has_snakemake_metadata: true,
-                    has_slurm_squeue: false,
-                    has_slurm_sacct: false,
-                    has_lsf_bjobs: false,
-                    has_lsf_bhist: false,
+                    ..Default::default()
                 },
                 is_target: false,
                 is_snakemake_job: true, // Jobs from snakemake metadata are always snakemake jobs
+                array: None,
+                attempts: Vec::new(),
             };
+            state.events.job_added(&job);
             state.jobs.insert(job_id.clone(), job);
 
             // Update jobs_by_rule index
@@ -143,3 +163,121 @@ pub fn merge_snakemake_jobs(state: &mut PipelineState, jobs: Vec<SnakemakeJob>)
     state.last_updated = Utc::now();
     state.generation = state.generation.wrapping_add(1);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camino::Utf8PathBuf;
+    use charmer_core::{LogJob, SnakemakeLogInfo, SnakemakeMetadata};
+
+    fn sm_job(output: &str, rule: &str) -> SnakemakeJob {
+        SnakemakeJob {
+            output_path: output.to_string(),
+            metadata: SnakemakeMetadata {
+                rule: rule.to_string(),
+                input: vec![],
+                log: vec![],
+                params: vec![],
+                shellcmd: String::new(),
+                incomplete: true,
+                starttime: Some(1000.0),
+                endtime: None,
+                job_hash: 0,
+                conda_env: None,
+                container_img_url: None,
+                input_checksums: Default::default(),
+            },
+        }
+    }
+
+    fn log_info(output: &str, wildcards: &str) -> SnakemakeLogInfo {
+        SnakemakeLogInfo {
+            jobs: vec![LogJob {
+                jobid: 4,
+                rule: "align".to_string(),
+                outputs: vec![output.to_string()],
+                wildcards: Some(wildcards.to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_wildcards_from_log() {
+        let output = "results/aligned/S1_L002.bam";
+
+        // Log parsed first
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        state.update_from_log_info(&log_info(output, "sample=S1, lane=L002"));
+        merge_snakemake_jobs(&mut state, vec![sm_job(output, "align")]);
+        assert_eq!(
+            state.jobs[output].wildcards.as_deref(),
+            Some("sample=S1, lane=L002")
+        );
+
+        // Metadata first: the heuristic guess is replaced once the log arrives
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_snakemake_jobs(&mut state, vec![sm_job(output, "align")]);
+        assert_eq!(
+            state.jobs[output].wildcards.as_deref(),
+            Some("sample=S1, var=L002")
+        );
+        state.update_from_log_info(&log_info(output, "sample=S1, lane=L002"));
+        assert_eq!(
+            state.jobs[output].wildcards.as_deref(),
+            Some("sample=S1, lane=L002")
+        );
+    }
+
+    #[test]
+    fn test_wildcards_heuristic_fallback() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        merge_snakemake_jobs(&mut state, vec![sm_job("results/S2.vcf", "call")]);
+        assert_eq!(
+            state.jobs["results/S2.vcf"].wildcards.as_deref(),
+            Some("sample=S2")
+        );
+    }
+
+    #[test]
+    fn test_log_placeholders() {
+        let log_job = |jobid: u64, output: &str| LogJob {
+            jobid,
+            rule: "align".to_string(),
+            outputs: vec![output.to_string()],
+            ..Default::default()
+        };
+        let mut info = SnakemakeLogInfo {
+            total_jobs: Some(4),
+            jobs_by_rule: [("align".to_string(), 3), ("all".to_string(), 1)].into(),
+            target_rules: ["all".to_string()].into(),
+            jobs: vec![log_job(1, "a.bam")],
+            ..Default::default()
+        };
+
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        state.update_from_log_info(&info);
+
+        // One scheduled job, two not yet scheduled, and the target rule
+        assert_eq!(state.jobs.len(), 4);
+        assert_eq!(state.job_counts().total, state.total_jobs.unwrap());
+        let scheduled = &state.jobs["a.bam"];
+        assert_eq!(scheduled.status, JobStatus::Pending);
+        assert!(scheduled.data_sources.has_main_log);
+        let unscheduled = state
+            .jobs
+            .keys()
+            .filter(|id| id.starts_with(crate::types::UNSCHEDULED_JOB_PREFIX))
+            .count();
+        assert_eq!(unscheduled, 2);
+
+        // A second job is scheduled and starts running
+        info.jobs.push(log_job(2, "b.bam"));
+        state.update_from_log_info(&info);
+        merge_snakemake_jobs(&mut state, vec![sm_job("b.bam", "align")]);
+        assert_eq!(state.jobs.len(), 4);
+        assert_eq!(state.jobs["b.bam"].status, JobStatus::Running);
+        assert_eq!(state.jobs_by_rule["align"].len(), 3);
+    }
+}
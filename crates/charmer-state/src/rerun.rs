@@ -0,0 +1,500 @@
+//! Profile overlay for rerunning failed jobs with more resources.
+//!
+//! Failure analyses suggest a larger memory or time limit for jobs the
+//! scheduler killed. The overlay collects those suggestions as snakemake
+//! `set-resources` entries in a charmer-managed workflow profile, so the
+//! pipeline can be rerun with `--workflow-profile` instead of editing the
+//! Snakefile by hand. Snakemake merges a workflow profile with the global
+//! `--profile`, so the run's own profile still applies.
+
+use crate::types::{FailureMode, JobStatus, PipelineState};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+/// A resource increase for every job of a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceBump {
+    pub rule: String,
+    /// Snakemake resource name ("mem_mb" or "runtime")
+    pub resource: String,
+    /// New value (MB for mem_mb, minutes for runtime)
+    pub value: u64,
+    /// Value the failed job ran with, if known
+    pub previous: Option<u64>,
+}
+
+impl ResourceBump {
+    /// The `set-resources` entry, e.g. `align:mem_mb=16384`.
+    pub fn entry(&self) -> String {
+        format!("{}:{}={}", self.rule, self.resource, self.value)
+    }
+}
+
+/// Resource increases suggested by the failure analyses of failed jobs.
+///
+/// One bump per rule and resource; the largest suggestion wins.
+pub fn suggested_bumps(state: &PipelineState) -> Vec<ResourceBump> {
+    let mut bumps: BTreeMap<(String, &str), ResourceBump> = BTreeMap::new();
+    for job in state.jobs.values() {
+        if job.status != JobStatus::Failed {
+            continue;
+        }
+        let Some(analysis) = job.error.as_ref().and_then(|e| e.analysis.as_ref()) else {
+            continue;
+        };
+        let (resource, value, previous) = match analysis.mode {
+            FailureMode::OutOfMemory => match analysis.suggested_mem_mb {
+                Some(mb) => ("mem_mb", mb, analysis.memory_limit_mb),
+                None => continue,
+            },
+            // Snakemake's runtime resource is in minutes
+            FailureMode::Timeout => match analysis.suggested_runtime_seconds {
+                Some(secs) => (
+                    "runtime",
+                    secs.div_ceil(60),
+                    analysis.time_limit_seconds.map(|s| s.div_ceil(60)),
+                ),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        let bump = bumps
+            .entry((job.rule.clone(), resource))
+            .or_insert_with(|| ResourceBump {
+                rule: job.rule.clone(),
+                resource: resource.to_string(),
+                value,
+                previous,
+            });
+        if value > bump.value {
+            bump.value = value;
+            bump.previous = previous;
+        }
+    }
+    bumps.into_values().collect()
+}
+
+/// First line of an overlay config written by charmer.
+const HEADER: &str = "# Written by charmer: resource increases for failed jobs.";
+
+/// The charmer-managed snakemake workflow profile holding resource overrides.
+pub struct ProfileOverlay {
+    working_dir: Utf8PathBuf,
+    dir: Utf8PathBuf,
+}
+
+impl ProfileOverlay {
+    /// Create an overlay for the given working directory.
+    ///
+    /// The profile is stored at `.snakemake/charmer/profile/config.yaml`.
+    pub fn new(working_dir: &Utf8Path) -> Self {
+        Self {
+            working_dir: working_dir.to_path_buf(),
+            dir: working_dir
+                .join(".snakemake")
+                .join("charmer")
+                .join("profile"),
+        }
+    }
+
+    /// Get the profile directory (the `--workflow-profile` argument).
+    pub fn dir(&self) -> &Utf8Path {
+        &self.dir
+    }
+
+    /// Get the path to the profile's config file.
+    pub fn config_path(&self) -> Utf8PathBuf {
+        self.dir.join("config.yaml")
+    }
+
+    /// Get the path the output of a detached rerun is written to.
+    pub fn log_path(&self) -> Utf8PathBuf {
+        self.dir.join("rerun.log")
+    }
+
+    /// Merge bumps into the overlay and write it out.
+    ///
+    /// Resources for other rules, and an existing value that is already
+    /// larger, are kept; so are the file's other settings, though not its
+    /// comments. The overlay replaces the run's workflow profile
+    /// (`workflow_profile`, or snakemake's default `profiles/default`), so a
+    /// new overlay starts from its settings. `set-resources` is read in
+    /// either form snakemake accepts and written as a mapping.
+    /// Returns the numeric entries now in the overlay.
+    pub fn write(
+        &self,
+        bumps: &[ResourceBump],
+        workflow_profile: Option<&str>,
+    ) -> io::Result<Vec<ResourceBump>> {
+        let existing = match read_config(&self.config_path())? {
+            Some(content) => content,
+            None => {
+                let base = self
+                    .working_dir
+                    .join(workflow_profile.unwrap_or("profiles/default"))
+                    .join("config.yaml");
+                read_config(&base)?.unwrap_or_default()
+            }
+        };
+
+        let mut config: Mapping = match serde_yaml::from_str(&existing) {
+            Ok(Value::Mapping(config)) => config,
+            Ok(Value::Null) => Mapping::new(),
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "profile config is not a mapping",
+                ));
+            }
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        let mut resources = config
+            .get("set-resources")
+            .map(parse_resources)
+            .unwrap_or_default();
+        for bump in bumps {
+            let value = resources
+                .entry(bump.rule.clone())
+                .or_default()
+                .entry(bump.resource.clone())
+                .or_insert(Value::Null);
+            // A value that isn't a plain number ("2h") can't be compared
+            if numeric(value).is_none_or(|existing| existing < bump.value) {
+                *value = Value::from(bump.value);
+            }
+        }
+        let entries = resources
+            .iter()
+            .flat_map(|(rule, settings)| {
+                settings.iter().filter_map(|(resource, value)| {
+                    Some(ResourceBump {
+                        rule: rule.clone(),
+                        resource: resource.clone(),
+                        value: numeric(value)?,
+                        previous: None,
+                    })
+                })
+            })
+            .collect();
+        let resources = serde_yaml::to_value(resources).map_err(io::Error::other)?;
+        config.insert(Value::from("set-resources"), resources);
+
+        let yaml = serde_yaml::to_string(&config).map_err(io::Error::other)?;
+        let content = format!(
+            "{}\n# Rerun with: {}\n{}",
+            HEADER,
+            self.rerun_command(&[]),
+            yaml
+        );
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.config_path(), content)?;
+        Ok(entries)
+    }
+
+    /// Arguments to snakemake that rerun incomplete jobs with the overlay:
+    /// the run's own `args` (targets, snakefile, profiles, executor, ...)
+    /// with the overlay in place of any workflow profile they named.
+    pub fn rerun_args(&self, args: &[String]) -> Vec<String> {
+        let mut rerun = Vec::with_capacity(args.len() + 3);
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--workflow-profile" {
+                iter.next();
+            } else if !arg.starts_with("--workflow-profile=") {
+                rerun.push(arg.clone());
+            }
+        }
+        if !rerun
+            .iter()
+            .any(|a| a == "--rerun-incomplete" || a == "--ri")
+        {
+            rerun.push("--rerun-incomplete".to_string());
+        }
+        rerun.push("--workflow-profile".to_string());
+        rerun.push(self.dir.to_string());
+        rerun
+    }
+
+    /// The full command line, for display.
+    pub fn rerun_command(&self, args: &[String]) -> String {
+        format!("snakemake {}", self.rerun_args(args).join(" "))
+    }
+}
+
+/// Value of a snakemake option (`--name value` or `--name=value`) in `args`.
+pub fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.strip_prefix(name)? {
+            "" => args.get(i + 1).map(String::as_str),
+            value => value.strip_prefix('='),
+        })
+}
+
+/// Content of a profile config, or `None` if there is none.
+fn read_config(path: &Utf8Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Resources per rule from a `set-resources` setting, given either as a
+/// mapping (`align: {mem_mb: 8000}`) or as a list of `"align:mem_mb=8000"`.
+fn parse_resources(value: &Value) -> BTreeMap<String, BTreeMap<String, Value>> {
+    let mut resources: BTreeMap<String, BTreeMap<String, Value>> = BTreeMap::new();
+    match value {
+        Value::Mapping(rules) => {
+            for (rule, settings) in rules {
+                let (Some(rule), Value::Mapping(settings)) = (rule.as_str(), settings) else {
+                    continue;
+                };
+                let rule = resources.entry(rule.to_string()).or_default();
+                for (resource, value) in settings {
+                    if let Some(resource) = resource.as_str() {
+                        rule.insert(resource.to_string(), value.clone());
+                    }
+                }
+            }
+        }
+        Value::Sequence(entries) => {
+            for entry in entries.iter().filter_map(Value::as_str) {
+                let Some((rule, setting)) = entry.split_once(':') else {
+                    continue;
+                };
+                let Some((resource, value)) = setting.split_once('=') else {
+                    continue;
+                };
+                let value = match value.parse::<u64>() {
+                    Ok(n) => Value::from(n),
+                    Err(_) => Value::from(value),
+                };
+                resources
+                    .entry(rule.to_string())
+                    .or_default()
+                    .insert(resource.to_string(), value);
+            }
+        }
+        _ => {}
+    }
+    resources
+}
+
+/// A resource value as a whole number, if it is one.
+fn numeric(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FailureAnalysis, JobError};
+    use tempfile::TempDir;
+
+    fn fail(state: &mut PipelineState, rule: &str, analysis: FailureAnalysis) {
+        state.sync_unscheduled_placeholders(rule, 1);
+        let job = state
+            .jobs
+            .values_mut()
+            .find(|job| job.rule == rule && job.error.is_none())
+            .unwrap();
+        job.status = JobStatus::Failed;
+        job.error = Some(JobError {
+            exit_code: 1,
+            message: String::new(),
+            analysis: Some(analysis),
+        });
+    }
+
+    fn analysis(mode: FailureMode) -> FailureAnalysis {
+        FailureAnalysis {
+            mode,
+            explanation: String::new(),
+            suggestion: String::new(),
+            exit_code: None,
+            memory_used_mb: None,
+            memory_limit_mb: None,
+            runtime_seconds: None,
+            time_limit_seconds: None,
+            suggested_mem_mb: None,
+            suggested_runtime_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_suggested_bumps_and_overlay() {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(temp.path()).unwrap();
+        let mut state = PipelineState::new(dir.to_path_buf());
+        fail(
+            &mut state,
+            "align",
+            FailureAnalysis {
+                memory_limit_mb: Some(8192),
+                suggested_mem_mb: Some(16384),
+                ..analysis(FailureMode::OutOfMemory)
+            },
+        );
+        fail(
+            &mut state,
+            "sort",
+            FailureAnalysis {
+                time_limit_seconds: Some(3600),
+                suggested_runtime_seconds: Some(7230),
+                ..analysis(FailureMode::Timeout)
+            },
+        );
+        fail(&mut state, "call", analysis(FailureMode::ExitCode));
+
+        let bumps = suggested_bumps(&state);
+        assert_eq!(
+            bumps.iter().map(|b| b.entry()).collect::<Vec<_>>(),
+            vec!["align:mem_mb=16384", "sort:runtime=121"]
+        );
+        assert_eq!(bumps[0].previous, Some(8192));
+        assert_eq!(bumps[1].previous, Some(60));
+
+        // The workflow's default profile is the starting point
+        fs::create_dir_all(dir.join("profiles/default")).unwrap();
+        fs::write(
+            dir.join("profiles/default/config.yaml"),
+            "printshellcmds: true\n",
+        )
+        .unwrap();
+
+        let overlay = ProfileOverlay::new(dir);
+        overlay.write(&bumps, None).unwrap();
+
+        // Settings added by hand survive rewrites
+        let config = fs::read_to_string(overlay.config_path()).unwrap();
+        fs::write(
+            overlay.config_path(),
+            format!("{}executor: slurm\njobs: 100\n", config),
+        )
+        .unwrap();
+
+        // A later, smaller suggestion doesn't lower the overlay
+        let entries = overlay
+            .write(
+                &[ResourceBump {
+                    rule: "align".to_string(),
+                    resource: "mem_mb".to_string(),
+                    value: 12000,
+                    previous: None,
+                }],
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            entries.iter().map(|b| b.entry()).collect::<Vec<_>>(),
+            vec!["align:mem_mb=16384", "sort:runtime=121"]
+        );
+
+        let config = fs::read_to_string(overlay.config_path()).unwrap();
+        assert_eq!(config.matches("# Rerun with").count(), 1);
+        let config: Value = serde_yaml::from_str(&config).unwrap();
+        assert_eq!(config["printshellcmds"], Value::from(true));
+        assert_eq!(config["executor"], Value::from("slurm"));
+        assert_eq!(config["jobs"], Value::from(100));
+        assert_eq!(
+            config["set-resources"]["align"]["mem_mb"],
+            Value::from(16384)
+        );
+        // The rerun keeps the run's arguments, swapping in the overlay
+        let args: Vec<String> = [
+            "-s",
+            "wf/Snakefile",
+            "--profile",
+            "slurm",
+            "--workflow-profile=wf/profile",
+            "all",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        assert_eq!(
+            overlay.rerun_command(&args),
+            format!(
+                "snakemake -s wf/Snakefile --profile slurm all --rerun-incomplete --workflow-profile {}",
+                overlay.dir()
+            )
+        );
+        // Rerunning a rerun doesn't pile up options
+        assert_eq!(
+            overlay.rerun_args(&overlay.rerun_args(&args)),
+            overlay.rerun_args(&args)
+        );
+    }
+
+    #[test]
+    fn test_overlay_merges_base_resources() {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(temp.path()).unwrap();
+        fs::create_dir_all(dir.join("wf")).unwrap();
+        fs::write(
+            dir.join("wf/config.yaml"),
+            "executor: slurm\n\
+             set-resources:\n  \
+               align:\n    \
+                 mem_mb: 8000\n    \
+                 slurm_partition: long\n  \
+               sort:\n    \
+                 runtime: 600\n",
+        )
+        .unwrap();
+
+        let bump = |rule: &str, resource: &str, value| ResourceBump {
+            rule: rule.to_string(),
+            resource: resource.to_string(),
+            value,
+            previous: None,
+        };
+        let overlay = ProfileOverlay::new(dir);
+        let entries = overlay
+            .write(
+                &[bump("align", "mem_mb", 16384), bump("sort", "runtime", 120)],
+                Some("wf"),
+            )
+            .unwrap();
+        assert_eq!(
+            entries.iter().map(|b| b.entry()).collect::<Vec<_>>(),
+            vec!["align:mem_mb=16384", "sort:runtime=600"]
+        );
+
+        let config: Value =
+            serde_yaml::from_str(&fs::read_to_string(overlay.config_path()).unwrap()).unwrap();
+        assert_eq!(config["executor"], Value::from("slurm"));
+        let resources = &config["set-resources"];
+        assert_eq!(resources["align"]["mem_mb"], Value::from(16384));
+        assert_eq!(resources["align"]["slurm_partition"], Value::from("long"));
+        assert_eq!(resources["sort"]["runtime"], Value::from(600));
+
+        // The list form is read too
+        let mut resources = parse_resources(
+            &serde_yaml::from_str("[\"align:mem_mb=8000\", \"align:slurm_partition=long\"]")
+                .unwrap(),
+        );
+        let align = resources.remove("align").unwrap();
+        assert_eq!(align["mem_mb"], Value::from(8000));
+        assert_eq!(align["slurm_partition"], Value::from("long"));
+    }
+
+    #[test]
+    fn test_option_value() {
+        let args: Vec<String> = ["-j", "10", "--profile", "slurm", "--workflow-profile=wf"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert_eq!(option_value(&args, "--profile"), Some("slurm"));
+        assert_eq!(option_value(&args, "--workflow-profile"), Some("wf"));
+        assert_eq!(option_value(&args, "--configfile"), None);
+        assert_eq!(option_value(&args[..3], "--profile"), None);
+    }
+}
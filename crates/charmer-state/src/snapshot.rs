@@ -0,0 +1,261 @@
+//! Persistent pipeline state snapshots.
+//!
+//! A snapshot lets charmer show the last known state immediately on restart,
+//! including failure analyses and resource usage that would otherwise have to
+//! be re-queried from the scheduler. What the run's main log or event stream
+//! said (job blocks, errors, completion) is not kept; it is read again.
+
+use crate::types::PipelineState;
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::time::SystemTime;
+use thiserror::Error;
+
+/// Bumped whenever the snapshot layout changes incompatibly.
+const SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Snapshot as written to disk.
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    saved_at: DateTime<Utc>,
+    /// Keyed by path relative to the working directory
+    metadata_mtimes: HashMap<String, SystemTime>,
+    state: &'a PipelineState,
+}
+
+/// Snapshot as read back from disk.
+#[derive(Deserialize)]
+struct Snapshot {
+    version: u32,
+    metadata_mtimes: HashMap<String, SystemTime>,
+    state: PipelineState,
+}
+
+/// Persistent storage for pipeline state snapshots.
+pub struct SnapshotStore {
+    working_dir: Utf8PathBuf,
+    path: Utf8PathBuf,
+}
+
+impl SnapshotStore {
+    /// Create a store for the given working directory.
+    ///
+    /// Snapshots are stored at `.snakemake/charmer/state.json` within the working directory.
+    pub fn new(working_dir: &Utf8Path) -> Self {
+        let path = working_dir
+            .join(".snakemake")
+            .join("charmer")
+            .join("state.json");
+        Self {
+            working_dir: working_dir.to_path_buf(),
+            path,
+        }
+    }
+
+    /// Get the path to the snapshot file.
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Load the last snapshot, with its metadata mtime cache restored.
+    ///
+    /// Paths are stored relative to the working directory, so a snapshot
+    /// still loads after the directory was moved or copied. Returns `None`
+    /// if there is no snapshot or it no longer matches the working
+    /// directory: written by another version, or listing metadata files that
+    /// have since been removed. Files that merely changed are re-parsed by
+    /// the next incremental scan.
+    pub fn load(&self) -> Result<Option<PipelineState>, SnapshotError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.path)?;
+        let snapshot: Snapshot = serde_json::from_str(&content)?;
+
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Ok(None);
+        }
+        let metadata_mtimes: HashMap<String, SystemTime> = snapshot
+            .metadata_mtimes
+            .into_iter()
+            .map(|(path, mtime)| (self.working_dir.join(path).into_string(), mtime))
+            .collect();
+        if metadata_mtimes
+            .keys()
+            .any(|path| !Utf8Path::new(path).exists())
+        {
+            return Ok(None);
+        }
+
+        let mut state = snapshot.state;
+        state.working_dir = self.working_dir.clone();
+        state.metadata_mtime_cache = metadata_mtimes;
+        Ok(Some(state))
+    }
+
+    /// Save a snapshot of the state to disk.
+    ///
+    /// Creates parent directories if needed. The file is replaced atomically
+    /// so a crash mid-write leaves the previous snapshot intact.
+    pub fn save(&self, state: &PipelineState) -> Result<(), SnapshotError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let metadata_mtimes = state
+            .metadata_mtime_cache
+            .iter()
+            .map(|(path, mtime)| {
+                let path = Utf8Path::new(path);
+                let relative = path.strip_prefix(&state.working_dir).unwrap_or(path);
+                (relative.to_string(), *mtime)
+            })
+            .collect();
+        let snapshot = SnapshotRef {
+            version: SNAPSHOT_VERSION,
+            saved_at: Utc::now(),
+            metadata_mtimes,
+            state,
+        };
+        let content = serde_json::to_string(&snapshot)?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Remove the snapshot, if any.
+    pub fn clear(&self) -> Result<(), SnapshotError> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FailureAnalysis, FailureMode, JobError, ResourceUsage};
+    use tempfile::TempDir;
+
+    fn temp_dir(temp: &TempDir) -> Utf8PathBuf {
+        Utf8Path::from_path(temp.path()).unwrap().to_path_buf()
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let working_dir = temp_dir(&temp);
+        let metadata_dir = working_dir.join(".snakemake").join("metadata");
+        fs::create_dir_all(&metadata_dir).unwrap();
+        let metadata_file = metadata_dir.join("cmVzdWx0cy9hLnR4dA==");
+        fs::write(&metadata_file, "{}").unwrap();
+
+        let mut state = PipelineState::new(working_dir.clone());
+        state.sync_unscheduled_placeholders("align", 1);
+        let job = state.jobs.values_mut().next().unwrap();
+        job.usage = Some(ResourceUsage {
+            max_rss_mb: Some(512),
+            ..Default::default()
+        });
+        job.error = Some(JobError {
+            exit_code: 1,
+            message: "exit 1".to_string(),
+            analysis: Some(FailureAnalysis {
+                mode: FailureMode::OutOfMemory,
+                explanation: "killed".to_string(),
+                suggestion: "more memory".to_string(),
+                exit_code: Some(137),
+                memory_used_mb: Some(512),
+                memory_limit_mb: Some(512),
+                runtime_seconds: None,
+                time_limit_seconds: None,
+                suggested_mem_mb: None,
+                suggested_runtime_seconds: None,
+            }),
+        });
+        state
+            .metadata_mtime_cache
+            .insert(metadata_file.to_string(), SystemTime::now());
+        // Run-scoped information is read again from the log
+        state.log_jobs.insert(3, Default::default());
+        state.external_ids.insert("4102".to_string(), 3);
+        state.has_logger_events = true;
+        state.set_process_exit(1, &[]);
+
+        let store = SnapshotStore::new(&working_dir);
+        store.save(&state).unwrap();
+        assert!(store.path().exists());
+
+        let loaded = store.load().unwrap().unwrap();
+        assert_eq!(loaded.jobs.len(), 1);
+        let job = loaded.jobs.values().next().unwrap();
+        assert_eq!(job.usage.as_ref().unwrap().max_rss_mb, Some(512));
+        assert_eq!(
+            job.error.as_ref().unwrap().analysis.as_ref().unwrap().mode,
+            FailureMode::OutOfMemory
+        );
+        assert!(loaded.log_jobs.is_empty());
+        assert!(loaded.external_ids.is_empty());
+        assert!(!loaded.has_logger_events);
+        assert!(!loaded.pipeline_finished);
+        assert!(loaded.pipeline_errors.is_empty());
+        assert_eq!(
+            loaded.metadata_mtime_cache.keys().collect::<Vec<_>>(),
+            vec![&metadata_file.to_string()]
+        );
+
+        // Metadata cleaned up since the snapshot: start over
+        fs::remove_file(&metadata_file).unwrap();
+        assert!(store.load().unwrap().is_none());
+
+        store.clear().unwrap();
+        store.clear().unwrap();
+        assert!(!store.path().exists());
+    }
+
+    #[test]
+    fn test_snapshot_moved_working_dir() {
+        let temp = TempDir::new().unwrap();
+        let original = temp_dir(&temp).join("original");
+        let metadata_dir = original.join(".snakemake").join("metadata");
+        fs::create_dir_all(&metadata_dir).unwrap();
+        let metadata_file = metadata_dir.join("cmVzdWx0cy9hLnR4dA==");
+        fs::write(&metadata_file, "{}").unwrap();
+
+        let mut state = PipelineState::new(original.clone());
+        state
+            .metadata_mtime_cache
+            .insert(metadata_file.to_string(), SystemTime::now());
+        SnapshotStore::new(&original).save(&state).unwrap();
+
+        // The whole directory is moved, e.g. copied off the cluster
+        let moved = temp_dir(&temp).join("moved");
+        fs::rename(&original, &moved).unwrap();
+
+        let loaded = SnapshotStore::new(&moved).load().unwrap().unwrap();
+        assert_eq!(loaded.working_dir, moved);
+        assert_eq!(
+            loaded.metadata_mtime_cache.keys().collect::<Vec<_>>(),
+            vec![
+                &moved
+                    .join(".snakemake")
+                    .join("metadata")
+                    .join("cmVzdWx0cy9hLnR4dA==")
+                    .to_string()
+            ]
+        );
+    }
+}
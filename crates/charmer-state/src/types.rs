@@ -1,5 +1,6 @@
 //! Unified job and pipeline state types.
 
+use crate::events::{EventLog, StateEventKind};
 use camino::Utf8PathBuf;
 use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
@@ -8,6 +9,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 
+pub use charmer_core::LogJob;
+pub use charmer_scheduler::{ArrayTask, FailureAnalysis, FailureMode, JobAction, ResourceUsage};
+
 // Pre-compiled regex patterns for environment detection
 static PIXI_ENV_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"pixi\s+run\s+(?:-e|--environment)\s+(\S+)").unwrap());
@@ -29,6 +33,9 @@ static EXIT_CODE_RE: Lazy<Regex> = Lazy::new(|| {
 /// Special job ID for the main snakemake pipeline log.
 pub const MAIN_PIPELINE_JOB_ID: &str = "__snakemake_main__";
 
+/// Prefix of placeholder jobs that "Job stats" counts but the log hasn't scheduled yet.
+pub const UNSCHEDULED_JOB_PREFIX: &str = "__pending_";
+
 /// Unified job status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JobStatus {
@@ -57,63 +64,25 @@ pub trait ToJobStatus {
     fn to_job_error(&self) -> Option<JobError>;
 }
 
-// Implementation for SLURM job states
-impl ToJobStatus for charmer_slurm::SlurmJobState {
+// Implementation for scheduler-neutral job states
+impl ToJobStatus for charmer_scheduler::SchedulerJobState {
     fn to_job_status(&self) -> JobStatus {
         match self {
             Self::Pending => JobStatus::Queued,
             Self::Running => JobStatus::Running,
-            Self::Completed { .. } => JobStatus::Completed,
+            Self::Suspended => JobStatus::Pending,
+            Self::Completed => JobStatus::Completed,
             Self::Failed { .. } => JobStatus::Failed,
             Self::Cancelled => JobStatus::Cancelled,
-            Self::Timeout => JobStatus::Failed,
-            Self::OutOfMemory => JobStatus::Failed,
             Self::Unknown(_) => JobStatus::Unknown,
         }
     }
 
     fn to_job_error(&self) -> Option<JobError> {
         match self {
-            Self::Failed { exit_code, error } => Some(JobError {
+            Self::Failed { exit_code, message } => Some(JobError {
                 exit_code: *exit_code,
-                message: error.clone(),
-                analysis: None, // Will be populated by failure analysis
-            }),
-            Self::Timeout => Some(JobError {
-                exit_code: -1,
-                message: "Job exceeded time limit".to_string(),
-                analysis: None,
-            }),
-            Self::OutOfMemory => Some(JobError {
-                exit_code: -1,
-                message: "Job exceeded memory limit".to_string(),
-                analysis: None,
-            }),
-            _ => None,
-        }
-    }
-}
-
-// Implementation for LSF job states
-impl ToJobStatus for charmer_lsf::LsfJobState {
-    fn to_job_status(&self) -> JobStatus {
-        match self {
-            Self::Pending => JobStatus::Queued,
-            Self::Running => JobStatus::Running,
-            Self::Done { .. } => JobStatus::Completed,
-            Self::Exit { .. } => JobStatus::Failed,
-            Self::UserSuspendedPending | Self::UserSuspended => JobStatus::Pending,
-            Self::SystemSuspended => JobStatus::Pending,
-            Self::Zombie => JobStatus::Unknown,
-            Self::Unknown(_) => JobStatus::Unknown,
-        }
-    }
-
-    fn to_job_error(&self) -> Option<JobError> {
-        match self {
-            Self::Exit { exit_code, error } => Some(JobError {
-                exit_code: *exit_code,
-                message: error.clone(),
+                message: message.clone(),
                 analysis: None, // Will be populated by failure analysis
             }),
             _ => None,
@@ -141,6 +110,9 @@ pub struct JobTiming {
     pub queued_at: Option<DateTime<Utc>>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// Scheduler's estimated start time while queued
+    #[serde(default)]
+    pub estimated_start: Option<DateTime<Utc>>,
 }
 
 /// Job resource allocation (requested).
@@ -151,17 +123,12 @@ pub struct JobResources {
     pub time_limit: Option<Duration>,
     pub partition: Option<String>,
     pub node: Option<String>,
-}
-
-/// Actual resource usage (from sacct/bhist for finished jobs).
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct ResourceUsage {
-    /// Maximum resident set size (actual memory used) in MB
-    pub max_rss_mb: Option<u64>,
-    /// Actual elapsed runtime in seconds
-    pub elapsed_seconds: Option<u64>,
-    /// Total CPU time in seconds
-    pub cpu_time_seconds: Option<u64>,
+    /// Why the scheduler hasn't started the job yet
+    #[serde(default)]
+    pub pending_reason: Option<String>,
+    /// Scheduling priority
+    #[serde(default)]
+    pub priority: Option<u64>,
 }
 
 /// Job error information.
@@ -173,42 +140,6 @@ pub struct JobError {
     pub analysis: Option<FailureAnalysis>,
 }
 
-/// Detailed failure analysis from SLURM/LSF.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FailureAnalysis {
-    /// Classified failure mode
-    pub mode: FailureMode,
-    /// Human-readable explanation
-    pub explanation: String,
-    /// Suggested fix
-    pub suggestion: String,
-    /// Memory used (MB) if available
-    pub memory_used_mb: Option<u64>,
-    /// Memory limit (MB) if available
-    pub memory_limit_mb: Option<u64>,
-    /// Runtime (seconds) if available
-    pub runtime_seconds: Option<u64>,
-    /// Time limit (seconds) if available
-    pub time_limit_seconds: Option<u64>,
-}
-
-/// Failure mode classification.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub enum FailureMode {
-    /// Job ran out of memory
-    OutOfMemory,
-    /// Job exceeded time limit
-    Timeout,
-    /// Job failed with exit code
-    ExitCode,
-    /// Job was cancelled/killed
-    Cancelled,
-    /// Node/host failure
-    NodeFailure,
-    /// Unknown failure
-    Unknown,
-}
-
 /// Execution environment type.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EnvType {
@@ -454,6 +385,73 @@ pub struct DataSources {
     pub has_slurm_sacct: bool,
     pub has_lsf_bjobs: bool,
     pub has_lsf_bhist: bool,
+    #[serde(default)]
+    pub has_pbs_qstat: bool,
+    #[serde(default)]
+    pub has_pbs_history: bool,
+    #[serde(default)]
+    pub has_sge_qstat: bool,
+    #[serde(default)]
+    pub has_sge_qacct: bool,
+    #[serde(default)]
+    pub has_condor_q: bool,
+    #[serde(default)]
+    pub has_condor_history: bool,
+    #[serde(default)]
+    pub has_local_proc: bool,
+    #[serde(default)]
+    pub has_main_log: bool,
+}
+
+impl DataSources {
+    /// Whether any scheduler query has reported this job.
+    pub fn has_scheduler(&self) -> bool {
+        self.has_slurm_squeue
+            || self.has_slurm_sacct
+            || self.has_lsf_bjobs
+            || self.has_lsf_bhist
+            || self.has_pbs_qstat
+            || self.has_pbs_history
+            || self.has_sge_qstat
+            || self.has_sge_qacct
+            || self.has_condor_q
+            || self.has_condor_history
+    }
+
+    /// Combine flags from another entry for the same job.
+    pub fn merge(&mut self, other: &DataSources) {
+        self.has_snakemake_metadata |= other.has_snakemake_metadata;
+        self.has_slurm_squeue |= other.has_slurm_squeue;
+        self.has_slurm_sacct |= other.has_slurm_sacct;
+        self.has_lsf_bjobs |= other.has_lsf_bjobs;
+        self.has_lsf_bhist |= other.has_lsf_bhist;
+        self.has_pbs_qstat |= other.has_pbs_qstat;
+        self.has_pbs_history |= other.has_pbs_history;
+        self.has_sge_qstat |= other.has_sge_qstat;
+        self.has_sge_qacct |= other.has_sge_qacct;
+        self.has_condor_q |= other.has_condor_q;
+        self.has_condor_history |= other.has_condor_history;
+        self.has_local_proc |= other.has_local_proc;
+        self.has_main_log |= other.has_main_log;
+    }
+
+    /// Record that a scheduler query reported this job.
+    pub fn mark_scheduler(&mut self, scheduler: &str, from_history: bool) {
+        match (scheduler, from_history) {
+            ("slurm", false) => self.has_slurm_squeue = true,
+            ("slurm", true) => self.has_slurm_sacct = true,
+            ("lsf", false) => self.has_lsf_bjobs = true,
+            ("lsf", true) => self.has_lsf_bhist = true,
+            ("pbs", false) => self.has_pbs_qstat = true,
+            ("pbs", true) => self.has_pbs_history = true,
+            ("sge", false) => self.has_sge_qstat = true,
+            ("sge", true) => self.has_sge_qacct = true,
+            ("htcondor", false) => self.has_condor_q = true,
+            ("htcondor", true) => self.has_condor_history = true,
+            ("local", _) => self.has_local_proc = true,
+            _ => {}
+        }
+    }
 }
 
 /// Unified job combining SLURM and snakemake data.
@@ -480,6 +478,10 @@ pub struct Job {
     /// Scheduler job ID (SLURM or LSF job ID, if submitted)
     pub scheduler_job_id: Option<String>,
 
+    /// Process ID (local executor jobs while running)
+    #[serde(default)]
+    pub local_pid: Option<u32>,
+
     /// Shell command
     pub shellcmd: String,
 
@@ -512,6 +514,97 @@ pub struct Job {
 
     /// Whether this job is from snakemake (has snakemake metadata or rule_ prefix in scheduler comment)
     pub is_snakemake_job: bool,
+
+    /// Array job membership, if the scheduler ran this as an array task
+    #[serde(default)]
+    pub array: Option<ArrayTask>,
+
+    /// Scheduler submissions, oldest first (Snakemake resubmits failed jobs
+    /// with `--retries`). The job's own fields describe the latest one.
+    #[serde(default)]
+    pub attempts: Vec<JobAttempt>,
+}
+
+/// One scheduler submission of a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobAttempt {
+    /// Scheduler job ID of this submission
+    pub scheduler_job_id: String,
+    /// Status of this submission
+    pub status: JobStatus,
+    /// Resources requested for this submission
+    pub resources: JobResources,
+    /// Timing of this submission
+    pub timing: JobTiming,
+    /// Error details, with failure analysis once queried
+    pub error: Option<JobError>,
+}
+
+impl Job {
+    /// Record a scheduler submission, updating it if already known.
+    ///
+    /// Returns whether it is the latest attempt, i.e. whether the job's own
+    /// fields should follow it.
+    pub fn record_attempt(&mut self, mut attempt: JobAttempt) -> bool {
+        let id = attempt.scheduler_job_id.clone();
+        match self.attempts.iter_mut().find(|a| a.scheduler_job_id == id) {
+            Some(existing) => {
+                // Keep what later polls don't repeat
+                if let (Some(new), Some(old)) = (attempt.error.as_mut(), existing.error.take())
+                    && new.analysis.is_none()
+                {
+                    new.analysis = old.analysis;
+                }
+                if attempt.timing.queued_at.is_none() {
+                    attempt.timing.queued_at = existing.timing.queued_at;
+                }
+                *existing = attempt;
+            }
+            None => {
+                self.attempts.push(attempt);
+                // Scheduler IDs increase with each submission
+                self.attempts.sort_by_key(|a| {
+                    (
+                        leading_number(&a.scheduler_job_id),
+                        a.scheduler_job_id.clone(),
+                    )
+                });
+            }
+        }
+        self.attempts
+            .last()
+            .is_some_and(|a| a.scheduler_job_id == id)
+    }
+
+    /// Attach failure analysis to the attempt with this scheduler job ID.
+    pub fn set_failure_analysis(&mut self, scheduler_job_id: &str, analysis: FailureAnalysis) {
+        let attach = |error: &mut Option<JobError>| match error {
+            Some(error) => error.analysis = Some(analysis.clone()),
+            None => {
+                *error = Some(JobError {
+                    exit_code: analysis.exit_code.unwrap_or(-1),
+                    message: analysis.explanation.clone(),
+                    analysis: Some(analysis.clone()),
+                })
+            }
+        };
+        if let Some(attempt) = self
+            .attempts
+            .iter_mut()
+            .find(|a| a.scheduler_job_id == scheduler_job_id)
+        {
+            attach(&mut attempt.error);
+        }
+        if self.scheduler_job_id.as_deref() == Some(scheduler_job_id) {
+            attach(&mut self.error);
+        }
+    }
+}
+
+/// Numeric prefix of a scheduler job ID ("12345", "12345_7", "12345.server").
+pub(crate) fn leading_number(id: &str) -> u64 {
+    let digits = id.len() - id.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    id[..digits].parse().unwrap_or(0)
 }
 
 /// Pipeline-level state.
@@ -541,10 +634,13 @@ pub struct PipelineState {
     /// Host machine name
     pub host: Option<String>,
 
-    /// Whether the pipeline has finished
+    /// Whether the pipeline has finished. Not persisted in snapshots.
+    #[serde(skip)]
     pub pipeline_finished: bool,
 
-    /// Pipeline-level errors from main log (structured)
+    /// Pipeline-level errors from main log (structured). Not persisted in
+    /// snapshots.
+    #[serde(skip)]
     pub pipeline_errors: Vec<PipelineError>,
 
     /// Generation counter, incremented on any mutation.
@@ -552,6 +648,37 @@ pub struct PipelineState {
     #[serde(default)]
     pub generation: u64,
 
+    /// Retired job IDs mapped to the entry they were correlated into
+    #[serde(default)]
+    pub job_aliases: HashMap<String, String>,
+
+    /// Job blocks from the main log, by snakemake job ID. Not persisted in
+    /// snapshots: the log is read again.
+    #[serde(skip)]
+    pub log_jobs: HashMap<u64, LogJob>,
+
+    /// Snakemake job ID per output file (from the main log)
+    #[serde(skip)]
+    pub log_job_by_output: HashMap<String, u64>,
+
+    /// Snakemake job ID per external (scheduler) job ID (from the main log)
+    #[serde(skip)]
+    pub external_ids: HashMap<String, u64>,
+
+    /// Whether structured logger events have been received. They take
+    /// precedence over the text of the main log. Not persisted in snapshots.
+    #[serde(skip)]
+    pub has_logger_events: bool,
+
+    /// Recent changes, for consumers that react to transitions
+    #[serde(default)]
+    pub events: EventLog,
+
+    /// Exit status of the snakemake process charmer started, once it exited.
+    /// The run stays finished after that, whatever the log says.
+    #[serde(skip)]
+    pub process_exit: Option<i32>,
+
     /// Mtime cache for metadata files (path -> last known mtime).
     /// Used for incremental scanning. Not serialized.
     #[serde(skip)]
@@ -572,14 +699,52 @@ impl PipelineState {
             pipeline_finished: false,
             pipeline_errors: Vec::new(),
             generation: 0,
+            job_aliases: HashMap::new(),
+            log_jobs: HashMap::new(),
+            log_job_by_output: HashMap::new(),
+            external_ids: HashMap::new(),
+            has_logger_events: false,
+            events: EventLog::default(),
+            process_exit: None,
             metadata_mtime_cache: HashMap::new(),
         }
     }
 
     /// Update pipeline state from snakemake log info.
+    ///
+    /// Once structured logger events have been received, they decide job
+    /// status and progress; the rest of the log still applies.
     pub fn update_from_log_info(&mut self, info: &charmer_core::SnakemakeLogInfo) {
-        if info.total_jobs.is_some() {
-            self.total_jobs = info.total_jobs;
+        for log_job in &info.jobs {
+            self.record_log_job(log_job);
+        }
+        self.external_ids.extend(
+            info.external_ids
+                .iter()
+                .map(|(external, jobid)| (external.clone(), *jobid)),
+        );
+        // New submission lines may pair up scheduler and metadata entries
+        crate::merge::correlate_jobs(self);
+
+        self.update_log_summary(info);
+    }
+
+    /// Update the run-level information of snakemake log info: totals,
+    /// host, errors, completion and target rules.
+    ///
+    /// Job blocks are not indexed here; a followed log applies them as they
+    /// arrive with `merge_log_events`. Totals, completion and job status
+    /// are left to structured logger events once any have been received.
+    pub fn update_log_summary(&mut self, info: &charmer_core::SnakemakeLogInfo) {
+        let structured = self.has_logger_events;
+        if !structured {
+            if info.total_jobs.is_some() {
+                self.total_jobs = info.total_jobs;
+            }
+            // Logs without a "Job stats" table still list each scheduled job
+            if !info.jobs.is_empty() {
+                self.total_jobs = Some(self.total_jobs.unwrap_or(0).max(info.jobs.len()));
+            }
         }
         if info.cores.is_some() {
             self.cores = info.cores;
@@ -587,10 +752,36 @@ impl PipelineState {
         if info.host.is_some() {
             self.host = info.host.clone();
         }
-        self.pipeline_finished = info.finished;
         if !info.errors.is_empty() {
-            self.pipeline_errors = info.errors.iter().map(|s| parse_error_string(s)).collect();
+            let errors = info.errors.iter().map(|s| parse_error_string(s));
+            if structured {
+                // Keep the errors the logger reported
+                for error in errors {
+                    if !self
+                        .pipeline_errors
+                        .iter()
+                        .any(|e| e.message == error.message)
+                    {
+                        self.pipeline_errors.push(error);
+                    }
+                }
+            } else {
+                self.pipeline_errors = errors.collect();
+            }
         }
+        let finished = if structured {
+            self.pipeline_finished
+        } else {
+            self.set_pipeline_finished(info.finished);
+            info.finished
+        };
+        let target_status = if finished && self.pipeline_errors.is_empty() {
+            JobStatus::Completed
+        } else if finished {
+            JobStatus::Failed
+        } else {
+            JobStatus::Pending
+        };
 
         // Create synthetic jobs for target rules (rules without output files)
         // These rules appear in jobs_by_rule from the log but won't have metadata files
@@ -598,37 +789,7 @@ impl PipelineState {
         // We detect these by parsing rule blocks in the log and checking for output: lines.
         for rule in &info.target_rules {
             if !self.jobs_by_rule.contains_key(rule) {
-                let job_id = format!("__target_{}__", rule);
-                let status = if info.finished && self.pipeline_errors.is_empty() {
-                    JobStatus::Completed
-                } else if info.finished {
-                    JobStatus::Failed
-                } else {
-                    JobStatus::Pending
-                };
-
-                let job = Job {
-                    id: job_id.clone(),
-                    rule: rule.clone(),
-                    wildcards: None,
-                    outputs: Vec::new(),
-                    inputs: Vec::new(),
-                    status,
-                    scheduler_job_id: None,
-                    shellcmd: String::new(),
-                    timing: JobTiming::default(),
-                    resources: JobResources::default(),
-                    usage: None,
-                    log_files: Vec::new(),
-                    error: None,
-                    conda_env: None,
-                    container_img_url: None,
-                    data_sources: DataSources::default(),
-                    is_target: true,
-                    is_snakemake_job: true, // Target rules from snakemake log are snakemake jobs
-                };
-                self.jobs.insert(job_id.clone(), job);
-                self.jobs_by_rule.insert(rule.clone(), vec![job_id]);
+                self.add_target_job(rule, target_status);
             }
         }
 
@@ -637,16 +798,13 @@ impl PipelineState {
             let job_id = format!("__target_{}__", rule);
             if let Some(job) = self.jobs.get_mut(&job_id)
                 && job.is_target
+                && !structured
             {
-                job.status = if info.finished && self.pipeline_errors.is_empty() {
-                    JobStatus::Completed
-                } else if info.finished {
-                    JobStatus::Failed
-                } else {
-                    JobStatus::Pending
-                };
+                let from = job.status;
+                job.status = target_status;
+                self.events.status_changed(&job_id, from, job.status);
                 // Update timing when pipeline finishes
-                if info.finished && job.timing.completed_at.is_none() {
+                if finished && job.timing.completed_at.is_none() {
                     job.timing.completed_at = Some(chrono::Utc::now());
                 }
             }
@@ -658,36 +816,313 @@ impl PipelineState {
                         && job.outputs.is_empty()
                     {
                         job.is_target = true;
-                        job.status = if info.finished && self.pipeline_errors.is_empty() {
-                            JobStatus::Completed
-                        } else if info.finished {
-                            JobStatus::Failed
-                        } else {
-                            JobStatus::Pending
-                        };
+                        if structured {
+                            continue;
+                        }
+                        let from = job.status;
+                        job.status = target_status;
+                        self.events.status_changed(&job_id, from, job.status);
                     }
                 }
             }
         }
 
+        // Jobs that "Job stats" counts but haven't been scheduled yet
+        if !structured {
+            for (rule, count) in &info.jobs_by_rule {
+                self.sync_unscheduled_placeholders(rule, *count);
+            }
+        }
+
         // Increment generation to signal state change
         self.generation = self.generation.wrapping_add(1);
     }
 
+    /// Forget what the main log said about the previous run, when reading
+    /// starts over with a new or rewritten log.
+    pub(crate) fn reset_log_run(&mut self) {
+        self.log_jobs.clear();
+        self.log_job_by_output.clear();
+        self.external_ids.clear();
+        self.total_jobs = None;
+        self.pipeline_errors.clear();
+        self.set_pipeline_finished(false);
+        let rules: Vec<String> = self.jobs_by_rule.keys().cloned().collect();
+        for rule in rules {
+            self.sync_unscheduled_placeholders(&rule, 0);
+        }
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Attach failure analysis to one attempt of a job and record it.
+    pub fn set_failure_analysis(
+        &mut self,
+        job_id: &str,
+        scheduler_job_id: &str,
+        analysis: FailureAnalysis,
+    ) {
+        let Some(job) = self.jobs.get_mut(job_id) else {
+            return;
+        };
+        let mode = analysis.mode.clone();
+        job.set_failure_analysis(scheduler_job_id, analysis);
+        self.events.push(StateEventKind::FailureAnalyzed {
+            job_id: job_id.to_string(),
+            mode,
+        });
+    }
+
+    /// Record the exit of the snakemake process charmer started.
+    ///
+    /// A failure the log didn't explain is classified from the last lines
+    /// of the process output instead.
+    pub fn set_process_exit(&mut self, code: i32, output: &[String]) {
+        self.process_exit = Some(code);
+        if code != 0 && self.pipeline_errors.is_empty() {
+            let error = if output.is_empty() {
+                PipelineError::new(PipelineErrorType::Generic, "snakemake failed")
+            } else {
+                parse_error_string(&output.join("\n"))
+            };
+            self.pipeline_errors.push(error.with_exit_code(code));
+        }
+        self.set_pipeline_finished(true);
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Forget the previous exit when snakemake is started again.
+    pub fn set_process_started(&mut self) {
+        self.process_exit = None;
+        self.pipeline_errors.clear();
+        self.pipeline_finished = false;
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Whether snakemake reported that the working directory is locked.
+    pub fn is_locked(&self) -> bool {
+        self.pipeline_errors
+            .iter()
+            .any(|e| e.error_type == PipelineErrorType::Locked)
+    }
+
+    /// Mark the run finished (or running again), recording when it ends.
+    pub(crate) fn set_pipeline_finished(&mut self, finished: bool) {
+        let finished = finished || self.process_exit.is_some();
+        if finished && !self.pipeline_finished {
+            self.events.push(StateEventKind::PipelineFinished {
+                success: self.pipeline_errors.is_empty(),
+            });
+        }
+        self.pipeline_finished = finished;
+    }
+
     pub fn job_counts(&self) -> JobCounts {
         let mut counts = JobCounts::default();
         for job in self.jobs.values() {
+            // A pending array line stands for all of its tasks
+            let n = job.array.as_ref().map_or(1, |a| a.task_count as usize);
             match job.status {
-                JobStatus::Pending => counts.pending += 1,
-                JobStatus::Queued => counts.queued += 1,
-                JobStatus::Running => counts.running += 1,
-                JobStatus::Completed => counts.completed += 1,
-                JobStatus::Failed => counts.failed += 1,
-                JobStatus::Cancelled => counts.cancelled += 1,
-                JobStatus::Unknown => counts.unknown += 1,
+                JobStatus::Pending => counts.pending += n,
+                JobStatus::Queued => counts.queued += n,
+                JobStatus::Running => counts.running += n,
+                JobStatus::Completed => counts.completed += n,
+                JobStatus::Failed => counts.failed += n,
+                JobStatus::Cancelled => counts.cancelled += n,
+                JobStatus::Unknown => counts.unknown += n,
             }
+            counts.total += n;
         }
-        counts.total = self.jobs.len();
+        counts
+    }
+
+    /// Index a main log job block and create its `Pending` entry.
+    pub(crate) fn record_log_job(&mut self, log_job: &LogJob) {
+        for output in &log_job.outputs {
+            self.log_job_by_output.insert(output.clone(), log_job.jobid);
+
+            // The log's wildcards are authoritative over path heuristics
+            if let Some(job) = self.jobs.get_mut(output)
+                && log_job.wildcards.is_some()
+            {
+                job.wildcards = log_job.wildcards.clone();
+            }
+        }
+        self.log_jobs.insert(log_job.jobid, log_job.clone());
+        self.add_scheduled_placeholder(log_job);
+    }
+
+    /// Create the synthetic entry for a target rule (no outputs, like "all").
+    pub(crate) fn add_target_job(&mut self, rule: &str, status: JobStatus) {
+        let job_id = format!("__target_{}__", rule);
+        let job = Job {
+            id: job_id.clone(),
+            rule: rule.to_string(),
+            wildcards: None,
+            outputs: Vec::new(),
+            inputs: Vec::new(),
+            status,
+            scheduler_job_id: None,
+            local_pid: None,
+            shellcmd: String::new(),
+            timing: JobTiming::default(),
+            resources: JobResources::default(),
+            usage: None,
+            log_files: Vec::new(),
+            error: None,
+            conda_env: None,
+            container_img_url: None,
+            data_sources: DataSources::default(),
+            is_target: true,
+            is_snakemake_job: true, // Target rules from snakemake log are snakemake jobs
+            array: None,
+            attempts: Vec::new(),
+        };
+        self.events.job_added(&job);
+        self.jobs.insert(job_id.clone(), job);
+        self.jobs_by_rule
+            .entry(rule.to_string())
+            .or_default()
+            .push(job_id);
+    }
+
+    /// Create a `Pending` entry for a job the main log has scheduled.
+    ///
+    /// Keyed by the first output so metadata for that job merges into it.
+    fn add_scheduled_placeholder(&mut self, log_job: &LogJob) {
+        let Some(job_id) = log_job.outputs.first() else {
+            return; // Target rules get their own synthetic entry
+        };
+        if let Some(job) = self.jobs.get_mut(job_id) {
+            job.data_sources.has_main_log = true;
+            return;
+        }
+        if log_job.outputs.iter().any(|o| self.jobs.contains_key(o)) {
+            return;
+        }
+
+        let job = Job {
+            id: job_id.clone(),
+            rule: log_job.rule.clone(),
+            wildcards: log_job.wildcards.clone(),
+            outputs: log_job.outputs.clone(),
+            inputs: log_job.inputs.clone(),
+            status: JobStatus::Pending,
+            scheduler_job_id: None,
+            local_pid: None,
+            shellcmd: String::new(),
+            timing: JobTiming::default(),
+            resources: JobResources {
+                cpus: log_job.threads,
+                memory_mb: log_job.resources.get("mem_mb").and_then(|m| m.parse().ok()),
+                ..Default::default()
+            },
+            usage: None,
+            log_files: log_job.log.clone(),
+            error: None,
+            conda_env: None,
+            container_img_url: None,
+            data_sources: DataSources {
+                has_main_log: true,
+                ..Default::default()
+            },
+            is_target: false,
+            is_snakemake_job: true,
+            array: None,
+            attempts: Vec::new(),
+        };
+        self.events.job_added(&job);
+        self.jobs.insert(job_id.clone(), job);
+        self.jobs_by_rule
+            .entry(log_job.rule.clone())
+            .or_default()
+            .push(job_id.clone());
+    }
+
+    /// Keep one unscheduled placeholder per job of `rule` not listed yet.
+    ///
+    /// Placeholders come and go in bulk and are not recorded as events.
+    pub(crate) fn sync_unscheduled_placeholders(&mut self, rule: &str, count: usize) {
+        let ids = self.jobs_by_rule.entry(rule.to_string()).or_default();
+        let (mut placeholders, listed): (Vec<String>, Vec<String>) = ids
+            .iter()
+            .cloned()
+            .partition(|id| id.starts_with(UNSCHEDULED_JOB_PREFIX));
+        let needed = count.saturating_sub(listed.len());
+        if placeholders.len() == needed {
+            return;
+        }
+
+        placeholders.sort();
+        for id in placeholders.drain(needed.min(placeholders.len())..) {
+            self.jobs.remove(&id);
+        }
+        let mut n = 0;
+        while placeholders.len() < needed {
+            let id = format!("{}{}_{}__", UNSCHEDULED_JOB_PREFIX, rule, n);
+            n += 1;
+            if placeholders.contains(&id) {
+                continue;
+            }
+            self.jobs.insert(
+                id.clone(),
+                Job {
+                    id: id.clone(),
+                    rule: rule.to_string(),
+                    wildcards: None,
+                    outputs: Vec::new(),
+                    inputs: Vec::new(),
+                    status: JobStatus::Pending,
+                    scheduler_job_id: None,
+                    local_pid: None,
+                    shellcmd: String::new(),
+                    timing: JobTiming::default(),
+                    resources: JobResources::default(),
+                    usage: None,
+                    log_files: Vec::new(),
+                    error: None,
+                    conda_env: None,
+                    container_img_url: None,
+                    data_sources: DataSources {
+                        has_main_log: true,
+                        ..Default::default()
+                    },
+                    is_target: false,
+                    is_snakemake_job: true,
+                    array: None,
+                    attempts: Vec::new(),
+                },
+            );
+            placeholders.push(id);
+        }
+
+        let mut ids = listed;
+        ids.extend(placeholders);
+        self.jobs_by_rule.insert(rule.to_string(), ids);
+    }
+
+    /// Main log job block that produces the given output file.
+    pub fn log_job_for_output(&self, output: &str) -> Option<&LogJob> {
+        self.log_jobs.get(self.log_job_by_output.get(output)?)
+    }
+
+    /// Number of queued jobs per pending reason, most common first.
+    pub fn pending_reason_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for job in self.jobs.values() {
+            if !matches!(job.status, JobStatus::Queued | JobStatus::Pending) {
+                continue;
+            }
+            if let Some(reason) = &job.resources.pending_reason {
+                let n = job.array.as_ref().map_or(1, |a| a.task_count as usize);
+                *counts.entry(reason).or_default() += n;
+            }
+        }
+
+        let mut counts: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(reason, n)| (reason.to_string(), n))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
         counts
     }
 
@@ -881,3 +1316,32 @@ fn extract_exit_code(error: &str) -> Option<i32> {
         .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
         .and_then(|m| m.as_str().parse().ok())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charmer_core::SnakemakeLogInfo;
+
+    #[test]
+    fn test_process_exit_finishes_run() {
+        let mut state = PipelineState::new(Utf8PathBuf::from("/tmp"));
+        state.set_process_exit(
+            1,
+            &[
+                "LockException:".to_string(),
+                "Error: Directory cannot be locked.".to_string(),
+            ],
+        );
+        assert!(state.pipeline_finished);
+        assert!(state.is_locked());
+        assert_eq!(state.pipeline_errors[0].exit_code, Some(1));
+
+        // The log of the killed run never says it finished
+        state.update_from_log_info(&SnakemakeLogInfo::default());
+        assert!(state.pipeline_finished);
+
+        state.set_process_started();
+        assert!(!state.pipeline_finished);
+        assert!(!state.is_locked());
+    }
+}
@@ -0,0 +1,11 @@
+//! Snakemake `--wms-monitor` endpoint for charmer.
+//!
+//! Snakemake posts each log record to a workflow management system server
+//! when run with `--wms-monitor http://host:port`. This crate serves that
+//! protocol and applies the records to `PipelineState`.
+
+pub mod protocol;
+pub mod server;
+
+pub use protocol::{WmsError, parse_form, parse_wms_message};
+pub use server::serve;
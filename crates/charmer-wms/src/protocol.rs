@@ -0,0 +1,161 @@
+//! Messages of the Snakemake WMS monitor protocol.
+//!
+//! `/update_workflow_status` receives a form with `msg` (the log record as
+//! JSON), `timestamp` and the workflow `id`. Records carry their kind in
+//! `level` and use the same fields as logger plugin events, except that
+//! jobs name their rule in `name` and `run_info` holds the "Job stats"
+//! table as text.
+
+use charmer_core::LoggerEvent;
+use charmer_core::main_log::parse_log_content;
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum WmsError {
+    #[error("Missing form field: {0}")]
+    MissingField(&'static str),
+    #[error("Invalid WMS message: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Decode an `application/x-www-form-urlencoded` body.
+pub fn parse_form(body: &str) -> HashMap<String, String> {
+    body.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+/// Percent-decode a form value ('+' is a space).
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                out.push(hex_value(bytes[i + 1]) << 4 | hex_value(bytes[i + 2]));
+                i += 2;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
+/// Convert one log record (the `msg` field) into a logger event.
+///
+/// Returns `None` for records charmer doesn't use.
+pub fn parse_wms_message(msg: &str) -> Result<Option<LoggerEvent>, WmsError> {
+    let mut record: Value = serde_json::from_str(msg)?;
+    let Some(fields) = record.as_object_mut() else {
+        return Ok(None);
+    };
+    let Some(level) = fields.get("level").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+
+    if level == "run_info" {
+        let text = fields.get("msg").and_then(Value::as_str).unwrap_or("");
+        let info = if text.contains("Job stats:") {
+            parse_log_content(text)
+        } else {
+            parse_log_content(&format!("Job stats:\n{}", text))
+        };
+        if info.total_jobs.is_none() && info.jobs_by_rule.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(LoggerEvent::RunInfo {
+            per_rule_job_counts: info.jobs_by_rule,
+            total_job_count: info.total_jobs,
+        }));
+    }
+
+    let level = Value::String(level.to_string());
+    fields.insert("event".to_string(), level);
+    if let Some(name) = fields.remove("name") {
+        fields.entry("rule_name").or_insert(name);
+    }
+    // job_error records carry a null message
+    if fields.get("msg").is_some_and(|m| !m.is_string()) {
+        fields.remove("msg");
+    }
+
+    match serde_json::from_value(record)? {
+        LoggerEvent::Other => Ok(None),
+        event => Ok(Some(event)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_form() {
+        let form = parse_form("msg=%7B%22level%22%3A+%22progress%22%7D&timestamp=Mon+Jan+15&id=7");
+        assert_eq!(form["msg"], r#"{"level": "progress"}"#);
+        assert_eq!(form["timestamp"], "Mon Jan 15");
+        assert_eq!(form["id"], "7");
+        assert_eq!(decode("100%"), "100%");
+    }
+
+    #[test]
+    fn test_parse_wms_message() {
+        assert_eq!(
+            parse_wms_message(r#"{"level": "progress", "done": 2, "total": 5}"#).unwrap(),
+            Some(LoggerEvent::Progress { done: 2, total: 5 })
+        );
+        assert_eq!(
+            parse_wms_message(
+                r#"{"level": "job_error", "name": "align", "jobid": 3, "msg": null}"#
+            )
+            .unwrap(),
+            Some(LoggerEvent::JobError {
+                jobid: 3,
+                msg: None
+            })
+        );
+        assert_eq!(
+            parse_wms_message(r#"{"level": "info", "msg": "Building DAG of jobs..."}"#).unwrap(),
+            None
+        );
+
+        let Some(LoggerEvent::JobInfo(job)) = parse_wms_message(
+            r#"{"level": "job_info", "jobid": 1, "name": "align", "output": ["a/S1.bam"]}"#,
+        )
+        .unwrap() else {
+            panic!("expected job_info");
+        };
+        assert_eq!(job.rule_name, "align");
+
+        let run_info = r#"{"level": "run_info", "msg": "Job stats:\njob      count\n-----  -------\nalign        2\nall          1\ntotal        3\n"}"#;
+        let Some(LoggerEvent::RunInfo {
+            per_rule_job_counts,
+            total_job_count,
+        }) = parse_wms_message(run_info).unwrap()
+        else {
+            panic!("expected run_info");
+        };
+        assert_eq!(per_rule_job_counts["align"], 2);
+        assert_eq!(total_job_count, Some(3));
+    }
+}
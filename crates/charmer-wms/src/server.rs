@@ -0,0 +1,226 @@
+//! HTTP listener speaking the Snakemake WMS monitor protocol.
+//!
+//! Endpoints:
+//! - `GET /api/service-info`: reports the server as running
+//! - `GET|POST /create_workflow`: hands out a workflow ID
+//! - `POST /update_workflow_status`: applies one log record
+
+use crate::protocol::{WmsError, parse_form, parse_wms_message};
+use charmer_state::{PipelineState, merge_logger_events};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+/// Shared by all connections.
+struct WmsServer {
+    state: Arc<Mutex<PipelineState>>,
+    next_workflow_id: AtomicU64,
+}
+
+/// Serve the WMS monitor protocol on `listener`, feeding `state`.
+pub async fn serve(listener: TcpListener, state: Arc<Mutex<PipelineState>>) -> std::io::Result<()> {
+    let server = Arc::new(WmsServer {
+        state,
+        next_workflow_id: AtomicU64::new(1),
+    });
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let server = Arc::clone(&server);
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                let server = Arc::clone(&server);
+                async move { Ok::<_, Infallible>(server.handle(request).await) }
+            });
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("WMS connection closed: {}", e);
+            }
+        });
+    }
+}
+
+impl WmsServer {
+    async fn handle(&self, request: Request<Incoming>) -> Response<Full<Bytes>> {
+        match request.uri().path() {
+            "/api/service-info" => json_response(StatusCode::OK, json!({ "status": "running" })),
+            "/create_workflow" => {
+                let id = self.next_workflow_id.fetch_add(1, Ordering::Relaxed);
+                json_response(StatusCode::OK, json!({ "id": id.to_string() }))
+            }
+            "/update_workflow_status" => {
+                let body = match request.into_body().collect().await {
+                    Ok(body) => body.to_bytes(),
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+                };
+                match self.update(&String::from_utf8_lossy(&body)).await {
+                    Ok(()) => json_response(StatusCode::OK, json!({})),
+                    Err(e) => {
+                        tracing::debug!("Rejected WMS update: {}", e);
+                        error_response(StatusCode::BAD_REQUEST, &e.to_string())
+                    }
+                }
+            }
+            _ => error_response(StatusCode::NOT_FOUND, "Not found"),
+        }
+    }
+
+    /// Apply the record in an `/update_workflow_status` body.
+    async fn update(&self, body: &str) -> Result<(), WmsError> {
+        // Snakemake sends a form; accept a JSON object as well
+        let msg = match serde_json::from_str::<Value>(body) {
+            Ok(Value::Object(fields)) => match fields.get("msg") {
+                Some(Value::String(msg)) => msg.clone(),
+                Some(msg) => msg.to_string(),
+                None => return Err(WmsError::MissingField("msg")),
+            },
+            _ => parse_form(body)
+                .remove("msg")
+                .ok_or(WmsError::MissingField("msg"))?,
+        };
+
+        if let Some(event) = parse_wms_message(&msg)? {
+            let mut state = self.state.lock().await;
+            merge_logger_events(&mut state, &[event]);
+        }
+        Ok(())
+    }
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    json_response(status, json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charmer_state::JobStatus;
+    use std::path::PathBuf;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Form-encode a value the way Python's requests does.
+    fn encode(value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        for b in value.bytes() {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    out.push(b as char)
+                }
+                b' ' => out.push('+'),
+                _ => out.push_str(&format!("%{:02X}", b)),
+            }
+        }
+        out
+    }
+
+    /// Send one request and return (status code, body).
+    async fn send(port: u16, method: &str, path: &str, form: Option<&str>) -> (u16, String) {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let body = form.unwrap_or("");
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        let status = response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .unwrap();
+        let body = response
+            .split_once("\r\n\r\n")
+            .map(|(_, b)| b.to_string())
+            .unwrap_or_default();
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn test_replay_recorded_messages() {
+        let state = Arc::new(Mutex::new(PipelineState::new("/tmp".into())));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(serve(listener, Arc::clone(&state)));
+
+        let (status, body) = send(port, "GET", "/api/service-info", None).await;
+        assert_eq!(status, 200);
+        assert!(body.contains(r#""status":"running""#));
+
+        let (status, body) = send(port, "GET", "/create_workflow", None).await;
+        assert_eq!(status, 200);
+        let id = serde_json::from_str::<Value>(&body).unwrap()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let fixture =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/wms_messages.jsonl");
+        for msg in std::fs::read_to_string(fixture).unwrap().lines() {
+            let form = format!(
+                "msg={}&timestamp={}&id={}",
+                encode(msg),
+                encode("Mon Jan 15 10:00:00 2024"),
+                id
+            );
+            let (status, _) = send(port, "POST", "/update_workflow_status", Some(&form)).await;
+            assert_eq!(status, 200);
+        }
+
+        let state = state.lock().await;
+        assert!(state.has_logger_events);
+        assert_eq!(state.total_jobs, Some(3));
+        assert_eq!(state.jobs["aligned/S1.bam"].status, JobStatus::Completed);
+        assert_eq!(state.jobs["aligned/S1.bam"].resources.cpus, Some(4));
+        assert_eq!(state.jobs["aligned/S2.bam"].status, JobStatus::Failed);
+        assert_eq!(
+            state.jobs["aligned/S2.bam"].wildcards.as_deref(),
+            Some("sample=S2")
+        );
+        // "all" is still waiting to be scheduled
+        assert_eq!(state.jobs_by_rule["all"].len(), 1);
+        assert_eq!(state.jobs.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_requests() {
+        let state = Arc::new(Mutex::new(PipelineState::new("/tmp".into())));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(serve(listener, Arc::clone(&state)));
+
+        let (status, _) = send(port, "POST", "/update_workflow_status", Some("id=1")).await;
+        assert_eq!(status, 400);
+        let (status, _) = send(port, "POST", "/update_workflow_status", Some("msg=oops")).await;
+        assert_eq!(status, 400);
+        let (status, _) = send(port, "GET", "/elsewhere", None).await;
+        assert_eq!(status, 404);
+        assert!(!state.lock().await.has_logger_events);
+    }
+}
@@ -0,0 +1,22 @@
+//! Job-control actions confirmed in the TUI.
+
+use charmer_monitor::ActionRequest;
+use charmer_scheduler::Scheduler;
+
+/// Send a confirmed action to the scheduler and describe the outcome.
+pub async fn run_action(scheduler: &dyn Scheduler, request: &ActionRequest) -> String {
+    match scheduler.control(request.action, &request.job_ids).await {
+        Ok(()) => format!(
+            "{} job(s) of {} {}",
+            request.job_ids.len(),
+            request.target,
+            request.action.past_tense()
+        ),
+        Err(e) => format!(
+            "{} of {} failed: {}",
+            request.action.label(),
+            request.target,
+            e
+        ),
+    }
+}
@@ -0,0 +1,151 @@
+//! Background reader for structured Snakemake logger events (JSON lines).
+
+use camino::Utf8PathBuf;
+use charmer_core::{LoggerEvent, parse_logger_line};
+use charmer_state::{PipelineState, merge_logger_events};
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncSeekExt};
+use tokio::sync::Mutex;
+
+/// How often a followed event file is checked for new lines.
+const FILE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Where logger events come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventSource {
+    /// A file the logger plugin appends to
+    File(Utf8PathBuf),
+    /// A Unix socket charmer listens on; the plugin connects and writes
+    Socket(Utf8PathBuf),
+}
+
+impl EventSource {
+    /// Parse a `--events` value: a file path, or `unix:/path` for a socket.
+    pub fn parse(value: &str) -> Self {
+        match value.strip_prefix("unix:") {
+            Some(path) => Self::Socket(Utf8PathBuf::from(path)),
+            None => Self::File(Utf8PathBuf::from(value)),
+        }
+    }
+}
+
+/// Start reading events from `source` in the background.
+pub fn init_event_stream(
+    state: Arc<Mutex<PipelineState>>,
+    source: EventSource,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let result = match &source {
+            EventSource::File(path) => follow_file(&state, path).await,
+            EventSource::Socket(path) => listen(&state, path).await,
+        };
+        if let Err(e) = result {
+            tracing::error!("Logger event stream {:?} stopped: {}", source, e);
+        }
+    })
+}
+
+/// Follow an event file as it grows, starting over if it is truncated.
+async fn follow_file(state: &Arc<Mutex<PipelineState>>, path: &Utf8PathBuf) -> std::io::Result<()> {
+    let mut offset = 0u64;
+    let mut partial: Vec<u8> = Vec::new();
+
+    loop {
+        match tokio::fs::File::open(path).await {
+            Ok(mut file) => {
+                let len = file.metadata().await?.len();
+                if len < offset {
+                    offset = 0;
+                    partial.clear();
+                }
+                if len > offset {
+                    file.seek(SeekFrom::Start(offset)).await?;
+                    let read = file.take(len - offset).read_to_end(&mut partial).await?;
+                    offset += read as u64;
+
+                    // Only complete lines are parsed; the rest waits for more data
+                    if let Some(end) = partial.iter().rposition(|&b| b == b'\n') {
+                        let lines: Vec<u8> = partial.drain(..=end).collect();
+                        apply(state, String::from_utf8_lossy(&lines).lines()).await;
+                    }
+                }
+            }
+            // The plugin may not have created the file yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        tokio::time::sleep(FILE_POLL_INTERVAL).await;
+    }
+}
+
+/// Accept logger plugin connections on a Unix socket.
+#[cfg(unix)]
+async fn listen(state: &Arc<Mutex<PipelineState>>, path: &Utf8PathBuf) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // A socket left behind by an earlier charmer would make bind fail;
+    // anything else at the path is not ours to remove
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path),
+            ));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let state = Arc::clone(state);
+        tokio::spawn(async move {
+            if let Err(e) = read_stream(&state, tokio::io::BufReader::new(stream)).await {
+                tracing::warn!("Logger event connection closed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+async fn listen(_state: &Arc<Mutex<PipelineState>>, _path: &Utf8PathBuf) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
+/// Apply events from one connection until it closes.
+#[cfg_attr(not(unix), allow(dead_code))]
+async fn read_stream(
+    state: &Arc<Mutex<PipelineState>>,
+    reader: impl AsyncBufRead + Unpin,
+) -> std::io::Result<()> {
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        apply(state, std::iter::once(line.as_str())).await;
+    }
+    Ok(())
+}
+
+/// Parse lines and merge the events they carry.
+async fn apply<'a>(state: &Arc<Mutex<PipelineState>>, lines: impl Iterator<Item = &'a str>) {
+    let events: Vec<LoggerEvent> = lines
+        .filter_map(|line| match parse_logger_line(line) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::debug!("Skipping malformed logger event: {}", e);
+                None
+            }
+        })
+        .collect();
+
+    if !events.is_empty() {
+        let mut state = state.lock().await;
+        merge_logger_events(&mut state, &events);
+    }
+}
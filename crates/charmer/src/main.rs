@@ -1,25 +1,37 @@
 //! Charmer - Snakemake pipeline monitor for SLURM/LSF.
 
+mod control;
+mod events;
+mod offline;
 mod polling;
+mod rerun;
+mod snapshot;
+mod supervisor;
 mod watcher;
 
-use charmer_cli::Args;
-use charmer_core::{parse_main_log, parse_metadata_file, scan_metadata_dir_incremental};
+use charmer_cli::{Args, Command};
+use charmer_core::{MainLogTailer, parse_metadata_file, scan_metadata_dir_incremental};
 use charmer_monitor::App;
 use charmer_runs::{RunStatus, RunStore};
-use charmer_state::{PipelineState, merge_snakemake_jobs};
+use charmer_scheduler::Scheduler;
+use charmer_state::{
+    PipelineState, ProfileOverlay, SnapshotStore, merge_log_events, merge_snakemake_jobs,
+};
 use clap::Parser;
 use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use events::{EventSource, init_event_stream};
 use miette::{IntoDiagnostic, Result};
 use polling::{PollingConfig, init_polling};
 use ratatui::prelude::*;
+use snapshot::{init_snapshots, save_snapshot};
 use std::collections::HashMap;
 use std::io;
 use std::sync::Arc;
 use std::time::Duration;
+use supervisor::Supervisor;
 use tokio::sync::Mutex;
 use watcher::{MetadataWatcher, WatcherEvent};
 
@@ -32,6 +44,10 @@ async fn main() -> Result<()> {
     let runs_state = run_store.load().unwrap_or_default();
 
     // Handle --list-runs flag
+    if args.list_runs && args.offline {
+        offline::print_runs(&args.dir);
+        return Ok(());
+    }
     if args.list_runs {
         if runs_state.runs.is_empty() {
             println!("No runs found in {}", args.dir);
@@ -62,51 +78,41 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Initialize pipeline state wrapped in Arc<Mutex<>> for sharing with polling service
-    let state = Arc::new(Mutex::new(PipelineState::new(args.dir.clone())));
-
-    // Scan existing metadata files on startup, filtering to recent jobs
-    {
-        let mut state_guard = state.lock().await;
-        if let Ok(result) =
-            scan_metadata_dir_incremental(&args.dir, &mut state_guard.metadata_mtime_cache)
-        {
-            let cutoff = chrono::Utc::now() - chrono::Duration::hours(args.history_hours as i64);
-            let recent_jobs: Vec<_> = result
-                .jobs
-                .into_iter()
-                .filter(|job| {
-                    // Keep jobs that are incomplete (still running) or started recently
-                    job.metadata.incomplete
-                        || job
-                            .metadata
-                            .starttime
-                            .map(|t| t > cutoff.timestamp() as f64)
-                            .unwrap_or(true)
-                })
-                .collect();
-
-            if !recent_jobs.is_empty() {
-                merge_snakemake_jobs(&mut state_guard, recent_jobs);
-            }
+    // `charmer run`: start snakemake first so its log is there to follow
+    let mut supervisor = match &args.command {
+        Some(Command::Run { .. }) if args.offline => {
+            return Err(miette::miette!(
+                "`charmer run` can't be combined with --offline"
+            ));
         }
-    }
-
-    // Initialize polling service in the background
-    let poll_config = PollingConfig {
-        active_poll_interval: Duration::from_secs(args.poll_interval),
-        history_poll_interval: Duration::from_secs(30),
-        run_uuid: args.run_uuid.clone(),
-        history_hours: args.history_hours,
+        Some(Command::Run { snakemake_args }) => {
+            let mut supervisor =
+                Supervisor::new(&args.dir, snakemake_args.clone()).into_diagnostic()?;
+            supervisor
+                .start()
+                .map_err(|e| miette::miette!("Failed to start snakemake: {}", e))?;
+            Some(supervisor)
+        }
+        None => None,
     };
 
-    let _polling_handle = init_polling(Arc::clone(&state), poll_config).await;
+    // Offline mode reads files once; otherwise start every live source
+    let snapshot_store =
+        (!args.no_snapshot && !args.offline).then(|| Arc::new(SnapshotStore::new(&args.dir)));
+    let (state, watcher, scheduler) = if args.offline {
+        let state = offline::load_state(&args.dir, args.log.as_deref())?;
+        (Arc::new(Mutex::new(state)), None, None)
+    } else {
+        let (state, scheduler) = start_live(&args, snapshot_store.clone()).await?;
+        (state, MetadataWatcher::new(&args.dir).ok(), scheduler)
+    };
 
     // Determine selected run (from args or auto-detect from runs state)
     let selected_run = args
         .run_uuid
         .clone()
-        .or_else(|| runs_state.current_run().map(|r| r.run_uuid.clone()));
+        .or_else(|| runs_state.current_run().map(|r| r.run_uuid.clone()))
+        .filter(|_| !args.offline);
 
     // Initialize app with a clone of the initial state
     let initial_state = {
@@ -120,6 +126,10 @@ async fn main() -> Result<()> {
         selected_run,
     );
     app.update_job_list();
+    if let Some(supervisor) = &supervisor {
+        app.set_main_log(supervisor.output_path().as_str());
+        app.set_snakemake_args(supervisor.args().to_vec());
+    }
 
     // Setup terminal
     enable_raw_mode().into_diagnostic()?;
@@ -129,17 +139,42 @@ async fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend).into_diagnostic()?;
 
-    // Create file watcher
-    let watcher = MetadataWatcher::new(&args.dir).ok();
-
     // Run the main loop
-    let res = run_app(&mut terminal, &mut app, state, watcher).await;
+    let live = !args.offline;
+    let res = run_app(
+        &mut terminal,
+        &mut app,
+        Arc::clone(&state),
+        watcher,
+        scheduler,
+        supervisor.as_mut(),
+        live,
+    )
+    .await;
 
     // Restore terminal
     disable_raw_mode().into_diagnostic()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen).into_diagnostic()?;
     terminal.show_cursor().into_diagnostic()?;
 
+    // Let snakemake stop cleanly; a second Ctrl+C leaves it to finish alone
+    if let Some(supervisor) = supervisor.as_mut().filter(|s| s.is_running()) {
+        eprintln!("Stopping snakemake (Ctrl+C to stop waiting)...");
+        tokio::select! {
+            result = supervisor.interrupt() => {
+                if let Err(e) = result {
+                    eprintln!("Failed to stop snakemake: {}", e);
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+
+    // Final snapshot on exit
+    if let Some(store) = &snapshot_store {
+        save_snapshot(&state, store).await;
+    }
+
     // Handle result
     if let Err(err) = res {
         eprintln!("Error: {}", err);
@@ -148,12 +183,108 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Load the initial state and start the background sources that keep it current.
+///
+/// Also returns the detected scheduler, which job-control actions go through.
+async fn start_live(
+    args: &Args,
+    snapshot_store: Option<Arc<SnapshotStore>>,
+) -> Result<(Arc<Mutex<PipelineState>>, Option<Arc<dyn Scheduler>>)> {
+    // Start from the last snapshot if it is still valid for this directory
+    let initial = match snapshot_store.as_deref().map(SnapshotStore::load) {
+        Some(Ok(Some(state))) => state,
+        Some(Err(e)) => {
+            tracing::warn!("Ignoring unreadable state snapshot: {}", e);
+            PipelineState::new(args.dir.clone())
+        }
+        _ => PipelineState::new(args.dir.clone()),
+    };
+
+    // Initialize pipeline state wrapped in Arc<Mutex<>> for sharing with polling service
+    let state = Arc::new(Mutex::new(initial));
+
+    // Scan existing metadata files on startup, filtering to recent jobs.
+    // With a snapshot loaded, only files changed since it was saved are parsed.
+    {
+        let mut state_guard = state.lock().await;
+        if let Ok(result) =
+            scan_metadata_dir_incremental(&args.dir, &mut state_guard.metadata_mtime_cache)
+        {
+            let cutoff = chrono::Utc::now() - chrono::Duration::hours(args.history_hours as i64);
+            let recent_jobs: Vec<_> = result
+                .jobs
+                .into_iter()
+                .filter(|job| {
+                    // Keep jobs that are incomplete (still running) or started recently
+                    job.metadata.incomplete
+                        || job
+                            .metadata
+                            .starttime
+                            .map(|t| t > cutoff.timestamp() as f64)
+                            .unwrap_or(true)
+                })
+                .collect();
+
+            if !recent_jobs.is_empty() {
+                merge_snakemake_jobs(&mut state_guard, recent_jobs);
+            }
+        }
+    }
+
+    // Initialize polling service in the background
+    let poll_config = PollingConfig {
+        active_poll_interval: Duration::from_secs(args.poll_interval),
+        history_poll_interval: Duration::from_secs(30),
+        run_uuid: args.run_uuid.clone(),
+        history_hours: args.history_hours,
+        slurmrestd: args
+            .slurmrestd
+            .clone()
+            .or_else(|| std::env::var("SLURMRESTD_URL").ok()),
+    };
+
+    let scheduler = init_polling(Arc::clone(&state), poll_config).await;
+
+    // Structured logger events, when a plugin is writing them
+    if let Some(source) = args.events.as_deref() {
+        init_event_stream(Arc::clone(&state), EventSource::parse(source));
+    }
+
+    // Snakemake --wms-monitor endpoint
+    if let Some(addr) = &args.wms_listen {
+        let listener = tokio::net::TcpListener::bind(addr.as_str())
+            .await
+            .into_diagnostic()?;
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(e) = charmer_wms::serve(listener, state).await {
+                tracing::error!("WMS monitor endpoint stopped: {}", e);
+            }
+        });
+    }
+
+    // Persist state on a cadence so a restart picks up where we left off
+    if let Some(store) = snapshot_store {
+        init_snapshots(Arc::clone(&state), store);
+    }
+
+    Ok((state, scheduler))
+}
+
 /// Main application loop.
+///
+/// When not `live` (offline mode) the state is only displayed, never refreshed.
+/// Job-control actions confirmed in the app are sent to `scheduler`. With a
+/// `supervisor` (`charmer run`), the snakemake child's exit ends the run and
+/// a failed run can be restarted.
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
     shared_state: Arc<Mutex<PipelineState>>,
     watcher: Option<MetadataWatcher>,
+    scheduler: Option<Arc<dyn Scheduler>>,
+    mut supervisor: Option<&mut Supervisor>,
+    live: bool,
 ) -> io::Result<()> {
     let tick_rate = Duration::from_millis(100);
     let update_interval = Duration::from_millis(500);
@@ -166,6 +297,8 @@ async fn run_app(
     let mut debounce_map: HashMap<String, std::time::Instant> = HashMap::new();
     let debounce_duration = Duration::from_millis(500);
     let mut last_generation: u64 = 0;
+    let mut log_tailer = MainLogTailer::new(shared_state.lock().await.working_dir.clone());
+    let (action_tx, mut action_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
 
     loop {
         // Periodically sync app state from shared state (updated by polling service)
@@ -181,14 +314,17 @@ async fn run_app(
         }
 
         // Periodically parse main snakemake log for pipeline-level info
-        if last_log_parse.elapsed() >= log_parse_interval {
-            let state_guard = shared_state.lock().await;
-            let working_dir = state_guard.working_dir.clone();
-            drop(state_guard);
-
-            if let Ok(log_info) = parse_main_log(&working_dir) {
-                let mut state_guard = shared_state.lock().await;
-                state_guard.update_from_log_info(&log_info);
+        if live && last_log_parse.elapsed() >= log_parse_interval {
+            // Only lines appended since the last read are parsed, and only
+            // the jobs they name are updated
+            match log_tailer.poll() {
+                Ok(events) if !events.is_empty() => {
+                    let mut state_guard = shared_state.lock().await;
+                    merge_log_events(&mut state_guard, &events);
+                    state_guard.update_log_summary(log_tailer.info());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Failed to read main log: {}", e),
             }
             last_log_parse = std::time::Instant::now();
         }
@@ -201,6 +337,100 @@ async fn run_app(
             // Event was handled
         }
 
+        // Send confirmed job-control actions without blocking the UI
+        for request in app.take_action_requests() {
+            let Some(scheduler) = scheduler.clone() else {
+                app.set_status_message("No scheduler available for job control");
+                continue;
+            };
+            let tx = action_tx.clone();
+            tokio::spawn(async move {
+                let _ = tx.send(control::run_action(scheduler.as_ref(), &request).await);
+            });
+        }
+        while let Ok(result) = action_rx.try_recv() {
+            app.set_status_message(result);
+        }
+
+        // The child's exit status decides whether the run finished
+        if let Some(supervisor) = supervisor.as_deref_mut() {
+            if let Some(code) = supervisor.try_exit().await {
+                let mut state_guard = shared_state.lock().await;
+                state_guard.set_process_exit(code, &supervisor.tail());
+                let locked = state_guard.is_locked();
+                drop(state_guard);
+                if code == 0 {
+                    app.set_status_message("Snakemake finished");
+                } else {
+                    app.offer_restart(code, locked);
+                }
+            }
+
+            if let Some(unlock) = app.take_restart_request() {
+                let unlocked = if unlock {
+                    match supervisor.unlock().await {
+                        Ok(status) if status.success() => true,
+                        Ok(status) => {
+                            app.set_status_message(format!(
+                                "snakemake --unlock failed ({})",
+                                status
+                            ));
+                            false
+                        }
+                        Err(e) => {
+                            app.set_status_message(format!(
+                                "Failed to run snakemake --unlock: {}",
+                                e
+                            ));
+                            false
+                        }
+                    }
+                } else {
+                    true
+                };
+                if unlocked {
+                    match supervisor.restart() {
+                        Ok(()) => {
+                            shared_state.lock().await.set_process_started();
+                            app.set_status_message("Restarted snakemake with --rerun-incomplete");
+                        }
+                        Err(e) => {
+                            app.set_status_message(format!("Failed to restart snakemake: {}", e))
+                        }
+                    }
+                }
+            }
+        }
+
+        // Start a confirmed fix-and-rerun: under `charmer run` as the
+        // supervised child, so its output reaches the TUI like the run's
+        if let Some(args) = app.take_rerun_request() {
+            match supervisor.as_deref_mut() {
+                Some(supervisor) if supervisor.is_running() => {
+                    app.set_status_message("Snakemake is still running; rerun once it has exited")
+                }
+                Some(supervisor) => match supervisor.rerun(args) {
+                    Ok(()) => {
+                        shared_state.lock().await.set_process_started();
+                        app.set_status_message("Restarted snakemake with the profile overlay");
+                    }
+                    Err(e) => app.set_status_message(format!("Failed to restart snakemake: {}", e)),
+                },
+                None => {
+                    let working_dir = app.state.working_dir.clone();
+                    let log_path = ProfileOverlay::new(&working_dir).log_path();
+                    app.set_status_message(
+                        match rerun::spawn_detached(&working_dir, &args, &log_path) {
+                            Ok(pid) => {
+                                format!("Started snakemake (pid {}), output in {}", pid, log_path)
+                            }
+                            Err(e) => format!("Failed to start snakemake: {}", e),
+                        },
+                    );
+                }
+            }
+        }
+
         // Check for file watcher events (non-blocking)
         if let Some(ref w) = watcher {
             while let Some(event) = w.try_recv_nonblocking() {
@@ -250,7 +480,7 @@ async fn run_app(
         }
 
         // Periodic re-scan as fallback (in case file watcher misses events)
-        if last_rescan.elapsed() >= rescan_interval {
+        if live && last_rescan.elapsed() >= rescan_interval {
             let mut state_guard = shared_state.lock().await;
             let working_dir = state_guard.working_dir.clone();
 
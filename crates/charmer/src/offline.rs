@@ -0,0 +1,211 @@
+//! Offline mode: rebuild the final state of a run from files alone.
+//!
+//! For inspecting a run later, or from a copy of the pipeline directory
+//! where no scheduler is available. Nothing is polled or watched.
+
+use camino::{Utf8Path, Utf8PathBuf};
+use charmer_core::{find_scheduler_logs, list_logs, parse_log_file_events, scan_metadata_dir};
+use charmer_state::{
+    PipelineState, SnapshotStore, merge_log_events, merge_scheduler_logs, merge_snakemake_jobs,
+};
+use miette::{IntoDiagnostic, Result, miette};
+
+/// Pick the main log of the run to show: `log` by file name or path, or the latest.
+fn choose_log(logs: &[Utf8PathBuf], log: Option<&str>) -> Result<Option<Utf8PathBuf>> {
+    let Some(wanted) = log else {
+        return Ok(logs.last().cloned());
+    };
+    logs.iter()
+        .find(|path| path.file_name() == Some(wanted) || path.as_str() == wanted)
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| {
+            miette!(
+                "No snakemake log named {} (see --offline --list-runs)",
+                wanted
+            )
+        })
+}
+
+/// Print the runs found in `dir`, one per main log, oldest first.
+pub fn print_runs(dir: &Utf8Path) {
+    let logs = list_logs(dir);
+    if logs.is_empty() {
+        println!("No snakemake logs found in {}", dir);
+        return;
+    }
+    println!("Runs in {}:", dir);
+    for path in &logs {
+        let Ok((info, _)) = parse_log_file_events(path) else {
+            continue;
+        };
+        let status = match (info.finished, info.has_errors) {
+            (_, true) => "FAILED",
+            (true, false) => "DONE",
+            (false, false) => "INCOMPLETE",
+        };
+        println!(
+            "  {} {} {}/{} jobs",
+            path.file_name().unwrap_or(path.as_str()),
+            status,
+            info.completed_jobs,
+            info.total_jobs.unwrap_or(0)
+        );
+    }
+}
+
+/// Build the state of a run from metadata, its main log, executor job logs
+/// and (for the latest run) the charmer snapshot.
+pub fn load_state(dir: &Utf8Path, log: Option<&str>) -> Result<PipelineState> {
+    let logs = list_logs(dir);
+    let chosen = choose_log(&logs, log)?;
+    let latest = chosen.as_ref() == logs.last();
+
+    // The snapshot only describes the latest run
+    let snapshot = if latest {
+        SnapshotStore::new(dir).load().ok().flatten()
+    } else {
+        None
+    };
+    let mut state = snapshot.unwrap_or_else(|| PipelineState::new(dir.to_path_buf()));
+
+    let mut log_events = Vec::new();
+    if let Some(path) = &chosen {
+        let (info, events) = parse_log_file_events(path).into_diagnostic()?;
+        state.update_from_log_info(&info);
+        log_events = events;
+    }
+
+    // Metadata holds the last execution of each output, whichever run that
+    // was. For earlier runs only outputs the run's own log lists are kept,
+    // and their status and timing are left to the log's events
+    let jobs: Vec<_> = scan_metadata_dir(dir)
+        .into_diagnostic()?
+        .into_iter()
+        .filter(|job| latest || state.log_job_by_output.contains_key(&job.output_path))
+        .map(|mut job| {
+            if !latest {
+                job.metadata.incomplete = false;
+                job.metadata.starttime = None;
+                job.metadata.endtime = None;
+            }
+            job
+        })
+        .collect();
+    if !jobs.is_empty() {
+        merge_snakemake_jobs(&mut state, jobs);
+    }
+
+    merge_log_events(&mut state, &log_events);
+    merge_scheduler_logs(&mut state, &find_scheduler_logs(dir));
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::prelude::*;
+    use charmer_state::JobStatus;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const EARLIER: &str = "2025-01-01T100000.000000.snakemake.log";
+    const LATER: &str = "2025-01-02T100000.000000.snakemake.log";
+
+    /// A main log where job 1 of rule align (writing `output`) ends with `outcome`.
+    fn main_log(output: &str, outcome: &str) -> String {
+        format!(
+            "Building DAG of jobs...\n\
+             [Wed Jan  1 10:00:00 2025]\n\
+             rule align:\n    \
+                 output: {}\n    \
+                 jobid: 1\n\
+             \n\
+             {}\n",
+            output, outcome
+        )
+    }
+
+    /// A pipeline directory with an earlier run where align failed, a later
+    /// run where it succeeded, metadata written by the later run and a
+    /// snapshot of it.
+    fn pipeline() -> (TempDir, Utf8PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(temp.path()).unwrap().to_path_buf();
+
+        let log_dir = dir.join(".snakemake/log");
+        fs::create_dir_all(&log_dir).unwrap();
+        fs::write(
+            log_dir.join(EARLIER),
+            main_log("results/a.bam", "Error in rule align:\n    jobid: 1\n"),
+        )
+        .unwrap();
+        fs::write(
+            log_dir.join(LATER),
+            main_log("results/a.bam", "Finished job 1."),
+        )
+        .unwrap();
+
+        let metadata_dir = dir.join(".snakemake/metadata");
+        fs::create_dir_all(&metadata_dir).unwrap();
+        for output in ["results/a.bam", "results/b.bam"] {
+            fs::write(
+                metadata_dir.join(BASE64_STANDARD.encode(output)),
+                r#"{"rule": "align", "incomplete": false, "starttime": 1735812000.0, "endtime": 1735812600.0}"#,
+            )
+            .unwrap();
+        }
+
+        let mut snapshot = PipelineState::new(dir.clone());
+        snapshot.run_uuid = Some("from-snapshot".to_string());
+        SnapshotStore::new(&dir).save(&snapshot).unwrap();
+
+        (temp, dir)
+    }
+
+    #[test]
+    fn test_choose_log() {
+        let (_temp, dir) = pipeline();
+        let logs = list_logs(&dir);
+
+        assert_eq!(choose_log(&logs, None).unwrap().as_ref(), logs.last());
+        assert_eq!(
+            choose_log(&logs, Some(EARLIER)).unwrap().as_ref(),
+            logs.first()
+        );
+        assert_eq!(
+            choose_log(&logs, Some(logs[1].as_str())).unwrap().as_ref(),
+            logs.get(1)
+        );
+        assert!(choose_log(&logs, Some("missing.snakemake.log")).is_err());
+        assert_eq!(choose_log(&[], None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_load_latest_run() {
+        let (_temp, dir) = pipeline();
+        let state = load_state(&dir, None).unwrap();
+
+        // The snapshot describes the latest run
+        assert_eq!(state.run_uuid.as_deref(), Some("from-snapshot"));
+        let job = &state.jobs["results/a.bam"];
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(job.timing.completed_at.is_some());
+        assert!(state.jobs.contains_key("results/b.bam"));
+    }
+
+    #[test]
+    fn test_load_earlier_run() {
+        let (_temp, dir) = pipeline();
+        let state = load_state(&dir, Some(EARLIER)).unwrap();
+
+        assert!(state.run_uuid.is_none());
+        // The later run's metadata says completed; this run's log says failed
+        let job = &state.jobs["results/a.bam"];
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.timing.completed_at.is_none());
+        assert!(job.data_sources.has_snakemake_metadata);
+        // Outputs this run didn't produce are left out
+        assert!(!state.jobs.contains_key("results/b.bam"));
+    }
+}
@@ -1,10 +1,15 @@
-//! Background polling service for SLURM and LSF schedulers.
-
-use charmer_lsf::{query_bhist, query_bjobs};
-use charmer_slurm::{query_resource_usage, query_sacct, query_squeue};
+//! Background polling service for cluster schedulers.
+
+use charmer_htcondor::HtcondorScheduler;
+use charmer_local::{LocalMonitor, is_local_host, local_hostname};
+use charmer_lsf::LsfScheduler;
+use charmer_pbs::PbsScheduler;
+use charmer_scheduler::{Scheduler, SchedulerJob};
+use charmer_sge::SgeScheduler;
+use charmer_slurm::{RestClient, SlurmScheduler};
 use charmer_state::{
-    FailureAnalysis, FailureMode, JobStatus, PipelineState, ResourceUsage, merge_lsf_jobs,
-    merge_slurm_jobs,
+    JobError, JobStatus, PipelineState, correlate_jobs, merge_local_processes,
+    merge_scheduler_jobs, retain_logged_jobs,
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -12,35 +17,36 @@ use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::time::interval;
 
-/// Scheduler type detection.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SchedulerType {
-    Slurm,
-    Lsf,
-}
-
 /// Detect which scheduler is available.
-pub async fn detect_scheduler() -> Option<SchedulerType> {
-    // Try SLURM first
-    if tokio::process::Command::new("squeue")
-        .arg("--version")
-        .output()
-        .await
-        .map(|o| o.status.success())
-        .unwrap_or(false)
-    {
-        return Some(SchedulerType::Slurm);
+///
+/// A configured slurmrestd URL is tried first; otherwise backends are tried
+/// in order and the first whose tools respond wins.
+pub async fn detect_scheduler(slurmrestd: Option<&str>) -> Option<Box<dyn Scheduler>> {
+    if let Some(url) = slurmrestd {
+        match RestClient::from_url(url) {
+            Ok(client) => {
+                let scheduler = SlurmScheduler::with_rest(client);
+                if scheduler.is_available().await {
+                    return Some(Box::new(scheduler));
+                }
+                tracing::warn!("slurmrestd at {} is not reachable, using squeue/sacct", url);
+            }
+            Err(e) => tracing::warn!("{}", e),
+        }
     }
 
-    // Try LSF
-    if tokio::process::Command::new("bjobs")
-        .arg("-V")
-        .output()
-        .await
-        .map(|o| o.status.success())
-        .unwrap_or(false)
-    {
-        return Some(SchedulerType::Lsf);
+    let candidates: Vec<Box<dyn Scheduler>> = vec![
+        Box::new(SlurmScheduler::default()),
+        Box::new(LsfScheduler),
+        Box::new(PbsScheduler),
+        Box::new(SgeScheduler),
+        Box::new(HtcondorScheduler),
+    ];
+
+    for scheduler in candidates {
+        if scheduler.is_available().await {
+            return Some(scheduler);
+        }
     }
 
     None
@@ -57,6 +63,8 @@ pub struct PollingConfig {
     pub run_uuid: Option<String>,
     /// Hours of history to fetch.
     pub history_hours: u64,
+    /// slurmrestd URL to query instead of squeue/sacct (optional).
+    pub slurmrestd: Option<String>,
 }
 
 impl Default for PollingConfig {
@@ -66,6 +74,7 @@ impl Default for PollingConfig {
             history_poll_interval: Duration::from_secs(30),
             run_uuid: None,
             history_hours: 24,
+            slurmrestd: None,
         }
     }
 }
@@ -74,14 +83,14 @@ impl Default for PollingConfig {
 pub struct PollingService {
     state: Arc<Mutex<PipelineState>>,
     config: PollingConfig,
-    scheduler: SchedulerType,
+    scheduler: Arc<dyn Scheduler>,
 }
 
 impl PollingService {
     pub fn new(
         state: Arc<Mutex<PipelineState>>,
         config: PollingConfig,
-        scheduler: SchedulerType,
+        scheduler: Arc<dyn Scheduler>,
     ) -> Self {
         Self {
             state,
@@ -120,137 +129,96 @@ impl PollingService {
 
     /// Poll active jobs (squeue or bjobs).
     async fn poll_active_jobs(&self) {
-        match self.scheduler {
-            SchedulerType::Slurm => {
-                if let Err(e) = self.poll_squeue().await {
-                    tracing::error!("Error polling squeue: {}", e);
-                }
+        let run_uuid = self.config.run_uuid.as_deref();
+        match self.scheduler.query_active(run_uuid).await {
+            Ok(mut jobs) => {
+                let mut state = self.state.lock().await;
+                self.retain_run(&state, &mut jobs);
+                merge_scheduler_jobs(&mut state, self.scheduler.name(), jobs, false);
+                correlate_jobs(&mut state);
             }
-            SchedulerType::Lsf => {
-                if let Err(e) = self.poll_bjobs().await {
-                    tracing::error!("Error polling bjobs: {}", e);
-                }
+            Err(e) => {
+                tracing::error!("Error polling {} active jobs: {}", self.scheduler.name(), e);
             }
         }
     }
 
     /// Poll historical jobs (sacct or bhist).
     async fn poll_historical_jobs(&self) {
-        match self.scheduler {
-            SchedulerType::Slurm => {
-                if let Err(e) = self.poll_sacct().await {
-                    tracing::error!("Error polling sacct: {}", e);
-                }
-            }
-            SchedulerType::Lsf => {
-                if let Err(e) = self.poll_bhist().await {
-                    tracing::error!("Error polling bhist: {}", e);
-                }
-            }
+        let capabilities = self.scheduler.capabilities();
+        if !capabilities.history {
+            return;
         }
-    }
-
-    /// Poll SLURM squeue.
-    async fn poll_squeue(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let run_uuid = self.config.run_uuid.as_deref();
-        let jobs = query_squeue(run_uuid).await?;
-
-        let mut state = self.state.lock().await;
-        merge_slurm_jobs(&mut state, jobs, false);
-
-        Ok(())
-    }
 
-    /// Poll SLURM sacct.
-    async fn poll_sacct(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let run_uuid = self.config.run_uuid.as_deref();
         let since = Some(Utc::now() - chrono::Duration::hours(self.config.history_hours as i64));
-        let jobs = query_sacct(run_uuid, since).await?;
+        let mut jobs = match self.scheduler.query_history(run_uuid, since).await {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                tracing::error!("Error polling {} history: {}", self.scheduler.name(), e);
+                return;
+            }
+        };
 
         let mut state = self.state.lock().await;
-        merge_slurm_jobs(&mut state, jobs, true);
+        self.retain_run(&state, &mut jobs);
+        merge_scheduler_jobs(&mut state, self.scheduler.name(), jobs, true);
+        correlate_jobs(&mut state);
 
         // Enrich failed jobs with failure analysis
-        self.enrich_failed_jobs_slurm(&mut state).await;
+        if capabilities.failure_analysis {
+            self.enrich_failed_jobs(&mut state).await;
+        }
 
         // Enrich completed jobs with resource usage
-        self.enrich_completed_jobs_slurm(&mut state).await;
-
-        Ok(())
-    }
-
-    /// Poll LSF bjobs.
-    async fn poll_bjobs(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let job_name_filter = self.config.run_uuid.as_deref();
-        let jobs = query_bjobs(job_name_filter).await?;
-
-        let mut state = self.state.lock().await;
-        merge_lsf_jobs(&mut state, jobs, false);
-
-        Ok(())
+        if capabilities.resource_usage {
+            self.enrich_completed_jobs(&mut state).await;
+        }
     }
 
-    /// Poll LSF bhist.
-    async fn poll_bhist(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let job_name_filter = self.config.run_uuid.as_deref();
-        let since = Some(Utc::now() - chrono::Duration::hours(self.config.history_hours as i64));
-        let jobs = query_bhist(job_name_filter, since).await?;
-
-        let mut state = self.state.lock().await;
-        merge_lsf_jobs(&mut state, jobs, true);
-
-        // Enrich failed jobs with failure analysis
-        self.enrich_failed_jobs_lsf(&mut state).await;
-
-        Ok(())
+    /// Apply the run filter the scheduler couldn't: when job names don't
+    /// carry the run UUID, keep the jobs the main log submitted.
+    fn retain_run(&self, state: &PipelineState, jobs: &mut Vec<SchedulerJob>) {
+        if self.config.run_uuid.is_some() && !self.scheduler.capabilities().run_names {
+            retain_logged_jobs(state, jobs);
+        }
     }
 
-    /// Enrich failed SLURM jobs with detailed failure analysis.
-    async fn enrich_failed_jobs_slurm(&self, state: &mut PipelineState) {
-        // Collect job IDs that need failure analysis
+    /// Enrich failed jobs with detailed failure analysis.
+    async fn enrich_failed_jobs(&self, state: &mut PipelineState) {
+        // Collect failed attempts (earlier retries included) that need analysis
+        let needs_analysis =
+            |error: &Option<JobError>| error.as_ref().map(|e| e.analysis.is_none()).unwrap_or(true);
         let jobs_needing_analysis: Vec<(String, String)> = state
             .jobs
             .iter()
-            .filter(|(_, job)| {
-                job.status == JobStatus::Failed
-                    && job.scheduler_job_id.is_some()
-                    && job
-                        .error
-                        .as_ref()
-                        .map(|e| e.analysis.is_none())
-                        .unwrap_or(true)
+            .flat_map(|(id, job)| {
+                let current = (job.status == JobStatus::Failed && needs_analysis(&job.error))
+                    .then(|| job.scheduler_job_id.clone())
+                    .flatten();
+                let earlier = job
+                    .attempts
+                    .iter()
+                    .filter(|a| a.status == JobStatus::Failed && needs_analysis(&a.error))
+                    .map(|a| a.scheduler_job_id.clone());
+                current
+                    .into_iter()
+                    .chain(earlier.filter(|a| Some(a) != job.scheduler_job_id.as_ref()))
+                    .map(move |scheduler_job_id| (id.clone(), scheduler_job_id))
             })
-            .map(|(id, job)| (id.clone(), job.scheduler_job_id.clone().unwrap()))
             .take(5) // Limit to avoid too many queries
             .collect();
 
-        // Analyze each failed job
+        // Analyze each failed attempt
         for (job_id, scheduler_job_id) in jobs_needing_analysis {
-            if let Ok(analysis) = charmer_slurm::analyze_failure(&scheduler_job_id).await
-                && let Some(job) = state.jobs.get_mut(&job_id)
-            {
-                // Convert SLURM analysis to unified format
-                let unified_analysis = convert_slurm_analysis(&analysis);
-
-                if let Some(ref mut error) = job.error {
-                    error.analysis = Some(unified_analysis);
-                } else {
-                    // Create error with analysis
-                    job.error = Some(charmer_state::JobError {
-                        exit_code: match &analysis.mode {
-                            charmer_slurm::FailureMode::ExitCode { code, .. } => *code,
-                            _ => -1,
-                        },
-                        message: analysis.explanation.clone(),
-                        analysis: Some(unified_analysis),
-                    });
-                }
+            if let Ok(analysis) = self.scheduler.analyze_failure(&scheduler_job_id).await {
+                state.set_failure_analysis(&job_id, &scheduler_job_id, analysis);
             }
         }
     }
 
-    /// Enrich completed SLURM jobs with resource usage data.
-    async fn enrich_completed_jobs_slurm(&self, state: &mut PipelineState) {
+    /// Enrich completed jobs with resource usage data.
+    async fn enrich_completed_jobs(&self, state: &mut PipelineState) {
         // Collect job IDs that need resource usage data
         let jobs_needing_usage: Vec<(String, String)> = state
             .jobs
@@ -267,156 +235,72 @@ impl PollingService {
 
         // Query resource usage for each job
         for (job_id, scheduler_job_id) in jobs_needing_usage {
-            if let Ok(Some(usage)) = query_resource_usage(&scheduler_job_id).await
-                && let Some(job) = state.jobs.get_mut(&job_id)
-            {
-                job.usage = Some(ResourceUsage {
-                    max_rss_mb: usage.max_rss_mb,
-                    elapsed_seconds: usage.elapsed_seconds,
-                    cpu_time_seconds: usage.cpu_time_seconds,
-                });
-            }
-        }
-    }
-
-    /// Enrich failed LSF jobs with detailed failure analysis.
-    async fn enrich_failed_jobs_lsf(&self, state: &mut PipelineState) {
-        // Collect job IDs that need failure analysis
-        let jobs_needing_analysis: Vec<(String, String)> = state
-            .jobs
-            .iter()
-            .filter(|(_, job)| {
-                job.status == JobStatus::Failed
-                    && job.scheduler_job_id.is_some()
-                    && job
-                        .error
-                        .as_ref()
-                        .map(|e| e.analysis.is_none())
-                        .unwrap_or(true)
-            })
-            .map(|(id, job)| (id.clone(), job.scheduler_job_id.clone().unwrap()))
-            .take(5) // Limit to avoid too many queries
-            .collect();
-
-        // Analyze each failed job
-        for (job_id, lsf_job_id) in jobs_needing_analysis {
-            if let Ok(analysis) = charmer_lsf::analyze_failure(&lsf_job_id).await
+            if let Ok(Some(usage)) = self.scheduler.query_resource_usage(&scheduler_job_id).await
                 && let Some(job) = state.jobs.get_mut(&job_id)
             {
-                // Convert LSF analysis to unified format
-                let unified_analysis = convert_lsf_analysis(&analysis);
-
-                if let Some(ref mut error) = job.error {
-                    error.analysis = Some(unified_analysis);
-                } else {
-                    // Create error with analysis
-                    job.error = Some(charmer_state::JobError {
-                        exit_code: match &analysis.mode {
-                            charmer_lsf::FailureMode::ExitCode { code, .. } => *code,
-                            _ => -1,
-                        },
-                        message: analysis.explanation.clone(),
-                        analysis: Some(unified_analysis),
-                    });
-                }
+                job.usage = Some(usage);
             }
         }
     }
 }
 
-/// Convert SLURM failure analysis to unified format.
-fn convert_slurm_analysis(analysis: &charmer_slurm::FailureAnalysis) -> FailureAnalysis {
-    let mode = match &analysis.mode {
-        charmer_slurm::FailureMode::OutOfMemory { .. } => FailureMode::OutOfMemory,
-        charmer_slurm::FailureMode::Timeout { .. } => FailureMode::Timeout,
-        charmer_slurm::FailureMode::ExitCode { .. } => FailureMode::ExitCode,
-        charmer_slurm::FailureMode::Cancelled { .. } => FailureMode::Cancelled,
-        charmer_slurm::FailureMode::NodeFailure { .. } => FailureMode::NodeFailure,
-        charmer_slurm::FailureMode::Unknown { .. } => FailureMode::Unknown,
-    };
-
-    let (memory_used_mb, memory_limit_mb) = match &analysis.mode {
-        charmer_slurm::FailureMode::OutOfMemory {
-            used_mb,
-            requested_mb,
-            ..
-        } => (Some(*used_mb), Some(*requested_mb)),
-        _ => (analysis.max_rss_mb, analysis.req_mem_mb),
-    };
-
-    let (runtime_seconds, time_limit_seconds) = match &analysis.mode {
-        charmer_slurm::FailureMode::Timeout {
-            elapsed_seconds,
-            limit_seconds,
-            ..
-        } => (Some(*elapsed_seconds), Some(*limit_seconds)),
-        _ => (analysis.elapsed_seconds, analysis.time_limit_seconds),
-    };
-
-    FailureAnalysis {
-        mode,
-        explanation: analysis.explanation.clone(),
-        suggestion: analysis.suggestion.clone(),
-        memory_used_mb,
-        memory_limit_mb,
-        runtime_seconds,
-        time_limit_seconds,
-    }
-}
-
-/// Convert LSF failure analysis to unified format.
-fn convert_lsf_analysis(analysis: &charmer_lsf::FailureAnalysis) -> FailureAnalysis {
-    let mode = match &analysis.mode {
-        charmer_lsf::FailureMode::OutOfMemory { .. } => FailureMode::OutOfMemory,
-        charmer_lsf::FailureMode::Timeout { .. } => FailureMode::Timeout,
-        charmer_lsf::FailureMode::ExitCode { .. } => FailureMode::ExitCode,
-        charmer_lsf::FailureMode::Killed { .. } => FailureMode::Cancelled,
-        charmer_lsf::FailureMode::HostFailure { .. } => FailureMode::NodeFailure,
-        charmer_lsf::FailureMode::Unknown { .. } => FailureMode::Unknown,
-    };
+/// Sample local job processes for pipelines run without a cluster scheduler.
+async fn run_local_polling(state: Arc<Mutex<PipelineState>>, config: PollingConfig) {
+    let working_dir = state.lock().await.working_dir.clone();
+    let hostname = local_hostname();
+    let mut monitor = LocalMonitor::new(working_dir);
+    let mut ticker = interval(config.active_poll_interval);
 
-    let (memory_used_mb, memory_limit_mb) = match &analysis.mode {
-        charmer_lsf::FailureMode::OutOfMemory {
-            used_mb, limit_mb, ..
-        } => (Some(*used_mb), Some(*limit_mb)),
-        _ => (analysis.max_mem_mb, analysis.mem_limit_mb),
-    };
+    loop {
+        ticker.tick().await;
 
-    let (runtime_seconds, time_limit_seconds) = match &analysis.mode {
-        charmer_lsf::FailureMode::Timeout {
-            elapsed_seconds,
-            limit_seconds,
-            ..
-        } => (Some(*elapsed_seconds), Some(*limit_seconds)),
-        _ => (analysis.run_time_seconds, analysis.run_limit_seconds),
-    };
+        // Processes can only be inspected on the host running snakemake
+        let host = state.lock().await.host.clone();
+        if host.as_deref().is_some_and(|h| !is_local_host(h)) {
+            continue;
+        }
 
-    FailureAnalysis {
-        mode,
-        explanation: analysis.explanation.clone(),
-        suggestion: analysis.suggestion.clone(),
-        memory_used_mb,
-        memory_limit_mb,
-        runtime_seconds,
-        time_limit_seconds,
+        match monitor.sample() {
+            Ok(processes) => {
+                let mut state = state.lock().await;
+                merge_local_processes(&mut state, &processes, hostname.as_deref());
+            }
+            Err(e) => {
+                tracing::error!("Error sampling local processes: {}", e);
+            }
+        }
     }
 }
 
-/// Initialize polling service and return a handle to it.
+/// Initialize polling service and return the detected scheduler.
+///
+/// Falls back to monitoring local processes when no scheduler is found,
+/// in which case None is returned.
 pub async fn init_polling(
     state: Arc<Mutex<PipelineState>>,
     config: PollingConfig,
-) -> Option<tokio::task::JoinHandle<()>> {
+) -> Option<Arc<dyn Scheduler>> {
     // Detect scheduler
-    let scheduler = detect_scheduler().await?;
+    let Some(scheduler) = detect_scheduler(config.slurmrestd.as_deref()).await else {
+        if cfg!(target_os = "linux") {
+            tracing::info!(
+                "No scheduler detected, sampling local processes every {} seconds",
+                config.active_poll_interval.as_secs()
+            );
+            tokio::spawn(run_local_polling(state, config));
+        }
+        return None;
+    };
+    let scheduler: Arc<dyn Scheduler> = Arc::from(scheduler);
 
     tracing::info!(
-        "Detected scheduler: {:?}, polling every {} seconds",
-        scheduler,
+        "Detected scheduler: {}, polling every {} seconds",
+        scheduler.name(),
         config.active_poll_interval.as_secs()
     );
 
     // Create and start the polling service
-    let service = PollingService::new(state, config, scheduler);
-    Some(service.start())
+    let service = PollingService::new(state, config, Arc::clone(&scheduler));
+    service.start();
+    Some(scheduler)
 }
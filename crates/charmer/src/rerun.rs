@@ -0,0 +1,35 @@
+//! Detached snakemake reruns started from the TUI.
+
+use camino::Utf8Path;
+use std::fs::File;
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Start snakemake with `args` in `working_dir`, detached from charmer.
+///
+/// Output goes to `log_path`. The process gets its own process group so it
+/// keeps running after charmer exits or the terminal sends Ctrl+C.
+/// Returns the process ID.
+pub fn spawn_detached(
+    working_dir: &Utf8Path,
+    args: &[String],
+    log_path: &Utf8Path,
+) -> io::Result<u32> {
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let log = File::create(log_path)?;
+
+    let mut cmd = Command::new("snakemake");
+    cmd.args(args)
+        .current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    Ok(cmd.spawn()?.id())
+}
@@ -0,0 +1,42 @@
+//! Periodic persistence of pipeline state snapshots.
+
+use charmer_state::{PipelineState, SnapshotStore};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::interval;
+
+/// How often the state is written out, if it changed.
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Save a snapshot of the current state.
+pub async fn save_snapshot(state: &Arc<Mutex<PipelineState>>, store: &SnapshotStore) {
+    // Clone so the lock isn't held while serializing
+    let state = state.lock().await.clone();
+    if let Err(e) = store.save(&state) {
+        tracing::warn!("Failed to save state snapshot to {}: {}", store.path(), e);
+    }
+}
+
+/// Start saving snapshots in the background whenever the state changes.
+pub fn init_snapshots(
+    state: Arc<Mutex<PipelineState>>,
+    store: Arc<SnapshotStore>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval(SNAPSHOT_INTERVAL);
+        let mut last_generation = state.lock().await.generation;
+
+        // Skip the first tick (fires immediately)
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let generation = state.lock().await.generation;
+            if generation != last_generation {
+                last_generation = generation;
+                save_snapshot(&state, &store).await;
+            }
+        }
+    })
+}
@@ -0,0 +1,291 @@
+//! Snakemake as a child process of charmer (`charmer run`).
+//!
+//! The child's stdout and stderr are appended to `.snakemake/charmer/run.log`,
+//! which the TUI shows as the main log, and the last lines are kept for
+//! classifying a failure the main log doesn't explain.
+
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+
+/// Number of output lines kept for failure classification.
+const TAIL_LINES: usize = 50;
+
+/// How long to wait for the rest of the output once the child has exited.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Consecutive read errors after which an output stream is given up.
+const MAX_READ_ERRORS: usize = 10;
+
+/// Output shared by the stdout and stderr readers.
+struct Output {
+    file: File,
+    tail: VecDeque<String>,
+}
+
+impl Output {
+    fn write(&mut self, line: &str) {
+        if let Err(e) = writeln!(self.file, "{}", line) {
+            tracing::warn!("Failed to write snakemake output: {}", e);
+        }
+    }
+
+    fn push(&mut self, line: String) {
+        self.write(&line);
+        if self.tail.len() == TAIL_LINES {
+            self.tail.pop_front();
+        }
+        self.tail.push_back(line);
+    }
+}
+
+/// Starts, restarts and stops the snakemake child.
+pub struct Supervisor {
+    working_dir: Utf8PathBuf,
+    program: String,
+    args: Vec<String>,
+    output_path: Utf8PathBuf,
+    output: Arc<Mutex<Output>>,
+    child: Option<Child>,
+    readers: Vec<JoinHandle<()>>,
+}
+
+impl Supervisor {
+    /// Prepare to run `snakemake args` in `working_dir`.
+    pub fn new(working_dir: &Utf8Path, args: Vec<String>) -> io::Result<Self> {
+        let output_path = working_dir
+            .join(".snakemake")
+            .join("charmer")
+            .join("run.log");
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::create(&output_path)?;
+        Ok(Self {
+            working_dir: working_dir.to_path_buf(),
+            program: "snakemake".to_string(),
+            args,
+            output_path,
+            output: Arc::new(Mutex::new(Output {
+                file,
+                tail: VecDeque::new(),
+            })),
+            child: None,
+            readers: Vec::new(),
+        })
+    }
+
+    /// Run `program` instead of snakemake.
+    #[cfg(test)]
+    fn with_program(mut self, program: &str) -> Self {
+        self.program = program.to_string();
+        self
+    }
+
+    /// Arguments snakemake is started with.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// File the child's output is written to.
+    pub fn output_path(&self) -> &Utf8Path {
+        &self.output_path
+    }
+
+    /// Last lines of output of the current (or last) child.
+    pub fn tail(&self) -> Vec<String> {
+        self.output.lock().unwrap().tail.iter().cloned().collect()
+    }
+
+    /// Start snakemake with the configured arguments.
+    pub fn start(&mut self) -> io::Result<()> {
+        self.output.lock().unwrap().tail.clear();
+        self.child = Some(self.spawn(&self.args.clone())?);
+        Ok(())
+    }
+
+    /// Start snakemake again after a failure, rerunning incomplete jobs.
+    pub fn restart(&mut self) -> io::Result<()> {
+        if !self
+            .args
+            .iter()
+            .any(|a| a == "--rerun-incomplete" || a == "--ri")
+        {
+            self.args.push("--rerun-incomplete".to_string());
+        }
+        self.start()
+    }
+
+    /// Start snakemake with new arguments, which later restarts keep.
+    pub fn rerun(&mut self, args: Vec<String>) -> io::Result<()> {
+        self.args = args;
+        self.start()
+    }
+
+    /// Remove a stale lock with `snakemake --unlock`, waiting for it to finish.
+    pub async fn unlock(&mut self) -> io::Result<ExitStatus> {
+        let mut args = self.args.clone();
+        args.push("--unlock".to_string());
+        self.spawn(&args)?.wait().await
+    }
+
+    /// Exit code of the child if it has exited since the last call, once
+    /// its remaining output has been read.
+    ///
+    /// A child killed by a signal reports -1.
+    pub async fn try_exit(&mut self) -> Option<i32> {
+        let status = self.child.as_mut()?.try_wait().ok()??;
+        self.child = None;
+        for reader in self.readers.drain(..) {
+            let _ = tokio::time::timeout(DRAIN_TIMEOUT, reader).await;
+        }
+        Some(status.code().unwrap_or(-1))
+    }
+
+    /// Whether the child is still running.
+    pub fn is_running(&self) -> bool {
+        self.child.is_some()
+    }
+
+    /// Ask the child to stop the way Ctrl+C would and wait for it to exit.
+    ///
+    /// Snakemake cancels its submitted jobs on SIGINT, which can take a while.
+    pub async fn interrupt(&mut self) -> io::Result<()> {
+        let Some(child) = self.child.as_mut() else {
+            return Ok(());
+        };
+        // The whole process group, as the terminal would deliver it
+        #[cfg(unix)]
+        if let Some(pid) = child.id() {
+            // SAFETY: kill(2) has no memory-safety preconditions
+            unsafe {
+                libc::kill(-(pid as libc::pid_t), libc::SIGINT);
+            }
+        }
+        #[cfg(not(unix))]
+        child.start_kill()?;
+        child.wait().await?;
+        self.child = None;
+        Ok(())
+    }
+
+    /// Spawn snakemake with `args`, teeing its output.
+    fn spawn(&mut self, args: &[String]) -> io::Result<Child> {
+        self.output
+            .lock()
+            .unwrap()
+            .write(&format!("$ {} {}", self.program, args.join(" ")));
+
+        let mut cmd = Command::new(&self.program);
+        cmd.args(args)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Its own process group, so the terminal's signals don't reach it
+        // behind charmer's back
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let mut child = cmd.spawn()?;
+        self.readers.retain(|reader| !reader.is_finished());
+        if let Some(stdout) = child.stdout.take() {
+            let output = Arc::clone(&self.output);
+            self.readers.push(tokio::spawn(read_lines(stdout, output)));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let output = Arc::clone(&self.output);
+            self.readers.push(tokio::spawn(read_lines(stderr, output)));
+        }
+        Ok(child)
+    }
+}
+
+/// Copy `stream` line by line into `output` until it closes.
+///
+/// Output that isn't valid UTF-8 is kept with replacement characters, and a
+/// failed read doesn't stop the copy: the child would block on a full pipe.
+async fn read_lines(stream: impl AsyncRead + Unpin, output: Arc<Mutex<Output>>) {
+    let mut reader = BufReader::new(stream);
+    let mut buf = Vec::new();
+    let mut errors = 0;
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) => break,
+            Ok(_) => {
+                errors = 0;
+                let line = String::from_utf8_lossy(&buf);
+                let line = line.trim_end_matches(['\n', '\r']).to_string();
+                output.lock().unwrap().push(line);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to read snakemake output: {}", e);
+                errors += 1;
+                if errors == MAX_READ_ERRORS {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn wait_exit(supervisor: &mut Supervisor) -> i32 {
+        for _ in 0..500 {
+            if let Some(code) = supervisor.try_exit().await {
+                return code;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("child did not exit");
+    }
+
+    #[tokio::test]
+    async fn test_start_exit_tail() {
+        let temp = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(temp.path()).unwrap();
+        let script = "echo Building DAG of jobs...; printf 'bad \\377 byte\\n' >&2; exit 3";
+        let mut supervisor = Supervisor::new(dir, vec!["-c".to_string(), script.to_string()])
+            .unwrap()
+            .with_program("sh");
+
+        supervisor.start().unwrap();
+        assert!(supervisor.is_running());
+        assert_eq!(wait_exit(&mut supervisor).await, 3);
+        assert!(!supervisor.is_running());
+
+        let mut tail = supervisor.tail();
+        tail.sort();
+        assert_eq!(tail, vec!["Building DAG of jobs...", "bad \u{fffd} byte"]);
+        let log = std::fs::read_to_string(supervisor.output_path()).unwrap();
+        assert!(log.starts_with("$ sh -c "));
+        assert!(log.contains("Building DAG of jobs...\n"));
+
+        // A restart reruns incomplete jobs and starts a fresh tail
+        supervisor.restart().unwrap();
+        assert_eq!(wait_exit(&mut supervisor).await, 3);
+        assert_eq!(supervisor.args().last().unwrap(), "--rerun-incomplete");
+        assert_eq!(supervisor.tail().len(), 2);
+
+        // A rerun replaces the arguments for good
+        supervisor
+            .rerun(vec!["-c".to_string(), "exit 0".to_string()])
+            .unwrap();
+        assert_eq!(wait_exit(&mut supervisor).await, 0);
+        supervisor.restart().unwrap();
+        assert_eq!(wait_exit(&mut supervisor).await, 0);
+        assert_eq!(supervisor.args(), ["-c", "exit 0", "--rerun-incomplete"]);
+    }
+}